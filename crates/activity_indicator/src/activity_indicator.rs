@@ -71,6 +71,9 @@ impl ActivityIndicator {
             })
             .detach();
             cx.observe(&project, |_, _, cx| cx.notify()).detach();
+            if let Some(git_state) = project.read(cx).git_state() {
+                cx.observe(git_state, |_, _, cx| cx.notify()).detach();
+            }
 
             if let Some(auto_updater) = auto_updater.as_ref() {
                 cx.observe(auto_updater, |_, _, cx| cx.notify()).detach();
@@ -245,6 +248,34 @@ impl ActivityIndicator {
             });
         }
 
+        // Show the progress of fetching, pulling or pushing.
+        if let Some(status) = self
+            .project
+            .read(cx)
+            .git_state()
+            .and_then(|git_state| git_state.read(cx).remote_operation().cloned())
+        {
+            let label = status.operation.label();
+            let message = match status.progress {
+                Some(progress) => format!("{label}: {progress}"),
+                None => format!("{label}..."),
+            };
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+            });
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
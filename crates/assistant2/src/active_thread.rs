@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{Tool, ToolPermissionSettings, ToolRiskLevel, ToolWorkingSet};
 use collections::HashMap;
 use gpui::{
     list, AbsoluteLength, AnyElement, AppContext, DefiniteLength, EdgesRefinement, Empty, Length,
@@ -8,14 +8,17 @@ use gpui::{
     TextStyleRefinement, UnderlineStyle, View, WeakView,
 };
 use language::LanguageRegistry;
-use language_model::Role;
+use language_model::{LanguageModelToolUseId, Role};
 use markdown::{Markdown, MarkdownStyle};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::prelude::*;
 use workspace::Workspace;
 
-use crate::thread::{MessageId, Thread, ThreadError, ThreadEvent};
+use crate::thread::{
+    MessageId, PendingToolUse, PendingToolUseStatus, Thread, ThreadError, ThreadEvent,
+    ToolUseApproval,
+};
 use crate::ui::ContextPill;
 
 pub struct ActiveThread {
@@ -210,34 +213,206 @@ impl ActiveThread {
                 cx.notify();
             }
             ThreadEvent::UsePendingTools => {
-                let pending_tool_uses = self
-                    .thread
-                    .read(cx)
-                    .pending_tool_uses()
-                    .into_iter()
-                    .filter(|tool_use| tool_use.status.is_idle())
-                    .cloned()
-                    .collect::<Vec<_>>();
-
-                for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
-
-                        self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(
-                                tool_use.assistant_message_id,
-                                tool_use.id.clone(),
-                                task,
-                                cx,
-                            );
-                        });
-                    }
+                let settings = self.tool_permission_settings(cx).clone();
+                let tool_uses = self.thread.update(cx, |thread, cx| {
+                    thread.check_tool_permissions(&settings, cx)
+                });
+
+                for (tool_use, tool, approval) in tool_uses {
+                    self.run_tool_use(tool_use, tool, approval, cx);
                 }
             }
             ThreadEvent::ToolFinished { .. } => {}
         }
     }
 
+    /// Returns the tool permissions for this workspace, including the restrictions
+    /// from its project settings.
+    fn tool_permission_settings<'a>(&self, cx: &'a AppContext) -> &'a ToolPermissionSettings {
+        match self.workspace.upgrade() {
+            Some(workspace) => {
+                ToolPermissionSettings::for_project(workspace.read(cx).project(), cx)
+            }
+            None => ToolPermissionSettings::get_global(cx),
+        }
+    }
+
+    fn run_tool_use(
+        &mut self,
+        tool_use: PendingToolUse,
+        tool: Arc<dyn Tool>,
+        approval: ToolUseApproval,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(
+                tool_use.assistant_message_id,
+                tool_use.id.clone(),
+                approval,
+                task,
+                cx,
+            );
+        });
+    }
+
+    fn allow_tool_use(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        always_allow: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let thread = self.thread.read(cx);
+        let Some(tool_use) = thread.pending_tool_use(tool_use_id).cloned() else {
+            return;
+        };
+
+        let tool_uses = if always_allow {
+            self.tools.always_allow(tool_use.name.clone());
+
+            // Run the other tool uses of the same tool that are waiting for confirmation, too.
+            thread
+                .pending_tool_uses()
+                .into_iter()
+                .filter(|pending| {
+                    pending.name == tool_use.name && pending.status.needs_confirmation()
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            vec![tool_use]
+        };
+
+        for tool_use in tool_uses {
+            if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
+                self.run_tool_use(tool_use, tool, ToolUseApproval::User, cx);
+            }
+        }
+    }
+
+    fn deny_tool_use(&mut self, tool_use_id: &LanguageModelToolUseId, cx: &mut ViewContext<Self>) {
+        self.thread.update(cx, |thread, cx| {
+            thread.deny_tool_use(tool_use_id.clone(), ToolUseApproval::User, cx);
+        });
+    }
+
+    fn render_tool_use(&self, tool_use: &PendingToolUse, cx: &ViewContext<Self>) -> AnyElement {
+        let colors = cx.theme().colors();
+        let risk_level = self
+            .tools
+            .tool(&tool_use.name, cx)
+            .map(|tool| tool.risk_level());
+
+        let (status_label, status_color) = match &tool_use.status {
+            PendingToolUseStatus::Idle => (SharedString::from("Pending"), Color::Muted),
+            PendingToolUseStatus::NeedsConfirmation => {
+                ("Waiting for confirmation".into(), Color::Warning)
+            }
+            PendingToolUseStatus::Running { .. } => ("Running".into(), Color::Muted),
+            PendingToolUseStatus::Finished => ("Completed".into(), Color::Success),
+            PendingToolUseStatus::Denied => ("Denied".into(), Color::Muted),
+            PendingToolUseStatus::Error(error) => (format!("Failed: {error}").into(), Color::Error),
+        };
+
+        let header = h_flex()
+            .gap_1p5()
+            .justify_between()
+            .child(
+                h_flex()
+                    .gap_1p5()
+                    .child(
+                        Icon::new(IconName::PocketKnife)
+                            .size(IconSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(tool_use.name.clone()).size(LabelSize::Small))
+                    .when_some(risk_level, |this, risk_level| {
+                        let (label, color) = match risk_level {
+                            ToolRiskLevel::ReadOnly => ("read-only", Color::Muted),
+                            ToolRiskLevel::Write => ("writes", Color::Warning),
+                            ToolRiskLevel::Destructive => ("destructive", Color::Error),
+                        };
+                        this.child(Label::new(label).size(LabelSize::XSmall).color(color))
+                    }),
+            )
+            .child(
+                Label::new(status_label)
+                    .size(LabelSize::XSmall)
+                    .color(status_color),
+            );
+
+        if !tool_use.status.needs_confirmation() {
+            return div().px_2p5().pb_1().child(header).into_any();
+        }
+
+        let input = serde_json::to_string_pretty(&tool_use.input).unwrap_or_default();
+        let tool_use_id = tool_use.id.clone();
+
+        div()
+            .px_2p5()
+            .pb_1p5()
+            .child(
+                v_flex()
+                    .gap_1p5()
+                    .p_2()
+                    .bg(colors.editor_background)
+                    .rounded_md()
+                    .border_1()
+                    .border_color(colors.border)
+                    .child(header)
+                    .child(
+                        div()
+                            .p_1()
+                            .rounded_sm()
+                            .bg(colors.editor_foreground.opacity(0.05))
+                            .font_buffer(cx)
+                            .child(Label::new(input).size(LabelSize::XSmall)),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .justify_end()
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!("deny-tool-use-{tool_use_id}")),
+                                    "Deny",
+                                )
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener({
+                                    let tool_use_id = tool_use_id.clone();
+                                    move |this, _, cx| this.deny_tool_use(&tool_use_id, cx)
+                                })),
+                            )
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!(
+                                        "always-allow-tool-use-{tool_use_id}"
+                                    )),
+                                    "Always Allow",
+                                )
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener({
+                                    let tool_use_id = tool_use_id.clone();
+                                    move |this, _, cx| this.allow_tool_use(&tool_use_id, true, cx)
+                                })),
+                            )
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!("allow-tool-use-{tool_use_id}")),
+                                    "Allow",
+                                )
+                                .label_size(LabelSize::Small)
+                                .style(ButtonStyle::Filled)
+                                .on_click(cx.listener(
+                                    move |this, _, cx| this.allow_tool_use(&tool_use_id, false, cx),
+                                )),
+                            ),
+                    ),
+            )
+            .into_any()
+    }
+
     fn render_message(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
         let message_id = self.messages[ix];
         let Some(message) = self.thread.read(cx).message(message_id) else {
@@ -249,6 +424,13 @@ impl ActiveThread {
         };
 
         let context = self.thread.read(cx).context_for_message(message_id);
+        let tool_uses = self
+            .thread
+            .read(cx)
+            .tool_uses_for_message(message_id)
+            .into_iter()
+            .map(|tool_use| self.render_tool_use(tool_use, cx))
+            .collect::<Vec<_>>();
         let colors = cx.theme().colors();
 
         let message_content = v_flex()
@@ -305,7 +487,10 @@ impl ActiveThread {
                         )
                         .child(message_content),
                 ),
-            Role::Assistant => div().id(("message-container", ix)).child(message_content),
+            Role::Assistant => div()
+                .id(("message-container", ix))
+                .child(message_content)
+                .children(tool_uses),
             Role::System => div().id(("message-container", ix)).py_1().px_2().child(
                 v_flex()
                     .bg(colors.editor_background)
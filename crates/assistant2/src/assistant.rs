@@ -5,6 +5,7 @@ mod assistant_panel;
 mod buffer_codegen;
 mod context;
 mod context_picker;
+mod context_server_sampling;
 mod context_store;
 mod context_strip;
 mod inline_assistant;
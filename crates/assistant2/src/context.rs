@@ -1,13 +1,16 @@
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::Arc;
 
+use context_server::manager::ContextServerManager;
 use file_icons::FileIcons;
-use gpui::{AppContext, Model, SharedString};
+use gpui::{AppContext, Model, SharedString, WeakModel};
 use language::Buffer;
 use language_model::{LanguageModelRequestMessage, MessageContent};
 use serde::{Deserialize, Serialize};
 use text::BufferId;
 use ui::IconName;
+use url::Url;
 use util::post_inc;
 
 use crate::{context_store::buffer_path_log_err, thread::Thread};
@@ -40,6 +43,7 @@ pub enum ContextKind {
     Directory,
     FetchedUrl,
     Thread,
+    Resource,
 }
 
 impl ContextKind {
@@ -49,6 +53,7 @@ impl ContextKind {
             ContextKind::Directory => "Folder",
             ContextKind::FetchedUrl => "Fetch",
             ContextKind::Thread => "Thread",
+            ContextKind::Resource => "Resource",
         }
     }
 
@@ -58,6 +63,7 @@ impl ContextKind {
             ContextKind::Directory => IconName::Folder,
             ContextKind::FetchedUrl => IconName::Globe,
             ContextKind::Thread => IconName::MessageCircle,
+            ContextKind::Resource => IconName::Server,
         }
     }
 }
@@ -68,6 +74,7 @@ pub enum Context {
     Directory(DirectoryContext),
     FetchedUrl(FetchedUrlContext),
     Thread(ThreadContext),
+    Resource(ResourceContext),
 }
 
 impl Context {
@@ -77,6 +84,7 @@ impl Context {
             Self::Directory(directory) => directory.snapshot.id,
             Self::FetchedUrl(url) => url.id,
             Self::Thread(thread) => thread.id,
+            Self::Resource(resource) => resource.id,
         }
     }
 }
@@ -111,6 +119,18 @@ pub struct ThreadContext {
     pub text: SharedString,
 }
 
+/// A resource provided by a context server, kept up to date while the server
+/// reports changes to it.
+#[derive(Debug)]
+pub struct ResourceContext {
+    pub id: ContextId,
+    pub context_server_manager: WeakModel<ContextServerManager>,
+    pub server_id: Arc<str>,
+    pub uri: Url,
+    pub name: SharedString,
+    pub text: SharedString,
+}
+
 // TODO: Model<Buffer> holds onto the buffer even if the file is deleted and closed. Should remove
 // the context from the message editor in this case.
 
@@ -129,6 +149,7 @@ impl Context {
             Self::Directory(directory_context) => Some(directory_context.snapshot()),
             Self::FetchedUrl(fetched_url_context) => Some(fetched_url_context.snapshot()),
             Self::Thread(thread_context) => Some(thread_context.snapshot(cx)),
+            Self::Resource(resource_context) => Some(resource_context.snapshot()),
         }
     }
 }
@@ -235,6 +256,20 @@ impl ThreadContext {
     }
 }
 
+impl ResourceContext {
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            parent: Some(self.server_id.to_string().into()),
+            tooltip: Some(self.uri.to_string().into()),
+            icon_path: None,
+            kind: ContextKind::Resource,
+            text: Box::new([self.text.clone()]),
+        }
+    }
+}
+
 pub fn attach_context_to_message(
     message: &mut LanguageModelRequestMessage,
     contexts: impl Iterator<Item = ContextSnapshot>,
@@ -243,6 +278,7 @@ pub fn attach_context_to_message(
     let mut directory_context = Vec::new();
     let mut fetch_context = Vec::new();
     let mut thread_context = Vec::new();
+    let mut resource_context = Vec::new();
 
     let mut capacity = 0;
     for context in contexts {
@@ -252,6 +288,7 @@ pub fn attach_context_to_message(
             ContextKind::Directory => directory_context.push(context),
             ContextKind::FetchedUrl => fetch_context.push(context),
             ContextKind::Thread => thread_context.push(context),
+            ContextKind::Resource => resource_context.push(context),
         }
     }
     if !file_context.is_empty() {
@@ -266,6 +303,9 @@ pub fn attach_context_to_message(
     if !thread_context.is_empty() {
         capacity += 1 + thread_context.len();
     }
+    if !resource_context.is_empty() {
+        capacity += 1 + resource_context.len();
+    }
     if capacity == 0 {
         return;
     }
@@ -310,6 +350,16 @@ pub fn attach_context_to_message(
         }
     }
 
+    if !resource_context.is_empty() {
+        context_chunks.push("The following resources are available:\n");
+        for context in &resource_context {
+            context_chunks.push(&context.name);
+            for chunk in &context.text {
+                context_chunks.push(&chunk);
+            }
+        }
+    }
+
     debug_assert!(
         context_chunks.len() == capacity,
         "attach_context_message calculated capacity of {}, but length was {}",
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use context_server::manager::ContextServerManager;
+use context_server::protocol::ServerCapability;
+use futures::future::join_all;
+use fuzzy::StringMatchCandidate;
+use gpui::{
+    AppContext, DismissEvent, FocusHandle, FocusableView, Model, Task, View, WeakModel, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem};
+use url::Url;
+use util::ResultExt as _;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::ContextStore;
+
+pub struct ResourceContextPicker {
+    picker: View<Picker<ResourceContextPickerDelegate>>,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        context_server_manager: Model<ContextServerManager>,
+        context_picker: WeakView<ContextPicker>,
+        context_store: WeakModel<ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let servers = context_server_manager.read(cx).servers();
+        let delegate = ResourceContextPickerDelegate::new(
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+        );
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+
+        // Resources are listed once when the picker opens, across all running servers.
+        let list_resources = servers.into_iter().filter_map(|server| {
+            let protocol = server.client()?;
+            if !protocol.capable(ServerCapability::Resources) {
+                return None;
+            }
+            let server_id = server.id();
+            Some(async move {
+                let response = protocol.list_resources().await.log_err()?;
+                Some(
+                    response
+                        .resources
+                        .into_iter()
+                        .map(|resource| ResourceContextEntry {
+                            server_id: server_id.clone(),
+                            uri: resource.uri,
+                            name: resource.name.into(),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+        });
+        let list_resources = join_all(list_resources.collect::<Vec<_>>());
+        let weak_picker = picker.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let resources = list_resources
+                .await
+                .into_iter()
+                .flatten()
+                .flatten()
+                .collect::<Vec<_>>();
+            weak_picker.update(&mut cx, |picker, cx| {
+                picker.delegate.resources = resources;
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+
+        ResourceContextPicker { picker }
+    }
+}
+
+impl FocusableView for ResourceContextPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceContextEntry {
+    pub server_id: Arc<str>,
+    pub uri: Url,
+    pub name: SharedString,
+}
+
+pub struct ResourceContextPickerDelegate {
+    context_server_manager: Model<ContextServerManager>,
+    context_picker: WeakView<ContextPicker>,
+    context_store: WeakModel<ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    resources: Vec<ResourceContextEntry>,
+    matches: Vec<ResourceContextEntry>,
+    selected_index: usize,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        context_server_manager: Model<ContextServerManager>,
+        context_picker: WeakView<ContextPicker>,
+        context_store: WeakModel<ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+            resources: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search resources…".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let resources = self.resources.clone();
+
+        let executor = cx.background_executor().clone();
+        let search_task = cx.background_executor().spawn(async move {
+            if query.is_empty() {
+                resources
+            } else {
+                let candidates = resources
+                    .iter()
+                    .enumerate()
+                    .map(|(id, resource)| StringMatchCandidate::new(id, &resource.name))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| resources[mat.candidate_id].clone())
+                    .collect()
+            }
+        });
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_resource(
+                    self.context_server_manager.clone(),
+                    entry.server_id,
+                    entry.uri,
+                    entry.name,
+                    cx,
+                )
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        let confirm_behavior = self.confirm_behavior;
+        cx.spawn(|this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |this, cx| match confirm_behavior {
+                ConfirmBehavior::KeepOpen => cx.notify(),
+                ConfirmBehavior::Close => this.delegate.dismissed(cx),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let resource = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_resource_context_entry(resource, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_resource_context_entry(
+    resource: &ResourceContextEntry,
+    context_store: WeakModel<ContextStore>,
+    cx: &mut WindowContext,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |ctx_store| {
+        ctx_store
+            .read(cx)
+            .includes_resource(&resource.server_id, &resource.uri)
+            .is_some()
+    });
+
+    h_flex()
+        .gap_1()
+        .w_full()
+        .child(Icon::new(IconName::Server).size(IconSize::Small))
+        .child(Label::new(resource.name.clone()))
+        .child(
+            Label::new(resource.server_id.to_string())
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use context_server::manager::SamplingHandler;
+use context_server::types::{self, CreateMessageRequest, CreateMessageResult};
+use futures::StreamExt as _;
+use gpui::{AsyncAppContext, PromptLevel};
+use language_model::{
+    LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+
+/// The number of characters of the request shown to the user when asking for approval.
+const MAX_PROMPT_DETAIL_CHARS: usize = 1000;
+
+/// Returns a handler that answers `sampling/createMessage` requests with the
+/// active language model, after the user approved the request.
+pub fn sampling_handler() -> SamplingHandler {
+    Arc::new(|server_id, request, cx| {
+        cx.spawn(|cx| async move { create_message(server_id, request, cx).await })
+    })
+}
+
+async fn create_message(
+    server_id: Arc<str>,
+    request: CreateMessageRequest,
+    mut cx: AsyncAppContext,
+) -> Result<CreateMessageResult> {
+    let model = cx
+        .update(|cx| LanguageModelRegistry::read_global(cx).active_model())?
+        .context("no language model is configured")?;
+
+    let request = completion_request(request)?;
+    let model_name = model.name().0.to_string();
+
+    let message = format!("Context server {server_id} wants to use {model_name}");
+    let detail = util::truncate_and_trailoff(
+        &request
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        MAX_PROMPT_DETAIL_CHARS,
+    );
+    let window = cx
+        .update(|cx| cx.active_window())?
+        .context("no window to ask for approval")?;
+    let answer = cx.update_window(window, |_, cx| {
+        cx.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(&detail),
+            &["Allow", "Deny"],
+        )
+    })?;
+    if answer.await? != 0 {
+        return Err(anyhow!("the user denied the sampling request"));
+    }
+
+    let mut stream = model.stream_completion_text(request, &cx).await?.stream;
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        text.push_str(&chunk?);
+    }
+
+    Ok(CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model_name,
+        stop_reason: Some("endTurn".into()),
+    })
+}
+
+fn completion_request(request: CreateMessageRequest) -> Result<LanguageModelRequest> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+        });
+    }
+    for message in request.messages {
+        let types::MessageContent::Text { text, .. } = message.content else {
+            return Err(anyhow!("only text messages are supported for sampling"));
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![text.into()],
+            cache: false,
+        });
+    }
+
+    Ok(LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_completion_request() {
+        let request: CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Hello" } },
+                { "role": "assistant", "content": { "type": "text", "text": "Hi!" } },
+                { "role": "user", "content": { "type": "text", "text": "Summarize this" } }
+            ],
+            "systemPrompt": "Be brief.",
+            "maxTokens": 100,
+            "stopSequences": ["\n\n"],
+            "temperature": 0.5
+        }))
+        .unwrap();
+
+        let request = completion_request(request).unwrap();
+        assert_eq!(
+            request
+                .messages
+                .iter()
+                .map(|message| (message.role, message.string_contents()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Role::System, "Be brief.".to_string()),
+                (Role::User, "Hello".to_string()),
+                (Role::Assistant, "Hi!".to_string()),
+                (Role::User, "Summarize this".to_string()),
+            ]
+        );
+        assert_eq!(request.stop, vec!["\n\n".to_string()]);
+        assert_eq!(request.temperature, Some(0.5));
+
+        let request: CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [
+                {
+                    "role": "user",
+                    "content": { "type": "image", "data": "", "mimeType": "image/png" }
+                }
+            ],
+            "maxTokens": 100
+        }))
+        .unwrap();
+        assert!(completion_request(request).is_err());
+    }
+}
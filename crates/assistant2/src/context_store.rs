@@ -1,19 +1,26 @@
+use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
 use collections::{BTreeMap, HashMap, HashSet};
+use context_server::manager::{self, ContextServerManager};
+use context_server::types::ResourceContentsType;
 use futures::{self, future, Future, FutureExt};
-use gpui::{AppContext, AsyncAppContext, Model, ModelContext, SharedString, Task, WeakView};
+use gpui::{
+    AppContext, AsyncAppContext, Model, ModelContext, SharedString, Subscription, Task, WeakView,
+};
 use language::Buffer;
 use project::{ProjectPath, Worktree};
 use rope::Rope;
 use text::BufferId;
+use url::Url;
+use util::ResultExt as _;
 use workspace::Workspace;
 
 use crate::context::{
     Context, ContextBuffer, ContextId, ContextSnapshot, DirectoryContext, FetchedUrlContext,
-    FileContext, ThreadContext,
+    FileContext, ResourceContext, ThreadContext,
 };
 use crate::context_strip::SuggestedContext;
 use crate::thread::{Thread, ThreadId};
@@ -27,6 +34,8 @@ pub struct ContextStore {
     directories: HashMap<PathBuf, ContextId>,
     threads: HashMap<ThreadId, ContextId>,
     fetched_urls: HashMap<String, ContextId>,
+    resources: HashMap<(Arc<str>, Url), ContextId>,
+    context_server_subscription: Option<Subscription>,
 }
 
 impl ContextStore {
@@ -39,6 +48,8 @@ impl ContextStore {
             directories: HashMap::default(),
             threads: HashMap::default(),
             fetched_urls: HashMap::default(),
+            resources: HashMap::default(),
+            context_server_subscription: None,
         }
     }
 
@@ -55,12 +66,17 @@ impl ContextStore {
         &self.context
     }
 
-    pub fn clear(&mut self) {
-        self.context.clear();
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        for context in self.context.drain(..) {
+            if let Context::Resource(resource) = context {
+                Self::unsubscribe_from_resource(resource, cx);
+            }
+        }
         self.files.clear();
         self.directories.clear();
         self.threads.clear();
         self.fetched_urls.clear();
+        self.resources.clear();
     }
 
     pub fn add_file_from_path(
@@ -85,10 +101,10 @@ impl ContextStore {
             let buffer_model = open_buffer_task.await?;
             let buffer_id = this.update(&mut cx, |_, cx| buffer_model.read(cx).remote_id())?;
 
-            let already_included = this.update(&mut cx, |this, _cx| {
+            let already_included = this.update(&mut cx, |this, cx| {
                 match this.will_include_buffer(buffer_id, &project_path.path) {
                     Some(FileInclusion::Direct(context_id)) => {
-                        this.remove_context(context_id);
+                        this.remove_context(context_id, cx);
                         true
                     }
                     Some(FileInclusion::InDirectory(_)) => true,
@@ -171,7 +187,7 @@ impl ContextStore {
 
         let already_included = if let Some(context_id) = self.includes_directory(&project_path.path)
         {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
             true
         } else {
             false
@@ -253,7 +269,7 @@ impl ContextStore {
 
     pub fn add_thread(&mut self, thread: Model<Thread>, cx: &mut ModelContext<Self>) {
         if let Some(context_id) = self.includes_thread(&thread.read(cx).id()) {
-            self.remove_context(context_id);
+            self.remove_context(context_id, cx);
         } else {
             self.insert_thread(thread, cx);
         }
@@ -285,6 +301,106 @@ impl ContextStore {
         }));
     }
 
+    /// Adds the resource with the given URI from a context server, or removes it
+    /// if it is already included. Included resources are refreshed whenever the
+    /// server reports that they changed.
+    pub fn add_resource(
+        &mut self,
+        context_server_manager: Model<ContextServerManager>,
+        server_id: Arc<str>,
+        uri: Url,
+        name: SharedString,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if let Some(context_id) = self.includes_resource(&server_id, &uri) {
+            self.remove_context(context_id, cx);
+            return Task::ready(Ok(()));
+        }
+
+        let Some(server) = context_server_manager.read(cx).get_server(&server_id) else {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        };
+        if self.context_server_subscription.is_none() {
+            self.context_server_subscription =
+                Some(cx.subscribe(&context_server_manager, Self::handle_context_server_event));
+        }
+
+        let context_server_manager = context_server_manager.downgrade();
+        cx.spawn(|this, mut cx| async move {
+            let protocol = server
+                .client()
+                .with_context(|| format!("context server {server_id} is not running"))?;
+            let response = protocol.read_resource(uri.clone()).await?;
+            server.subscribe_to_resource(uri.clone()).await.log_err();
+
+            this.update(&mut cx, |this, _cx| {
+                this.insert_resource(ResourceContext {
+                    id: this.next_context_id.post_inc(),
+                    context_server_manager,
+                    server_id,
+                    uri,
+                    name,
+                    text: resource_text(response.contents).into(),
+                });
+            })?;
+
+            anyhow::Ok(())
+        })
+    }
+
+    fn insert_resource(&mut self, resource: ResourceContext) {
+        self.resources.insert(
+            (resource.server_id.clone(), resource.uri.clone()),
+            resource.id,
+        );
+        self.context.push(Context::Resource(resource));
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        context_server_manager: Model<ContextServerManager>,
+        event: &manager::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let manager::Event::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+        let Some(context_id) = self.includes_resource(server_id, uri) else {
+            return;
+        };
+        let Some(server) = context_server_manager.read(cx).get_server(server_id) else {
+            return;
+        };
+
+        let uri = uri.clone();
+        cx.spawn(|this, mut cx| async move {
+            let protocol = server.client().context("context server is not running")?;
+            let response = protocol.read_resource(uri).await?;
+            this.update(&mut cx, |this, _cx| {
+                if let Some(Context::Resource(resource)) = this
+                    .context
+                    .iter_mut()
+                    .find(|context| context.id() == context_id)
+                {
+                    resource.text = resource_text(response.contents).into();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn unsubscribe_from_resource(resource: ResourceContext, cx: &mut ModelContext<Self>) {
+        let Some(server) = resource
+            .context_server_manager
+            .upgrade()
+            .and_then(|manager| manager.read(cx).get_server(&resource.server_id))
+        else {
+            return;
+        };
+        cx.spawn(|_, _| async move { server.unsubscribe_from_resource(resource.uri).await })
+            .detach_and_log_err(cx);
+    }
+
     pub fn accept_suggested_context(
         &mut self,
         suggested: &SuggestedContext,
@@ -309,7 +425,7 @@ impl ContextStore {
         Task::ready(Ok(()))
     }
 
-    pub fn remove_context(&mut self, id: ContextId) {
+    pub fn remove_context(&mut self, id: ContextId, cx: &mut ModelContext<Self>) {
         let Some(ix) = self.context.iter().position(|context| context.id() == id) else {
             return;
         };
@@ -327,6 +443,10 @@ impl ContextStore {
             Context::Thread(_) => {
                 self.threads.retain(|_, context_id| *context_id != id);
             }
+            Context::Resource(resource) => {
+                self.resources.retain(|_, context_id| *context_id != id);
+                Self::unsubscribe_from_resource(resource, cx);
+            }
         }
     }
 
@@ -392,6 +512,12 @@ impl ContextStore {
         self.fetched_urls.get(url).copied()
     }
 
+    pub fn includes_resource(&self, server_id: &Arc<str>, uri: &Url) -> Option<ContextId> {
+        self.resources
+            .get(&(server_id.clone(), uri.clone()))
+            .copied()
+    }
+
     /// Replaces the context that matches the ID of the new context, if any match.
     fn replace_context(&mut self, new_context: Context) {
         let id = new_context.id();
@@ -411,7 +537,10 @@ impl ContextStore {
                     let buffer = file.context_buffer.buffer.read(cx);
                     buffer_path_log_err(buffer).map(|p| p.to_path_buf())
                 }
-                Context::Directory(_) | Context::FetchedUrl(_) | Context::Thread(_) => None,
+                Context::Directory(_)
+                | Context::FetchedUrl(_)
+                | Context::Thread(_)
+                | Context::Resource(_) => None,
             })
             .collect()
     }
@@ -510,6 +639,35 @@ fn to_fenced_codeblock(path: &Path, content: Rope) -> SharedString {
     buffer.into()
 }
 
+/// Formats the contents of a resource for the model, omitting binary data.
+fn resource_text(contents: Vec<ResourceContentsType>) -> String {
+    let mut text = String::new();
+    for content in contents {
+        match content {
+            ResourceContentsType::Text(content) => {
+                text.push_str("```");
+                text.push_str(content.uri.as_str());
+                text.push('\n');
+                text.push_str(&content.text);
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str("```\n");
+            }
+            ResourceContentsType::Blob(content) => {
+                writeln!(
+                    text,
+                    "{} contains binary data of type {} that was omitted.",
+                    content.uri,
+                    content.mime_type.as_deref().unwrap_or("unknown")
+                )
+                .ok();
+            }
+        }
+    }
+    text
+}
+
 fn collect_files_in_path(worktree: &Worktree, path: &Path) -> Vec<Arc<Path>> {
     let mut files = Vec::new();
 
@@ -551,6 +709,8 @@ pub fn refresh_context_store_text(
             // and doing the caching properly could be tricky (unless it's already handled by
             // the HttpClient?).
             Context::FetchedUrl(_) => {}
+            // Resources are refreshed as soon as the context server reports a change.
+            Context::Resource(_) => {}
         }
     }
 
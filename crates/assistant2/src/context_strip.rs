@@ -280,9 +280,9 @@ impl ContextStrip {
         if let Some(index) = self.focused_index {
             let mut is_empty = false;
 
-            self.context_store.update(cx, |this, _cx| {
+            self.context_store.update(cx, |this, cx| {
                 if let Some(item) = this.context().get(index) {
-                    this.remove_context(item.id());
+                    this.remove_context(item.id(), cx);
                 }
 
                 is_empty = this.context().is_empty();
@@ -444,8 +444,8 @@ impl Render for ContextStrip {
                         let id = context.id;
                         let context_store = self.context_store.clone();
                         Rc::new(cx.listener(move |_this, _event, cx| {
-                            context_store.update(cx, |this, _cx| {
-                                this.remove_context(id);
+                            context_store.update(cx, |this, cx| {
+                                this.remove_context(id, cx);
                             });
                             cx.notify();
                         }))
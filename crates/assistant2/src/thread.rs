@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{Tool, ToolPermission, ToolPermissionSettings, ToolWorkingSet};
 use chrono::{DateTime, Utc};
 use collections::{BTreeMap, HashMap, HashSet};
 use futures::future::Shared;
@@ -72,6 +72,7 @@ pub struct Thread {
     tool_uses_by_message: HashMap<MessageId, Vec<LanguageModelToolUse>>,
     tool_results_by_message: HashMap<MessageId, Vec<LanguageModelToolResult>>,
     pending_tool_uses_by_id: HashMap<LanguageModelToolUseId, PendingToolUse>,
+    tool_use_log: Vec<ToolUseLogEntry>,
 }
 
 impl Thread {
@@ -91,6 +92,7 @@ impl Thread {
             tool_uses_by_message: HashMap::default(),
             tool_results_by_message: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            tool_use_log: Vec::new(),
         }
     }
 
@@ -155,6 +157,25 @@ impl Thread {
         self.pending_tool_uses_by_id.values().collect()
     }
 
+    pub fn pending_tool_use(&self, id: &LanguageModelToolUseId) -> Option<&PendingToolUse> {
+        self.pending_tool_uses_by_id.get(id)
+    }
+
+    /// Returns the tool uses requested by the given message, in the order they were requested.
+    pub fn tool_uses_for_message(&self, id: MessageId) -> Vec<&PendingToolUse> {
+        self.tool_uses_by_message
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|tool_use| self.pending_tool_uses_by_id.get(&tool_use.id))
+            .collect()
+    }
+
+    /// Returns every tool use that was allowed or denied in this thread, along with its outcome.
+    pub fn tool_use_log(&self) -> &[ToolUseLogEntry] {
+        &self.tool_use_log
+    }
+
     pub fn insert_user_message(
         &mut self,
         text: impl Into<String>,
@@ -201,6 +222,16 @@ impl Thread {
 
             text.push_str(&message.text);
             text.push('\n');
+
+            for entry in &self.tool_use_log {
+                if entry.assistant_message_id == message.id {
+                    text.push_str(&format!(
+                        "[Tool use: {} ({})]\n",
+                        entry.name,
+                        entry.outcome.label()
+                    ));
+                }
+            }
         }
 
         text
@@ -319,28 +350,7 @@ impl Thread {
                                 }
                             }
                             LanguageModelCompletionEvent::ToolUse(tool_use) => {
-                                if let Some(last_assistant_message) = thread
-                                    .messages
-                                    .iter()
-                                    .rfind(|message| message.role == Role::Assistant)
-                                {
-                                    thread
-                                        .tool_uses_by_message
-                                        .entry(last_assistant_message.id)
-                                        .or_default()
-                                        .push(tool_use.clone());
-
-                                    thread.pending_tool_uses_by_id.insert(
-                                        tool_use.id.clone(),
-                                        PendingToolUse {
-                                            assistant_message_id: last_assistant_message.id,
-                                            id: tool_use.id,
-                                            name: tool_use.name,
-                                            input: tool_use.input,
-                                            status: PendingToolUseStatus::Idle,
-                                        },
-                                    );
-                                }
+                                thread.insert_tool_use(tool_use);
                             }
                         }
 
@@ -457,52 +467,161 @@ impl Thread {
         });
     }
 
+    /// Adds a tool use requested by the last Assistant message.
+    fn insert_tool_use(&mut self, tool_use: LanguageModelToolUse) {
+        let Some(last_assistant_message) = self
+            .messages
+            .iter()
+            .rfind(|message| message.role == Role::Assistant)
+        else {
+            return;
+        };
+
+        self.tool_uses_by_message
+            .entry(last_assistant_message.id)
+            .or_default()
+            .push(tool_use.clone());
+
+        self.pending_tool_uses_by_id.insert(
+            tool_use.id.clone(),
+            PendingToolUse {
+                assistant_message_id: last_assistant_message.id,
+                id: tool_use.id,
+                name: tool_use.name,
+                input: tool_use.input,
+                status: PendingToolUseStatus::Idle,
+            },
+        );
+    }
+
+    /// Checks the permissions of the tool uses that haven't been handled yet.
+    ///
+    /// Denied tool uses are reported to the model and the ones that need the user's
+    /// confirmation wait for it. Returns the tool uses that may run right away.
+    pub fn check_tool_permissions(
+        &mut self,
+        settings: &ToolPermissionSettings,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<(PendingToolUse, Arc<dyn Tool>, ToolUseApproval)> {
+        let pending_tool_uses = self
+            .pending_tool_uses()
+            .into_iter()
+            .filter(|tool_use| tool_use.status.is_idle())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut allowed_tool_uses = Vec::new();
+        for tool_use in pending_tool_uses {
+            let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                continue;
+            };
+
+            if self.tools.is_always_allowed(&tool_use.name) {
+                allowed_tool_uses.push((tool_use, tool, ToolUseApproval::User));
+                continue;
+            }
+
+            match settings.permission(&tool_use.name, tool.risk_level()) {
+                ToolPermission::Allow => {
+                    allowed_tool_uses.push((tool_use, tool, ToolUseApproval::Settings));
+                }
+                ToolPermission::Deny => {
+                    self.deny_tool_use(tool_use.id, ToolUseApproval::Settings, cx);
+                }
+                ToolPermission::Confirm => {
+                    self.request_tool_use_confirmation(&tool_use.id, cx);
+                }
+            }
+        }
+        allowed_tool_uses
+    }
+
+    /// Marks the tool use as waiting for the user to allow or deny it.
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
+    /// Denies the tool use, reporting the denial to the model as the tool's result.
+    pub fn deny_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        approval: ToolUseApproval,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(&tool_use_id) else {
+            return;
+        };
+        tool_use.status = PendingToolUseStatus::Denied;
+        let assistant_message_id = tool_use.assistant_message_id;
+        self.log_tool_use(&tool_use_id, approval, ToolUseOutcome::Denied);
+
+        let content = match approval {
+            ToolUseApproval::Settings => "The user's settings don't allow running this tool.",
+            ToolUseApproval::User => "The user denied permission to run this tool.",
+        };
+        self.push_tool_result(
+            assistant_message_id,
+            &tool_use_id,
+            content.to_string(),
+            true,
+        );
+
+        cx.emit(ThreadEvent::ToolFinished { tool_use_id });
+        cx.notify();
+    }
+
     pub fn insert_tool_output(
         &mut self,
         assistant_message_id: MessageId,
         tool_use_id: LanguageModelToolUseId,
+        approval: ToolUseApproval,
         output: Task<Result<String>>,
         cx: &mut ModelContext<Self>,
     ) {
+        self.log_tool_use(&tool_use_id, approval, ToolUseOutcome::Running);
+
         let insert_output_task = cx.spawn(|thread, mut cx| {
             let tool_use_id = tool_use_id.clone();
             async move {
                 let output = output.await;
                 thread
-                    .update(&mut cx, |thread, cx| {
-                        // The tool use was requested by an Assistant message,
-                        // so we want to attach the tool results to the next
-                        // user message.
-                        let next_user_message = MessageId(assistant_message_id.0 + 1);
-
-                        let tool_results = thread
-                            .tool_results_by_message
-                            .entry(next_user_message)
-                            .or_default();
-
-                        match output {
-                            Ok(output) => {
-                                tool_results.push(LanguageModelToolResult {
-                                    tool_use_id: tool_use_id.to_string(),
-                                    content: output,
-                                    is_error: false,
-                                });
-
-                                cx.emit(ThreadEvent::ToolFinished { tool_use_id });
-                            }
-                            Err(err) => {
-                                tool_results.push(LanguageModelToolResult {
-                                    tool_use_id: tool_use_id.to_string(),
-                                    content: err.to_string(),
-                                    is_error: true,
-                                });
-
-                                if let Some(tool_use) =
-                                    thread.pending_tool_uses_by_id.get_mut(&tool_use_id)
-                                {
-                                    tool_use.status = PendingToolUseStatus::Error(err.to_string());
-                                }
-                            }
+                    .update(&mut cx, |thread, cx| match output {
+                        Ok(output) => {
+                            thread.push_tool_result(
+                                assistant_message_id,
+                                &tool_use_id,
+                                output,
+                                false,
+                            );
+                            thread.set_tool_use_outcome(
+                                &tool_use_id,
+                                PendingToolUseStatus::Finished,
+                                ToolUseOutcome::Succeeded,
+                            );
+
+                            cx.emit(ThreadEvent::ToolFinished { tool_use_id });
+                            cx.notify();
+                        }
+                        Err(err) => {
+                            thread.push_tool_result(
+                                assistant_message_id,
+                                &tool_use_id,
+                                err.to_string(),
+                                true,
+                            );
+                            thread.set_tool_use_outcome(
+                                &tool_use_id,
+                                PendingToolUseStatus::Error(err.to_string()),
+                                ToolUseOutcome::Failed(err.to_string().into()),
+                            );
+                            cx.notify();
                         }
                     })
                     .ok();
@@ -516,6 +635,65 @@ impl Thread {
         }
     }
 
+    fn push_tool_result(
+        &mut self,
+        assistant_message_id: MessageId,
+        tool_use_id: &LanguageModelToolUseId,
+        content: String,
+        is_error: bool,
+    ) {
+        // The tool use was requested by an Assistant message,
+        // so we want to attach the tool results to the next
+        // user message.
+        let next_user_message = MessageId(assistant_message_id.0 + 1);
+
+        self.tool_results_by_message
+            .entry(next_user_message)
+            .or_default()
+            .push(LanguageModelToolResult {
+                tool_use_id: tool_use_id.to_string(),
+                content,
+                is_error,
+            });
+    }
+
+    fn log_tool_use(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        approval: ToolUseApproval,
+        outcome: ToolUseOutcome,
+    ) {
+        let Some(tool_use) = self.pending_tool_uses_by_id.get(tool_use_id) else {
+            return;
+        };
+        self.tool_use_log.push(ToolUseLogEntry {
+            tool_use_id: tool_use_id.clone(),
+            assistant_message_id: tool_use.assistant_message_id,
+            name: tool_use.name.clone(),
+            input: tool_use.input.clone(),
+            approval,
+            outcome,
+        });
+    }
+
+    fn set_tool_use_outcome(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        status: PendingToolUseStatus,
+        outcome: ToolUseOutcome,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = status;
+        }
+        if let Some(entry) = self
+            .tool_use_log
+            .iter_mut()
+            .rfind(|entry| &entry.tool_use_id == tool_use_id)
+        {
+            entry.outcome = outcome;
+        }
+    }
+
     /// Cancels the last pending completion, if there are any pending.
     ///
     /// Returns whether a completion was canceled.
@@ -569,12 +747,242 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
+    NeedsConfirmation,
     Running { _task: Shared<Task<()>> },
-    Error(#[allow(unused)] String),
+    Finished,
+    Denied,
+    Error(String),
 }
 
 impl PendingToolUseStatus {
     pub fn is_idle(&self) -> bool {
         matches!(self, PendingToolUseStatus::Idle)
     }
+
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::NeedsConfirmation)
+    }
+}
+
+/// A record of a tool use that was allowed or denied, kept in the thread's history.
+#[derive(Debug, Clone)]
+pub struct ToolUseLogEntry {
+    pub tool_use_id: LanguageModelToolUseId,
+    /// The ID of the Assistant message in which the tool use was requested.
+    pub assistant_message_id: MessageId,
+    pub name: String,
+    pub input: serde_json::Value,
+    pub approval: ToolUseApproval,
+    pub outcome: ToolUseOutcome,
+}
+
+/// Who decided whether a tool use could run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolUseApproval {
+    /// The tool permission settings allowed or denied the tool use.
+    Settings,
+    /// The user allowed or denied the tool use when asked for confirmation.
+    User,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolUseOutcome {
+    Running,
+    Succeeded,
+    Failed(SharedString),
+    Denied,
+}
+
+impl ToolUseOutcome {
+    pub fn label(&self) -> SharedString {
+        match self {
+            ToolUseOutcome::Running => "running".into(),
+            ToolUseOutcome::Succeeded => "succeeded".into(),
+            ToolUseOutcome::Failed(error) => format!("failed: {error}").into(),
+            ToolUseOutcome::Denied => "denied".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assistant_tool::{ToolRegistry, ToolRiskLevel};
+    use gpui::{Context as _, TestAppContext, WeakView, WindowContext};
+    use workspace::Workspace;
+
+    struct TestTool {
+        name: &'static str,
+        risk_level: ToolRiskLevel,
+    }
+
+    impl Tool for TestTool {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn description(&self) -> String {
+            String::new()
+        }
+
+        fn risk_level(&self) -> ToolRiskLevel {
+            self.risk_level
+        }
+
+        fn run(
+            self: Arc<Self>,
+            _input: serde_json::Value,
+            _workspace: WeakView<Workspace>,
+            _cx: &mut WindowContext,
+        ) -> Task<Result<String>> {
+            Task::ready(Ok(String::new()))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_tool_permissions(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let registry = ToolRegistry::default_global(cx);
+            for (name, risk_level) in [
+                ("read_file", ToolRiskLevel::ReadOnly),
+                ("regex_search", ToolRiskLevel::ReadOnly),
+                ("edit_files", ToolRiskLevel::Write),
+                ("delete_path", ToolRiskLevel::Destructive),
+                ("run_command", ToolRiskLevel::Destructive),
+            ] {
+                registry.register_tool(TestTool { name, risk_level });
+            }
+        });
+        let tools = Arc::new(ToolWorkingSet::default());
+        tools.always_allow("run_command");
+        let settings = ToolPermissionSettings {
+            auto_approve_read_only: true,
+            allow: vec!["edit_files".into()],
+            deny: vec!["regex_search".into()],
+        };
+
+        let thread = cx.new_model(|cx| Thread::new(tools.clone(), cx));
+        let assistant_message_id = thread.update(cx, |thread, cx| {
+            let message_id = thread.insert_message(Role::Assistant, "Let me check.", cx);
+            for name in [
+                "read_file",
+                "regex_search",
+                "edit_files",
+                "delete_path",
+                "run_command",
+            ] {
+                thread.insert_tool_use(LanguageModelToolUse {
+                    id: name.into(),
+                    name: name.into(),
+                    input: serde_json::Value::Null,
+                });
+            }
+            message_id
+        });
+
+        let mut allowed_tool_uses = thread.update(cx, |thread, cx| {
+            thread
+                .check_tool_permissions(&settings, cx)
+                .into_iter()
+                .map(|(tool_use, _, approval)| (tool_use.name, approval))
+                .collect::<Vec<_>>()
+        });
+        allowed_tool_uses.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            allowed_tool_uses,
+            [
+                ("edit_files".to_string(), ToolUseApproval::Settings),
+                ("read_file".to_string(), ToolUseApproval::Settings),
+                ("run_command".to_string(), ToolUseApproval::User),
+            ]
+        );
+
+        thread.update(cx, |thread, cx| {
+            let status = |thread: &Thread, id: &str| {
+                thread.pending_tool_use(&id.into()).unwrap().status.clone()
+            };
+            assert!(status(thread, "delete_path").needs_confirmation());
+            assert!(matches!(
+                status(thread, "regex_search"),
+                PendingToolUseStatus::Denied
+            ));
+
+            // Tool uses that were already handled aren't checked again.
+            assert!(thread.check_tool_permissions(&settings, cx).is_empty());
+
+            thread.deny_tool_use("delete_path".into(), ToolUseApproval::User, cx);
+            assert!(matches!(
+                status(thread, "delete_path"),
+                PendingToolUseStatus::Denied
+            ));
+
+            thread.insert_tool_output(
+                assistant_message_id,
+                "read_file".into(),
+                ToolUseApproval::Settings,
+                Task::ready(Ok("contents".into())),
+                cx,
+            );
+        });
+        cx.run_until_parked();
+
+        thread.read_with(cx, |thread, _| {
+            assert!(matches!(
+                thread.pending_tool_use(&"read_file".into()).unwrap().status,
+                PendingToolUseStatus::Finished
+            ));
+
+            let tool_results = thread
+                .tool_results_by_message
+                .get(&MessageId(assistant_message_id.0 + 1))
+                .unwrap()
+                .iter()
+                .map(|result| {
+                    (
+                        result.tool_use_id.as_str(),
+                        result.content.as_str(),
+                        result.is_error,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tool_results,
+                [
+                    (
+                        "regex_search",
+                        "The user's settings don't allow running this tool.",
+                        true
+                    ),
+                    (
+                        "delete_path",
+                        "The user denied permission to run this tool.",
+                        true
+                    ),
+                    ("read_file", "contents", false),
+                ]
+            );
+
+            let log = thread
+                .tool_use_log()
+                .iter()
+                .map(|entry| (entry.name.as_str(), entry.approval, entry.outcome.clone()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                log,
+                [
+                    (
+                        "regex_search",
+                        ToolUseApproval::Settings,
+                        ToolUseOutcome::Denied
+                    ),
+                    ("delete_path", ToolUseApproval::User, ToolUseOutcome::Denied),
+                    (
+                        "read_file",
+                        ToolUseApproval::Settings,
+                        ToolUseOutcome::Succeeded
+                    ),
+                ]
+            );
+        });
+    }
 }
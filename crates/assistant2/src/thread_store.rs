@@ -10,6 +10,7 @@ use project::Project;
 use unindent::Unindent;
 use util::ResultExt as _;
 
+use crate::context_server_sampling::sampling_handler;
 use crate::thread::{Thread, ThreadId};
 
 pub struct ThreadStore {
@@ -32,7 +33,13 @@ impl ThreadStore {
                 let context_server_factory_registry =
                     ContextServerFactoryRegistry::default_global(cx);
                 let context_server_manager = cx.new_model(|cx| {
-                    ContextServerManager::new(context_server_factory_registry, project.clone(), cx)
+                    let mut manager = ContextServerManager::new(
+                        context_server_factory_registry,
+                        project.clone(),
+                        cx,
+                    );
+                    manager.set_sampling_handler(sampling_handler());
+                    manager
                 });
 
                 let mut this = Self {
@@ -52,6 +59,10 @@ impl ThreadStore {
         })
     }
 
+    pub fn context_server_manager(&self) -> Model<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
     /// Returns the number of non-empty threads.
     pub fn non_empty_len(&self, cx: &AppContext) -> usize {
         self.threads
@@ -155,6 +166,7 @@ impl ThreadStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -1,11 +1,11 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_settings::AssistantSettings;
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection, SlashCommandWorkingSet};
 use assistant_slash_commands::{
     selections_creases, DefaultSlashCommand, DocsSlashCommand, DocsSlashCommandArgs,
     FileSlashCommand,
 };
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{ToolPermission, ToolPermissionSettings, ToolWorkingSet};
 use client::{proto, zed_urls};
 use collections::{hash_map, BTreeSet, HashMap, HashSet};
 use editor::{
@@ -728,7 +728,26 @@ impl ContextEditor {
 
                 for tool_use in pending_tool_uses {
                     if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+                        let permission = if self.tools.is_always_allowed(&tool_use.name) {
+                            ToolPermission::Allow
+                        } else {
+                            ToolPermissionSettings::for_project(&self.project, cx)
+                                .permission(&tool_use.name, tool.risk_level())
+                        };
+
+                        // Text threads have no way to ask the user for confirmation,
+                        // so only the tools allowed by the settings run here.
+                        let task = match permission {
+                            ToolPermission::Allow => {
+                                tool.run(tool_use.input, self.workspace.clone(), cx)
+                            }
+                            ToolPermission::Deny => Task::ready(Err(anyhow!(
+                                "The user's settings don't allow running this tool."
+                            ))),
+                            ToolPermission::Confirm => Task::ready(Err(anyhow!(
+                                "This tool needs the user's confirmation to run, which is not available in text threads."
+                            ))),
+                        };
 
                         self.context.update(cx, |context, cx| {
                             context.insert_tool_output(tool_use.id.clone(), task, cx);
@@ -15,7 +15,7 @@ use language::{Anchor, CodeLabel, LspAdapterDelegate};
 use language_model::{LanguageModelRegistry, LanguageModelTool};
 use prompt_library::PromptBuilder;
 use schemars::JsonSchema;
-use semantic_index::SemanticDb;
+use semantic_index::{SearchFilter, SemanticDb};
 use serde::Deserialize;
 use ui::prelude::*;
 use workspace::Workspace;
@@ -122,7 +122,7 @@ impl SlashCommand for ProjectSlashCommand {
 
             let results = project_index
                 .read_with(&cx, |project_index, cx| {
-                    project_index.search(search_queries.clone(), 25, cx)
+                    project_index.search(search_queries.clone(), 25, SearchFilter::default(), cx)
                 })?
                 .await?;
 
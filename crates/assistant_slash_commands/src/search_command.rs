@@ -6,12 +6,13 @@ use assistant_slash_command::{
 use feature_flags::FeatureFlag;
 use gpui::{AppContext, Task, WeakView};
 use language::{CodeLabel, LspAdapterDelegate};
-use semantic_index::{LoadedSearchResult, SemanticDb};
+use semantic_index::{LoadedSearchResult, SearchFilter, SemanticDb};
 use std::{
     fmt::Write,
     sync::{atomic::AtomicBool, Arc},
 };
 use ui::{prelude::*, IconName};
+use util::paths::PathMatcher;
 use workspace::Workspace;
 
 use crate::create_label_for_command;
@@ -35,7 +36,7 @@ impl SlashCommand for SearchSlashCommand {
     }
 
     fn label(&self, cx: &AppContext) -> CodeLabel {
-        create_label_for_command("search", &["--n"], cx)
+        create_label_for_command("search", &["--n", "--path=glob", "--language=name"], cx)
     }
 
     fn description(&self) -> String {
@@ -81,6 +82,8 @@ impl SlashCommand for SearchSlashCommand {
         };
 
         let mut limit = None;
+        let mut globs = Vec::new();
+        let mut filter = SearchFilter::default();
         let mut query = String::new();
         for part in arguments {
             if let Some(parameter) = part.strip_prefix("--") {
@@ -88,6 +91,14 @@ impl SlashCommand for SearchSlashCommand {
                     limit = Some(count);
                     continue;
                 }
+                if let Some(glob) = parameter.strip_prefix("path=") {
+                    globs.push(glob.to_string());
+                    continue;
+                }
+                if let Some(language) = parameter.strip_prefix("language=") {
+                    filter.languages.push(language.to_string());
+                    continue;
+                }
             }
 
             query.push_str(part);
@@ -98,6 +109,10 @@ impl SlashCommand for SearchSlashCommand {
         if query.is_empty() {
             return Task::ready(Err(anyhow::anyhow!("missing search query")));
         }
+        filter.files_to_include = match PathMatcher::new(&globs) {
+            Ok(matcher) => matcher,
+            Err(error) => return Task::ready(Err(anyhow::anyhow!(error))),
+        };
 
         let project = workspace.read(cx).project().clone();
         let fs = project.read(cx).fs().clone();
@@ -110,7 +125,7 @@ impl SlashCommand for SearchSlashCommand {
         cx.spawn(|cx| async move {
             let results = project_index
                 .read_with(&cx, |project_index, cx| {
-                    project_index.search(vec![query.clone()], limit.unwrap_or(5), cx)
+                    project_index.search(vec![query.clone()], limit.unwrap_or(5), filter, cx)
                 })?
                 .await?;
 
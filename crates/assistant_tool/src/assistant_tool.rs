@@ -1,3 +1,4 @@
+mod tool_permissions;
 mod tool_registry;
 mod tool_working_set;
 
@@ -5,15 +6,31 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use gpui::{AppContext, Task, WeakView, WindowContext};
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use workspace::Workspace;
 
+pub use crate::tool_permissions::*;
 pub use crate::tool_registry::*;
 pub use crate::tool_working_set::*;
 
 pub fn init(cx: &mut AppContext) {
+    ToolPermissionSettings::register(cx);
     ToolRegistry::default_global(cx);
 }
 
+/// How much running a [`Tool`] can affect the user's project or machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolRiskLevel {
+    /// The tool only reads information.
+    ReadOnly,
+    /// The tool changes the project, e.g. by editing files.
+    Write,
+    /// The tool can make changes that are hard to undo, e.g. by running commands.
+    Destructive,
+}
+
 /// A tool that can be used by a language model.
 pub trait Tool: 'static + Send + Sync {
     /// Returns the name of the tool.
@@ -22,6 +39,11 @@ pub trait Tool: 'static + Send + Sync {
     /// Returns the description of the tool.
     fn description(&self) -> String;
 
+    /// Returns how much running the tool can affect the user's project or machine.
+    ///
+    /// This decides whether the tool needs the user's confirmation before it runs.
+    fn risk_level(&self) -> ToolRiskLevel;
+
     /// Returns the JSON schema that describes the tool's input.
     fn input_schema(&self) -> serde_json::Value {
         serde_json::Value::Object(serde_json::Map::default())
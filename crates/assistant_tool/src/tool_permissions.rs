@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use anyhow::Result;
+use gpui::{AppContext, Model};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsLocation, SettingsSources};
+
+use crate::ToolRiskLevel;
+
+/// Settings controlling which tools the assistant may run without asking.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ToolPermissionSettings {
+    pub auto_approve_read_only: bool,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// The `tool_permissions` settings.
+///
+/// Project settings in `.zed/settings.json` can only restrict the tools that run
+/// without asking: they may add tools to `deny` or turn `auto_approve_read_only` off.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct ToolPermissionSettingsContent {
+    /// Whether tools that only read from the project run without asking for confirmation.
+    ///
+    /// Default: true
+    pub auto_approve_read_only: Option<bool>,
+    /// The names of the tools that always run without asking for confirmation.
+    /// Ignored in project settings.
+    ///
+    /// Default: []
+    pub allow: Option<Vec<String>>,
+    /// The names of the tools that are never run. Takes precedence over `allow`.
+    ///
+    /// Default: []
+    pub deny: Option<Vec<String>>,
+}
+
+/// Whether a tool use may run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolPermission {
+    /// The tool runs right away.
+    Allow,
+    /// The tool must not run.
+    Deny,
+    /// The user has to approve the tool use before it runs.
+    Confirm,
+}
+
+impl ToolPermissionSettings {
+    /// Returns the tool permissions for the given project, including the
+    /// restrictions from the settings of its first visible worktree.
+    pub fn for_project<'a>(project: &Model<Project>, cx: &'a AppContext) -> &'a Self {
+        let worktree_id = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).id());
+
+        Self::get(
+            worktree_id.map(|worktree_id| SettingsLocation {
+                worktree_id,
+                path: Path::new(""),
+            }),
+            cx,
+        )
+    }
+
+    /// Returns whether the tool with the given name and risk level may run.
+    pub fn permission(&self, tool_name: &str, risk_level: ToolRiskLevel) -> ToolPermission {
+        if self.deny.iter().any(|name| name == tool_name) {
+            ToolPermission::Deny
+        } else if self.allow.iter().any(|name| name == tool_name) {
+            ToolPermission::Allow
+        } else if risk_level == ToolRiskLevel::ReadOnly && self.auto_approve_read_only {
+            ToolPermission::Allow
+        } else {
+            ToolPermission::Confirm
+        }
+    }
+}
+
+impl Settings for ToolPermissionSettings {
+    const KEY: Option<&'static str> = Some("tool_permissions");
+
+    type FileContent = ToolPermissionSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        let mut settings: Self = SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.extensions)
+                .chain(sources.user)
+                .chain(sources.release_channel)
+                .chain(sources.server),
+        )?;
+
+        // Project settings come with the repository, so they must not be able to
+        // let tools run without the user's confirmation.
+        for project in sources.project {
+            if project.auto_approve_read_only == Some(false) {
+                settings.auto_approve_read_only = false;
+            }
+            if let Some(deny) = &project.deny {
+                settings.deny.extend(deny.iter().cloned());
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_permission() {
+        let settings = ToolPermissionSettings {
+            auto_approve_read_only: true,
+            allow: Vec::new(),
+            deny: Vec::new(),
+        };
+        assert_eq!(
+            settings.permission("read_file", ToolRiskLevel::ReadOnly),
+            ToolPermission::Allow
+        );
+        assert_eq!(
+            settings.permission("edit_files", ToolRiskLevel::Write),
+            ToolPermission::Confirm
+        );
+        assert_eq!(
+            settings.permission("run_command", ToolRiskLevel::Destructive),
+            ToolPermission::Confirm
+        );
+
+        let settings = ToolPermissionSettings {
+            auto_approve_read_only: false,
+            allow: vec!["edit_files".into(), "read_file".into()],
+            deny: vec!["read_file".into()],
+        };
+        assert_eq!(
+            settings.permission("read_file", ToolRiskLevel::ReadOnly),
+            ToolPermission::Deny
+        );
+        assert_eq!(
+            settings.permission("list_directory", ToolRiskLevel::ReadOnly),
+            ToolPermission::Confirm
+        );
+        assert_eq!(
+            settings.permission("edit_files", ToolRiskLevel::Write),
+            ToolPermission::Allow
+        );
+    }
+
+    #[gpui::test]
+    fn test_project_settings_only_restrict_tools(cx: &mut AppContext) {
+        let default = ToolPermissionSettingsContent {
+            auto_approve_read_only: Some(true),
+            allow: Some(Vec::new()),
+            deny: Some(Vec::new()),
+        };
+        let user = ToolPermissionSettingsContent {
+            allow: Some(vec!["edit_files".into()]),
+            ..Default::default()
+        };
+        let project = ToolPermissionSettingsContent {
+            auto_approve_read_only: Some(false),
+            allow: Some(vec!["run_command".into()]),
+            deny: Some(vec!["edit_files".into()]),
+        };
+        let settings = ToolPermissionSettings::load(
+            SettingsSources {
+                default: &default,
+                extensions: None,
+                user: Some(&user),
+                release_channel: None,
+                server: None,
+                project: &[&project],
+            },
+            cx,
+        )
+        .unwrap();
+
+        assert_eq!(
+            settings.permission("read_file", ToolRiskLevel::ReadOnly),
+            ToolPermission::Confirm
+        );
+        assert_eq!(
+            settings.permission("edit_files", ToolRiskLevel::Write),
+            ToolPermission::Deny
+        );
+        assert_eq!(
+            settings.permission("run_command", ToolRiskLevel::Destructive),
+            ToolPermission::Confirm
+        );
+
+        let project = ToolPermissionSettingsContent {
+            auto_approve_read_only: Some(true),
+            ..Default::default()
+        };
+        let user = ToolPermissionSettingsContent {
+            auto_approve_read_only: Some(false),
+            ..Default::default()
+        };
+        let settings = ToolPermissionSettings::load(
+            SettingsSources {
+                default: &default,
+                extensions: None,
+                user: Some(&user),
+                release_channel: None,
+                server: None,
+                project: &[&project],
+            },
+            cx,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.permission("read_file", ToolRiskLevel::ReadOnly),
+            ToolPermission::Confirm
+        );
+    }
+}
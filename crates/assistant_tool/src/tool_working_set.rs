@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use gpui::AppContext;
 use parking_lot::Mutex;
 
@@ -20,6 +20,7 @@ struct WorkingSetState {
     context_server_tools_by_id: HashMap<ToolId, Arc<dyn Tool>>,
     context_server_tools_by_name: HashMap<String, Arc<dyn Tool>>,
     next_tool_id: ToolId,
+    always_allowed_tools: HashSet<String>,
 }
 
 impl ToolWorkingSet {
@@ -56,6 +57,19 @@ impl ToolWorkingSet {
         command_id
     }
 
+    /// Lets the tool with the given name run without confirmation for as long
+    /// as this working set lives.
+    pub fn always_allow(&self, tool_name: impl Into<String>) {
+        self.state
+            .lock()
+            .always_allowed_tools
+            .insert(tool_name.into());
+    }
+
+    pub fn is_always_allowed(&self, tool_name: &str) -> bool {
+        self.state.lock().always_allowed_tools.contains(tool_name)
+    }
+
     pub fn remove(&self, command_ids_to_remove: &[ToolId]) {
         let mut state = self.state.lock();
         state
@@ -1,13 +1,66 @@
+mod diagnostics_tool;
+mod edit_files_tool;
+mod list_directory_tool;
 mod now_tool;
+mod read_file_tool;
+mod regex_search_tool;
+mod symbols_tool;
 
 use assistant_tool::ToolRegistry;
 use gpui::AppContext;
 
+use crate::diagnostics_tool::DiagnosticsTool;
+use crate::edit_files_tool::EditFilesTool;
+use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::regex_search_tool::RegexSearchTool;
+use crate::symbols_tool::SymbolsTool;
 
 pub fn init(cx: &mut AppContext) {
     assistant_tool::init(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(ListDirectoryTool);
+    registry.register_tool(RegexSearchTool);
+    registry.register_tool(DiagnosticsTool);
+    registry.register_tool(SymbolsTool);
+    registry.register_tool(EditFilesTool);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use assistant_tool::Tool;
+    use gpui::{SemanticVersion, TestAppContext, View, VisualTestContext};
+    use project::Project;
+    use settings::SettingsStore;
+    use workspace::Workspace;
+
+    pub fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+
+    pub async fn run_tool(
+        tool: impl Tool,
+        input: serde_json::Value,
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Result<String> {
+        let task = cx.update(|cx| Arc::new(tool).run(input, workspace.downgrade(), cx));
+        task.await
+    }
 }
@@ -0,0 +1,180 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use gpui::{Task, WeakView, WindowContext};
+use language::{DiagnosticSeverity, OffsetRangeExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The relative path of the file to get diagnostics for.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a top-level directory in a project.
+    pub path: String,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors and warnings reported by language servers for a file in the project."
+            .into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let Some(project_path) = project
+            .read(cx)
+            .find_project_path(Path::new(&input.path), cx)
+        else {
+            return Task::ready(Err(anyhow!("Path {} not found in project", input.path)));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+
+            let mut output = String::new();
+            for (_, group) in snapshot.diagnostic_groups(None) {
+                let entry = &group.entries[group.primary_ix];
+                let severity = match entry.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => "error",
+                    DiagnosticSeverity::WARNING => "warning",
+                    _ => continue,
+                };
+                let range = entry.range.to_point(&snapshot);
+                writeln!(
+                    output,
+                    "{}:{}: {severity}: {}",
+                    range.start.row + 1,
+                    range.start.column + 1,
+                    entry.diagnostic.message
+                )?;
+            }
+
+            if output.is_empty() {
+                Ok(format!("{} has no errors or warnings.", input.path))
+            } else {
+                Ok(output)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use gpui::TestAppContext;
+    use lsp::{LanguageServerId, Url};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_diagnostics(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "let one = 1;\nlet two = one + x;\n",
+                "b.rs": "let three = 3;\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_diagnostics(
+                    LanguageServerId(0),
+                    lsp::PublishDiagnosticsParams {
+                        uri: Url::from_file_path("/root/a.rs").unwrap(),
+                        version: None,
+                        diagnostics: vec![
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 4),
+                                    lsp::Position::new(0, 7),
+                                ),
+                                severity: Some(DiagnosticSeverity::WARNING),
+                                message: "unused variable `one`".to_string(),
+                                ..Default::default()
+                            },
+                            lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(1, 16),
+                                    lsp::Position::new(1, 17),
+                                ),
+                                severity: Some(DiagnosticSeverity::ERROR),
+                                message: "cannot find value `x`".to_string(),
+                                ..Default::default()
+                            },
+                        ],
+                    },
+                    &[],
+                    cx,
+                )
+                .unwrap();
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "root/a.rs" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output,
+            "1:5: warning: unused variable `one`\n2:17: error: cannot find value `x`\n"
+        );
+
+        let output = run_tool(DiagnosticsTool, json!({ "path": "b.rs" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "b.rs has no errors or warnings.");
+
+        assert!(
+            run_tool(DiagnosticsTool, json!({ "path": "c.rs" }), &workspace, cx)
+                .await
+                .is_err()
+        );
+    }
+}
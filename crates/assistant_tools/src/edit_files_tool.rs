@@ -0,0 +1,283 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use editor::{ProposedChangeLocation, ProposedChangesEditor};
+use futures::future::try_join_all;
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::Buffer;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFilesToolInput {
+    /// A short title summarizing the changes, shown to the user when reviewing them.
+    pub title: String,
+    /// The edits to propose.
+    pub edits: Vec<EditFilesToolEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFilesToolEdit {
+    /// The relative path of the file to edit.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a top-level directory in a project.
+    pub path: String,
+    /// The text to replace. It must occur exactly once in the file, so include
+    /// enough surrounding lines to make it unique.
+    pub old_text: String,
+    /// The text to replace `old_text` with.
+    pub new_text: String,
+}
+
+pub struct EditFilesTool;
+
+impl Tool for EditFilesTool {
+    fn name(&self) -> String {
+        "edit_files".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes edits to files in the project by replacing text. The edits are not saved: they are opened as a diff that the user can review, accept or discard.".into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::Write
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFilesToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: EditFilesToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        if input.edits.is_empty() {
+            return Task::ready(Err(anyhow!("No edits were provided")));
+        }
+        let Some(project) = workspace
+            .update(cx, |workspace, _| workspace.project().clone())
+            .ok()
+        else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let mut open_buffers = Vec::new();
+        for edit in &input.edits {
+            if edit.old_text.is_empty() {
+                return Task::ready(Err(anyhow!(
+                    "The old text of an edit to {} is empty",
+                    edit.path
+                )));
+            }
+            let Some(project_path) = project
+                .read(cx)
+                .find_project_path(Path::new(&edit.path), cx)
+            else {
+                return Task::ready(Err(anyhow!("Path {} not found in project", edit.path)));
+            };
+            open_buffers
+                .push(project.update(cx, |project, cx| project.open_buffer(project_path, cx)));
+        }
+
+        cx.spawn(|mut cx| async move {
+            let buffers = try_join_all(open_buffers).await?;
+
+            let mut edits_by_buffer: Vec<(Model<Buffer>, Vec<(Range<usize>, String)>)> = Vec::new();
+            for (edit, buffer) in input.edits.into_iter().zip(buffers) {
+                let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+                let range = find_unique_range(&text, &edit.old_text)
+                    .with_context(|| format!("Failed to apply edit to {}", edit.path))?;
+
+                let ix = match edits_by_buffer
+                    .iter()
+                    .position(|(existing, _)| *existing == buffer)
+                {
+                    Some(ix) => ix,
+                    None => {
+                        edits_by_buffer.push((buffer, Vec::new()));
+                        edits_by_buffer.len() - 1
+                    }
+                };
+                let buffer_edits = &mut edits_by_buffer[ix].1;
+                if buffer_edits
+                    .iter()
+                    .any(|(existing, _)| existing.start < range.end && range.start < existing.end)
+                {
+                    return Err(anyhow!("Edits to {} overlap", edit.path));
+                }
+                buffer_edits.push((range, edit.new_text));
+            }
+
+            let file_count = edits_by_buffer.len();
+            let edit_count = edits_by_buffer
+                .iter()
+                .map(|(_, edits)| edits.len())
+                .sum::<usize>();
+
+            let editor = cx.new_view(|cx| {
+                let editor = ProposedChangesEditor::new(
+                    input.title.clone(),
+                    edits_by_buffer
+                        .iter()
+                        .map(|(buffer, edits)| ProposedChangeLocation {
+                            buffer: buffer.clone(),
+                            ranges: edits.iter().map(|(range, _)| range.clone()).collect(),
+                        })
+                        .collect(),
+                    Some(project.clone()),
+                    cx,
+                );
+                for (buffer, edits) in edits_by_buffer {
+                    if let Some(branch) = editor.branch_buffer_for_base(&buffer) {
+                        branch.update(cx, |branch, cx| branch.edit(edits, None, cx));
+                    }
+                }
+                editor.recalculate_all_buffer_diffs();
+                editor
+            })?;
+
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor), None, false, cx)
+            })?;
+
+            Ok(format!(
+                "Proposed {edit_count} edit(s) across {file_count} file(s) in \"{}\". The user can now review them and decide whether to apply them.",
+                input.title
+            ))
+        })
+    }
+}
+
+/// Returns the range of the only occurrence of `old_text` in `text`.
+fn find_unique_range(text: &str, old_text: &str) -> Result<Range<usize>> {
+    let mut matches = text.match_indices(old_text);
+    let (start, _) = matches
+        .next()
+        .ok_or_else(|| anyhow!("The old text was not found in the file"))?;
+    if matches.next().is_some() {
+        return Err(anyhow!(
+            "The old text occurs more than once in the file, include more context to make it unique"
+        ));
+    }
+    Ok(start..start + old_text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_edit_files(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "fn one() -> u32 {\n    1\n}\n\nfn two() -> u32 {\n    2\n}\n",
+                "b.rs": "const THREE: u32 = 3;\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let buffer_a = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/a.rs", cx)
+            })
+            .await
+            .unwrap();
+        let buffer_b = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/b.rs", cx)
+            })
+            .await
+            .unwrap();
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(
+            EditFilesTool,
+            json!({
+                "title": "Bump numbers",
+                "edits": [
+                    { "path": "root/a.rs", "old_text": "    1\n", "new_text": "    10\n" },
+                    { "path": "a.rs", "old_text": "    2\n", "new_text": "    20\n" },
+                    { "path": "b.rs", "old_text": "= 3", "new_text": "= 30" },
+                ]
+            }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output,
+            "Proposed 3 edit(s) across 2 file(s) in \"Bump numbers\". The user can now review them and decide whether to apply them."
+        );
+        cx.run_until_parked();
+
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace
+                .active_item_as::<ProposedChangesEditor>(cx)
+                .expect("proposed changes editor should be open");
+            let editor = editor.read(cx);
+
+            let branch_a = editor.branch_buffer_for_base(&buffer_a).unwrap();
+            assert_eq!(
+                branch_a.read(cx).text(),
+                "fn one() -> u32 {\n    10\n}\n\nfn two() -> u32 {\n    20\n}\n"
+            );
+            let branch_b = editor.branch_buffer_for_base(&buffer_b).unwrap();
+            assert_eq!(branch_b.read(cx).text(), "const THREE: u32 = 30;\n");
+
+            // The base buffers are only changed once the user accepts the edits.
+            assert_eq!(
+                buffer_a.read(cx).text(),
+                "fn one() -> u32 {\n    1\n}\n\nfn two() -> u32 {\n    2\n}\n"
+            );
+            assert_eq!(buffer_b.read(cx).text(), "const THREE: u32 = 3;\n");
+        });
+
+        let error = run_tool(
+            EditFilesTool,
+            json!({
+                "title": "Ambiguous",
+                "edits": [{ "path": "a.rs", "old_text": "-> u32", "new_text": "-> u64" }]
+            }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("more than once"));
+
+        let error = run_tool(
+            EditFilesTool,
+            json!({
+                "title": "Missing",
+                "edits": [{ "path": "b.rs", "old_text": "FOUR", "new_text": "FIVE" }]
+            }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("not found"));
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The relative path of the directory to list.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a top-level directory in a project.
+    /// Pass the name of a top-level directory to list its contents.
+    pub path: String,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list_directory".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories directly inside a directory of the project. Directories are suffixed with a `/`.".into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let project = workspace.read(cx).project().read(cx);
+        let Some(project_path) = project.find_project_path(Path::new(&input.path), cx) else {
+            return Task::ready(Err(anyhow!("Path {} not found in project", input.path)));
+        };
+        let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("Worktree not found")));
+        };
+        let worktree = worktree.read(cx);
+
+        let Some(entry) = worktree.entry_for_path(&project_path.path) else {
+            return Task::ready(Err(anyhow!("Path not found: {}", input.path)));
+        };
+        if !entry.is_dir() {
+            return Task::ready(Err(anyhow!("{} is not a directory", input.path)));
+        }
+
+        let root_name = Path::new(worktree.root_name());
+        let mut output = String::new();
+        for entry in worktree.child_entries(&project_path.path) {
+            let path = root_name.join(&entry.path);
+            output.push_str(&path.to_string_lossy());
+            if entry.is_dir() {
+                output.push('/');
+            }
+            output.push('\n');
+        }
+        if output.is_empty() {
+            return Task::ready(Ok(format!("{} is empty.", input.path)));
+        }
+
+        Task::ready(Ok(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_list_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "Cargo.toml": "",
+                "src": {
+                    "main.rs": "",
+                    "utils": {
+                        "mod.rs": "",
+                    },
+                },
+                "empty": {},
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(ListDirectoryTool, json!({ "path": "root" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "root/Cargo.toml\nroot/empty/\nroot/src/\n");
+
+        let output = run_tool(ListDirectoryTool, json!({ "path": "src" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "root/src/main.rs\nroot/src/utils/\n");
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/empty" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "root/empty is empty.");
+
+        assert!(run_tool(
+            ListDirectoryTool,
+            json!({ "path": "src/main.rs" }),
+            &workspace,
+            cx
+        )
+        .await
+        .is_err());
+        assert!(run_tool(
+            ListDirectoryTool,
+            json!({ "path": "missing" }),
+            &workspace,
+            cx
+        )
+        .await
+        .is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use assistant_tool::Tool;
+use assistant_tool::{Tool, ToolRiskLevel};
 use chrono::{Local, Utc};
 use gpui::{Task, WeakView, WindowContext};
 use schemars::JsonSchema;
@@ -33,6 +33,10 @@ impl Tool for NowTool {
         "Returns the current datetime in RFC 3339 format. Only use this tool when the user specifically asks for it or the current task would benefit from knowing the current datetime.".into()
     }
 
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
     fn input_schema(&self) -> serde_json::Value {
         let schema = schemars::schema_for!(FileToolInput);
         serde_json::to_value(&schema).unwrap()
@@ -0,0 +1,172 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The relative path of the file to read.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a top-level directory in a project.
+    pub path: String,
+    /// The 1-based line at which to start reading. Defaults to the first line.
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    /// The 1-based line at which to stop reading (inclusive). Defaults to the last line.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Reads the content of a file in the project. Optionally restricts the output to a range of lines.".into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let Some(project_path) = project
+            .read(cx)
+            .find_project_path(Path::new(&input.path), cx)
+        else {
+            return Task::ready(Err(anyhow!("Path {} not found in project", input.path)));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+            select_lines(&text, input.start_line, input.end_line)
+        })
+    }
+}
+
+/// Returns the lines of `text` within the given 1-based, inclusive range.
+fn select_lines(text: &str, start_line: Option<u32>, end_line: Option<u32>) -> Result<String> {
+    if start_line.is_none() && end_line.is_none() {
+        return Ok(text.to_string());
+    }
+
+    let line_count = text.lines().count();
+    let start = start_line.unwrap_or(1).max(1) as usize;
+    let end = end_line.map_or(line_count, |end| (end as usize).min(line_count));
+    if start > end {
+        return Err(anyhow!(
+            "Invalid line range {start}..={end}, the file has {line_count} lines"
+        ));
+    }
+
+    let mut result = String::new();
+    for line in text.lines().skip(start - 1).take(end + 1 - start) {
+        result.push_str(line);
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_read_file(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "main.rs": "fn main() {\n    println!(\"hello\");\n}\n",
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/src/main.rs" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "fn main() {\n    println!(\"hello\");\n}\n");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "src/main.rs", "start_line": 2, "end_line": 3 }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "    println!(\"hello\");\n}\n");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "src/main.rs", "start_line": 3, "end_line": 100 }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "}\n");
+
+        assert!(run_tool(
+            ReadFileTool,
+            json!({ "path": "src/main.rs", "start_line": 5 }),
+            &workspace,
+            cx,
+        )
+        .await
+        .is_err());
+        assert!(run_tool(
+            ReadFileTool,
+            json!({ "path": "src/lib.rs" }),
+            &workspace,
+            cx
+        )
+        .await
+        .is_err());
+    }
+}
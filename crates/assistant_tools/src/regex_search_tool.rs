@@ -0,0 +1,208 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use gpui::{Task, WeakView, WindowContext};
+use language::{OffsetRangeExt, Point};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RegexSearchToolInput {
+    /// A regular expression pattern to search for in the project.
+    pub regex: String,
+    /// An optional glob restricting the search to matching paths, e.g. `src/**/*.rs`.
+    #[serde(default)]
+    pub include: Option<String>,
+    /// Whether the search should be case sensitive. Defaults to false.
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+pub struct RegexSearchTool;
+
+impl Tool for RegexSearchTool {
+    fn name(&self) -> String {
+        "regex_search".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the contents of all files in the project for a regular expression, returning each matching line along with its path and line number.".into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RegexSearchToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: RegexSearchToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let files_to_include = match input.include {
+            Some(include) => match PathMatcher::new(&[include]) {
+                Ok(matcher) => matcher,
+                Err(err) => return Task::ready(Err(anyhow!(err))),
+            },
+            None => PathMatcher::default(),
+        };
+        let query = match SearchQuery::regex(
+            &input.regex,
+            false,
+            input.case_sensitive,
+            false,
+            files_to_include,
+            PathMatcher::default(),
+            None,
+        ) {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut matches = Vec::new();
+            let mut limit_reached = false;
+            while let Ok(result) = results.recv().await {
+                match result {
+                    SearchResult::Buffer { buffer, ranges } => {
+                        let file_matches = buffer.read_with(&cx, |buffer, cx| {
+                            let path = buffer
+                                .file()
+                                .map(|file| file.full_path(cx).to_string_lossy().to_string())
+                                .unwrap_or_else(|| "untitled".to_string());
+                            let mut rows = ranges
+                                .iter()
+                                .map(|range| range.to_point(buffer).start.row)
+                                .collect::<Vec<_>>();
+                            rows.dedup();
+                            let lines = rows
+                                .into_iter()
+                                .map(|row| {
+                                    let line = buffer
+                                        .text_for_range(
+                                            Point::new(row, 0)
+                                                ..Point::new(row, buffer.line_len(row)),
+                                        )
+                                        .collect::<String>();
+                                    (row + 1, line)
+                                })
+                                .collect::<Vec<_>>();
+                            (path, lines)
+                        })?;
+                        matches.push(file_matches);
+                    }
+                    SearchResult::LimitReached => limit_reached = true,
+                }
+            }
+
+            if matches.is_empty() {
+                return Ok(format!("No matches found for `{}`.", input.regex));
+            }
+
+            matches.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let mut output = String::new();
+            for (path, lines) in matches {
+                writeln!(output, "{path}")?;
+                for (line_number, line) in lines {
+                    writeln!(output, "{line_number}: {line}")?;
+                }
+            }
+            if limit_reached {
+                writeln!(
+                    output,
+                    "The search result limit was reached, refine the regex to see more matches."
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_regex_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "a.rs": "fn one() {}\nfn two() {}\nstruct Three;\n",
+                    "b.rs": "// nothing to see\nfn four() { one(); }\n",
+                },
+                "notes.txt": "fn five\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(
+            RegexSearchTool,
+            json!({ "regex": "fn \\w+\\(\\)" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            output,
+            "root/src/a.rs\n1: fn one() {}\n2: fn two() {}\nroot/src/b.rs\n2: fn four() { one(); }\n"
+        );
+
+        let output = run_tool(
+            RegexSearchTool,
+            json!({ "regex": "fn", "include": "**/*.txt" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "root/notes.txt\n1: fn five\n");
+
+        let output = run_tool(
+            RegexSearchTool,
+            json!({ "regex": "three", "case_sensitive": true }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "No matches found for `three`.");
+
+        assert!(
+            run_tool(RegexSearchTool, json!({ "regex": "(" }), &workspace, cx)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{Tool, ToolRiskLevel};
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SymbolsToolInput {
+    /// The name, or part of the name, of the symbols to look up.
+    pub query: String,
+}
+
+pub struct SymbolsTool;
+
+impl Tool for SymbolsTool {
+    fn name(&self) -> String {
+        "symbols".into()
+    }
+
+    fn description(&self) -> String {
+        "Looks up symbols (functions, types, constants, ...) across the project by name, using the project's language servers. Returns the kind and location of each symbol.".into()
+    }
+
+    fn risk_level(&self) -> ToolRiskLevel {
+        ToolRiskLevel::ReadOnly
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SymbolsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SymbolsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace dropped")));
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let symbols = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+
+        cx.spawn(|cx| async move {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!("No symbols found matching `{}`.", input.query));
+            }
+
+            project.read_with(&cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols {
+                    let path = match project.worktree_for_id(symbol.path.worktree_id, cx) {
+                        Some(worktree) => Path::new(worktree.read(cx).root_name())
+                            .join(&symbol.path.path)
+                            .to_string_lossy()
+                            .to_string(),
+                        None => symbol.path.path.to_string_lossy().to_string(),
+                    };
+                    let kind = format!("{:?}", symbol.kind).to_lowercase();
+                    writeln!(
+                        output,
+                        "{} ({kind}) {}:{}",
+                        symbol.name,
+                        path,
+                        symbol.range.start.0.row + 1
+                    )
+                    .unwrap();
+                }
+                output
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{init_test, run_tool};
+    use futures::StreamExt;
+    use gpui::TestAppContext;
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_symbols(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "lib.rs": "fn one() {}\nstruct Two;\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_servers =
+            language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp("/root/lib.rs", cx)
+            })
+            .await
+            .unwrap();
+
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(
+            |params: lsp::WorkspaceSymbolParams, _| async move {
+                let symbols = [
+                    symbol("one", lsp::SymbolKind::FUNCTION, 0),
+                    symbol("Two", lsp::SymbolKind::STRUCT, 1),
+                ];
+                Ok(Some(lsp::WorkspaceSymbolResponse::Flat(
+                    symbols
+                        .into_iter()
+                        .filter(|symbol| {
+                            symbol
+                                .name
+                                .to_lowercase()
+                                .contains(&params.query.to_lowercase())
+                        })
+                        .collect(),
+                )))
+            },
+        );
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let output = run_tool(SymbolsTool, json!({ "query": "two" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "Two (struct) root/lib.rs:2\n");
+
+        let output = run_tool(SymbolsTool, json!({ "query": "three" }), &workspace, cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "No symbols found matching `three`.");
+    }
+
+    fn symbol(name: &str, kind: lsp::SymbolKind, row: u32) -> lsp::SymbolInformation {
+        #[allow(deprecated)]
+        lsp::SymbolInformation {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            deprecated: None,
+            container_name: None,
+            location: lsp::Location::new(
+                lsp::Url::from_file_path("/root/lib.rs").unwrap(),
+                lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 0)),
+            ),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use gpui::{actions, AppContext, Model, ModelContext, Task};
+use hierarchy_view::{HierarchyChild, HierarchySource, HierarchyView};
+use language::{Anchor, Buffer};
+use project::{CallHierarchyItem, Location, Project};
+use workspace::Workspace;
+
+actions!(call_hierarchy, [ShowIncomingCalls, ShowOutgoingCalls]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, cx| {
+            CallHierarchyView::deploy(workspace, CallDirection::Incoming, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+            CallHierarchyView::deploy(workspace, CallDirection::Outgoing, cx);
+        });
+    })
+    .detach();
+}
+
+/// A tree of the calls into or out of the symbol under the cursor.
+pub type CallHierarchyView = HierarchyView<CallDirection>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallDirection {
+    /// Show the callers of each item.
+    Incoming,
+    /// Show the callees of each item.
+    Outgoing,
+}
+
+impl HierarchySource for CallDirection {
+    type Item = CallHierarchyItem;
+
+    const KEY_CONTEXT: &'static str = "CallHierarchyView";
+    const NAME: &'static str = "call hierarchy";
+    const TELEMETRY_EVENT_TEXT: &'static str = "Call Hierarchy Opened";
+
+    fn label(&self) -> &'static str {
+        match self {
+            CallDirection::Incoming => "Incoming Calls",
+            CallDirection::Outgoing => "Outgoing Calls",
+        }
+    }
+
+    fn toggled(&self) -> Self {
+        match self {
+            CallDirection::Incoming => CallDirection::Outgoing,
+            CallDirection::Outgoing => CallDirection::Incoming,
+        }
+    }
+
+    fn prepare(
+        project: &mut Project,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        project.prepare_call_hierarchy(buffer, position, cx)
+    }
+
+    fn children(
+        &self,
+        project: &mut Project,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<CallHierarchyItem>>>> {
+        let calls = match self {
+            CallDirection::Incoming => project.incoming_calls(item, cx),
+            CallDirection::Outgoing => project.outgoing_calls(item, cx),
+        };
+        cx.spawn(|_, _| async move {
+            Ok(calls
+                .await?
+                .into_iter()
+                .map(|call| HierarchyChild {
+                    item: call.item,
+                    references: call.call_sites,
+                })
+                .collect())
+        })
+    }
+
+    fn name(item: &CallHierarchyItem) -> &str {
+        &item.lsp_item.name
+    }
+
+    fn detail(item: &CallHierarchyItem) -> Option<&str> {
+        item.lsp_item.detail.as_deref()
+    }
+
+    fn location(item: &CallHierarchyItem) -> &Location {
+        &item.location
+    }
+}
@@ -774,6 +774,9 @@ impl Database {
                         proto::RepositoryEntry {
                             work_directory_id: db_repository_entry.work_directory_id as u64,
                             branch: db_repository_entry.branch,
+                            // Upstream tracking isn't persisted, so guests only learn it from
+                            // the host's next update.
+                            upstream_tracking: None,
                             updated_statuses,
                             removed_statuses: Vec::new(),
                         },
@@ -739,6 +739,7 @@ impl Database {
                         worktree.updated_repositories.push(proto::RepositoryEntry {
                             work_directory_id: db_repository.work_directory_id as u64,
                             branch: db_repository.branch,
+                            upstream_tracking: None,
                             updated_statuses,
                             removed_statuses,
                         });
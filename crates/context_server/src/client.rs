@@ -1,16 +1,12 @@
 use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
-use futures::{channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, FutureExt};
+use futures::{channel::oneshot, select, FutureExt, StreamExt};
 use gpui::{AsyncAppContext, BackgroundExecutor, Task};
 use parking_lot::Mutex;
 use postage::barrier;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
-use smol::{
-    channel,
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    process::Child,
-};
+use smol::channel;
 use std::{
     fmt,
     path::PathBuf,
@@ -22,6 +18,8 @@ use std::{
 };
 use util::TryFutureExt;
 
+use crate::transport::{StdioTransport, Transport};
+
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -34,6 +32,7 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncAppContext)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -48,6 +47,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -55,7 +55,6 @@ pub struct Client {
     #[allow(dead_code)]
     output_done_rx: Mutex<Option<barrier::Receiver>>,
     executor: BackgroundExecutor,
-    server: Arc<Mutex<Option<Child>>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -123,8 +122,29 @@ struct AnyNotification<'a> {
     params: Option<Value>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct AnyRequest<'a> {
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -138,63 +158,52 @@ pub struct ModelContextServerBinary {
 impl Client {
     /// Creates a new Client instance for a context server.
     ///
-    /// This function initializes a new Client by spawning a child process for the context server,
-    /// setting up communication channels, and initializing handlers for input/output operations.
-    /// It takes a server ID, binary information, and an async app context as input.
+    /// This function sets up communication channels over the given transport and
+    /// initializes handlers for input/output operations.
     pub fn new(
         server_id: ContextServerId,
-        binary: ModelContextServerBinary,
+        name: Arc<str>,
+        transport: Arc<dyn Transport>,
         cx: AsyncAppContext,
-    ) -> Result<Self> {
-        log::info!(
-            "starting context server (executable={:?}, args={:?})",
-            binary.executable,
-            &binary.args
-        );
-
-        let mut command = util::command::new_smol_command(&binary.executable);
-        command
-            .args(&binary.args)
-            .envs(binary.env.unwrap_or_default())
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .kill_on_drop(true);
-
-        let mut server = command.spawn().with_context(|| {
-            format!(
-                "failed to spawn command. (path={:?}, args={:?})",
-                binary.executable, &binary.args
-            )
-        })?;
-
-        let stdin = server.stdin.take().unwrap();
-        let stdout = server.stdout.take().unwrap();
-        let stderr = server.stderr.take().unwrap();
-
+    ) -> Self {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
+            let transport = transport.clone();
             move |cx| {
-                Self::handle_input(stdout, notification_handlers, response_handlers, cx).log_err()
+                Self::handle_input(
+                    transport,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
             }
         });
-        let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
+        let stderr_input_task = cx.spawn({
+            let transport = transport.clone();
+            move |_| Self::handle_stderr(transport).log_err()
+        });
         let input_task = cx.spawn(|_| async move {
             let (stdout, stderr) = futures::join!(stdout_input_task, stderr_input_task);
             stdout.or(stderr)
         });
         let output_task = cx.background_executor().spawn({
             Self::handle_output(
-                stdin,
+                transport,
                 outbound_rx,
                 output_done_tx,
                 response_handlers.clone(),
@@ -202,54 +211,60 @@ impl Client {
             .log_err()
         });
 
-        let mut context_server = Self {
+        Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: "".into(),
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
-            server: Arc::new(Mutex::new(Some(server))),
-        };
-
-        if let Some(name) = binary.executable.file_name() {
-            context_server.name = name.to_string_lossy().into();
         }
+    }
 
-        Ok(context_server)
+    /// Creates a new Client instance for a context server that runs as a child
+    /// process, communicating with it over its stdin and stdout.
+    pub fn stdio(
+        server_id: ContextServerId,
+        binary: ModelContextServerBinary,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let name = binary
+            .executable
+            .file_name()
+            .map(|name| name.to_string_lossy().into())
+            .unwrap_or_else(|| "".into());
+        let transport = Arc::new(StdioTransport::new(binary, &cx)?);
+        Ok(Self::new(server_id, name, transport, cx))
     }
 
-    /// Handles input from the server's stdout.
+    /// Handles input from the server.
     ///
-    /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
-    async fn handle_input<Stdout>(
-        stdout: Stdout,
+    /// This function continuously receives messages from the transport,
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches
+    /// them to the appropriate handlers. It processes requests (which are answered
+    /// by registered handlers), responses (which are matched to pending requests)
+    /// and notifications (which trigger registered handlers).
+    async fn handle_input(
+        transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: AsyncAppContext,
-    ) -> anyhow::Result<()>
-    where
-        Stdout: AsyncRead + Unpin + Send + 'static,
-    {
-        let mut stdout = BufReader::new(stdout);
-        let mut buffer = String::new();
-
-        loop {
-            buffer.clear();
-            if stdout.read_line(&mut buffer).await? == 0 {
-                return Ok(());
-            }
+    ) -> anyhow::Result<()> {
+        let mut receiver = transport.receive();
 
-            let content = buffer.trim();
+        while let Some(message) = receiver.next().await {
+            let content = message.trim();
 
             if !content.is_empty() {
-                if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
+                if let Ok(request) = serde_json::from_str::<AnyRequest>(content) {
+                    Self::handle_request(request, &request_handlers, outbound_tx.clone(), &cx);
+                } else if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
                     if let Some(handlers) = response_handlers.lock().as_mut() {
                         if let Some(handler) = handlers.remove(&response.id) {
                             handler(Ok(content.to_string()));
@@ -267,40 +282,79 @@ impl Client {
 
             smol::future::yield_now().await;
         }
+
+        Ok(())
     }
 
-    /// Handles the stderr output from the context server.
-    /// Continuously reads and logs any error messages from the server.
-    async fn handle_stderr<Stderr>(stderr: Stderr) -> anyhow::Result<()>
-    where
-        Stderr: AsyncRead + Unpin + Send + 'static,
-    {
-        let mut stderr = BufReader::new(stderr);
-        let mut buffer = String::new();
+    /// Answers a request sent by the context server, using the handler registered
+    /// for its method.
+    fn handle_request(
+        request: AnyRequest,
+        request_handlers: &Mutex<HashMap<&'static str, RequestHandler>>,
+        outbound_tx: channel::Sender<String>,
+        cx: &AsyncAppContext,
+    ) {
+        let result = request_handlers
+            .lock()
+            .get_mut(request.method.as_str())
+            .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+        let id = request.id;
+        let method = request.method;
+
+        cx.spawn(|_| async move {
+            let (result, error) = match result {
+                Some(result) => match result.await {
+                    Ok(result) => (Some(result), None),
+                    Err(error) => (
+                        None,
+                        Some(Error {
+                            code: INTERNAL_ERROR,
+                            message: format!("{error:#}"),
+                        }),
+                    ),
+                },
+                None => (
+                    None,
+                    Some(Error {
+                        code: METHOD_NOT_FOUND,
+                        message: format!("method not found: {method}"),
+                    }),
+                ),
+            };
+            let response = serde_json::to_string(&OutgoingResponse {
+                jsonrpc: JSON_RPC_VERSION,
+                id,
+                result,
+                error,
+            })
+            .unwrap();
+            outbound_tx.send(response).await.ok();
+        })
+        .detach();
+    }
 
-        loop {
-            buffer.clear();
-            if stderr.read_line(&mut buffer).await? == 0 {
-                return Ok(());
-            }
-            log::warn!("context server stderr: {}", buffer.trim());
+    /// Handles the error output from the context server, such as its stderr.
+    /// Continuously receives and logs any error messages from the transport.
+    async fn handle_stderr(transport: Arc<dyn Transport>) -> anyhow::Result<()> {
+        let mut receiver = transport.receive_err();
+
+        while let Some(message) = receiver.next().await {
+            log::warn!("context server stderr: {}", message.trim());
             smol::future::yield_now().await;
         }
+
+        Ok(())
     }
 
-    /// Handles the output to the context server's stdin.
+    /// Handles the output to the context server.
     /// This function continuously receives messages from the outbound channel,
-    /// writes them to the server's stdin, and manages the lifecycle of response handlers.
-    async fn handle_output<Stdin>(
-        stdin: Stdin,
+    /// sends them over the transport, and manages the lifecycle of response handlers.
+    async fn handle_output(
+        transport: Arc<dyn Transport>,
         outbound_rx: channel::Receiver<String>,
         output_done_tx: barrier::Sender,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
-    ) -> anyhow::Result<()>
-    where
-        Stdin: AsyncWrite + Unpin + Send + 'static,
-    {
-        let mut stdin = BufWriter::new(stdin);
+    ) -> anyhow::Result<()> {
         let _clear_response_handlers = util::defer({
             let response_handlers = response_handlers.clone();
             move || {
@@ -310,9 +364,7 @@ impl Client {
         while let Ok(message) = outbound_rx.recv().await {
             log::trace!("outgoing message: {}", message);
 
-            stdin.write_all(message.as_bytes()).await?;
-            stdin.write_all(b"\n").await?;
-            stdin.flush().await?;
+            transport.send(message).await?;
         }
         drop(output_done_tx);
         Ok(())
@@ -352,7 +404,7 @@ impl Client {
         let send = self
             .outbound_tx
             .try_send(request)
-            .context("failed to write to context server's stdin");
+            .context("failed to send request to context server");
 
         let executor = self.executor.clone();
         let started = Instant::now();
@@ -407,6 +459,20 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler answering the requests the context server sends
+    /// with the given method.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
+    /// Returns whether a handler answers requests with the given method.
+    pub fn handles_request(&self, method: &str) -> bool {
+        self.request_handlers.lock().contains_key(method)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -416,14 +482,6 @@ impl Client {
     }
 }
 
-impl Drop for Client {
-    fn drop(&mut self) {
-        if let Some(mut server) = self.server.lock().take() {
-            let _ = server.kill();
-        }
-    }
-}
-
 impl fmt::Display for ContextServerId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -438,3 +496,79 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::Stream;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::pin::Pin;
+
+    struct FakeTransport {
+        incoming: channel::Receiver<String>,
+        outgoing: channel::Sender<String>,
+    }
+
+    #[async_trait]
+    impl Transport for FakeTransport {
+        async fn send(&self, message: String) -> Result<()> {
+            self.outgoing.send(message).await?;
+            Ok(())
+        }
+
+        fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(self.incoming.clone())
+        }
+
+        fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(futures::stream::pending())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_server_requests(cx: &mut TestAppContext) {
+        let (server_tx, incoming) = channel::unbounded();
+        let (outgoing, server_rx) = channel::unbounded();
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(FakeTransport { incoming, outgoing }),
+            cx.to_async(),
+        );
+        client.on_request("roots/list", |_, _| {
+            Task::ready(Ok(json!({ "roots": [{ "uri": "file:///project" }] })))
+        });
+        assert!(client.handles_request("roots/list"));
+        assert!(!client.handles_request("sampling/createMessage"));
+
+        server_tx
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "roots/list" }).to_string())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        let response: Value = serde_json::from_str(&server_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(
+            response,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "roots": [{ "uri": "file:///project" }] }
+            })
+        );
+
+        server_tx
+            .send(
+                json!({ "jsonrpc": "2.0", "id": "two", "method": "sampling/createMessage" })
+                    .to_string(),
+            )
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        let response: Value = serde_json::from_str(&server_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(response["id"], json!("two"));
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert!(response.get("result").is_none());
+    }
+}
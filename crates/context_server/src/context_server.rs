@@ -4,10 +4,13 @@ mod extension_context_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
+pub mod transport;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
-pub use context_server_settings::{ContextServerSettings, ServerCommand, ServerConfig};
+pub use context_server_settings::{
+    ContextServerSettings, ServerCommand, ServerConfig, ServerTransport,
+};
 use gpui::{actions, AppContext};
 
 pub use crate::context_server_tool::ContextServerTool;
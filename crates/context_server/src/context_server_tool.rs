@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail};
-use assistant_tool::Tool;
+use assistant_tool::{Tool, ToolRiskLevel};
 use gpui::{Model, Task, WindowContext};
 
 use crate::manager::ContextServerManager;
@@ -36,6 +36,12 @@ impl Tool for ContextServerTool {
         self.tool.description.clone().unwrap_or_default()
     }
 
+    fn risk_level(&self) -> ToolRiskLevel {
+        // Context servers can do anything when running a tool, so we can't
+        // assume their tools are safe.
+        ToolRiskLevel::Destructive
+    }
+
     fn input_schema(&self) -> serde_json::Value {
         match &self.tool.input_schema {
             serde_json::Value::Null => {
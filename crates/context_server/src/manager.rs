@@ -2,7 +2,8 @@
 //!
 //! It provides functionality to:
 //! - Define and load context server settings
-//! - Manage individual context servers (start, stop, restart)
+//! - Manage individual context servers (start, stop, restart), either running
+//!   them as subprocesses or connecting to them over HTTP
 //! - Maintain a global manager for all context servers
 //!
 //! Key components:
@@ -17,27 +18,45 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context as _, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel};
+use gpui::{
+    AppContext, AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel,
+};
 use log;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use project::Project;
 use settings::{Settings, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
-use crate::{ContextServerSettings, ServerConfig};
+use crate::{ContextServerSettings, ServerConfig, ServerTransport};
 
 use crate::{
     client::{self, Client},
+    protocol::ServerCapability,
+    transport::{SseTransport, StreamableHttpTransport, Transport},
     types, ContextServerFactoryRegistry, CONTEXT_SERVERS_NAMESPACE,
 };
 
+/// Answers `sampling/createMessage` requests, which context servers send to
+/// have a language model generate a message on their behalf.
+pub type SamplingHandler = Arc<
+    dyn Fn(
+            Arc<str>,
+            types::CreateMessageRequest,
+            AsyncAppContext,
+        ) -> Task<Result<types::CreateMessageResult>>
+        + Send
+        + Sync,
+>;
+
 pub struct ContextServer {
     pub id: Arc<str>,
     pub config: Arc<ServerConfig>,
     pub client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
+    resource_subscriptions: Mutex<HashMap<Url, usize>>,
 }
 
 impl ContextServer {
@@ -46,6 +65,7 @@ impl ContextServer {
             id,
             config,
             client: RwLock::new(None),
+            resource_subscriptions: Mutex::new(HashMap::default()),
         }
     }
 
@@ -61,20 +81,46 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncAppContext) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakModel<ContextServerManager>,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let server_id = client::ContextServerId(self.id.clone());
+        let client = if let Some(url) = &self.config.url {
+            let url = Url::parse(url)
+                .with_context(|| format!("invalid url for server {}: {url}", self.id))?;
+            let headers = self.config.headers.clone().unwrap_or_default();
+            let http_client = cx.update(|cx| cx.http_client())?;
+            let executor = cx.background_executor().clone();
+            let transport: Arc<dyn Transport> = match self.config.transport {
+                ServerTransport::StreamableHttp => Arc::new(StreamableHttpTransport::new(
+                    url,
+                    headers,
+                    http_client,
+                    executor,
+                )),
+                ServerTransport::Sse => {
+                    Arc::new(SseTransport::new(url, headers, http_client, executor))
+                }
+            };
+            Client::new(server_id, self.id.clone(), transport, cx.clone())
+        } else if let Some(command) = &self.config.command {
+            Client::stdio(
+                server_id,
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or url specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+
+        self.register_handlers(&client, manager, cx)?;
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -93,6 +139,114 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Registers the handlers answering the requests and notifications the
+    /// server sends. This has to happen before initialization, as the client
+    /// capabilities announced to the server depend on them.
+    fn register_handlers(
+        &self,
+        client: &Client,
+        manager: WeakModel<ContextServerManager>,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
+        client.on_request(types::RequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx));
+                Task::ready(roots.and_then(|roots| {
+                    Ok(serde_json::to_value(types::ListRootsResponse {
+                        roots,
+                        meta: None,
+                    })?)
+                }))
+            }
+        });
+
+        if manager.read_with(cx, |manager, _| manager.sampling_handler.is_some())? {
+            client.on_request(types::RequestType::CreateMessage.as_str(), {
+                let server_id = self.id.clone();
+                let manager = manager.clone();
+                move |params, cx| {
+                    let server_id = server_id.clone();
+                    let manager = manager.clone();
+                    cx.spawn(|cx| async move {
+                        let request: types::CreateMessageRequest = serde_json::from_value(params)?;
+                        let handler = manager
+                            .read_with(&cx, |manager, _| manager.sampling_handler.clone())?
+                            .context("sampling is not supported")?;
+                        let result = handler(server_id, request, cx).await?;
+                        Ok(serde_json::to_value(result)?)
+                    })
+                }
+            });
+        }
+
+        client.on_notification(types::NotificationType::ResourcesUpdated.as_str(), {
+            let server_id = self.id.clone();
+            move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                manager
+                    .update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id: server_id.clone(),
+                            uri: params.uri,
+                        })
+                    })
+                    .ok();
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Subscribes to updates of the resource with the given URI, which are
+    /// reported as [`Event::ResourceUpdated`].
+    ///
+    /// Subscriptions are counted, so that the server is only asked to stop
+    /// sending updates once every subscriber unsubscribed.
+    pub async fn subscribe_to_resource(&self, uri: Url) -> Result<()> {
+        let protocol = self.client().context("server is not running")?;
+        if !protocol.capable(ServerCapability::ResourceSubscriptions) {
+            return Ok(());
+        }
+
+        let is_first = {
+            let mut subscriptions = self.resource_subscriptions.lock();
+            let count = subscriptions.entry(uri.clone()).or_default();
+            *count += 1;
+            *count == 1
+        };
+        if is_first {
+            protocol.subscribe_to_resource(uri).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn unsubscribe_from_resource(&self, uri: Url) -> Result<()> {
+        let protocol = self.client().context("server is not running")?;
+        let is_last = {
+            let mut subscriptions = self.resource_subscriptions.lock();
+            match subscriptions.get_mut(&uri) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    false
+                }
+                Some(_) => {
+                    subscriptions.remove(&uri);
+                    true
+                }
+                None => false,
+            }
+        };
+        if is_last {
+            protocol.unsubscribe_from_resource(uri).await?;
+        }
+        Ok(())
+    }
+
     pub fn stop(&self) -> Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -106,6 +260,7 @@ pub struct ContextServerManager {
     servers: HashMap<Arc<str>, Arc<ContextServer>>,
     project: Model<Project>,
     registry: Model<ContextServerFactoryRegistry>,
+    sampling_handler: Option<SamplingHandler>,
     update_servers_task: Option<Task<Result<()>>>,
     needs_server_update: bool,
     _subscriptions: Vec<Subscription>,
@@ -114,6 +269,7 @@ pub struct ContextServerManager {
 pub enum Event {
     ServerStarted { server_id: Arc<str> },
     ServerStopped { server_id: Arc<str> },
+    ResourceUpdated { server_id: Arc<str>, uri: Url },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -132,9 +288,17 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| {
+                    if let project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) =
+                        event
+                    {
+                        this.roots_changed();
+                    }
+                }),
             ],
             project,
             registry,
+            sampling_handler: None,
             needs_server_update: false,
             servers: HashMap::default(),
             update_servers_task: None,
@@ -173,6 +337,37 @@ impl ContextServerManager {
         }
     }
 
+    /// Sets the handler answering the sampling requests of context servers.
+    ///
+    /// Only servers started afterwards are told that sampling is supported.
+    pub fn set_sampling_handler(&mut self, handler: SamplingHandler) {
+        self.sampling_handler = Some(handler);
+    }
+
+    /// Returns the roots context servers may operate on, which are the visible
+    /// worktrees of the project.
+    pub fn roots(&self, cx: &AppContext) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.servers() {
+            if let Some(protocol) = server.client() {
+                protocol.notify_roots_list_changed().log_err();
+            }
+        }
+    }
+
     pub fn get_server(&self, id: &str) -> Option<Arc<ContextServer>> {
         self.servers
             .get(id)
@@ -191,7 +386,7 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(&mut cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
@@ -234,7 +429,7 @@ impl ContextServerManager {
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.url.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -276,7 +471,7 @@ impl ContextServerManager {
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
                 this.update(&mut cx, |_, cx| {
                     cx.emit(Event::ServerStarted { server_id: id })
                 })?;
@@ -8,6 +8,8 @@
 use anyhow::Result;
 use collections::HashMap;
 
+use url::Url;
+
 use crate::client::Client;
 use crate::types;
 
@@ -34,8 +36,16 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: self
+                    .inner
+                    .handles_request(types::RequestType::CreateMessage.as_str())
+                    .then(|| serde_json::json!({})),
+                roots: self
+                    .inner
+                    .handles_request(types::RequestType::ListRoots.as_str())
+                    .then(|| types::RootsCapabilities {
+                        list_changed: Some(true),
+                    }),
             },
             meta: None,
             client_info,
@@ -80,6 +90,7 @@ pub enum ServerCapability {
     Logging,
     Prompts,
     Resources,
+    ResourceSubscriptions,
     Tools,
 }
 
@@ -91,6 +102,13 @@ impl InitializedContextServerProtocol {
             ServerCapability::Logging => self.initialize.capabilities.logging.is_some(),
             ServerCapability::Prompts => self.initialize.capabilities.prompts.is_some(),
             ServerCapability::Resources => self.initialize.capabilities.resources.is_some(),
+            ServerCapability::ResourceSubscriptions => self
+                .initialize
+                .capabilities
+                .resources
+                .as_ref()
+                .and_then(|resources| resources.subscribe)
+                .unwrap_or(false),
             ServerCapability::Tools => self.initialize.capabilities.tools.is_some(),
         }
     }
@@ -136,6 +154,44 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// Reads the contents of the MCP resource with the given URI.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Asks the server to send `notifications/resources/updated` whenever the
+    /// resource with the given URI changes.
+    pub async fn subscribe_to_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::ResourceSubscriptions)?;
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stops the updates requested with [`Self::subscribe_to_resource`].
+    pub async fn unsubscribe_from_resource(&self, uri: Url) -> Result<()> {
+        self.check_capability(ServerCapability::ResourceSubscriptions)?;
+
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -224,6 +280,14 @@ impl InitializedContextServerProtocol {
 }
 
 impl InitializedContextServerProtocol {
+    /// Lets the server know that the roots returned for `roots/list` changed.
+    pub fn notify_roots_list_changed(&self) -> Result<()> {
+        self.inner.notify(
+            types::NotificationType::RootsListChanged.as_str(),
+            serde_json::json!({}),
+        )
+    }
+
     pub async fn request<R: serde::de::DeserializeOwned>(
         &self,
         method: &str,
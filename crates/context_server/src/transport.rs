@@ -0,0 +1,78 @@
+mod event_stream;
+mod sse_transport;
+mod stdio_transport;
+mod streamable_http_transport;
+
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::Stream;
+use http_client::{http, Method};
+use url::Url;
+
+pub use sse_transport::SseTransport;
+pub use stdio_transport::StdioTransport;
+pub use streamable_http_transport::StreamableHttpTransport;
+
+/// The number of consecutive failed attempts after which an HTTP transport
+/// stops trying to reconnect to its server.
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// How long a connection has to stay up to no longer count as a failed
+/// attempt, when the server didn't send any message over it.
+const MIN_STABLE_CONNECTION_DURATION: Duration = Duration::from_secs(30);
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A channel over which JSON-RPC messages are exchanged with a context server.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends a JSON-RPC message to the server.
+    async fn send(&self, message: String) -> Result<()>;
+
+    /// Returns the stream of JSON-RPC messages received from the server.
+    ///
+    /// The stream ends once the server is gone for good.
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
+
+    /// Returns the stream of diagnostic output from the server, such as the
+    /// stderr of a subprocess or connection errors.
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
+}
+
+/// Returns how long to wait before the given reconnection attempt, doubling
+/// the delay after every failed attempt.
+fn reconnect_delay(attempt: u32) -> Duration {
+    INITIAL_RECONNECT_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RECONNECT_DELAY)
+}
+
+fn http_request(
+    method: Method,
+    url: &Url,
+    headers: &HashMap<String, String>,
+) -> http::request::Builder {
+    let mut builder = http::Request::builder().method(method).uri(url.as_str());
+    for (name, value) in headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_delay() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(3), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(5), Duration::from_secs(16));
+        assert_eq!(reconnect_delay(6), Duration::from_secs(30));
+        assert_eq!(reconnect_delay(100), Duration::from_secs(30));
+    }
+}
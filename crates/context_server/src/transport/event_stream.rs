@@ -0,0 +1,126 @@
+//! A parser for [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html),
+//! which both HTTP transports use to receive messages from the server.
+
+use anyhow::Result;
+use futures::{io::BufReader, AsyncBufReadExt, AsyncRead, Stream, StreamExt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Event {
+    pub event: String,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+#[derive(Default)]
+struct PendingEvent {
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+}
+
+impl PendingEvent {
+    fn push_line(&mut self, line: &str) {
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => {
+                let data = self.data.get_or_insert_with(String::new);
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(value);
+            }
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    /// Returns the event collected so far, if it carries any data.
+    fn take(&mut self) -> Option<Event> {
+        let pending = std::mem::take(self);
+        Some(Event {
+            event: pending.event.unwrap_or_else(|| "message".to_string()),
+            data: pending.data?,
+            id: pending.id,
+        })
+    }
+}
+
+/// Parses the server-sent events in the given body, ending once the body does.
+pub(super) fn parse_events<R>(body: R) -> impl Stream<Item = Result<Event>> + Send
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let lines = BufReader::new(body).lines();
+    futures::stream::unfold(Some(lines), |lines| async move {
+        let mut lines = lines?;
+        let mut pending = PendingEvent::default();
+        loop {
+            match lines.next().await {
+                Some(Ok(line)) if line.is_empty() => {
+                    if let Some(event) = pending.take() {
+                        return Some((Ok(event), Some(lines)));
+                    }
+                }
+                Some(Ok(line)) => pending.push_line(&line),
+                Some(Err(error)) => return Some((Err(error.into()), None)),
+                None => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+
+    #[test]
+    fn test_parse_events() {
+        let body = concat!(
+            ": a comment\n",
+            "event: endpoint\n",
+            "data: /messages?session=1\n",
+            "\n",
+            "id: 7\r\n",
+            "data: {\"a\":\n",
+            "data:1}\n",
+            "\n",
+            "event: ignored\n",
+            "\n",
+            "data\n",
+            "\n",
+            "data: unterminated\n",
+        );
+        let events =
+            smol::block_on(parse_events(futures::io::Cursor::new(body)).try_collect::<Vec<_>>())
+                .unwrap();
+        assert_eq!(
+            events,
+            [
+                Event {
+                    event: "endpoint".into(),
+                    data: "/messages?session=1".into(),
+                    id: None,
+                },
+                Event {
+                    event: "message".into(),
+                    data: "{\"a\":\n1}".into(),
+                    id: Some("7".into()),
+                },
+                Event {
+                    event: "message".into(),
+                    data: "".into(),
+                    id: None,
+                },
+            ]
+        );
+    }
+}
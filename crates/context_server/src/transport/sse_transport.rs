@@ -0,0 +1,436 @@
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{Stream, StreamExt};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method, Url};
+use postage::{prelude::Stream as _, watch};
+use smol::channel;
+
+use crate::transport::event_stream::parse_events;
+use crate::transport::{
+    http_request, reconnect_delay, Transport, MAX_RECONNECT_ATTEMPTS,
+    MIN_STABLE_CONNECTION_DURATION,
+};
+
+/// A transport for context servers implementing the HTTP with SSE transport
+/// from the 2024-11-05 protocol revision.
+///
+/// The server streams its messages over a long-lived `GET` request. The first
+/// event on that stream announces the endpoint to which messages are `POST`ed.
+pub struct SseTransport {
+    headers: HashMap<String, String>,
+    http_client: Arc<dyn HttpClient>,
+    endpoint_receiver: watch::Receiver<Option<Url>>,
+    message_receiver: channel::Receiver<String>,
+    error_receiver: channel::Receiver<String>,
+    _listener: Task<()>,
+}
+
+impl SseTransport {
+    pub fn new(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let (endpoint_sender, endpoint_receiver) = watch::channel();
+        let (message_sender, message_receiver) = channel::unbounded();
+        let (error_sender, error_receiver) = channel::unbounded();
+        let listener = executor.spawn(Self::listen(
+            url,
+            headers.clone(),
+            http_client.clone(),
+            executor.clone(),
+            endpoint_sender,
+            message_sender,
+            error_sender,
+        ));
+
+        Self {
+            headers,
+            http_client,
+            endpoint_receiver,
+            message_receiver,
+            error_receiver,
+            _listener: listener,
+        }
+    }
+
+    /// Keeps the event stream open, reconnecting with an increasing delay
+    /// whenever it drops, until too many attempts in a row have failed.
+    async fn listen(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+        mut endpoint_sender: watch::Sender<Option<Url>>,
+        message_sender: channel::Sender<String>,
+        error_sender: channel::Sender<String>,
+    ) {
+        let mut last_event_id = None;
+        let mut failed_attempts = 0;
+        loop {
+            match Self::connect(&url, &headers, &http_client, last_event_id.as_deref()).await {
+                Ok(body) => {
+                    let connected_at = executor.now();
+                    let mut received_message = false;
+                    let mut events = pin!(parse_events(body));
+                    while let Some(event) = events.next().await {
+                        let event = match event {
+                            Ok(event) => event,
+                            Err(error) => {
+                                error_sender
+                                    .try_send(format!("event stream from {url} failed: {error:#}"))
+                                    .ok();
+                                break;
+                            }
+                        };
+                        if let Some(id) = event.id {
+                            last_event_id = Some(id);
+                        }
+                        match event.event.as_str() {
+                            "endpoint" => match resolve_endpoint(&url, &event.data) {
+                                Ok(endpoint) => {
+                                    *endpoint_sender.borrow_mut() = Some(endpoint);
+                                }
+                                Err(error) => {
+                                    error_sender.try_send(format!("{error:#}")).ok();
+                                }
+                            },
+                            "message" => {
+                                received_message = true;
+                                if message_sender.send(event.data).await.is_err() {
+                                    return;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    // The server announces a new endpoint once we reconnect.
+                    *endpoint_sender.borrow_mut() = None;
+
+                    // Servers that close the stream right away keep counting
+                    // as failed attempts, so that we eventually give up.
+                    if received_message
+                        || executor.now() - connected_at >= MIN_STABLE_CONNECTION_DURATION
+                    {
+                        failed_attempts = 0;
+                    }
+                }
+                Err(error) => {
+                    error_sender
+                        .try_send(format!("failed to connect to {url}: {error:#}"))
+                        .ok();
+                }
+            }
+
+            failed_attempts += 1;
+            if failed_attempts > MAX_RECONNECT_ATTEMPTS {
+                error_sender
+                    .try_send(format!(
+                        "giving up on {url} after {MAX_RECONNECT_ATTEMPTS} reconnection attempts"
+                    ))
+                    .ok();
+                return;
+            }
+            executor.timer(reconnect_delay(failed_attempts)).await;
+        }
+    }
+
+    async fn connect(
+        url: &Url,
+        headers: &HashMap<String, String>,
+        http_client: &Arc<dyn HttpClient>,
+        last_event_id: Option<&str>,
+    ) -> Result<AsyncBody> {
+        let mut request =
+            http_request(Method::GET, url, headers).header("Accept", "text/event-stream");
+        if let Some(last_event_id) = last_event_id {
+            request = request.header("Last-Event-ID", last_event_id);
+        }
+        let response = http_client.send(request.body(AsyncBody::empty())?).await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("server responded with {}", response.status()));
+        }
+        Ok(response.into_body())
+    }
+
+    /// Waits until the server has announced the endpoint to post messages to.
+    async fn endpoint(&self) -> Result<Url> {
+        let mut endpoint_receiver = self.endpoint_receiver.clone();
+        while let Some(endpoint) = endpoint_receiver.recv().await {
+            if let Some(endpoint) = endpoint {
+                return Ok(endpoint);
+            }
+        }
+        Err(anyhow!("connection to context server closed"))
+    }
+}
+
+/// Resolves the endpoint announced by the server against the URL of the event
+/// stream. Messages are sent with the configured headers, so the endpoint has
+/// to be on the same origin.
+fn resolve_endpoint(url: &Url, endpoint: &str) -> Result<Url> {
+    let endpoint = url
+        .join(endpoint.trim())
+        .with_context(|| format!("invalid endpoint {endpoint:?}"))?;
+    if endpoint.origin() != url.origin() {
+        return Err(anyhow!(
+            "endpoint {endpoint} is not on the same origin as {url}"
+        ));
+    }
+    Ok(endpoint)
+}
+
+#[async_trait]
+impl Transport for SseTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let endpoint = self.endpoint().await?;
+        let request = http_request(Method::POST, &endpoint, &self.headers)
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(message))?;
+        let response = self
+            .http_client
+            .send(request)
+            .await
+            .with_context(|| format!("failed to post message to {endpoint}"))?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "server responded to message with {}",
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.message_receiver.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.error_receiver.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Client, ContextServerId};
+    use futures::{channel::mpsc, AsyncReadExt as _, FutureExt as _, TryStreamExt as _};
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Request, Response};
+    use parking_lot::Mutex;
+    use serde_json::{json, Value};
+    use std::{io, iter, time::Duration};
+
+    #[derive(Default)]
+    struct FakeServer {
+        connections: Vec<Request<()>>,
+        posts: Vec<Request<()>>,
+        stream: Option<mpsc::UnboundedSender<io::Result<Vec<u8>>>>,
+        next_event_id: usize,
+        /// Prepended to the endpoint the server announces, e.g. to make it absolute.
+        endpoint_prefix: String,
+        /// Whether the server closes the stream right after announcing the endpoint.
+        close_after_endpoint: bool,
+    }
+
+    impl FakeServer {
+        fn send_event(&mut self, event: &str, data: &str) {
+            self.next_event_id += 1;
+            let event = format!(
+                "id: {}\nevent: {event}\ndata: {data}\n\n",
+                self.next_event_id
+            );
+            if let Some(stream) = &self.stream {
+                stream.unbounded_send(Ok(event.into_bytes())).ok();
+            }
+        }
+    }
+
+    fn fake_http_client(server: Arc<Mutex<FakeServer>>) -> Arc<dyn HttpClient> {
+        FakeHttpClient::create(move |request| {
+            let server = server.clone();
+            async move {
+                let (parts, body) = request.into_parts();
+                let mut body_text = String::new();
+                let mut body = body;
+                body.read_to_string(&mut body_text).await?;
+                let request = Request::from_parts(parts, ());
+
+                let mut server = server.lock();
+                if request.method() == Method::GET {
+                    let (stream, body) = mpsc::unbounded();
+                    server.stream = Some(stream);
+                    server.connections.push(request);
+                    let endpoint = format!(
+                        "{}/messages?session={}",
+                        server.endpoint_prefix,
+                        server.connections.len()
+                    );
+                    server.send_event("endpoint", &endpoint);
+                    if server.close_after_endpoint {
+                        server.stream.take();
+                    }
+                    return Ok(Response::builder()
+                        .status(200)
+                        .header("Content-Type", "text/event-stream")
+                        .body(AsyncBody::from_reader(body.into_async_read()))
+                        .unwrap());
+                }
+
+                let message: Value = serde_json::from_str(&body_text)?;
+                server.posts.push(request);
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "result": { "method": message["method"] },
+                });
+                server.send_event("message", &response.to_string());
+                Ok(Response::builder()
+                    .status(202)
+                    .body(AsyncBody::empty())
+                    .unwrap())
+            }
+        })
+    }
+
+    fn received_errors(errors: &mut Pin<Box<dyn Stream<Item = String> + Send>>) -> Vec<String> {
+        iter::from_fn(|| errors.next().now_or_never().flatten()).collect()
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport(cx: &mut TestAppContext) {
+        let server = Arc::new(Mutex::new(FakeServer::default()));
+        let http_client = fake_http_client(server.clone());
+
+        let transport = SseTransport::new(
+            Url::parse("http://test.example/sse").unwrap(),
+            HashMap::from_iter([("Authorization".to_string(), "Bearer token".to_string())]),
+            http_client,
+            cx.executor(),
+        );
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(transport),
+            cx.to_async(),
+        );
+
+        let response: Value = client.request("ping", ()).await.unwrap();
+        assert_eq!(response, json!({ "method": "ping" }));
+        {
+            let server = server.lock();
+            assert_eq!(server.connections.len(), 1);
+            assert_eq!(server.posts.len(), 1);
+            let post = &server.posts[0];
+            assert_eq!(post.uri(), "http://test.example/messages?session=1");
+            assert_eq!(post.headers()["Authorization"], "Bearer token");
+            assert_eq!(
+                server.connections[0].headers()["Authorization"],
+                "Bearer token"
+            );
+        }
+
+        // When the stream drops, the transport reconnects after a delay,
+        // resuming from the last event it received.
+        server.lock().stream.take();
+        cx.run_until_parked();
+        assert_eq!(server.lock().connections.len(), 1);
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        {
+            let server = server.lock();
+            assert_eq!(server.connections.len(), 2);
+            assert_eq!(server.connections[1].headers()["Last-Event-ID"], "2");
+        }
+
+        let response: Value = client.request("tools/list", ()).await.unwrap();
+        assert_eq!(response, json!({ "method": "tools/list" }));
+        assert_eq!(
+            server.lock().posts[1].uri(),
+            "http://test.example/messages?session=2"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport_gives_up_on_server_closing_stream(cx: &mut TestAppContext) {
+        let server = Arc::new(Mutex::new(FakeServer {
+            close_after_endpoint: true,
+            ..FakeServer::default()
+        }));
+        let transport = SseTransport::new(
+            Url::parse("http://test.example/sse").unwrap(),
+            HashMap::default(),
+            fake_http_client(server.clone()),
+            cx.executor(),
+        );
+        let mut errors = transport.receive_err();
+
+        // Connections that don't deliver any message count as failed attempts,
+        // even though the server accepted them.
+        for _ in 0..10 {
+            cx.run_until_parked();
+            cx.executor().advance_clock(Duration::from_secs(30));
+        }
+        cx.run_until_parked();
+        assert_eq!(
+            server.lock().connections.len(),
+            MAX_RECONNECT_ATTEMPTS as usize + 1
+        );
+        assert!(received_errors(&mut errors)
+            .iter()
+            .any(|error| error.starts_with("giving up on http://test.example/sse")));
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport_rejects_endpoint_on_other_origin(cx: &mut TestAppContext) {
+        let url = Url::parse("http://test.example/sse").unwrap();
+        assert_eq!(
+            resolve_endpoint(&url, "/messages").unwrap().as_str(),
+            "http://test.example/messages"
+        );
+        assert_eq!(
+            resolve_endpoint(&url, "http://test.example/messages")
+                .unwrap()
+                .as_str(),
+            "http://test.example/messages"
+        );
+        for endpoint in [
+            "http://other.example/messages",
+            "https://test.example/messages",
+            "http://test.example:8080/messages",
+            "//other.example/messages",
+        ] {
+            assert!(resolve_endpoint(&url, endpoint).is_err(), "{endpoint}");
+        }
+
+        let server = Arc::new(Mutex::new(FakeServer {
+            endpoint_prefix: "http://other.example".into(),
+            ..FakeServer::default()
+        }));
+        let transport = Arc::new(SseTransport::new(
+            url,
+            HashMap::from_iter([("Authorization".to_string(), "Bearer token".to_string())]),
+            fake_http_client(server.clone()),
+            cx.executor(),
+        ));
+        let mut errors = transport.receive_err();
+        let _send = cx.executor().spawn({
+            let transport = transport.clone();
+            async move { transport.send("{}".into()).await }
+        });
+        cx.run_until_parked();
+
+        assert_eq!(server.lock().connections.len(), 1);
+        assert!(server.lock().posts.is_empty());
+        assert_eq!(
+            received_errors(&mut errors),
+            ["endpoint http://other.example/messages?session=1 is not on the same origin as http://test.example/sse"]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::pin::Pin;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use futures::{AsyncRead, Stream};
+use gpui::{AsyncAppContext, Task};
+use smol::{
+    channel,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
+    lock::Mutex,
+    process::{Child, ChildStdin},
+};
+use util::ResultExt as _;
+
+use crate::client::ModelContextServerBinary;
+use crate::transport::Transport;
+
+/// A transport that spawns the context server as a subprocess and exchanges
+/// newline-delimited messages over its stdin and stdout.
+pub struct StdioTransport {
+    stdin: Mutex<BufWriter<ChildStdin>>,
+    stdout_receiver: channel::Receiver<String>,
+    stderr_receiver: channel::Receiver<String>,
+    server: Child,
+    _io_tasks: (Task<()>, Task<()>),
+}
+
+impl StdioTransport {
+    pub fn new(binary: ModelContextServerBinary, cx: &AsyncAppContext) -> Result<Self> {
+        log::info!(
+            "starting context server (executable={:?}, args={:?})",
+            binary.executable,
+            &binary.args
+        );
+
+        let mut command = util::command::new_smol_command(&binary.executable);
+        command
+            .args(&binary.args)
+            .envs(binary.env.unwrap_or_default())
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut server = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn command. (path={:?}, args={:?})",
+                binary.executable, &binary.args
+            )
+        })?;
+
+        let stdin = server.stdin.take().unwrap();
+        let stdout = server.stdout.take().unwrap();
+        let stderr = server.stderr.take().unwrap();
+
+        let (stdout_sender, stdout_receiver) = channel::unbounded();
+        let (stderr_sender, stderr_receiver) = channel::unbounded();
+        let executor = cx.background_executor();
+        let stdout_task = executor.spawn(Self::read_lines(stdout, stdout_sender));
+        let stderr_task = executor.spawn(Self::read_lines(stderr, stderr_sender));
+
+        Ok(Self {
+            stdin: Mutex::new(BufWriter::new(stdin)),
+            stdout_receiver,
+            stderr_receiver,
+            server,
+            _io_tasks: (stdout_task, stderr_task),
+        })
+    }
+
+    /// Forwards each non-empty line of the given output to the sender, until
+    /// the output is closed.
+    async fn read_lines<Output>(output: Output, sender: channel::Sender<String>)
+    where
+        Output: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut output = BufReader::new(output);
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            match output.read_line(&mut buffer).await.log_err() {
+                Some(0) | None => return,
+                Some(_) => {}
+            }
+
+            let line = buffer.trim();
+            if !line.is_empty() && sender.send(line.to_string()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(message.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stdout_receiver.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stderr_receiver.clone())
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        let _ = self.server.kill();
+    }
+}
@@ -0,0 +1,446 @@
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{AsyncReadExt as _, Stream, StreamExt};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{http, AsyncBody, HttpClient, Method, StatusCode, Url};
+use parking_lot::Mutex;
+use smol::channel;
+use util::ResultExt as _;
+
+use crate::transport::event_stream::parse_events;
+use crate::transport::{
+    http_request, reconnect_delay, Transport, MAX_RECONNECT_ATTEMPTS,
+    MIN_STABLE_CONNECTION_DURATION,
+};
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+
+/// A transport for context servers implementing the streamable HTTP transport.
+///
+/// Every message is `POST`ed to a single URL, and the server answers with
+/// either a JSON body or an event stream. Once connected, a `GET` request to
+/// the same URL listens for messages the server sends on its own.
+pub struct StreamableHttpTransport {
+    state: Arc<State>,
+    message_receiver: channel::Receiver<String>,
+    error_receiver: channel::Receiver<String>,
+    listener: Mutex<Option<Task<()>>>,
+}
+
+struct State {
+    url: Url,
+    headers: HashMap<String, String>,
+    http_client: Arc<dyn HttpClient>,
+    executor: BackgroundExecutor,
+    session_id: Mutex<Option<String>>,
+    message_sender: channel::Sender<String>,
+    error_sender: channel::Sender<String>,
+}
+
+impl StreamableHttpTransport {
+    pub fn new(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let (message_sender, message_receiver) = channel::unbounded();
+        let (error_sender, error_receiver) = channel::unbounded();
+        Self {
+            state: Arc::new(State {
+                url,
+                headers,
+                http_client,
+                executor,
+                session_id: Mutex::new(None),
+                message_sender,
+                error_sender,
+            }),
+            message_receiver,
+            error_receiver,
+            listener: Mutex::new(None),
+        }
+    }
+
+    fn start_listener(&self) {
+        let mut listener = self.listener.lock();
+        if listener.is_none() {
+            let state = self.state.clone();
+            *listener = Some(self.state.executor.spawn(state.listen()));
+        }
+    }
+}
+
+impl State {
+    fn request(&self, method: Method) -> http::request::Builder {
+        let request = http_request(method, &self.url, &self.headers);
+        match self.session_id.lock().as_deref() {
+            Some(session_id) => request.header(SESSION_ID_HEADER, session_id),
+            None => request,
+        }
+    }
+
+    /// Keeps a stream for server-initiated messages open, reconnecting with an
+    /// increasing delay whenever it drops, until too many attempts in a row
+    /// have failed or the server turns out not to offer such a stream.
+    async fn listen(self: Arc<Self>) {
+        let mut last_event_id = None;
+        let mut failed_attempts = 0;
+        loop {
+            match self.open_stream(last_event_id.as_deref()).await {
+                Ok(Some(body)) => {
+                    let connected_at = self.executor.now();
+                    let mut received_message = false;
+                    if let Err(error) = self
+                        .forward_events(body, &mut last_event_id, &mut received_message)
+                        .await
+                    {
+                        self.report_error(format!(
+                            "event stream from {} failed: {error:#}",
+                            self.url
+                        ));
+                    }
+
+                    // Servers that close the stream right away keep counting
+                    // as failed attempts, so that we eventually give up.
+                    if received_message
+                        || self.executor.now() - connected_at >= MIN_STABLE_CONNECTION_DURATION
+                    {
+                        failed_attempts = 0;
+                    }
+                }
+                Ok(None) => return,
+                Err(error) => {
+                    self.report_error(format!("failed to connect to {}: {error:#}", self.url));
+                }
+            }
+
+            failed_attempts += 1;
+            if failed_attempts > MAX_RECONNECT_ATTEMPTS {
+                self.report_error(format!(
+                    "giving up on {} after {MAX_RECONNECT_ATTEMPTS} reconnection attempts",
+                    self.url
+                ));
+                return;
+            }
+            self.executor.timer(reconnect_delay(failed_attempts)).await;
+        }
+    }
+
+    /// Opens a stream for server-initiated messages, returning `None` if the
+    /// server doesn't offer one.
+    async fn open_stream(&self, last_event_id: Option<&str>) -> Result<Option<AsyncBody>> {
+        let mut request = self
+            .request(Method::GET)
+            .header("Accept", "text/event-stream");
+        if let Some(last_event_id) = last_event_id {
+            request = request.header("Last-Event-ID", last_event_id);
+        }
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await?;
+        if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("server responded with {}", response.status()));
+        }
+        Ok(Some(response.into_body()))
+    }
+
+    async fn forward_events(
+        &self,
+        body: AsyncBody,
+        last_event_id: &mut Option<String>,
+        received_message: &mut bool,
+    ) -> Result<()> {
+        let mut events = pin!(parse_events(body));
+        while let Some(event) = events.next().await {
+            let event = event?;
+            if event.id.is_some() {
+                *last_event_id = event.id;
+            }
+            if event.event == "message" {
+                *received_message = true;
+                self.forward_json(&event.data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forwards the messages in the given JSON, which is either a single
+    /// message or a batch of them.
+    async fn forward_json(&self, json: &str) -> Result<()> {
+        let json = json.trim();
+        if json.is_empty() {
+            return Ok(());
+        }
+
+        let messages = match serde_json::from_str(json)? {
+            serde_json::Value::Array(messages) => messages
+                .into_iter()
+                .map(|message| message.to_string())
+                .collect(),
+            _ => vec![json.to_string()],
+        };
+        for message in messages {
+            self.message_sender.send(message).await?;
+        }
+        Ok(())
+    }
+
+    fn report_error(&self, error: String) {
+        self.error_sender.try_send(error).ok();
+    }
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let state = &self.state;
+        let had_session = state.session_id.lock().is_some();
+        let request = state
+            .request(Method::POST)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .body(AsyncBody::from(message))?;
+        let response = state
+            .http_client
+            .send(request)
+            .await
+            .with_context(|| format!("failed to post message to {}", state.url))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND && had_session {
+            state.session_id.lock().take();
+            return Err(anyhow!("context server session expired"));
+        }
+        if !status.is_success() {
+            return Err(anyhow!("server responded to message with {status}"));
+        }
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|session_id| session_id.to_str().ok())
+        {
+            *state.session_id.lock() = Some(session_id.to_string());
+        }
+        self.start_listener();
+
+        if status == StatusCode::ACCEPTED {
+            return Ok(());
+        }
+
+        let content_type = response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let mut body = response.into_body();
+        if content_type.starts_with("text/event-stream") {
+            // The server may keep the stream open to send requests of its own
+            // before the response, so consume it without blocking other messages.
+            let state = state.clone();
+            state
+                .executor
+                .clone()
+                .spawn(async move {
+                    state
+                        .forward_events(body, &mut None, &mut false)
+                        .await
+                        .context("failed to read response stream")
+                        .log_err();
+                })
+                .detach();
+        } else if content_type.starts_with("application/json") {
+            let mut json = String::new();
+            body.read_to_string(&mut json).await?;
+            state.forward_json(&json).await?;
+        }
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.message_receiver.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.error_receiver.clone())
+    }
+}
+
+impl Drop for StreamableHttpTransport {
+    fn drop(&mut self) {
+        if self.state.session_id.lock().is_none() {
+            return;
+        }
+
+        // Let the server know it can discard the session.
+        if let Some(request) = self
+            .state
+            .request(Method::DELETE)
+            .body(AsyncBody::empty())
+            .log_err()
+        {
+            let response = self.state.http_client.send(request);
+            self.state
+                .executor
+                .spawn(async move {
+                    response.await.log_err();
+                })
+                .detach();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Client, ContextServerId};
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Request, Response};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FakeServer {
+        posts: Vec<(Option<String>, Value)>,
+        listen_attempts: usize,
+        deleted_sessions: Vec<String>,
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_transport(cx: &mut TestAppContext) {
+        let server = Arc::new(Mutex::new(FakeServer::default()));
+        let http_client = FakeHttpClient::create({
+            let server = server.clone();
+            move |request: Request<AsyncBody>| {
+                let server = server.clone();
+                async move {
+                    let session_id = request
+                        .headers()
+                        .get(SESSION_ID_HEADER)
+                        .map(|session_id| session_id.to_str().unwrap().to_string());
+                    let method = request.method().clone();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+
+                    let mut server = server.lock();
+                    if method == Method::GET {
+                        server.listen_attempts += 1;
+                        return Ok(Response::builder()
+                            .status(405)
+                            .body(AsyncBody::empty())
+                            .unwrap());
+                    }
+                    if method == Method::DELETE {
+                        server.deleted_sessions.extend(session_id);
+                        return Ok(Response::builder()
+                            .status(200)
+                            .body(AsyncBody::empty())
+                            .unwrap());
+                    }
+
+                    let message: Value = serde_json::from_str(&body)?;
+                    server.posts.push((session_id, message.clone()));
+                    let response = json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"],
+                        "result": { "method": message["method"] },
+                    });
+                    let response = match message["method"].as_str() {
+                        Some("initialize") => Response::builder()
+                            .status(200)
+                            .header("Content-Type", "application/json")
+                            .header(SESSION_ID_HEADER, "session-1")
+                            .body(AsyncBody::from(response.to_string())),
+                        Some("tools/call") => {
+                            let progress = json!({
+                                "jsonrpc": "2.0",
+                                "method": "notifications/progress",
+                                "params": { "progress": 1 },
+                            });
+                            Response::builder()
+                                .status(200)
+                                .header("Content-Type", "text/event-stream")
+                                .body(AsyncBody::from(format!(
+                                    "data: {progress}\n\ndata: {response}\n\n"
+                                )))
+                        }
+                        Some("tools/list") => Response::builder()
+                            .status(200)
+                            .header("Content-Type", "application/json")
+                            .body(AsyncBody::from(json!([response]).to_string())),
+                        _ => Response::builder().status(202).body(AsyncBody::empty()),
+                    };
+                    Ok(response.unwrap())
+                }
+            }
+        });
+
+        let transport = StreamableHttpTransport::new(
+            Url::parse("http://test.example/mcp").unwrap(),
+            HashMap::default(),
+            http_client,
+            cx.executor(),
+        );
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(transport),
+            cx.to_async(),
+        );
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        client.on_notification("notifications/progress", {
+            let notifications = notifications.clone();
+            move |params, _| notifications.lock().push(params)
+        });
+
+        let response: Value = client.request("initialize", ()).await.unwrap();
+        assert_eq!(response, json!({ "method": "initialize" }));
+        client.notify("notifications/initialized", ()).unwrap();
+        cx.run_until_parked();
+
+        // Responses may arrive as an event stream, preceded by notifications.
+        let response: Value = client.request("tools/call", ()).await.unwrap();
+        assert_eq!(response, json!({ "method": "tools/call" }));
+        assert_eq!(*notifications.lock(), [json!({ "progress": 1 })]);
+
+        // Or as a batch of JSON messages.
+        let response: Value = client.request("tools/list", ()).await.unwrap();
+        assert_eq!(response, json!({ "method": "tools/list" }));
+
+        let session_ids = server
+            .lock()
+            .posts
+            .iter()
+            .map(|(session_id, _)| session_id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            session_ids,
+            [
+                None,
+                Some("session-1".to_string()),
+                Some("session-1".to_string()),
+                Some("session-1".to_string()),
+            ]
+        );
+
+        // The server doesn't offer a stream of its own, so the transport stops
+        // trying to open one.
+        cx.executor().advance_clock(Duration::from_secs(60));
+        cx.run_until_parked();
+        assert_eq!(server.lock().listen_attempts, 1);
+
+        drop(client);
+        cx.run_until_parked();
+        assert_eq!(server.lock().deleted_sessions, ["session-1"]);
+    }
+}
@@ -18,6 +18,16 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The URL of a remote context server to connect to instead of running a command.
+    pub url: Option<String>,
+    /// Additional HTTP headers to send with every request to the remote
+    /// context server, e.g. for authentication.
+    pub headers: Option<HashMap<String, String>>,
+    /// The transport used to talk to the remote context server at `url`.
+    ///
+    /// Default: streamable_http
+    #[serde(default)]
+    pub transport: ServerTransport,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
@@ -40,6 +50,17 @@ pub struct ServerCommand {
     pub env: Option<HashMap<String, String>>,
 }
 
+/// The transport used to connect to a remote context server.
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerTransport {
+    /// Send every message in an HTTP POST request, receiving responses as JSON or server-sent events.
+    #[default]
+    StreamableHttp,
+    /// Receive messages over server-sent events, and send them to the endpoint announced by the server.
+    Sse,
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ContextServerSettings {
     /// Settings for context servers used in the Assistant.
@@ -216,9 +216,12 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptBoth,
         AcceptInlineCompletion,
         AcceptPartialCopilotSuggestion,
         AcceptPartialInlineCompletion,
+        AcceptOurs,
+        AcceptTheirs,
         AddSelectionAbove,
         AddSelectionBelow,
         ApplyAllDiffHunks,
@@ -366,6 +369,7 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
         SwitchSourceHeader,
         Tab,
         TabPrev,
@@ -0,0 +1,356 @@
+use std::{sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use futures::future::join_all;
+use gpui::{Model, Task, ViewContext, WeakView};
+use language::{
+    language_settings::language_settings, Buffer, BufferId, Point, ToOffset as _, ToPoint as _,
+};
+use multi_buffer::ExcerptId;
+use project::{CodeLens, ResolveState};
+use ui::{prelude::*, ButtonLike, ButtonStyle, LabelSize};
+use util::ResultExt;
+use workspace::notifications::NotifyTaskExt;
+
+use crate::{
+    display_map::{
+        BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+const RESOLVE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// The code lenses of an editor's buffers and the blocks that show them.
+#[derive(Default)]
+pub(super) struct CodeLensState {
+    update_task: Option<Task<Option<()>>>,
+    resolve_task: Option<Task<Option<()>>>,
+    buffers: HashMap<BufferId, BufferCodeLens>,
+    blocks: HashMap<CustomBlockId, CodeLensBlock>,
+}
+
+struct BufferCodeLens {
+    buffer: Model<Buffer>,
+    /// The lenses as the project returned them, which stay the same until the
+    /// buffer changes.
+    fetched: Arc<[CodeLens]>,
+    /// The same lenses, with those that were shown resolved.
+    lenses: Vec<CodeLens>,
+}
+
+/// A block showing the lenses of a line, which is kept while the line has
+/// lenses so that the lines below don't move when they're fetched again.
+struct CodeLensBlock {
+    excerpt_id: ExcerptId,
+    buffer_id: BufferId,
+    anchor: text::Anchor,
+}
+
+/// Fetches the code lenses of the editor's buffers whose language has them
+/// enabled, and shows each line's lenses in a block above it.
+pub(super) fn refresh_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.code_lens.update_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let buffers = editor
+            .update(&mut cx, |editor, cx| {
+                editor
+                    .buffer
+                    .read(cx)
+                    .all_buffers()
+                    .into_iter()
+                    .filter(|buffer| {
+                        let buffer = buffer.read(cx);
+                        language_settings(
+                            buffer.language().map(|language| language.name()),
+                            buffer.file(),
+                            cx,
+                        )
+                        .code_lens
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let lenses = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .iter()
+                    .map(|buffer| project.code_lens(buffer, cx))
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let lenses = join_all(lenses).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let mut buffer_lenses = HashMap::default();
+                for (buffer, fetched) in buffers.into_iter().zip(lenses) {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    let previous = editor.code_lens.buffers.remove(&buffer_id);
+                    let lenses = match (fetched.log_err(), previous) {
+                        // Keep what was resolved while the buffer didn't change.
+                        (Some(fetched), Some(previous))
+                            if Arc::ptr_eq(&fetched, &previous.fetched) =>
+                        {
+                            previous
+                        }
+                        (Some(fetched), _) => BufferCodeLens {
+                            buffer,
+                            lenses: fetched.to_vec(),
+                            fetched,
+                        },
+                        (None, Some(previous)) => previous,
+                        (None, None) => continue,
+                    };
+                    buffer_lenses.insert(buffer_id, lenses);
+                }
+                editor.code_lens.buffers = buffer_lenses;
+
+                resolve_visible_code_lens(editor, cx);
+                update_code_lens_blocks(editor, cx);
+            })
+            .ok()
+    }));
+    None
+}
+
+/// Resolves the lenses that were scrolled into view, once scrolling settles.
+pub(super) fn refresh_visible_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.code_lens.buffers.is_empty() {
+        return;
+    }
+    editor.code_lens.resolve_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(RESOLVE_DEBOUNCE).await;
+        editor
+            .update(&mut cx, |editor, cx| resolve_visible_code_lens(editor, cx))
+            .ok()
+    }));
+}
+
+/// Requests the commands of the visible lenses that the language server sent
+/// without one, since resolving them can be expensive, e.g. counting references.
+fn resolve_visible_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let mut resolve_tasks = Vec::new();
+    for (buffer, _, visible_range) in editor
+        .excerpts_for_inlay_hints_query(None, cx)
+        .into_values()
+    {
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(buffer_lenses) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+            continue;
+        };
+        let snapshot = buffer.read(cx).text_snapshot();
+        for (ix, lens) in buffer_lenses.lenses.iter_mut().enumerate() {
+            if !matches!(lens.resolve_state, ResolveState::CanResolve(..)) {
+                continue;
+            }
+            let offset = lens.range.start.to_offset(&snapshot);
+            if offset < visible_range.start || offset > visible_range.end {
+                continue;
+            }
+            let resolve = project.update(cx, |project, cx| {
+                project.resolve_code_lens(lens.clone(), buffer.clone(), cx)
+            });
+            lens.resolve_state = ResolveState::Resolving;
+            let fetched = buffer_lenses.fetched.clone();
+            resolve_tasks.push(async move { (buffer_id, fetched, ix, resolve.await) });
+        }
+    }
+    if resolve_tasks.is_empty() {
+        return;
+    }
+
+    cx.spawn(|editor, mut cx| async move {
+        let resolved_lenses = join_all(resolve_tasks).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, fetched, ix, resolved) in resolved_lenses {
+                    let Some(buffer_lenses) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+                        continue;
+                    };
+                    if !Arc::ptr_eq(&buffer_lenses.fetched, &fetched) {
+                        continue;
+                    }
+                    match resolved.log_err() {
+                        Some(lens) => buffer_lenses.lenses[ix] = lens,
+                        // Don't request it again, it stays hidden.
+                        None => buffer_lenses.lenses[ix].resolve_state = ResolveState::Resolved,
+                    }
+                }
+                update_code_lens_blocks(editor, cx);
+            })
+            .ok()
+    })
+    .detach();
+}
+
+/// Updates the blocks showing the lenses, reusing the block of each line that
+/// still has lenses.
+fn update_code_lens_blocks(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multibuffer = editor.buffer.read(cx);
+    let mut lenses_by_line =
+        HashMap::<(ExcerptId, BufferId, u32), (Model<Buffer>, Vec<CodeLens>)>::default();
+    for (buffer_id, buffer_lenses) in &editor.code_lens.buffers {
+        let buffer = &buffer_lenses.buffer;
+        let buffer_snapshot = buffer.read(cx).text_snapshot();
+        for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+            let context = excerpt_range.context;
+            for lens in &buffer_lenses.lenses {
+                let start = &lens.range.start;
+                if start.cmp(&context.start, &buffer_snapshot).is_lt()
+                    || start.cmp(&context.end, &buffer_snapshot).is_gt()
+                {
+                    continue;
+                }
+                let row = start.to_point(&buffer_snapshot).row;
+                lenses_by_line
+                    .entry((excerpt_id, *buffer_id, row))
+                    .or_insert_with(|| (buffer.clone(), Vec::new()))
+                    .1
+                    .push(lens.clone());
+            }
+        }
+    }
+
+    let editor_handle = cx.view().downgrade();
+    let render = |buffer: Model<Buffer>, lenses: Vec<CodeLens>| -> RenderBlock {
+        let editor = editor_handle.clone();
+        Arc::new(move |cx: &mut BlockContext| render_code_lens_line(&editor, &buffer, &lenses, cx))
+    };
+
+    let mut replaced_blocks = HashMap::default();
+    let mut removed_blocks = HashSet::default();
+    for (block_id, block) in &editor.code_lens.blocks {
+        let line = editor
+            .code_lens
+            .buffers
+            .get(&block.buffer_id)
+            .map(|buffer_lenses| {
+                let snapshot = buffer_lenses.buffer.read(cx).text_snapshot();
+                let row = block.anchor.to_point(&snapshot).row;
+                (block.excerpt_id, block.buffer_id, row)
+            });
+        match line.and_then(|line| lenses_by_line.remove(&line)) {
+            Some((buffer, lenses)) => {
+                replaced_blocks.insert(*block_id, render(buffer, lenses));
+            }
+            None => {
+                removed_blocks.insert(*block_id);
+            }
+        }
+    }
+
+    let snapshot = multibuffer.snapshot(cx);
+    let mut new_blocks = Vec::new();
+    let mut new_block_lines = Vec::new();
+    for ((excerpt_id, buffer_id, row), (buffer, lenses)) in lenses_by_line {
+        // Align the lenses with the text of the line they're shown above.
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let indent = buffer_snapshot.indent_size_for_line(row).len;
+        let buffer_anchor = buffer_snapshot.anchor_before(Point::new(row, indent));
+        let Some(anchor) = snapshot.anchor_in_excerpt(excerpt_id, buffer_anchor) else {
+            continue;
+        };
+        new_blocks.push(BlockProperties {
+            placement: BlockPlacement::Above(anchor),
+            height: 1,
+            style: BlockStyle::Flex,
+            render: render(buffer, lenses),
+            priority: 0,
+        });
+        new_block_lines.push(CodeLensBlock {
+            excerpt_id,
+            buffer_id,
+            anchor: buffer_anchor,
+        });
+    }
+
+    editor
+        .code_lens
+        .blocks
+        .retain(|block_id, _| !removed_blocks.contains(block_id));
+    editor.remove_blocks(removed_blocks, None, cx);
+    editor.replace_blocks(replaced_blocks, None, cx);
+    let block_ids = editor.insert_blocks(new_blocks, None, cx);
+    editor
+        .code_lens
+        .blocks
+        .extend(block_ids.into_iter().zip(new_block_lines));
+    cx.notify();
+}
+
+fn render_code_lens_line(
+    editor: &WeakView<Editor>,
+    buffer: &Model<Buffer>,
+    lenses: &[CodeLens],
+    cx: &mut BlockContext,
+) -> AnyElement {
+    h_flex()
+        .h(cx.line_height)
+        .pl(cx.anchor_x)
+        .gap_1()
+        // Lenses that aren't resolved yet have no title to show.
+        .children(
+            lenses
+                .iter()
+                .filter(|lens| lens.lsp_lens.command.is_some())
+                .enumerate()
+                .map(|(ix, lens)| {
+                    let label = Label::new(lens.title().to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted);
+                    if !lens.executable {
+                        return label.into_any_element();
+                    }
+
+                    let editor = editor.clone();
+                    let buffer = buffer.clone();
+                    let lens = lens.clone();
+                    ButtonLike::new(ix)
+                        .style(ButtonStyle::Transparent)
+                        .child(label)
+                        .on_click(move |_, cx| {
+                            let task = editor
+                                .update(cx, |editor, cx| {
+                                    apply_code_lens(editor, buffer.clone(), lens.clone(), cx)
+                                })
+                                .ok()
+                                .flatten();
+                            if let Some(task) = task {
+                                task.detach_and_notify_err(cx);
+                            }
+                        })
+                        .into_any_element()
+                }),
+        )
+        .into_any_element()
+}
+
+/// Runs the command of a code lens, opening the edits it made to other buffers, if any.
+fn apply_code_lens(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    lens: CodeLens,
+    cx: &mut ViewContext<Editor>,
+) -> Option<Task<anyhow::Result<()>>> {
+    let project = editor.project.clone()?;
+    let workspace = editor.workspace()?.downgrade();
+    let title = lens.title().to_string();
+    let apply_code_lens =
+        project.update(cx, |project, cx| project.apply_code_lens(buffer, lens, cx));
+    Some(cx.spawn(|editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    }))
+}
@@ -0,0 +1,462 @@
+use std::{ops::Range, sync::Arc, time::Duration};
+
+use collections::HashSet;
+use futures::{channel::mpsc, StreamExt as _};
+use git::conflict::parse_conflicts;
+use gpui::{AppContext, Hsla, Model, ViewContext, WeakView};
+use language::{Buffer, BufferId, BufferSnapshot};
+use project::{git::GitState, ProjectItem as _};
+use text::OffsetRangeExt as _;
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::notifications::NotifyTaskExt as _;
+
+use crate::{
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle},
+    AcceptBoth, AcceptOurs, AcceptTheirs, Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+struct ConflictOursHighlight;
+struct ConflictBaseHighlight;
+struct ConflictTheirsHighlight;
+
+/// A merge conflict in one of the editor's buffers.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferConflict {
+    buffer_id: BufferId,
+    range: Range<text::Anchor>,
+    ours: Range<text::Anchor>,
+    base: Option<Range<text::Anchor>>,
+    theirs: Range<text::Anchor>,
+    ours_label: SharedString,
+    theirs_label: SharedString,
+}
+
+/// Which side's lines replace a merge conflict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+impl BufferConflict {
+    fn resolved_text(&self, resolution: ConflictResolution, buffer: &BufferSnapshot) -> String {
+        let ours = buffer.text_for_range(self.ours.clone());
+        let theirs = buffer.text_for_range(self.theirs.clone());
+        match resolution {
+            ConflictResolution::Ours => ours.collect(),
+            ConflictResolution::Theirs => theirs.collect(),
+            ConflictResolution::Both => ours.chain(theirs).collect(),
+        }
+    }
+
+    fn contains(&self, offset: usize, buffer: &BufferSnapshot) -> bool {
+        let range = self.range.to_offset(buffer);
+        range.start <= offset && offset < range.end.max(range.start + 1)
+    }
+}
+
+/// Finds the conflict markers in the editor's buffers whose files git reports
+/// as conflicted, highlighting each side of every conflict and showing the
+/// actions resolving it above it. Only buffers that changed since they were
+/// last scanned are scanned again. Files whose conflicts are all resolved are
+/// staged once they're saved.
+pub(super) fn refresh_conflicts(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    editor.conflicts_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let snapshots = editor
+            .update(&mut cx, |editor, cx| {
+                let git_state = editor
+                    .project
+                    .as_ref()
+                    .and_then(|project| project.read(cx).git_state().cloned());
+                let buffers = editor.buffer.read(cx).all_buffers();
+                let buffer_ids = buffers
+                    .iter()
+                    .map(|buffer| buffer.read(cx).remote_id())
+                    .collect::<HashSet<_>>();
+                editor
+                    .conflicts
+                    .retain(|buffer_id, _| buffer_ids.contains(buffer_id));
+
+                let mut snapshots = Vec::new();
+                for buffer in buffers {
+                    let buffer = buffer.read(cx);
+                    let buffer_id = buffer.remote_id();
+                    let has_conflicts = editor.conflicted_buffers.contains(&buffer_id)
+                        || is_conflicted_in_git(buffer, git_state.as_ref(), cx);
+                    if !has_conflicts {
+                        editor.conflicts.remove(&buffer_id);
+                    } else if editor
+                        .conflicts
+                        .get(&buffer_id)
+                        .map_or(true, |(version, _)| buffer.version().changed_since(version))
+                    {
+                        snapshots.push(buffer.snapshot());
+                    }
+                }
+                // Nothing to scan, and no conflicts to stop showing.
+                if snapshots.is_empty()
+                    && editor.conflicts.is_empty()
+                    && editor.conflict_blocks.is_empty()
+                {
+                    return None;
+                }
+                Some(snapshots)
+            })
+            .ok()??;
+        let conflicts = cx
+            .background_executor()
+            .spawn(async move {
+                snapshots
+                    .iter()
+                    .map(|snapshot| {
+                        let conflicts = parse_conflicts(snapshot.as_rope())
+                            .into_iter()
+                            .map(|conflict| {
+                                let anchor_range = |range: Range<usize>| {
+                                    snapshot.anchor_before(range.start)
+                                        ..snapshot.anchor_before(range.end)
+                                };
+                                BufferConflict {
+                                    buffer_id: snapshot.remote_id(),
+                                    range: anchor_range(conflict.range),
+                                    ours: anchor_range(conflict.ours),
+                                    base: conflict.base.map(anchor_range),
+                                    theirs: anchor_range(conflict.theirs),
+                                    ours_label: conflict.ours_label.into(),
+                                    theirs_label: conflict.theirs_label.into(),
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        (
+                            snapshot.remote_id(),
+                            (snapshot.version().clone(), conflicts),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.conflicts.extend(conflicts);
+                show_conflicts(editor, cx);
+                stage_resolved_buffers(editor, cx);
+            })
+            .ok()
+    }));
+}
+
+/// Whether git reports the buffer's file as conflicted, i.e. as being merged.
+fn is_conflicted_in_git(
+    buffer: &Buffer,
+    git_state: Option<&Model<GitState>>,
+    cx: &AppContext,
+) -> bool {
+    let (Some(git_state), Some(project_path)) = (git_state, buffer.project_path(cx)) else {
+        return false;
+    };
+    git_state
+        .read(cx)
+        .repository_and_path_for_project_path(&project_path)
+        .and_then(|(repository, repo_path)| repository.status_for_path(&repo_path))
+        .map_or(false, |entry| entry.status.is_conflicted())
+}
+
+fn show_conflicts(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multibuffer = editor.buffer.read(cx);
+    let snapshot = multibuffer.snapshot(cx);
+    let mut ours_highlights = Vec::new();
+    let mut base_highlights = Vec::new();
+    let mut theirs_highlights = Vec::new();
+    let mut conflicts_in_excerpts = Vec::new();
+    for conflict in editor
+        .conflicts
+        .values()
+        .flat_map(|(_, conflicts)| conflicts)
+    {
+        let Some(buffer) = multibuffer.buffer(conflict.buffer_id) else {
+            continue;
+        };
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(&buffer, cx) {
+            let context = excerpt_range.context;
+            let start = &conflict.range.start;
+            if start.cmp(&context.start, &buffer_snapshot).is_lt()
+                || start.cmp(&context.end, &buffer_snapshot).is_gt()
+            {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                snapshot.anchor_in_excerpt(excerpt_id, conflict.range.start),
+                snapshot.anchor_in_excerpt(excerpt_id, conflict.range.end),
+            ) else {
+                continue;
+            };
+            let to_multibuffer = |anchor: text::Anchor| {
+                snapshot
+                    .anchor_in_excerpt(excerpt_id, anchor)
+                    .unwrap_or(end)
+            };
+            ours_highlights.push(start..to_multibuffer(conflict.ours.end));
+            if let Some(base) = &conflict.base {
+                base_highlights.push(to_multibuffer(conflict.ours.end)..to_multibuffer(base.end));
+            }
+            theirs_highlights.push(to_multibuffer(conflict.theirs.start)..end);
+            conflicts_in_excerpts.push((start, conflict.clone()));
+        }
+    }
+
+    editor.clear_row_highlights::<ConflictOursHighlight>();
+    editor.clear_row_highlights::<ConflictBaseHighlight>();
+    editor.clear_row_highlights::<ConflictTheirsHighlight>();
+    let (ours_color, base_color, theirs_color) = conflict_colors(cx);
+    for range in ours_highlights {
+        editor.highlight_rows::<ConflictOursHighlight>(range, ours_color, false, cx);
+    }
+    for range in base_highlights {
+        editor.highlight_rows::<ConflictBaseHighlight>(range, base_color, false, cx);
+    }
+    for range in theirs_highlights {
+        editor.highlight_rows::<ConflictTheirsHighlight>(range, theirs_color, false, cx);
+    }
+
+    let editor_handle = cx.view().downgrade();
+    let blocks = conflicts_in_excerpts
+        .into_iter()
+        .map(|(anchor, conflict)| BlockProperties {
+            placement: BlockPlacement::Above(anchor),
+            height: 1,
+            style: BlockStyle::Flex,
+            render: Arc::new({
+                let editor = editor_handle.clone();
+                move |cx: &mut BlockContext| render_conflict_actions(&editor, &conflict, cx)
+            }),
+            priority: 0,
+        })
+        .collect::<Vec<_>>();
+    let previous_blocks = std::mem::take(&mut editor.conflict_blocks);
+    editor.remove_blocks(previous_blocks.into_iter().collect(), None, cx);
+    editor.conflict_blocks = editor.insert_blocks(blocks, None, cx);
+    cx.notify();
+}
+
+fn conflict_colors(cx: &AppContext) -> (Hsla, Hsla, Hsla) {
+    let status = cx.theme().status();
+    let mut ours = status.created;
+    let mut base = status.ignored;
+    let mut theirs = status.info;
+    for color in [&mut ours, &mut base, &mut theirs] {
+        color.fade_out(0.7);
+    }
+    (ours, base, theirs)
+}
+
+fn render_conflict_actions(
+    editor: &WeakView<Editor>,
+    conflict: &BufferConflict,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let button = |id: &'static str, label: String, resolution: ConflictResolution| {
+        let editor = editor.clone();
+        let conflict = conflict.clone();
+        Button::new(id, label)
+            .style(ButtonStyle::Transparent)
+            .label_size(LabelSize::Small)
+            .color(Color::Muted)
+            .on_click(move |_, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.resolve_conflicts(vec![conflict.clone()], resolution, cx)
+                    })
+                    .ok();
+            })
+    };
+    let with_label = |text: &str, label: &SharedString| {
+        if label.is_empty() {
+            text.to_string()
+        } else {
+            format!("{text} ({label})")
+        }
+    };
+
+    h_flex()
+        .h(cx.line_height)
+        .pl(cx.anchor_x)
+        .gap_1()
+        .child(button(
+            "accept-ours",
+            with_label("Accept Ours", &conflict.ours_label),
+            ConflictResolution::Ours,
+        ))
+        .child(button(
+            "accept-theirs",
+            with_label("Accept Theirs", &conflict.theirs_label),
+            ConflictResolution::Theirs,
+        ))
+        .child(button(
+            "accept-both",
+            "Accept Both".to_string(),
+            ConflictResolution::Both,
+        ))
+        .into_any_element()
+}
+
+/// Stages the files of the buffers that had conflicts when none are left in
+/// them, which marks them as resolved. Unsaved buffers are staged once they're
+/// saved.
+fn stage_resolved_buffers(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let mut resolved_buffers = Vec::new();
+    for (buffer_id, (version, conflicts)) in &editor.conflicts {
+        if !conflicts.is_empty() {
+            editor.conflicted_buffers.insert(*buffer_id);
+            continue;
+        }
+        // Buffers edited since they were scanned are staged after they're scanned again.
+        let is_scanned = editor
+            .buffer
+            .read(cx)
+            .buffer(*buffer_id)
+            .map_or(false, |buffer| {
+                !buffer.read(cx).version().changed_since(version)
+            });
+        if is_scanned && editor.conflicted_buffers.contains(buffer_id) {
+            resolved_buffers.push(*buffer_id);
+        }
+    }
+
+    let Some(git_state) = editor
+        .project
+        .as_ref()
+        .and_then(|project| project.read(cx).git_state().cloned())
+    else {
+        return;
+    };
+    let (err_sender, mut err_receiver) = mpsc::channel(1);
+    for buffer_id in resolved_buffers {
+        let Some(buffer) = editor.buffer.read(cx).buffer(buffer_id) else {
+            editor.conflicted_buffers.remove(&buffer_id);
+            continue;
+        };
+        let buffer = buffer.read(cx);
+        if buffer.is_dirty() {
+            continue;
+        }
+        editor.conflicted_buffers.remove(&buffer_id);
+        let Some((repository, repo_path)) = buffer.project_path(cx).and_then(|project_path| {
+            git_state
+                .read(cx)
+                .repository_and_path_for_project_path(&project_path)
+        }) else {
+            continue;
+        };
+        let is_conflicted = repository
+            .status_for_path(&repo_path)
+            .map_or(false, |entry| entry.status.is_conflicted());
+        if is_conflicted {
+            repository
+                .stage_entries(vec![repo_path], err_sender.clone())
+                .log_err();
+        }
+    }
+    drop(err_sender);
+
+    cx.spawn(|_, _| async move {
+        match err_receiver.next().await {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    })
+    .detach_and_notify_err(cx);
+}
+
+impl Editor {
+    pub fn accept_ours(&mut self, _: &AcceptOurs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Ours, cx);
+    }
+
+    pub fn accept_theirs(&mut self, _: &AcceptTheirs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Theirs, cx);
+    }
+
+    pub fn accept_both(&mut self, _: &AcceptBoth, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_conflicts(ConflictResolution::Both, cx);
+    }
+
+    /// Resolves the conflicts containing the heads of the selections.
+    fn resolve_selected_conflicts(
+        &mut self,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let heads = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .filter_map(|selection| {
+                let (buffer, offset) = snapshot.point_to_buffer_offset(selection.head())?;
+                Some((buffer, offset))
+            })
+            .collect::<Vec<_>>();
+        let conflicts = self
+            .conflicts
+            .values()
+            .flat_map(|(_, conflicts)| conflicts)
+            .filter(|conflict| {
+                heads.iter().any(|(buffer, offset)| {
+                    buffer.remote_id() == conflict.buffer_id && conflict.contains(*offset, buffer)
+                })
+            })
+            .cloned()
+            .collect();
+        self.resolve_conflicts(conflicts, resolution, cx);
+    }
+
+    /// Replaces the conflicts with the lines of the given side, saving the
+    /// buffers left without conflicts so that their files can be staged.
+    pub(crate) fn resolve_conflicts(
+        &mut self,
+        conflicts: Vec<BufferConflict>,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if conflicts.is_empty() {
+            return;
+        }
+
+        let mut resolved_buffers = HashSet::default();
+        self.transact(cx, |editor, cx| {
+            for conflict in &conflicts {
+                let Some(buffer) = editor.buffer.read(cx).buffer(conflict.buffer_id) else {
+                    continue;
+                };
+                buffer.update(cx, |buffer, cx| {
+                    let text = conflict.resolved_text(resolution, &buffer.snapshot());
+                    buffer.edit([(conflict.range.clone(), text)], None, cx);
+                });
+                resolved_buffers.insert(buffer);
+            }
+        });
+
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        for buffer in resolved_buffers {
+            if parse_conflicts(buffer.read(cx).as_rope()).is_empty() {
+                project
+                    .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                    .detach_and_notify_err(cx);
+            }
+        }
+    }
+}
@@ -85,6 +85,7 @@ pub trait ToDisplayPoint {
 }
 
 type TextHighlights = TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
+type SemanticTokenHighlights = Arc<[(Range<Anchor>, HighlightStyle)]>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
@@ -107,6 +108,9 @@ pub struct DisplayMap {
     block_map: BlockMap,
     /// Regions of text that should be highlighted.
     text_highlights: TextHighlights,
+    /// Styles of the semantic tokens reported by language servers, sorted by
+    /// their start and layered over the tree-sitter highlights.
+    semantic_token_highlights: SemanticTokenHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
@@ -160,6 +164,7 @@ impl DisplayMap {
             crease_map,
             fold_placeholder,
             text_highlights: Default::default(),
+            semantic_token_highlights: Arc::new([]),
             inlay_highlights: Default::default(),
             clip_at_line_ends: false,
             masked: false,
@@ -187,6 +192,7 @@ impl DisplayMap {
             block_snapshot,
             crease_snapshot: self.crease_map.snapshot(),
             text_highlights: self.text_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
@@ -471,6 +477,13 @@ impl DisplayMap {
             .insert(type_id, Arc::new((style, ranges)));
     }
 
+    pub(crate) fn set_semantic_token_highlights(
+        &mut self,
+        highlights: Vec<(Range<Anchor>, HighlightStyle)>,
+    ) {
+        self.semantic_token_highlights = highlights.into();
+    }
+
     pub(crate) fn highlight_inlays(
         &mut self,
         type_id: TypeId,
@@ -565,6 +578,7 @@ impl DisplayMap {
 #[derive(Debug, Default)]
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
+    pub semantic_token_highlights: Option<&'a SemanticTokenHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
     pub styles: HighlightStyles,
 }
@@ -699,6 +713,7 @@ pub struct DisplaySnapshot {
     wrap_snapshot: WrapSnapshot,
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
+    semantic_token_highlights: SemanticTokenHighlights,
     inlay_highlights: InlayHighlights,
     clip_at_line_ends: bool,
     masked: bool,
@@ -886,6 +901,7 @@ impl DisplaySnapshot {
             self.masked,
             Highlights {
                 text_highlights: Some(&self.text_highlights),
+                semantic_token_highlights: Some(&self.semantic_token_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
                 styles: highlight_styles,
             },
@@ -17,10 +17,13 @@ mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
+mod conflicts;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -38,6 +41,7 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -132,8 +136,9 @@ use project::{
     buffer_store::BufferChangeSet,
     lsp_store::{FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
-    CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
-    LspStore, PrepareRenameResponse, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
+    CodeAction, Completion, CompletionIntent, DocumentHighlight, DocumentLink, InlayHint, Location,
+    LocationLink, LspStore, PrepareRenameResponse, Project, ProjectItem, ProjectTransaction,
+    TaskSourceKind,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
@@ -191,6 +196,7 @@ pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 
 pub(crate) const FORMAT_TIMEOUT: Duration = Duration::from_secs(2);
 pub(crate) const SCROLL_CENTER_TOP_BOTTOM_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
+pub(crate) const SELECTION_RANGES_TIMEOUT: Duration = Duration::from_millis(200);
 
 pub fn render_parsed_markdown(
     element_id: impl Into<ElementId>,
@@ -612,6 +618,8 @@ pub struct Editor {
     autoclose_regions: Vec<AutocloseRegion>,
     snippet_stack: InvalidationStack<SnippetState>,
     select_larger_syntax_node_stack: Vec<Box<[Selection<usize>]>>,
+    select_larger_syntax_node_task: Option<Task<()>>,
+    pending_larger_syntax_node_expansions: usize,
     ime_transaction: Option<TransactionId>,
     active_diagnostics: Option<ActiveDiagnosticGroup>,
     soft_wrap_mode_override: Option<language_settings::SoftWrap>,
@@ -655,6 +663,17 @@ pub struct Editor {
     code_actions_task: Option<Task<Result<()>>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    semantic_tokens_task: Option<Task<Option<()>>>,
+    folding_ranges_task: Option<Task<Option<()>>>,
+    folding_range_creases: Vec<CreaseId>,
+    code_lens: code_lens::CodeLensState,
+    conflicts_task: Option<Task<Option<()>>>,
+    /// The conflicts of each buffer with conflicted files, and the version of
+    /// the buffer they were found in.
+    conflicts: HashMap<BufferId, (clock::Global, Vec<conflicts::BufferConflict>)>,
+    conflict_blocks: Vec<CustomBlockId>,
+    /// The buffers that had conflicts, whose files get staged once they're resolved.
+    conflicted_buffers: HashSet<BufferId>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -1189,6 +1208,13 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, cx);
+                    } else if let project::Event::RefreshCodeLens = event {
+                        code_lens::refresh_code_lens(editor, cx);
+                    } else if let project::Event::LanguageServerAdded(..) = event {
+                        folding_ranges::refresh_folding_ranges(editor, cx);
+                        code_lens::refresh_code_lens(editor, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1259,6 +1285,8 @@ impl Editor {
             autoclose_regions: Default::default(),
             snippet_stack: Default::default(),
             select_larger_syntax_node_stack: Vec::new(),
+            select_larger_syntax_node_task: None,
+            pending_larger_syntax_node_expansions: 0,
             ime_transaction: Default::default(),
             active_diagnostics: None,
             soft_wrap_mode_override,
@@ -1301,6 +1329,14 @@ impl Editor {
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            semantic_tokens_task: Default::default(),
+            folding_ranges_task: Default::default(),
+            folding_range_creases: Vec::new(),
+            code_lens: Default::default(),
+            conflicts_task: Default::default(),
+            conflicts: HashMap::default(),
+            conflict_blocks: Vec::new(),
+            conflicted_buffers: HashSet::default(),
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
@@ -1380,6 +1416,10 @@ impl Editor {
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        semantic_tokens::refresh_semantic_tokens(&mut this, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, cx);
+        code_lens::refresh_code_lens(&mut this, cx);
+        conflicts::refresh_conflicts(&mut this, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -8923,6 +8963,168 @@ impl Editor {
         &mut self,
         _: &SelectLargerSyntaxNode,
         cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            self.select_larger_node(&HashMap::default(), cx);
+            return;
+        };
+
+        // Without a language server to wait for, expand right away.
+        if self.pending_larger_syntax_node_expansions == 0 {
+            match self.lsp_selection_ranges(&project, cx) {
+                Some(selection_ranges) => {
+                    self.pending_larger_syntax_node_expansions = 1;
+                    self.select_larger_syntax_node_task =
+                        Some(self.expand_to_selection_ranges(selection_ranges, cx));
+                }
+                None => self.select_larger_node(&HashMap::default(), cx),
+            }
+            return;
+        }
+
+        // Expansions must apply one after the other, so that pressing the
+        // keybinding repeatedly expands the selections as many times.
+        self.pending_larger_syntax_node_expansions += 1;
+        let previous_task = self.select_larger_syntax_node_task.take();
+        self.select_larger_syntax_node_task = Some(cx.spawn(|editor, mut cx| async move {
+            if let Some(previous_task) = previous_task {
+                previous_task.await;
+            }
+            let Ok(expansion) = editor.update(&mut cx, |editor, cx| {
+                match editor.lsp_selection_ranges(&project, cx) {
+                    Some(selection_ranges) => {
+                        Some(editor.expand_to_selection_ranges(selection_ranges, cx))
+                    }
+                    None => {
+                        editor.pending_larger_syntax_node_expansions -= 1;
+                        editor.select_larger_node(&HashMap::default(), cx);
+                        None
+                    }
+                }
+            }) else {
+                return;
+            };
+            if let Some(expansion) = expansion {
+                expansion.await;
+            }
+        }));
+    }
+
+    /// Expands the selections once the language servers respond, falling back
+    /// to syntax nodes when they take longer than [`SELECTION_RANGES_TIMEOUT`].
+    fn expand_to_selection_ranges(
+        &self,
+        selection_ranges: Task<HashMap<usize, Vec<Range<Anchor>>>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<()> {
+        let mut timeout = cx
+            .background_executor()
+            .timer(SELECTION_RANGES_TIMEOUT)
+            .fuse();
+        cx.spawn(|editor, mut cx| async move {
+            let selection_ranges = futures::select_biased! {
+                selection_ranges = selection_ranges.fuse() => selection_ranges,
+                () = timeout => {
+                    log::debug!("timed out waiting for selection ranges");
+                    HashMap::default()
+                }
+            };
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.pending_larger_syntax_node_expansions -= 1;
+                    editor.select_larger_node(&selection_ranges, cx)
+                })
+                .ok();
+        })
+    }
+
+    /// Requests the selection ranges of the language servers around the start
+    /// of every selection, keyed by selection id. Returns `None` when none of
+    /// the servers of the selected buffers can provide selection ranges.
+    fn lsp_selection_ranges(
+        &self,
+        project: &Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<HashMap<usize, Vec<Range<Anchor>>>>> {
+        let selections = self.selections.all::<usize>(cx);
+        let multibuffer = self.buffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        let mut selections_by_buffer = HashMap::<BufferId, (Model<Buffer>, Vec<_>)>::default();
+        for selection in selections {
+            let Some(excerpt) = snapshot.excerpt_containing(selection.range()) else {
+                continue;
+            };
+            let Some(buffer) = multibuffer.buffer(excerpt.buffer_id()) else {
+                continue;
+            };
+            let position = excerpt
+                .buffer()
+                .anchor_before(excerpt.map_offset_to_buffer(selection.start));
+            selections_by_buffer
+                .entry(excerpt.buffer_id())
+                .or_insert_with(|| (buffer, Vec::new()))
+                .1
+                .push((selection.id, excerpt.id(), position));
+        }
+        selections_by_buffer.retain(|_, (buffer, _)| {
+            project.update(cx, |project, cx| {
+                project.supports_selection_ranges(buffer, cx)
+            })
+        });
+        if selections_by_buffer.is_empty() {
+            return None;
+        }
+
+        let requests = selections_by_buffer
+            .into_values()
+            .map(|(buffer, selections)| {
+                let positions = selections
+                    .iter()
+                    .map(|(_, _, position)| *position)
+                    .collect();
+                let request = project.update(cx, |project, cx| {
+                    project.selection_ranges(&buffer, positions, cx)
+                });
+                async move { (selections, request.await) }
+            })
+            .collect::<Vec<_>>();
+        Some(cx.spawn(|editor, mut cx| async move {
+            let responses = future::join_all(requests).await;
+            editor
+                .update(&mut cx, |editor, cx| {
+                    let snapshot = editor.buffer.read(cx).snapshot(cx);
+                    let mut selection_ranges = HashMap::default();
+                    for (selections, response) in responses {
+                        let Some(response) = response.log_err() else {
+                            continue;
+                        };
+                        for ((selection_id, excerpt_id, _), ranges) in
+                            selections.into_iter().zip(response)
+                        {
+                            let ranges = ranges
+                                .into_iter()
+                                .filter_map(|range| {
+                                    Some(
+                                        snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+                                            ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+                                    )
+                                })
+                                .collect();
+                            selection_ranges.insert(selection_id, ranges);
+                        }
+                    }
+                    selection_ranges
+                })
+                .unwrap_or_default()
+        }))
+    }
+
+    /// Expands every selection to the smallest enclosing syntax node or
+    /// language server selection range, whichever is smaller.
+    fn select_larger_node(
+        &mut self,
+        selection_ranges: &HashMap<usize, Vec<Range<Anchor>>>,
+        cx: &mut ViewContext<Self>,
     ) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
@@ -8947,6 +9149,26 @@ impl Editor {
                     }
                 }
 
+                let lsp_range = selection_ranges
+                    .get(&selection.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|range| range.start.to_offset(&buffer)..range.end.to_offset(&buffer))
+                    .filter(|range| {
+                        range.start <= old_range.start
+                            && range.end >= old_range.end
+                            && range.len() > old_range.len()
+                            && !display_map.intersects_fold(range.start)
+                            && !display_map.intersects_fold(range.end)
+                    })
+                    .min_by_key(|range| range.len());
+                if let Some(lsp_range) = lsp_range {
+                    if new_range == old_range || lsp_range.len() < new_range.len() {
+                        new_range = lsp_range;
+                        new_node = None;
+                    }
+                }
+
                 if let Some(node) = new_node {
                     // Log the ancestor, to support using this action as a way to explore TreeSitter
                     // nodes. Parent and grandparent are also logged because this operation will not
@@ -8983,6 +9205,8 @@ impl Editor {
         _: &SelectSmallerSyntaxNode,
         cx: &mut ViewContext<Self>,
     ) {
+        self.select_larger_syntax_node_task = None;
+        self.pending_larger_syntax_node_expansions = 0;
         let mut stack = mem::take(&mut self.select_larger_syntax_node_stack);
         if let Some(selections) = stack.pop() {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
@@ -12506,6 +12730,7 @@ impl Editor {
                     }
                 }
 
+                conflicts::refresh_conflicts(self, cx);
                 let Some(project) = &self.project else { return };
                 let (telemetry, is_via_ssh) = {
                     let project = project.read(cx);
@@ -12514,6 +12739,9 @@ impl Editor {
                     (telemetry, is_via_ssh)
                 };
                 refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, cx);
                 telemetry.log_edit_event("editor", is_via_ssh);
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -12534,6 +12762,10 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, cx);
+                conflicts::refresh_conflicts(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -12556,11 +12788,17 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
             multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
+            multi_buffer::Event::Saved => {
+                conflicts::refresh_conflicts(self, cx);
+                cx.emit(EditorEvent::Saved)
+            }
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
                 cx.emit(EditorEvent::TitleChanged)
             }
@@ -12586,6 +12824,9 @@ impl Editor {
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(cx));
         self.refresh_inline_completion(true, false, cx);
+        semantic_tokens::refresh_semantic_tokens(self, cx);
+        folding_ranges::refresh_folding_ranges(self, cx);
+        code_lens::refresh_code_lens(self, cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
@@ -13690,6 +13931,19 @@ pub trait SemanticsProvider {
         cx: &mut AppContext,
     ) -> Option<Task<Result<Vec<LocationLink>>>>;
 
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Arc<[DocumentLink]>>>>;
+
+    fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<DocumentLink>>>;
+
     fn range_for_rename(
         &self,
         buffer: &Model<Buffer>,
@@ -14058,6 +14312,25 @@ impl SemanticsProvider for Model<Project> {
         }))
     }
 
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Arc<[DocumentLink]>>>> {
+        Some(self.update(cx, |project, cx| project.document_links(buffer, cx)))
+    }
+
+    fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<DocumentLink>>> {
+        Some(self.update(cx, |project, cx| {
+            project.resolve_document_link(link, buffer_handle, cx)
+        }))
+    }
+
     fn supports_inlay_hints(&self, buffer: &Model<Buffer>, cx: &mut AppContext) -> bool {
         // TODO: make this work for remote projects
         buffer.update(cx, |buffer, cx| {
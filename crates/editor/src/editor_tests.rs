@@ -3372,6 +3372,281 @@ async fn test_custom_newlines_cause_no_false_positive_diffs(
     });
 }
 
+#[gpui::test]
+async fn test_stage_selected_hunks(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let fs =
+        cx.update_editor(|editor, cx| editor.project.as_ref().unwrap().read(cx).fs().as_fake());
+    let dot_git = EditorTestContext::root_path().join(".git");
+    let index_text = |fs: &FakeFs| {
+        let mut text = None;
+        fs.with_git_state(&dot_git, false, |state| {
+            text = state.index_contents.get(Path::new("file")).cloned();
+        });
+        text
+    };
+
+    cx.set_state(
+        &r#"
+        ˇONE
+        two
+        three
+        FOUR
+        FIVE
+        "#
+        .unindent(),
+    );
+    cx.set_diff_base(
+        &r#"
+        one
+        two
+        three
+        "#
+        .unindent(),
+    );
+    executor.run_until_parked();
+
+    // A cursor stages the whole hunk it's in.
+    cx.update_editor(|editor, cx| editor.stage_selected_hunks(&StageSelectedHunks, cx));
+    executor.run_until_parked();
+    assert_eq!(
+        index_text(&fs).as_deref(),
+        Some(
+            r#"
+            ONE
+            two
+            three
+            "#
+            .unindent()
+            .as_str()
+        )
+    );
+
+    // A selection only stages the lines it spans.
+    cx.set_state(
+        &r#"
+        ONE
+        two
+        three
+        FOUR
+        «FIVEˇ»
+        "#
+        .unindent(),
+    );
+    cx.update_editor(|editor, cx| editor.stage_selected_hunks(&StageSelectedHunks, cx));
+    executor.run_until_parked();
+    assert_eq!(
+        index_text(&fs).as_deref(),
+        Some(
+            r#"
+            ONE
+            two
+            three
+            FIVE
+            "#
+            .unindent()
+            .as_str()
+        )
+    );
+}
+
+#[gpui::test]
+async fn test_stage_part_of_modified_hunk(
+    executor: BackgroundExecutor,
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let fs =
+        cx.update_editor(|editor, cx| editor.project.as_ref().unwrap().read(cx).fs().as_fake());
+    let dot_git = EditorTestContext::root_path().join(".git");
+    let index_text = |fs: &FakeFs| {
+        let mut text = None;
+        fs.with_git_state(&dot_git, false, |state| {
+            text = state.index_contents.get(Path::new("file")).cloned();
+        });
+        text
+    };
+
+    cx.set_state(
+        &r#"
+        one
+        TWO
+        «THREEˇ»
+        five
+        "#
+        .unindent(),
+    );
+    cx.set_diff_base(
+        &r#"
+        one
+        two
+        three
+        four
+        five
+        "#
+        .unindent(),
+    );
+    executor.run_until_parked();
+
+    // Only the index line paired with the selected row is replaced, and the
+    // lines the hunk removes are kept.
+    cx.update_editor(|editor, cx| editor.stage_selected_hunks(&StageSelectedHunks, cx));
+    executor.run_until_parked();
+    assert_eq!(
+        index_text(&fs).as_deref(),
+        Some(
+            r#"
+            one
+            two
+            THREE
+            four
+            five
+            "#
+            .unindent()
+            .as_str()
+        )
+    );
+}
+
+#[gpui::test]
+async fn test_resolve_merge_conflicts(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+    let fs =
+        cx.update_editor(|editor, cx| editor.project.as_ref().unwrap().read(cx).fs().as_fake());
+    let dot_git = EditorTestContext::root_path().join(".git");
+    let status = |fs: &FakeFs| {
+        let mut status = None;
+        fs.with_git_state(&dot_git, false, |state| {
+            status = state
+                .statuses
+                .get(&git::repository::RepoPath::from("file"))
+                .copied();
+        });
+        status
+    };
+    fs.set_status_for_repo_via_git_operation(
+        &dot_git,
+        &[(
+            Path::new("file"),
+            git::status::UnmergedStatus {
+                first_head: git::status::UnmergedStatusCode::Updated,
+                second_head: git::status::UnmergedStatusCode::Updated,
+            }
+            .into(),
+        )],
+    );
+
+    cx.set_state(
+        &r#"
+        one
+        <<<<<<< HEAD
+        ˇtwo
+        =======
+        TWO
+        >>>>>>> feature
+        three
+        <<<<<<< HEAD
+        four
+        ||||||| base
+        4
+        =======
+        FOUR
+        >>>>>>> feature
+        "#
+        .unindent(),
+    );
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+
+    cx.update_editor(|editor, cx| editor.accept_theirs(&AcceptTheirs, cx));
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+    assert_eq!(
+        cx.update_editor(|editor, cx| editor.text(cx)),
+        r#"
+        one
+        TWO
+        three
+        <<<<<<< HEAD
+        four
+        ||||||| base
+        4
+        =======
+        FOUR
+        >>>>>>> feature
+        "#
+        .unindent(),
+    );
+    // The file isn't resolved while it has conflicts.
+    assert!(status(&fs).unwrap().is_conflicted());
+
+    cx.set_selections_state(
+        &r#"
+        one
+        TWO
+        three
+        <<<<<<< HEAD
+        four
+        ||||||| base
+        4
+        =======
+        FOUˇR
+        >>>>>>> feature
+        "#
+        .unindent(),
+    );
+    cx.update_editor(|editor, cx| editor.accept_both(&AcceptBoth, cx));
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+    assert_eq!(
+        cx.update_editor(|editor, cx| editor.text(cx)),
+        "one\nTWO\nthree\nfour\nFOUR\n"
+    );
+
+    // Resolving the last conflict saves and stages the file.
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+    cx.update_editor(|editor, cx| assert!(!editor.buffer().read(cx).is_dirty(cx)));
+    assert_eq!(
+        status(&fs),
+        Some(git::status::FileStatus::index(
+            git::status::StatusCode::Modified
+        ))
+    );
+}
+
+#[gpui::test]
+async fn test_conflict_markers_in_unmerged_files(
+    executor: BackgroundExecutor,
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Files that git doesn't report as conflicted, such as test fixtures, may
+    // contain conflict markers too. They aren't treated as conflicts.
+    let text = r#"
+        one
+        <<<<<<< HEAD
+        ˇtwo
+        =======
+        TWO
+        >>>>>>> feature
+        "#
+    .unindent();
+    cx.set_state(&text);
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+
+    cx.update_editor(|editor, cx| editor.accept_theirs(&AcceptTheirs, cx));
+    executor.advance_clock(Duration::from_secs(1));
+    executor.run_until_parked();
+    cx.assert_editor_state(&text);
+}
+
 #[gpui::test]
 async fn test_manipulate_lines_with_single_selection(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5480,6 +5755,140 @@ async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_larger_syntax_node_with_lsp_selection_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {r#"
+        fn main() {
+            let s = "hello woˇrld";
+            let t = "oˇne two";
+        }
+    "#});
+
+    // The language server selects the words within strings, which are
+    // smaller than any syntax node containing the cursors.
+    let mut requests =
+        cx.handle_request::<lsp::request::SelectionRangeRequest, _, _>(|_, params, _| async move {
+            Ok(Some(
+                params
+                    .positions
+                    .into_iter()
+                    .map(|position| {
+                        let word = if position.line == 1 { 19..24 } else { 13..16 };
+                        lsp::SelectionRange {
+                            range: lsp::Range::new(
+                                lsp::Position::new(position.line, word.start),
+                                lsp::Position::new(position.line, word.end),
+                            ),
+                            parent: None,
+                        }
+                    })
+                    .collect(),
+            ))
+        });
+
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    requests.next().await;
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        fn main() {
+            let s = "hello «worldˇ»";
+            let t = "«oneˇ» two";
+        }
+    "#});
+
+    cx.update_editor(|editor, cx| editor.select_smaller_syntax_node(&SelectSmallerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {r#"
+        fn main() {
+            let s = "hello woˇrld";
+            let t = "oˇne two";
+        }
+    "#});
+}
+
+#[gpui::test]
+async fn test_select_larger_syntax_node_without_lsp_selection_ranges(
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(lsp::ServerCapabilities::default(), cx).await;
+    cx.set_state(indoc! {r#"
+        fn main() {
+            let x = foo(aˇbc);
+        }
+    "#});
+
+    // Without a server able to provide selection ranges, the selections
+    // expand synchronously.
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.assert_editor_state(indoc! {r#"
+        fn main() {
+            let x = foo(«abcˇ»);
+        }
+    "#});
+}
+
+#[gpui::test]
+async fn test_select_larger_syntax_node_with_slow_lsp_selection_ranges(
+    cx: &mut gpui::TestAppContext,
+) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {r#"
+        fn main() {
+            let x = foo(aˇbc);
+        }
+    "#});
+
+    // The language server never responds.
+    let mut requests =
+        cx.handle_request::<lsp::request::SelectionRangeRequest, _, _>(|_, _, _| async move {
+            futures::future::pending().await
+        });
+
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    cx.update_editor(|editor, cx| editor.select_larger_syntax_node(&SelectLargerSyntaxNode, cx));
+    requests.next().await;
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        fn main() {
+            let x = foo(aˇbc);
+        }
+    "#});
+
+    // Both expansions fall back to syntax nodes once the requests time out.
+    cx.executor().advance_clock(SELECTION_RANGES_TIMEOUT);
+    requests.next().await;
+    cx.run_until_parked();
+    cx.executor().advance_clock(SELECTION_RANGES_TIMEOUT);
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {r#"
+        fn main() {
+            let x = foo«(abc)ˇ»;
+        }
+    "#});
+}
+
 #[gpui::test]
 async fn test_autoindent(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
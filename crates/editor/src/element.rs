@@ -457,6 +457,10 @@ impl EditorElement {
         register_action(view, cx, Editor::accept_inline_completion);
         register_action(view, cx, Editor::revert_file);
         register_action(view, cx, Editor::revert_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_hunks);
+        register_action(view, cx, Editor::accept_ours);
+        register_action(view, cx, Editor::accept_theirs);
+        register_action(view, cx, Editor::accept_both);
         register_action(view, cx, Editor::apply_all_diff_hunks);
         register_action(view, cx, Editor::apply_selected_diff_hunks);
         register_action(view, cx, Editor::open_active_item_in_terminal);
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use language::language_settings::language_settings;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{display_map::Crease, Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Fetches the folding ranges of the editor's buffers whose language enables
+/// LSP folding ranges, and registers them as creases so that they take
+/// precedence over the indentation-based folds.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.folding_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let buffers = editor
+            .update(&mut cx, |editor, cx| {
+                editor
+                    .buffer
+                    .read(cx)
+                    .all_buffers()
+                    .into_iter()
+                    .filter(|buffer| {
+                        let buffer = buffer.read(cx);
+                        language_settings(
+                            buffer.language().map(|language| language.name()),
+                            buffer.file(),
+                            cx,
+                        )
+                        .lsp_folding_ranges
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let ranges = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .iter()
+                    .map(|buffer| project.folding_ranges(buffer, cx))
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let ranges = join_all(ranges).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer.read(cx);
+                let snapshot = multibuffer.snapshot(cx);
+                let placeholder = editor.display_map.read(cx).fold_placeholder.clone();
+                let mut creases = Vec::new();
+                for (buffer, ranges) in buffers.iter().zip(ranges) {
+                    let Some(ranges) = ranges.log_err() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).text_snapshot();
+                    for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+                        let context = excerpt_range.context;
+                        for range in ranges.iter() {
+                            let range = &range.range;
+                            if range.start.cmp(&context.start, &buffer_snapshot).is_lt()
+                                || range.end.cmp(&context.end, &buffer_snapshot).is_gt()
+                            {
+                                continue;
+                            }
+
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, range.start);
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, range.end);
+                            if let Some((start, end)) = start.zip(end) {
+                                creases.push(Crease::simple(start..end, placeholder.clone()));
+                            }
+                        }
+                    }
+                }
+
+                let previous_creases = std::mem::take(&mut editor.folding_range_creases);
+                editor.remove_creases(previous_creases, cx);
+                editor.folding_range_creases = editor.insert_creases(creases, cx);
+                cx.notify();
+            })
+            .ok()
+    }));
+    None
+}
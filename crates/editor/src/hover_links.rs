@@ -4,6 +4,7 @@ use crate::{
     scroll::ScrollAmount,
     Anchor, Editor, EditorSettings, EditorSnapshot, FindAllReferences, GoToDefinition,
     GoToTypeDefinition, GotoDefinitionKind, InlayId, Navigated, PointForPosition, SelectPhase,
+    SemanticsProvider,
 };
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
 use language::{Bias, ToOffset};
@@ -513,7 +514,24 @@ pub fn show_link_definition(
         async move {
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
+                    if let Some((link_range, link)) = find_document_link(
+                        &buffer,
+                        provider.as_deref(),
+                        project.clone(),
+                        buffer_position,
+                        &mut cx,
+                    )
+                    .await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+                        Some((range, vec![link]))
+                    } else if let Some((url_range, url)) =
+                        find_url(&buffer, buffer_position, cx.clone())
+                    {
                         this.update(&mut cx, |_, _| {
                             let range = maybe!({
                                 let start =
@@ -632,6 +650,54 @@ pub fn show_link_definition(
     editor.hovered_link_state = Some(hovered_link_state);
 }
 
+/// Finds the link reported by the language server at the given position, if any.
+///
+/// Links to local files open them in the workspace, other links open in the browser.
+async fn find_document_link(
+    buffer: &Model<language::Buffer>,
+    provider: Option<&dyn SemanticsProvider>,
+    project: Option<Model<Project>>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let links = cx
+        .update(|cx| provider?.document_links(buffer, cx))
+        .ok()??
+        .await
+        .ok()?;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let link = links
+        .iter()
+        .find(|link| {
+            link.range.start.cmp(&position, &snapshot).is_le()
+                && link.range.end.cmp(&position, &snapshot).is_gt()
+        })?
+        .clone();
+    // Only the link under the cursor is resolved, as resolving all of a
+    // document's links would flood the language server with requests.
+    let link = if link.target.is_some() {
+        link
+    } else {
+        cx.update(|cx| provider?.resolve_document_link(link, buffer.clone(), cx))
+            .ok()??
+            .await
+            .log_err()?
+    };
+    let target = link.target?;
+
+    if target.scheme() != "file" {
+        return Some((link.range, HoverLink::Url(target.to_string())));
+    }
+    let path = target.to_file_path().ok()?;
+    let resolved_path = project?
+        .update(cx, |project, cx| {
+            project.resolve_abs_path(&path.to_string_lossy(), cx)
+        })
+        .ok()?
+        .await?;
+    Some((link.range, HoverLink::File(resolved_path)))
+}
+
 pub(crate) fn find_url(
     buffer: &Model<language::Buffer>,
     position: text::Anchor,
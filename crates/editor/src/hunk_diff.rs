@@ -1,4 +1,5 @@
 use collections::{HashMap, HashSet};
+use futures::{channel::mpsc, StreamExt as _};
 use git::diff::DiffHunkStatus;
 use gpui::{
     Action, AppContext, Corner, CursorStyle, Hsla, Model, MouseButton, Subscription, Task, View,
@@ -9,21 +10,22 @@ use multi_buffer::{
     MultiBufferSnapshot, ToOffset, ToPoint,
 };
 use project::buffer_store::BufferChangeSet;
-use std::{ops::Range, sync::Arc};
+use std::{iter, ops::Range, sync::Arc};
 use sum_tree::TreeMap;
-use text::OffsetRangeExt;
+use text::{OffsetRangeExt, Rope, ToPoint as _};
 use ui::{
     prelude::*, ActiveTheme, ContextMenu, IconButtonShape, InteractiveElement, IntoElement,
     ParentElement, PopoverMenu, Styled, Tooltip, ViewContext, VisualContext,
 };
-use util::RangeExt;
-use workspace::Item;
+use util::{RangeExt, ResultExt as _};
+use workspace::{notifications::NotifyTaskExt as _, Item};
 
 use crate::{
-    editor_settings::CurrentLineHighlight, hunk_status, hunks_for_selections, ApplyAllDiffHunks,
-    ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight,
-    DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs, GoToHunk, GoToPrevHunk,
-    RevertFile, RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    editor_settings::CurrentLineHighlight, hunk_status, hunks_for_ranges, hunks_for_selections,
+    ApplyAllDiffHunks, ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId,
+    DiffRowHighlight, DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs,
+    GoToHunk, GoToPrevHunk, RevertFile, RevertSelectedHunks, StageSelectedHunks, ToDisplayPoint,
+    ToggleHunkDiff,
 };
 
 #[derive(Debug, Clone)]
@@ -524,6 +526,117 @@ impl Editor {
         }
     }
 
+    pub fn stage_selected_hunks(&mut self, _: &StageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let snapshot = self.snapshot(cx);
+        let mut hunks = Vec::<(MultiBufferDiffHunk, Vec<Range<MultiBufferRow>>)>::new();
+        for selection in self.selections.all::<Point>(cx) {
+            // A cursor stages the whole hunks it touches, while a selection only
+            // stages the lines it spans.
+            let selected_rows = (!selection.is_empty()).then(|| {
+                let end_row =
+                    if selection.end.column == 0 && selection.end.row > selection.start.row {
+                        selection.end.row
+                    } else {
+                        selection.end.row + 1
+                    };
+                MultiBufferRow(selection.start.row)..MultiBufferRow(end_row)
+            });
+            for hunk in hunks_for_ranges(iter::once(selection.range()), &snapshot) {
+                let rows = selected_rows
+                    .clone()
+                    .unwrap_or_else(|| hunk.row_range.clone());
+                let existing = hunks.iter_mut().find(|(existing, _)| {
+                    existing.buffer_id == hunk.buffer_id
+                        && existing.diff_base_byte_range == hunk.diff_base_byte_range
+                });
+                if let Some((_, existing_rows)) = existing {
+                    existing_rows.push(rows);
+                } else {
+                    hunks.push((hunk, vec![rows]));
+                }
+            }
+        }
+        self.stage_hunks(hunks, cx);
+    }
+
+    fn stage_hunk(&mut self, hunk: &HoveredHunk, cx: &mut ViewContext<Editor>) {
+        let snapshot = self.buffer.read(cx).read(cx);
+        let Some(hunk) = to_diff_hunk(hunk, &snapshot) else {
+            return;
+        };
+        drop(snapshot);
+        let rows = hunk.row_range.clone();
+        self.stage_hunks(vec![(hunk, vec![rows])], cx);
+    }
+
+    /// Writes the given rows of each hunk to the index of the hunk's
+    /// repository.
+    fn stage_hunks(
+        &mut self,
+        hunks: Vec<(MultiBufferDiffHunk, Vec<Range<MultiBufferRow>>)>,
+        cx: &mut ViewContext<Editor>,
+    ) {
+        let Some(git_state) = self
+            .project
+            .as_ref()
+            .and_then(|project| project.read(cx).git_state().cloned())
+        else {
+            return;
+        };
+        let mut hunks_by_buffer = HashMap::<BufferId, Vec<_>>::default();
+        for (hunk, rows) in hunks {
+            hunks_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push((hunk, rows));
+        }
+
+        let (err_sender, mut err_receiver) = mpsc::channel(1);
+        for (buffer_id, mut hunks) in hunks_by_buffer {
+            let Some(buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            // The hunks of branch buffers are relative to their base buffer,
+            // rather than to the index.
+            if buffer.base_buffer().is_some() {
+                continue;
+            }
+            let Some(index_text) = self
+                .diff_map
+                .diff_bases
+                .get(&buffer_id)
+                .and_then(|diff_base| diff_base.change_set.read(cx).base_text.clone())
+            else {
+                continue;
+            };
+            let Some((repository, repo_path)) = buffer.project_path(cx).and_then(|project_path| {
+                git_state
+                    .read(cx)
+                    .repository_and_path_for_project_path(&project_path)
+            }) else {
+                continue;
+            };
+            let new_index_text = staged_text(
+                index_text.read(cx).as_rope(),
+                &buffer.text_snapshot(),
+                &mut hunks,
+            );
+            repository
+                .set_index_text(repo_path, Some(new_index_text), err_sender.clone())
+                .log_err();
+        }
+        drop(err_sender);
+
+        cx.spawn(|_, _| async move {
+            match err_receiver.next().await {
+                Some(error) => Err(error),
+                None => Ok(()),
+            }
+        })
+        .detach_and_notify_err(cx);
+    }
+
     fn has_multiple_hunks(&self, cx: &AppContext) -> bool {
         let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut hunks = self.diff_map.snapshot.diff_hunks(&snapshot);
@@ -658,6 +771,33 @@ impl Editor {
                                                     }),
                                             )
                                         })
+                                        .when(!is_branch_buffer, |row| {
+                                            row.child(
+                                                IconButton::new("stage", IconName::Check)
+                                                    .shape(IconButtonShape::Square)
+                                                    .icon_size(IconSize::Small)
+                                                    .tooltip({
+                                                        let focus_handle = editor.focus_handle(cx);
+                                                        move |cx| {
+                                                            Tooltip::for_action_in(
+                                                                "Stage Hunk",
+                                                                &StageSelectedHunks,
+                                                                &focus_handle,
+                                                                cx,
+                                                            )
+                                                        }
+                                                    })
+                                                    .on_click({
+                                                        let editor = editor.clone();
+                                                        let hunk = hunk.clone();
+                                                        move |_event, cx| {
+                                                            editor.update(cx, |editor, cx| {
+                                                                editor.stage_hunk(&hunk, cx);
+                                                            });
+                                                        }
+                                                    }),
+                                            )
+                                        })
                                         .child(
                                             IconButton::new("discard", IconName::Undo)
                                                 .shape(IconButtonShape::Square)
@@ -1121,6 +1261,65 @@ pub(crate) fn to_diff_hunk(
     })
 }
 
+/// Returns the index text after staging the given rows of each hunk.
+///
+/// A hunk whose rows are all selected replaces the part of the index text it
+/// changes. Otherwise, like `git add -p`, each selected row only replaces the
+/// index line at the same position in the hunk, and the other index lines are
+/// kept.
+fn staged_text(
+    index_text: &Rope,
+    buffer: &text::BufferSnapshot,
+    hunks: &mut [(MultiBufferDiffHunk, Vec<Range<MultiBufferRow>>)],
+) -> String {
+    hunks.sort_by_key(|(hunk, _)| hunk.diff_base_byte_range.start);
+    let mut text = String::new();
+    let mut index_offset = 0;
+    for (hunk, selected_rows) in hunks.iter() {
+        let base_range = hunk.diff_base_byte_range.clone();
+        text.extend(index_text.chunks_in_range(index_offset..base_range.start));
+        index_offset = base_range.end;
+
+        let is_selected = |row: u32| {
+            selected_rows
+                .iter()
+                .any(|rows| rows.contains(&MultiBufferRow(row)))
+        };
+        let start_row = hunk.buffer_range.start.to_point(buffer).row;
+        let buffer_line = |ix: u32| {
+            let end = Point::new(start_row + ix + 1, 0).min(buffer.max_point());
+            buffer.text_for_range(Point::new(start_row + ix, 0)..end)
+        };
+        let row_count = hunk.row_range.end.0 - hunk.row_range.start.0;
+        if (hunk.row_range.start.0..hunk.row_range.end.0).all(is_selected) {
+            for ix in 0..row_count {
+                text.extend(buffer_line(ix));
+            }
+            continue;
+        }
+
+        let base_start_row = index_text.offset_to_point(base_range.start).row;
+        let base_end = index_text.offset_to_point(base_range.end);
+        let base_row_count = base_end.row + (base_end.column > 0) as u32 - base_start_row;
+        let base_line = |ix: u32| {
+            let start = index_text.point_to_offset(Point::new(base_start_row + ix, 0));
+            let end = index_text
+                .point_to_offset(Point::new(base_start_row + ix + 1, 0))
+                .min(base_range.end);
+            index_text.chunks_in_range(start..end)
+        };
+        for ix in 0..row_count.max(base_row_count) {
+            if ix < row_count && is_selected(hunk.row_range.start.0 + ix) {
+                text.extend(buffer_line(ix));
+            } else if ix < base_row_count {
+                text.extend(base_line(ix));
+            }
+        }
+    }
+    text.extend(index_text.chunks_in_range(index_offset..index_text.len()));
+    text
+}
+
 fn added_hunk_color(cx: &AppContext) -> Hsla {
     let mut created_color = cx.theme().status().git().created;
     created_color.fade_out(0.7);
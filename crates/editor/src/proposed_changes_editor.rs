@@ -6,7 +6,7 @@ use language::{Buffer, BufferEvent, Capability};
 use multi_buffer::{ExcerptRange, MultiBuffer};
 use project::{buffer_store::BufferChangeSet, Project};
 use smol::stream::StreamExt;
-use std::{any::TypeId, ops::Range, rc::Rc, time::Duration};
+use std::{any::TypeId, ops::Range, rc::Rc, sync::Arc, time::Duration};
 use text::ToOffset;
 use ui::{prelude::*, ButtonLike, KeyBinding};
 use workspace::{
@@ -484,6 +484,25 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
         self.0.definitions(&buffer, position, kind, cx)
     }
 
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Arc<[project::DocumentLink]>>>> {
+        let buffer = self.to_base(&buffer, &[], cx)?;
+        self.0.document_links(&buffer, cx)
+    }
+
+    fn resolve_document_link(
+        &self,
+        link: project::DocumentLink,
+        buffer: Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<gpui::Result<project::DocumentLink>>> {
+        let buffer = self.to_base(&buffer, &[], cx)?;
+        self.0.resolve_document_link(link, buffer, cx)
+    }
+
     fn range_for_rename(
         &self,
         _: &Model<Buffer>,
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{FontWeight, HighlightStyle, StrikethroughStyle, UnderlineStyle};
+use language::{language_settings::language_settings, HighlightMap};
+use project::{BufferSemanticTokens, SemanticToken};
+use theme::{ActiveTheme, SyntaxTheme};
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Fetches the semantic tokens of the editor's buffers whose language has them
+/// enabled, and highlights them over the tree-sitter highlights.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.semantic_tokens_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let buffers = editor
+            .update(&mut cx, |editor, cx| {
+                editor
+                    .buffer
+                    .read(cx)
+                    .all_buffers()
+                    .into_iter()
+                    .filter(|buffer| {
+                        let buffer = buffer.read(cx);
+                        language_settings(
+                            buffer.language().map(|language| language.name()),
+                            buffer.file(),
+                            cx,
+                        )
+                        .semantic_tokens
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let tokens = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .iter()
+                    .map(|buffer| project.semantic_tokens(buffer, cx))
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        let tokens = join_all(tokens).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let theme = cx.theme().syntax().clone();
+                let multibuffer = editor.buffer.read(cx);
+                let snapshot = multibuffer.snapshot(cx);
+                let mut highlights = Vec::new();
+                for (buffer, tokens) in buffers.iter().zip(tokens) {
+                    let Some(tokens) = tokens.log_err() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).text_snapshot();
+                    let mut styles = HashMap::default();
+                    let excerpts = multibuffer.excerpts_for_buffer(buffer, cx);
+                    for (excerpt_id, excerpt_range) in excerpts {
+                        let context = excerpt_range.context;
+                        for token in &tokens.tokens {
+                            let range = &token.range;
+                            if range.end.cmp(&context.start, &buffer_snapshot).is_le() {
+                                continue;
+                            }
+                            if range.start.cmp(&context.end, &buffer_snapshot).is_ge() {
+                                break;
+                            }
+
+                            let Some(style) = *styles
+                                .entry((token.token_type, token.token_modifiers))
+                                .or_insert_with(|| semantic_token_style(&tokens, token, &theme))
+                            else {
+                                continue;
+                            };
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, range.start);
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, range.end);
+                            if let Some((start, end)) = start.zip(end) {
+                                highlights.push((start..end, style));
+                            }
+                        }
+                    }
+                }
+                highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_semantic_token_highlights(highlights)
+                });
+                cx.notify();
+            })
+            .ok()
+    }));
+    None
+}
+
+/// Maps a semantic token to the theme's syntax styles, by translating its type
+/// and modifiers into the capture names used by tree-sitter highlight queries.
+fn semantic_token_style(
+    tokens: &BufferSemanticTokens,
+    token: &SemanticToken,
+    theme: &SyntaxTheme,
+) -> Option<HighlightStyle> {
+    let token_type = tokens.token_type(token)?;
+    let mut capture_name = match token_type {
+        "namespace" => "namespace",
+        "type" | "class" | "struct" | "union" | "typeAlias" | "selfTypeKeyword" => "type",
+        "interface" | "trait" => "type.interface",
+        "builtinType" => "type.builtin",
+        "typeParameter" => "type.parameter",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" => "variable",
+        "selfKeyword" => "variable.special",
+        "property" => "property",
+        "function" => "function",
+        "method" => "function.method",
+        "macro" | "derive" => "function.special",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "escapeSequence" => "string.escape",
+        "regexp" => "string.regex",
+        "number" => "number",
+        "boolean" => "boolean",
+        "operator" => "operator",
+        "decorator" | "attribute" => "attribute",
+        "lifetime" => "lifetime",
+        "label" => "label",
+        _ => token_type,
+    }
+    .to_string();
+    for modifier in tokens.token_modifiers(token) {
+        capture_name.push('.');
+        capture_name.push_str(match modifier {
+            "documentation" => "doc",
+            "declaration" | "definition" => "definition",
+            modifier => modifier,
+        });
+    }
+
+    let mut style = HighlightMap::new(&[&capture_name], theme)
+        .get(0)
+        .style(theme)
+        .unwrap_or_default();
+    // Most themes don't style these modifiers, yet they carry useful
+    // information, so they get a default style akin to other editors'.
+    for modifier in tokens.token_modifiers(token) {
+        match modifier {
+            "mutable" if style.underline.is_none() => {
+                style.underline = Some(UnderlineStyle {
+                    thickness: 1.0.into(),
+                    ..Default::default()
+                });
+            }
+            "unsafe" if style.font_weight.is_none() => {
+                style.font_weight = Some(FontWeight::BOLD);
+            }
+            "deprecated" if style.strikethrough.is_none() => {
+                style.strikethrough = Some(StrikethroughStyle {
+                    thickness: 1.0.into(),
+                    ..Default::default()
+                });
+            }
+            _ => {}
+        }
+    }
+
+    (style != HighlightStyle::default()).then_some(style)
+}
@@ -14,7 +14,7 @@ use open_ai::OpenAiEmbeddingModel;
 use project::Project;
 use reqwest_client::ReqwestClient;
 use semantic_index::{
-    EmbeddingProvider, OpenAiEmbeddingProvider, ProjectIndex, SemanticDb, Status,
+    EmbeddingProvider, OpenAiEmbeddingProvider, ProjectIndex, SearchFilter, SemanticDb, Status,
 };
 use serde::{Deserialize, Serialize};
 use settings::SettingsStore;
@@ -439,7 +439,12 @@ async fn run_eval_project(
             loop {
                 match cx.update(|cx| {
                     let project_index = project_index.read(cx);
-                    project_index.search(vec![query.query.clone()], SEARCH_RESULT_LIMIT, cx)
+                    project_index.search(
+                        vec![query.query.clone()],
+                        SEARCH_RESULT_LIMIT,
+                        SearchFilter::default(),
+                        cx,
+                    )
                 }) {
                     Ok(task) => match task.await {
                         Ok(answer) => {
@@ -0,0 +1,261 @@
+use rope::Rope;
+use std::ops::Range;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A region of a file where git couldn't merge the changes of both sides,
+/// delimited by conflict markers:
+///
+/// ```text
+/// <<<<<<< HEAD
+/// our lines
+/// ||||||| base
+/// the lines both sides started from, with `merge.conflictStyle=diff3`
+/// =======
+/// their lines
+/// >>>>>>> feature
+/// ```
+///
+/// All ranges are byte offsets spanning whole lines, including their newlines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The whole region, from the `<<<<<<<` marker to the `>>>>>>>` marker.
+    pub range: Range<usize>,
+    /// Our lines, between the `<<<<<<<` marker and the next one.
+    pub ours: Range<usize>,
+    /// The lines of the common ancestor, when the conflict includes them.
+    pub base: Option<Range<usize>>,
+    /// Their lines, between the `=======` and `>>>>>>>` markers.
+    pub theirs: Range<usize>,
+    /// The name git gave to our side, like `HEAD`.
+    pub ours_label: String,
+    /// The name git gave to their side, like the merged branch.
+    pub theirs_label: String,
+}
+
+impl Conflict {
+    /// The range of the `|||||||` marker and the lines that follow it.
+    pub fn base_with_marker(&self) -> Option<Range<usize>> {
+        let base = self.base.as_ref()?;
+        Some(self.ours.end..base.end)
+    }
+
+    /// The range of the `<<<<<<<` marker and our lines.
+    pub fn ours_with_marker(&self) -> Range<usize> {
+        self.range.start..self.ours.end
+    }
+
+    /// The range of their lines and the `>>>>>>>` marker.
+    pub fn theirs_with_marker(&self) -> Range<usize> {
+        self.theirs.start..self.range.end
+    }
+}
+
+/// Finds the conflicts in the given text. Markers that aren't part of a
+/// complete conflict are ignored.
+pub fn parse_conflicts(text: &Rope) -> Vec<Conflict> {
+    enum State {
+        Outside,
+        Ours {
+            start: usize,
+            ours_start: usize,
+            ours_label: String,
+        },
+        Base {
+            start: usize,
+            ours: Range<usize>,
+            base_start: usize,
+            ours_label: String,
+        },
+        Theirs {
+            start: usize,
+            ours: Range<usize>,
+            base: Option<Range<usize>>,
+            theirs_start: usize,
+            ours_label: String,
+        },
+    }
+
+    let mut conflicts = Vec::new();
+    let mut state = State::Outside;
+    let mut lines = text.chunks().lines();
+    let mut line_start = 0;
+    while let Some(line) = lines.next() {
+        let line_end = (line_start + line.len() + 1).min(text.len());
+        if let Some(label) = marker_label(line, OURS_MARKER) {
+            // A new conflict starts over any incomplete one.
+            state = State::Ours {
+                start: line_start,
+                ours_start: line_end,
+                ours_label: label.to_string(),
+            };
+        } else {
+            state = match state {
+                State::Ours {
+                    start,
+                    ours_start,
+                    ours_label,
+                } if marker_label(line, BASE_MARKER).is_some() => State::Base {
+                    start,
+                    ours: ours_start..line_start,
+                    base_start: line_end,
+                    ours_label,
+                },
+                State::Ours {
+                    start,
+                    ours_start,
+                    ours_label,
+                } if line.trim_end() == SEPARATOR_MARKER => State::Theirs {
+                    start,
+                    ours: ours_start..line_start,
+                    base: None,
+                    theirs_start: line_end,
+                    ours_label,
+                },
+                State::Base {
+                    start,
+                    ours,
+                    base_start,
+                    ours_label,
+                } if line.trim_end() == SEPARATOR_MARKER => State::Theirs {
+                    start,
+                    ours,
+                    base: Some(base_start..line_start),
+                    theirs_start: line_end,
+                    ours_label,
+                },
+                State::Theirs {
+                    start,
+                    ours,
+                    base,
+                    theirs_start,
+                    ours_label,
+                } => match marker_label(line, THEIRS_MARKER) {
+                    Some(theirs_label) => {
+                        conflicts.push(Conflict {
+                            range: start..line_end,
+                            ours,
+                            base,
+                            theirs: theirs_start..line_start,
+                            ours_label,
+                            theirs_label: theirs_label.to_string(),
+                        });
+                        State::Outside
+                    }
+                    None => State::Theirs {
+                        start,
+                        ours,
+                        base,
+                        theirs_start,
+                        ours_label,
+                    },
+                },
+                state => state,
+            };
+        }
+        line_start += line.len() + 1;
+    }
+    conflicts
+}
+
+/// Returns the text following the marker at the start of the line, if the
+/// line is that marker.
+fn marker_label<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(marker)?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use unindent::Unindent as _;
+
+    fn sides(text: &str, conflict: &Conflict) -> (String, Option<String>, String) {
+        (
+            text[conflict.ours.clone()].to_string(),
+            conflict.base.clone().map(|base| text[base].to_string()),
+            text[conflict.theirs.clone()].to_string(),
+        )
+    }
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = r#"
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>> feature
+            three
+            <<<<<<< HEAD
+            four
+            ||||||| base
+            4
+            =======
+            >>>>>>> feature
+            "#
+        .unindent();
+        let conflicts = parse_conflicts(&Rope::from(text.as_str()));
+        assert_eq!(conflicts.len(), 2);
+
+        assert_eq!(
+            &text[conflicts[0].range.clone()],
+            "<<<<<<< HEAD\ntwo\n=======\nTWO\n>>>>>>> feature\n"
+        );
+        assert_eq!(
+            sides(&text, &conflicts[0]),
+            ("two\n".to_string(), None, "TWO\n".to_string())
+        );
+        assert_eq!(conflicts[0].ours_label, "HEAD");
+        assert_eq!(conflicts[0].theirs_label, "feature");
+
+        assert_eq!(
+            sides(&text, &conflicts[1]),
+            ("four\n".to_string(), Some("4\n".to_string()), String::new())
+        );
+        assert_eq!(
+            &text[conflicts[1].base_with_marker().unwrap()],
+            "||||||| base\n4\n"
+        );
+        assert_eq!(conflicts[1].range.end, text.len());
+    }
+
+    #[test]
+    fn test_parse_incomplete_conflicts() {
+        // Markers without a closing one, or out of order, aren't conflicts.
+        let text = r#"
+            <<<<<<< HEAD
+            one
+            =======
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>>
+            =======
+            >>>>>>> feature
+            <<<<<<<< not a marker
+            <<<<<<< HEAD
+            three
+            ======= not a marker
+            =======
+            THREE"#
+            .unindent();
+        let conflicts = parse_conflicts(&Rope::from(text.as_str()));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            sides(&text, &conflicts[0]),
+            ("two\n".to_string(), None, "TWO\n".to_string())
+        );
+        assert_eq!(conflicts[0].theirs_label, "");
+    }
+}
@@ -1,8 +1,11 @@
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 pub mod diff;
+pub mod history;
 mod hosting_provider;
 mod remote;
+pub mod remote_command;
 pub mod repository;
 pub mod status;
 
@@ -39,7 +42,15 @@ actions!(
         RevertAll,
         CommitChanges,
         CommitAllChanges,
-        ClearCommitMessage
+        AmendCommit,
+        ClearCommitMessage,
+        StashChanges,
+        PopStash,
+        Fetch,
+        Pull,
+        PullRebase,
+        Push,
+        SetUpstream
     ]
 );
 
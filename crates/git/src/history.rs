@@ -0,0 +1,215 @@
+use crate::{repository::RepoPath, Oid};
+use anyhow::{anyhow, Context, Result};
+use gpui::SharedString;
+use std::path::Path;
+
+/// Separates the fields of a commit in the output of `git log`.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Precedes every commit in the output of `git log`.
+const COMMIT_SEPARATOR: char = '\x1e';
+
+/// Which commits [`crate::repository::GitRepository::log`] lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// The number of commits to skip, to load the log a page at a time.
+    pub skip: usize,
+    /// The maximum number of commits to list, or zero for no limit.
+    pub limit: usize,
+    /// Only list the commits that changed one of these paths.
+    pub paths: Vec<RepoPath>,
+    /// Only list the commits whose author's name or email contains this text, ignoring case.
+    pub author: Option<String>,
+    /// Only list the commits whose message contains this text, ignoring case.
+    pub message: Option<String>,
+    /// Follow the history of the single path in `paths` across renames.
+    pub follow_renames: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: Oid,
+    pub parents: Vec<Oid>,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// The commit time, as a Unix timestamp.
+    pub timestamp: i64,
+    /// The branches and tags pointing at the commit.
+    pub refs: Vec<SharedString>,
+    /// The first line of the commit message.
+    pub subject: SharedString,
+    /// The paths the log was limited to that the commit changed, as they were
+    /// named at that commit.
+    pub paths: Vec<RepoPath>,
+}
+
+/// The changes made by a commit, relative to its first parent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitDetails {
+    pub sha: Oid,
+    pub message: SharedString,
+    pub files: Vec<CommitFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFile {
+    pub path: RepoPath,
+    /// The contents of the file before the commit, or `None` if it was added.
+    pub old_text: Option<String>,
+    /// The contents of the file after the commit, or `None` if it was deleted.
+    pub new_text: Option<String>,
+}
+
+pub(crate) fn log(
+    git_binary: &Path,
+    working_directory: &Path,
+    options: &LogOptions,
+) -> Result<Vec<LogEntry>> {
+    let mut command = util::command::new_std_command(git_binary);
+    command
+        .current_dir(working_directory)
+        .arg("log")
+        .arg("--topo-order")
+        .arg("--no-color")
+        .arg(format!(
+            "--format={1}%H{0}%P{0}%an{0}%ae{0}%ct{0}%D{0}%s",
+            FIELD_SEPARATOR, COMMIT_SEPARATOR
+        ))
+        .arg(format!("--skip={}", options.skip));
+    if options.limit > 0 {
+        command.arg(format!("--max-count={}", options.limit));
+    }
+    if options.author.is_some() || options.message.is_some() {
+        command.args(["--regexp-ignore-case", "--fixed-strings"]);
+    }
+    if let Some(author) = &options.author {
+        command.arg(format!("--author={author}"));
+    }
+    if let Some(message) = &options.message {
+        command.arg(format!("--grep={message}"));
+    }
+    if !options.paths.is_empty() {
+        command.arg("--name-only");
+    }
+    if options.follow_renames {
+        command.arg("--follow");
+    }
+    command
+        .arg("--")
+        .args(options.paths.iter().map(|path| path.as_os_str()));
+
+    let output = command
+        .output()
+        .map_err(|error| anyhow!("failed to start git log process: {error}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git log failed: {}", stderr.trim()));
+    }
+    parse_log(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_log(output: &str) -> Result<Vec<LogEntry>> {
+    output
+        .split(COMMIT_SEPARATOR)
+        .filter(|commit| !commit.trim().is_empty())
+        .map(|commit| {
+            // The paths changed by the commit, if any, follow its fields on
+            // their own lines.
+            let mut lines = commit.lines();
+            let mut fields = lines.next().unwrap_or_default().split(FIELD_SEPARATOR);
+            let mut next_field = || fields.next().context("missing field in git log output");
+            let sha = next_field()?.parse()?;
+            let parents = next_field()?
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_>>()?;
+            let author_name = next_field()?.to_string().into();
+            let author_email = next_field()?.to_string().into();
+            let timestamp = next_field()?
+                .parse()
+                .context("failed to parse commit timestamp")?;
+            let refs = next_field()?
+                .split(", ")
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string().into())
+                .collect();
+            let subject = next_field()?.to_string().into();
+            let paths = lines
+                .filter(|line| !line.is_empty())
+                .map(RepoPath::from)
+                .collect();
+            Ok(LogEntry {
+                sha,
+                parents,
+                author_name,
+                author_email,
+                timestamp,
+                refs,
+                subject,
+                paths,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_log() {
+        let output = [
+            [
+                "1111111111111111111111111111111111111111",
+                "2222222222222222222222222222222222222222 3333333333333333333333333333333333333333",
+                "Jane Doe",
+                "jane@example.com",
+                "1700000000",
+                "HEAD -> main, tag: v1.0",
+                "Merge branch 'feature'\n",
+            ],
+            [
+                "3333333333333333333333333333333333333333",
+                "",
+                "John Doe",
+                "john@example.com",
+                "1600000000",
+                "",
+                "Initial commit\n\nsrc/main.rs\nREADME.md\n",
+            ],
+        ]
+        .iter()
+        .map(|fields| format!("{COMMIT_SEPARATOR}{}", fields.join("\x1f")))
+        .collect::<String>();
+
+        let oid = |sha: &str| sha.parse::<Oid>().unwrap();
+        assert_eq!(
+            parse_log(&output).unwrap(),
+            [
+                LogEntry {
+                    sha: oid("1111111111111111111111111111111111111111"),
+                    parents: vec![
+                        oid("2222222222222222222222222222222222222222"),
+                        oid("3333333333333333333333333333333333333333"),
+                    ],
+                    author_name: "Jane Doe".into(),
+                    author_email: "jane@example.com".into(),
+                    timestamp: 1700000000,
+                    refs: vec!["HEAD -> main".into(), "tag: v1.0".into()],
+                    subject: "Merge branch 'feature'".into(),
+                    paths: Vec::new(),
+                },
+                LogEntry {
+                    sha: oid("3333333333333333333333333333333333333333"),
+                    parents: Vec::new(),
+                    author_name: "John Doe".into(),
+                    author_email: "john@example.com".into(),
+                    timestamp: 1600000000,
+                    refs: Vec::new(),
+                    subject: "Initial commit".into(),
+                    paths: vec!["src/main.rs".into(), "README.md".into()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use anyhow::{anyhow, Result};
+use futures::channel::oneshot;
+use smol::io::{AsyncRead, AsyncReadExt as _};
+use std::{path::Path, process::Stdio};
+use util::command::new_smol_command;
+
+/// Receives the progress of a git command that talks to a remote, and answers
+/// the prompts it makes for credentials.
+pub trait RemoteCommandDelegate: Send + Sync {
+    /// Reports a line of progress printed by git, like `Receiving objects: 50% (1/2)`.
+    fn progress(&self, message: &str);
+
+    /// Asks the user to answer a prompt for a username, password or SSH
+    /// passphrase. Dropping the sender or sending an error cancels the command.
+    fn ask_password(&self, prompt: String) -> oneshot::Receiver<Result<String>>;
+}
+
+/// Runs a git command that may connect to a remote, streaming its progress to
+/// the delegate and forwarding its credential prompts to it.
+pub(crate) fn run_remote_command(
+    git_binary: &Path,
+    working_directory: &Path,
+    args: &[&str],
+    delegate: &dyn RemoteCommandDelegate,
+) -> Result<()> {
+    let subcommand = args.first().copied().unwrap_or_default();
+    smol::block_on(async {
+        let mut command = new_smol_command(git_binary);
+        command
+            .current_dir(working_directory)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            // Prompting on a terminal would block forever, as there's none.
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .kill_on_drop(true);
+
+        #[cfg(unix)]
+        let (_temp_dir, prompts) = {
+            let temp_dir = tempfile::Builder::new()
+                .prefix("zed-git-askpass")
+                .tempdir()?;
+            let (script, listener) = askpass::start(temp_dir.path()).await?;
+            command
+                .env("GIT_ASKPASS", &script)
+                .env("SSH_ASKPASS", &script)
+                .env("SSH_ASKPASS_REQUIRE", "force");
+            (temp_dir, askpass::answer_prompts(listener, delegate))
+        };
+        #[cfg(not(unix))]
+        let prompts = futures::future::pending::<()>();
+
+        let mut child = command.spawn()?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| anyhow!("failed to capture the output of git {subcommand}"))?;
+        let run = async {
+            let (status, output) = futures::join!(child.status(), read_progress(stderr, delegate));
+            Some((status, output))
+        };
+        // The prompts are only answered until one of them is cancelled, in
+        // which case the command is killed.
+        let cancelled = async {
+            prompts.await;
+            None
+        };
+        match smol::future::or(run, cancelled).await {
+            Some((status, output)) => {
+                if status?.success() {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "git {subcommand} failed: {}",
+                        error_message(&output)
+                    ))
+                }
+            }
+            None => Err(anyhow!("git {subcommand} was cancelled")),
+        }
+    })
+}
+
+/// Reads the standard error of git, reporting each progress line as it's
+/// printed. Progress lines are terminated by carriage returns when they
+/// overwrite the previous one.
+async fn read_progress(
+    mut stderr: impl AsyncRead + Unpin,
+    delegate: &dyn RemoteCommandDelegate,
+) -> String {
+    let mut output = Vec::new();
+    let mut line_start = 0;
+    let mut chunk = [0; 1024];
+    loop {
+        let len = match stderr.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => len,
+        };
+        output.extend_from_slice(&chunk[..len]);
+        while let Some(line_len) = output[line_start..]
+            .iter()
+            .position(|byte| *byte == b'\r' || *byte == b'\n')
+        {
+            let line = String::from_utf8_lossy(&output[line_start..line_start + line_len]);
+            let line = line.trim();
+            if !line.is_empty() {
+                delegate.progress(line);
+            }
+            line_start += line_len + 1;
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Returns the lines printed by git, keeping only the final state of the
+/// progress lines that were overwritten.
+fn error_message(output: &str) -> String {
+    output
+        .lines()
+        .filter_map(|line| {
+            line.rsplit('\r')
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(unix)]
+mod askpass {
+    use super::RemoteCommandDelegate;
+    use anyhow::{Context as _, Result};
+    use smol::fs::unix::PermissionsExt as _;
+    use smol::io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader};
+    use smol::net::unix::UnixListener;
+    use std::path::{Path, PathBuf};
+    use util::ResultExt as _;
+
+    /// Creates a script for git and ssh to run when they need credentials,
+    /// which forwards the prompt to a socket and prints the answer.
+    pub(super) async fn start(temp_dir: &Path) -> Result<(PathBuf, UnixListener)> {
+        let socket = temp_dir.join("askpass.sock");
+        let listener = UnixListener::bind(&socket).context("failed to create askpass socket")?;
+        let script = format!(
+            "#!/bin/sh\nprintf '%s\\0' \"$@\" | {nc} -U {socket} 2> /dev/null\n",
+            // on macOS `brew install netcat` provides the GNU netcat implementation
+            // which does not support -U.
+            nc = if cfg!(target_os = "macos") {
+                "/usr/bin/nc"
+            } else {
+                "nc"
+            },
+            socket = socket.display(),
+        );
+        let script_path = temp_dir.join("askpass.sh");
+        smol::fs::write(&script_path, script).await?;
+        smol::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).await?;
+        Ok((script_path, listener))
+    }
+
+    /// Answers the prompts made through the askpass script, returning once
+    /// the user cancels one of them.
+    pub(super) async fn answer_prompts(
+        listener: UnixListener,
+        delegate: &dyn RemoteCommandDelegate,
+    ) {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buffer = Vec::new();
+            let mut reader = BufReader::new(&mut stream);
+            if reader.read_until(b'\0', &mut buffer).await.is_err() {
+                buffer.clear();
+            }
+            let prompt = String::from_utf8_lossy(&buffer)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string();
+            match delegate.ask_password(prompt).await {
+                Ok(Ok(password)) => {
+                    stream.write_all(password.as_bytes()).await.log_err();
+                }
+                Ok(Err(_)) | Err(_) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{GitRepository, RealGitRepository, UpstreamTracking};
+    use crate::GitHostingProviderRegistry;
+    use parking_lot::Mutex;
+    use pretty_assertions::assert_eq;
+    use std::{path::PathBuf, sync::Arc};
+
+    #[derive(Default)]
+    struct TestDelegate {
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl RemoteCommandDelegate for TestDelegate {
+        fn progress(&self, _message: &str) {}
+
+        fn ask_password(&self, prompt: String) -> oneshot::Receiver<Result<String>> {
+            self.prompts.lock().push(prompt);
+            let (tx, rx) = oneshot::channel();
+            tx.send(Err(anyhow!("no credentials in tests"))).ok();
+            rx
+        }
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "init.defaultBranch=main"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// Clones the repository, with an identity to commit and rebase with.
+    fn clone(root: &Path, name: &str) -> PathBuf {
+        git(root, &["clone", "--quiet", "remote.git", name]);
+        let path = root.join(name);
+        git(&path, &["config", "user.name", "Test"]);
+        git(&path, &["config", "user.email", "test@example.com"]);
+        path
+    }
+
+    fn commit(dir: &Path, file: &str, text: &str) {
+        std::fs::write(dir.join(file), text).unwrap();
+        git(dir, &["add", file]);
+        git(dir, &["commit", "--quiet", "-m", file]);
+    }
+
+    fn open(path: PathBuf) -> RealGitRepository {
+        RealGitRepository::new(
+            git2::Repository::open(path).unwrap(),
+            None,
+            Arc::new(GitHostingProviderRegistry::default()),
+        )
+    }
+
+    #[test]
+    fn test_remote_commands_with_local_bare_repository() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "--quiet", "--bare", "remote.git"]);
+        let local_path = clone(root, "local");
+        commit(&local_path, "a.txt", "a");
+
+        let local = open(local_path.clone());
+        let delegate = TestDelegate::default();
+        assert_eq!(local.upstream_tracking(), None);
+        local.push(true, &delegate).unwrap();
+        assert_eq!(
+            local.upstream_tracking(),
+            Some(UpstreamTracking {
+                ahead: 0,
+                behind: 0
+            })
+        );
+
+        // Someone else pushes a commit, which is only seen once fetched.
+        let other_path = clone(root, "other");
+        commit(&other_path, "b.txt", "b");
+        git(&other_path, &["push", "--quiet"]);
+        assert_eq!(
+            local.upstream_tracking(),
+            Some(UpstreamTracking {
+                ahead: 0,
+                behind: 0
+            })
+        );
+        local.fetch(&delegate).unwrap();
+        commit(&local_path, "c.txt", "c");
+        assert_eq!(
+            local.upstream_tracking(),
+            Some(UpstreamTracking {
+                ahead: 1,
+                behind: 1
+            })
+        );
+
+        local.pull(true, &delegate).unwrap();
+        assert_eq!(
+            local.upstream_tracking(),
+            Some(UpstreamTracking {
+                ahead: 1,
+                behind: 0
+            })
+        );
+        assert!(local_path.join("b.txt").exists());
+
+        local.push(false, &delegate).unwrap();
+        assert_eq!(
+            local.upstream_tracking(),
+            Some(UpstreamTracking {
+                ahead: 0,
+                behind: 0
+            })
+        );
+        assert_eq!(*delegate.prompts.lock(), Vec::<String>::new());
+
+        // Pushing from the other clone is rejected, as it didn't pull.
+        commit(&other_path, "d.txt", "d");
+        let error = open(other_path)
+            .push(false, &delegate)
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("git push failed"), "{error}");
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message(
+                "Counting objects: 50% (1/2)\rCounting objects: 100% (2/2), done.\n\
+                 fatal: could not read Username for 'https://example.com'\n"
+            ),
+            "Counting objects: 100% (2/2), done.\n\
+             fatal: could not read Username for 'https://example.com'"
+        );
+    }
+}
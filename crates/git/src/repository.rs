@@ -1,6 +1,8 @@
-use crate::status::FileStatus;
-use crate::GitHostingProviderRegistry;
+use crate::history::{CommitDetails, CommitFile, LogEntry, LogOptions};
+use crate::remote_command::{run_remote_command, RemoteCommandDelegate};
+use crate::status::{FileStatus, StatusCode};
 use crate::{blame::Blame, status::GitStatus};
+use crate::{GitHostingProviderRegistry, Oid};
 use anyhow::{anyhow, Context, Result};
 use collections::{HashMap, HashSet};
 use git2::BranchType;
@@ -26,6 +28,22 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+/// How the current branch compares to the branch it tracks.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct UpstreamTracking {
+    /// The number of local commits that haven't been pushed.
+    pub ahead: u32,
+    /// The number of fetched commits that haven't been pulled.
+    pub behind: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, with zero being the most recent.
+    pub index: usize,
+    pub message: SharedString,
+}
+
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
@@ -33,10 +51,18 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads the contents of a file at the given commit, or `None` if the file
+    /// didn't exist at that commit.
+    fn load_text_at_revision(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
 
+    /// Returns how many commits the current branch is ahead and behind its
+    /// upstream, or `None` if it doesn't track one.
+    fn upstream_tracking(&self) -> Option<UpstreamTracking>;
+
     /// Returns the SHA of the current HEAD.
     fn head_sha(&self) -> Option<String>;
 
@@ -62,7 +88,41 @@ pub trait GitRepository: Send + Sync {
     /// If any of the paths were previously staged but do not exist in HEAD, they will be removed from the index.
     fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
 
+    /// Sets the contents of the given path in the index, or removes it from
+    /// the index if `content` is `None`, leaving the worktree untouched.
+    ///
+    /// This is used to stage individual hunks of a file.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
     fn commit(&self, message: &str) -> Result<()>;
+
+    /// Replaces the HEAD commit with one containing the staged changes, keeping
+    /// its message if `message` is `None`.
+    fn amend_commit(&self, message: Option<&str>) -> Result<()>;
+
+    /// Saves the local changes, including untracked files, to a new stash
+    /// entry and reverts the worktree to HEAD.
+    fn stash_push(&self, message: Option<&str>) -> Result<()>;
+    /// Applies the stash entry at the given index and removes it from the stash.
+    fn stash_pop(&self, index: usize) -> Result<()>;
+    /// Lists the stash entries, most recent first.
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+
+    /// Downloads the commits and refs of all remotes.
+    fn fetch(&self, delegate: &dyn RemoteCommandDelegate) -> Result<()>;
+    /// Integrates the commits of the upstream branch into the current one, by
+    /// rebasing the local commits onto them or by merging them.
+    fn pull(&self, rebase: bool, delegate: &dyn RemoteCommandDelegate) -> Result<()>;
+    /// Pushes the current branch to its upstream. When `set_upstream` is true,
+    /// the branch is pushed to the default remote instead, which it then tracks.
+    fn push(&self, set_upstream: bool, delegate: &dyn RemoteCommandDelegate) -> Result<()>;
+
+    /// Lists the commits reachable from HEAD, children before their parents.
+    fn log(&self, options: &LogOptions) -> Result<Vec<LogEntry>>;
+
+    /// Returns the message of the given commit, along with the contents of
+    /// the files it changed before and after it.
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -89,6 +149,56 @@ impl RealGitRepository {
             hosting_provider_registry,
         }
     }
+
+    /// Runs git in the work directory, returning its standard output.
+    fn run_git_command(&self, args: &[&str]) -> Result<String> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Runs a git command that talks to a remote in the work directory.
+    fn run_remote_command(
+        &self,
+        args: &[&str],
+        delegate: &dyn RemoteCommandDelegate,
+    ) -> Result<()> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        run_remote_command(&self.git_binary_path, &working_directory, args, delegate)
+    }
+
+    /// Returns the remote new branches are pushed to, preferring `origin`.
+    fn default_remote(&self) -> Result<String> {
+        let repo = self.repository.lock();
+        let remotes = repo.remotes()?;
+        let names = remotes.iter().flatten().collect::<Vec<_>>();
+        names
+            .iter()
+            .find(|name| **name == "origin")
+            .or(names.first())
+            .map(|name| name.to_string())
+            .context("the repository has no remotes")
+    }
 }
 
 // https://git-scm.com/book/en/v2/Git-Internals-Git-Objects
@@ -130,6 +240,19 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_text_at_revision(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>> {
+        check_path_to_repo_path_errors(path)?;
+        let repo = self.repository.lock();
+        let tree = repo.find_commit(sha.0)?.tree()?;
+        let entry = match tree.get_path(path) {
+            Ok(entry) => entry,
+            Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let content = repo.find_blob(entry.id())?.content().to_owned();
+        Ok(Some(String::from_utf8(content)?))
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -147,6 +270,23 @@ impl GitRepository for RealGitRepository {
         Some(self.repository.lock().head().ok()?.target()?.to_string())
     }
 
+    fn upstream_tracking(&self) -> Option<UpstreamTracking> {
+        let repo = self.repository.lock();
+        let head = repo.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        let branch = git2::Branch::wrap(head);
+        let upstream = branch.upstream().ok()?;
+        let (ahead, behind) = repo
+            .graph_ahead_behind(branch.get().target()?, upstream.get().target()?)
+            .ok()?;
+        Some(UpstreamTracking {
+            ahead: ahead as u32,
+            behind: behind as u32,
+        })
+    }
+
     fn status(&self, path_prefixes: &[RepoPath]) -> Result<GitStatus> {
         let working_directory = self
             .repository
@@ -283,6 +423,45 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        check_path_to_repo_path_errors(path)?;
+        let (working_directory, cache_info) = {
+            let repo = self.repository.lock();
+            let working_directory = repo
+                .workdir()
+                .context("failed to read git work directory")?
+                .to_path_buf();
+            let cache_info = if let Some(content) = content {
+                let mode = repo
+                    .index()?
+                    .get_path(path, STAGE_NORMAL)
+                    .map_or(0o100644, |entry| entry.mode);
+                let oid = repo.blob(content.as_bytes())?;
+                Some(format!("{mode:o},{oid},{}", path.to_string_lossy()))
+            } else {
+                None
+            };
+            (working_directory, cache_info)
+        };
+
+        let mut command = new_std_command(&self.git_binary_path);
+        command.current_dir(&working_directory).arg("update-index");
+        if let Some(cache_info) = cache_info {
+            command.args(["--add", "--cacheinfo", &cache_info]);
+        } else {
+            command.args(["--force-remove", "--"]).arg(path.as_os_str());
+        }
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to update the index for {path}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
     fn commit(&self, message: &str) -> Result<()> {
         let working_directory = self
             .repository
@@ -300,6 +479,131 @@ impl GitRepository for RealGitRepository {
         }
         Ok(())
     }
+
+    fn amend_commit(&self, message: Option<&str>) -> Result<()> {
+        let mut args = vec!["commit", "--quiet", "--amend"];
+        match message {
+            Some(message) => args.extend(["-m", message]),
+            None => args.push("--no-edit"),
+        }
+        self.run_git_command(&args)
+            .context("Failed to amend commit")?;
+        Ok(())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut args = vec!["stash", "push", "--quiet", "--include-untracked"];
+        if let Some(message) = message {
+            args.extend(["-m", message]);
+        }
+        self.run_git_command(&args)
+            .context("Failed to stash changes")?;
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        self.run_git_command(&["stash", "pop", "--quiet", &format!("stash@{{{index}}}")])
+            .context("Failed to pop stash")?;
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let output = self
+            .run_git_command(&["stash", "list", "--format=%gs"])
+            .context("Failed to list stash")?;
+        Ok(output
+            .lines()
+            .enumerate()
+            .map(|(index, message)| StashEntry {
+                index,
+                message: message.to_string().into(),
+            })
+            .collect())
+    }
+
+    fn fetch(&self, delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        self.run_remote_command(&["fetch", "--all", "--prune", "--progress"], delegate)
+    }
+
+    fn pull(&self, rebase: bool, delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        let strategy = if rebase { "--rebase" } else { "--no-rebase" };
+        self.run_remote_command(&["pull", "--progress", strategy], delegate)
+    }
+
+    fn push(&self, set_upstream: bool, delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        if set_upstream {
+            let remote = self.default_remote()?;
+            self.run_remote_command(
+                &["push", "--progress", "--set-upstream", &remote, "HEAD"],
+                delegate,
+            )
+        } else {
+            self.run_remote_command(&["push", "--progress"], delegate)
+        }
+    }
+
+    fn log(&self, options: &LogOptions) -> Result<Vec<LogEntry>> {
+        let working_directory = {
+            let repo = self.repository.lock();
+            // Listing the commits of an unborn branch is an error for git.
+            if repo.head().is_err() {
+                return Ok(Vec::new());
+            }
+            repo.workdir()
+                .context("failed to read git work directory")?
+                .to_path_buf()
+        };
+        crate::history::log(&self.git_binary_path, &working_directory, options)
+    }
+
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0)?;
+        let new_tree = commit.tree()?;
+        let old_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+
+        let load_blob = |file: git2::DiffFile| -> Result<Option<git2::Blob>> {
+            if file.id().is_zero() {
+                return Ok(None);
+            }
+            Ok(Some(repo.find_blob(file.id())?))
+        };
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .context("missing path in commit diff")?;
+            let old_blob = load_blob(delta.old_file())?;
+            let new_blob = load_blob(delta.new_file())?;
+            if old_blob
+                .iter()
+                .chain(&new_blob)
+                .any(|blob| blob.is_binary())
+            {
+                continue;
+            }
+            let text = |blob: Option<git2::Blob>| {
+                blob.map(|blob| String::from_utf8_lossy(blob.content()).into_owned())
+            };
+            files.push(CommitFile {
+                path: RepoPath::from(path),
+                old_text: text(old_blob),
+                new_text: text(new_blob),
+            });
+        }
+
+        Ok(CommitDetails {
+            sha,
+            message: commit.message().unwrap_or_default().to_string().into(),
+            files,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -316,6 +620,16 @@ pub struct FakeGitRepositoryState {
     pub statuses: HashMap<RepoPath, FileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    /// The commits listed by `log`, children before their parents.
+    pub log: Vec<LogEntry>,
+    pub commit_details: HashMap<Oid, CommitDetails>,
+    /// The contents of files at given commits.
+    pub revision_contents: HashMap<(Oid, RepoPath), String>,
+    /// The stash entries, most recent first, with the statuses they saved.
+    pub stashes: Vec<(String, HashMap<RepoPath, FileStatus>)>,
+    pub upstream_tracking: Option<UpstreamTracking>,
+    /// The number of commits that fetching finds on the upstream branch.
+    pub unfetched_commits: u32,
 }
 
 impl FakeGitRepository {
@@ -334,6 +648,12 @@ impl FakeGitRepositoryState {
             statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            log: Default::default(),
+            commit_details: Default::default(),
+            revision_contents: Default::default(),
+            stashes: Default::default(),
+            upstream_tracking: Default::default(),
+            unfetched_commits: Default::default(),
         }
     }
 }
@@ -346,6 +666,11 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_text_at_revision(&self, sha: Oid, path: &RepoPath) -> Result<Option<String>> {
+        let state = self.state.lock();
+        Ok(state.revision_contents.get(&(sha, path.clone())).cloned())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -359,6 +684,11 @@ impl GitRepository for FakeGitRepository {
         None
     }
 
+    fn upstream_tracking(&self) -> Option<UpstreamTracking> {
+        let state = self.state.lock();
+        state.upstream_tracking
+    }
+
     fn dot_git_dir(&self) -> PathBuf {
         let state = self.state.lock();
         state.dot_git_dir.clone()
@@ -436,17 +766,220 @@ impl GitRepository for FakeGitRepository {
             .cloned()
     }
 
-    fn stage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
-        unimplemented!()
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            let staged = match state.statuses.get(path) {
+                Some(FileStatus::Tracked(status)) => match status.worktree_status {
+                    StatusCode::Unmodified => continue,
+                    worktree_status => FileStatus::index(worktree_status),
+                },
+                Some(FileStatus::Unmerged(_)) => FileStatus::index(StatusCode::Modified),
+                Some(FileStatus::Untracked) => FileStatus::index(StatusCode::Added),
+                Some(FileStatus::Ignored) | None => continue,
+            };
+            state.statuses.insert(path.clone(), staged);
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
     }
 
     fn unstage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
         unimplemented!()
     }
 
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        match content {
+            Some(content) => state.index_contents.insert(path.to_path_buf(), content),
+            None => state.index_contents.remove(&**path),
+        };
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
     fn commit(&self, _message: &str) -> Result<()> {
         unimplemented!()
     }
+
+    fn amend_commit(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        let head = state.log.first_mut().context("no commit to amend")?;
+        if let Some(message) = message {
+            head.subject = message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+                .into();
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_push(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.statuses.is_empty() {
+            return Err(anyhow!("No local changes to save"));
+        }
+        let message = match message {
+            Some(message) => format!(
+                "On {}: {message}",
+                state.current_branch_name.as_deref().unwrap_or_default()
+            ),
+            None => format!(
+                "WIP on {}",
+                state.current_branch_name.as_deref().unwrap_or_default()
+            ),
+        };
+        let statuses = std::mem::take(&mut state.statuses);
+        state.stashes.insert(0, (message, statuses));
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        if index >= state.stashes.len() {
+            return Err(anyhow!("stash@{{{index}}} is not a valid reference"));
+        }
+        let (_, statuses) = state.stashes.remove(index);
+        state.statuses.extend(statuses);
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, (message, _))| StashEntry {
+                index,
+                message: message.clone().into(),
+            })
+            .collect())
+    }
+
+    fn fetch(&self, _delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        let mut state = self.state.lock();
+        let unfetched_commits = std::mem::take(&mut state.unfetched_commits);
+        if let Some(tracking) = state.upstream_tracking.as_mut() {
+            tracking.behind += unfetched_commits;
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn pull(&self, _rebase: bool, _delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        let mut state = self.state.lock();
+        state
+            .upstream_tracking
+            .as_mut()
+            .context("There is no tracking information for the current branch")?
+            .behind = 0;
+        state.unfetched_commits = 0;
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn push(&self, set_upstream: bool, _delegate: &dyn RemoteCommandDelegate) -> Result<()> {
+        let mut state = self.state.lock();
+        let unfetched_commits = state.unfetched_commits;
+        match state.upstream_tracking.as_mut() {
+            Some(tracking) if tracking.behind > 0 || unfetched_commits > 0 => {
+                return Err(anyhow!(
+                    "Updates were rejected because the remote contains work that you do not have locally"
+                ));
+            }
+            Some(tracking) => tracking.ahead = 0,
+            None if set_upstream => state.upstream_tracking = Some(UpstreamTracking::default()),
+            None => return Err(anyhow!("The current branch has no upstream branch")),
+        }
+        state
+            .event_emitter
+            .try_send(state.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn log(&self, options: &LogOptions) -> Result<Vec<LogEntry>> {
+        let state = self.state.lock();
+        let contains =
+            |text: &str, query: &str| text.to_lowercase().contains(&query.to_lowercase());
+        let entries = state
+            .log
+            .iter()
+            .filter_map(|entry| {
+                let mut entry = entry.clone();
+                if !options.paths.is_empty() {
+                    let details = state.commit_details.get(&entry.sha)?;
+                    entry.paths = details
+                        .files
+                        .iter()
+                        .filter(|file| options.paths.iter().any(|path| file.path.starts_with(path)))
+                        .map(|file| file.path.clone())
+                        .collect();
+                    if entry.paths.is_empty() {
+                        return None;
+                    }
+                }
+                Some(entry)
+            })
+            .filter(|entry| {
+                options.author.as_ref().map_or(true, |author| {
+                    contains(&entry.author_name, author) || contains(&entry.author_email, author)
+                })
+            })
+            .filter(|entry| {
+                options.message.as_ref().map_or(true, |message| {
+                    let full_message = state
+                        .commit_details
+                        .get(&entry.sha)
+                        .map_or(&entry.subject, |details| &details.message);
+                    contains(full_message, message)
+                })
+            })
+            .skip(options.skip)
+            .take(if options.limit > 0 {
+                options.limit
+            } else {
+                usize::MAX
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    fn commit_details(&self, sha: Oid) -> Result<CommitDetails> {
+        let state = self.state.lock();
+        state
+            .commit_details
+            .get(&sha)
+            .with_context(|| format!("failed to get details of commit {sha}"))
+            .cloned()
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use editor::Editor;
+use futures::channel::oneshot;
+use gpui::{
+    AnyWindowHandle, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, View,
+    WeakView,
+};
+use ui::prelude::*;
+use workspace::{ModalView, Workspace};
+
+/// Asks the user for the username, password or SSH passphrase that git
+/// prompts for while fetching, pulling or pushing.
+pub struct AskPassModal {
+    prompt: SharedString,
+    editor: View<Editor>,
+    tx: Option<oneshot::Sender<Result<String>>>,
+}
+
+impl AskPassModal {
+    /// Shows the prompt in the workspace, returning the user's answer. The
+    /// answer is cancelled when the modal is dismissed.
+    pub fn prompt(
+        workspace: WeakView<Workspace>,
+        window: AnyWindowHandle,
+        prompt: String,
+        cx: &mut AppContext,
+    ) -> oneshot::Receiver<Result<String>> {
+        let (tx, rx) = oneshot::channel();
+        window
+            .update(cx, |_, cx| {
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(cx, |cx| Self::new(prompt, tx, cx));
+                })
+            })
+            .ok();
+        rx
+    }
+
+    fn new(
+        prompt: String,
+        tx: oneshot::Sender<Result<String>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        // Usernames and confirmations of SSH host keys aren't secret.
+        let masked = !prompt.starts_with("Username") && !prompt.contains("yes/no");
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_masked(masked, cx);
+            editor
+        });
+        Self {
+            prompt: prompt.trim().to_string().into(),
+            editor,
+            tx: Some(tx),
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(tx) = self.tx.take() {
+            tx.send(Ok(self.editor.read(cx).text(cx))).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for AskPassModal {}
+
+impl EventEmitter<DismissEvent> for AskPassModal {}
+
+impl FocusableView for AskPassModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for AskPassModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .elevation_2(cx)
+            .key_context("AskPassModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(self.prompt.clone())),
+            )
+            .child(div().px_2().py_1().child(self.editor.clone()))
+    }
+}
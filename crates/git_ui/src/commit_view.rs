@@ -0,0 +1,312 @@
+use anyhow::{Context as _, Result};
+use editor::{Editor, EditorEvent, ExcerptRange, MultiBuffer};
+use git::{history::CommitDetails, Oid};
+use gpui::{
+    AnyView, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView, Model,
+    Render, Task, View, WeakView,
+};
+use language::{Buffer, Capability, DiskState, File, LocalFile};
+use project::{
+    buffer_store::BufferChangeSet, git::RepositoryHandle, Project, ProjectPath, WorktreeId,
+};
+use std::{
+    any::{Any, TypeId},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use ui::prelude::*;
+use workspace::{
+    item::{Item, ItemEvent},
+    searchable::SearchableItemHandle,
+    Workspace,
+};
+
+/// The changes made by a single commit, shown as a read-only multibuffer with
+/// one excerpt per changed file, diffed against the commit's first parent.
+pub struct CommitView {
+    editor: View<Editor>,
+    sha: Oid,
+    subject: SharedString,
+}
+
+/// The contents of a file at a given commit, which don't exist on disk.
+struct CommitBlob {
+    worktree_id: WorktreeId,
+    path: Arc<Path>,
+    full_path: PathBuf,
+    is_deleted: bool,
+}
+
+impl CommitView {
+    /// Loads the given commit and opens it in the active pane, or activates
+    /// the existing view for that commit.
+    pub fn open(
+        repository: RepositoryHandle,
+        sha: Oid,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<()>> {
+        let details = repository.commit_details(sha, cx);
+        cx.spawn(|mut cx| async move {
+            let details = details.await?;
+            let project = workspace.update(&mut cx, |workspace, _| workspace.project().clone())?;
+            let mut files = Vec::with_capacity(details.files.len());
+            for file in &details.files {
+                let project_path = repository
+                    .unrelativize(&file.path)
+                    .context("commit file is outside of the worktree")?;
+                let buffer = build_revision_buffer(
+                    file.new_text.clone().unwrap_or_default(),
+                    project_path,
+                    file.new_text.is_none(),
+                    &project,
+                    &mut cx,
+                )
+                .await?;
+                let change_set =
+                    build_change_set(&buffer, file.old_text.clone().unwrap_or_default(), &mut cx)?;
+                files.push((buffer, change_set));
+            }
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let existing = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items_of_type::<CommitView>()
+                    .find(|view| view.read(cx).sha == sha);
+                if let Some(existing) = existing {
+                    workspace.activate_item(&existing, true, true, cx);
+                    return;
+                }
+
+                let view = cx.new_view(|cx| CommitView::new(&details, files, cx));
+                workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+            })
+        })
+    }
+
+    fn new(
+        details: &CommitDetails,
+        files: Vec<(Model<Buffer>, Model<BufferChangeSet>)>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(Capability::ReadOnly);
+            for (buffer, _) in &files {
+                let len = buffer.read(cx).len();
+                multibuffer.push_excerpts(
+                    buffer.clone(),
+                    [ExcerptRange {
+                        context: 0..len,
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        });
+        let editor = cx.new_view(|cx| {
+            // The buffers don't belong to the project, whose change sets would
+            // otherwise replace the ones diffing against the parent commit.
+            let mut editor = Editor::for_multibuffer(multibuffer, None, true, cx);
+            editor.set_read_only(true);
+            editor.set_expand_all_diff_hunks();
+            for (_, change_set) in files {
+                editor.add_change_set(change_set, cx);
+            }
+            editor
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+
+        Self {
+            editor,
+            sha: details.sha,
+            subject: details
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+                .into(),
+        }
+    }
+}
+
+/// Creates a read-only buffer holding the contents of a file at some commit,
+/// highlighted according to the file's path in the project.
+pub(crate) async fn build_revision_buffer(
+    text: String,
+    project_path: ProjectPath,
+    is_deleted: bool,
+    project: &Model<Project>,
+    cx: &mut AsyncWindowContext,
+) -> Result<Model<Buffer>> {
+    let languages = project.read_with(cx, |project, _| project.languages().clone())?;
+    let language = languages
+        .language_for_file_path(&project_path.path)
+        .await
+        .ok();
+    let blob = project.read_with(cx, |project, cx| {
+        let root_name = project
+            .worktree_for_id(project_path.worktree_id, cx)
+            .map(|worktree| worktree.read(cx).root_name().to_string())
+            .unwrap_or_default();
+        CommitBlob {
+            worktree_id: project_path.worktree_id,
+            full_path: Path::new(&root_name).join(&project_path.path),
+            path: project_path.path.clone(),
+            is_deleted,
+        }
+    })?;
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        buffer.file_updated(Arc::new(blob), cx);
+        buffer.set_language(language, cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        buffer
+    })
+}
+
+/// Creates a change set diffing the buffer against the given base text.
+pub(crate) fn build_change_set(
+    buffer: &Model<Buffer>,
+    base_text: String,
+    cx: &mut AsyncWindowContext,
+) -> Result<Model<BufferChangeSet>> {
+    cx.new_model(|cx| {
+        let mut change_set = BufferChangeSet::new(buffer.read(cx));
+        let _ = change_set.set_base_text(base_text, buffer.read(cx).text_snapshot(), cx);
+        change_set
+    })
+}
+
+impl File for CommitBlob {
+    fn as_local(&self) -> Option<&dyn LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        if self.is_deleted {
+            DiskState::Deleted
+        } else {
+            DiskState::New
+        }
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &AppContext) -> PathBuf {
+        self.full_path.clone()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a AppContext) -> &'a OsStr {
+        self.full_path.file_name().unwrap_or_default()
+    }
+
+    fn worktree_id(&self, _: &AppContext) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &AppContext) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.to_string_lossy().into(),
+            mtime: None,
+            is_deleted: self.is_deleted,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+impl Render for CommitView {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .key_context("CommitView")
+            .child(self.editor.clone())
+    }
+}
+
+impl FocusableView for CommitView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitView {}
+
+impl Item for CommitView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(format!("{} {}", self.sha.display_short(), self.subject).into())
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(format!("{} {}", self.sha, self.subject).into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Commit View Opened")
+    }
+
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, Item::deactivated);
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: workspace::ItemNavHistory,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, cx)
+        });
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::Result;
+use collections::HashMap;
+use editor::{Editor, EditorEvent, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT};
+use futures::future::join_all;
+use git::conflict::parse_conflicts;
+use gpui::{
+    actions, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Subscription,
+    Task, View,
+};
+use language::{Buffer, Capability};
+use project::{Project, ProjectPath};
+use std::any::{Any, TypeId};
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{
+    item::{Item, ItemEvent},
+    searchable::SearchableItemHandle,
+    ItemNavHistory, Workspace,
+};
+
+actions!(git, [OpenConflicts]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &OpenConflicts, cx| {
+            ConflictView::deploy(workspace, cx);
+        });
+    })
+    .detach();
+}
+
+/// The files of the project with merge conflicts, shown as a multibuffer with
+/// an excerpt around each conflict. Files leave the view once their conflicts
+/// are resolved and staged.
+pub struct ConflictView {
+    project: Model<Project>,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    buffers: HashMap<ProjectPath, Model<Buffer>>,
+    update_task: Task<()>,
+    _subscription: Option<Subscription>,
+}
+
+impl ConflictView {
+    fn deploy(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        if let Some(existing) = workspace.item_of_type::<Self>(cx) {
+            workspace.activate_item(&existing, true, true, cx);
+        } else {
+            let project = workspace.project().clone();
+            let view = cx.new_view(|cx| Self::new(project, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+        }
+    }
+
+    fn new(project: Model<Project>, cx: &mut ViewContext<Self>) -> Self {
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(Capability::ReadWrite));
+        let editor = cx.new_view(|cx| {
+            Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), true, cx)
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+        let subscription = project.read(cx).git_state().map(|git_state| {
+            cx.subscribe(git_state, |this, _, _: &project::git::Event, cx| {
+                this.update(cx)
+            })
+        });
+
+        let mut this = Self {
+            project,
+            editor,
+            multibuffer,
+            buffers: HashMap::default(),
+            update_task: Task::ready(()),
+            _subscription: subscription,
+        };
+        this.update(cx);
+        this
+    }
+
+    /// Removes the files that are no longer conflicted and adds the newly
+    /// conflicted ones.
+    fn update(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = conflicted_paths(&self.project, cx);
+        let resolved = self
+            .buffers
+            .keys()
+            .filter(|path| !paths.contains(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in resolved {
+            if let Some(buffer) = self.buffers.remove(&path) {
+                self.multibuffer.update(cx, |multibuffer, cx| {
+                    let excerpts = multibuffer
+                        .excerpts_for_buffer(&buffer, cx)
+                        .into_iter()
+                        .map(|(excerpt_id, _)| excerpt_id)
+                        .collect::<Vec<_>>();
+                    multibuffer.remove_excerpts(excerpts, cx);
+                });
+            }
+        }
+
+        let added = paths
+            .into_iter()
+            .filter(|path| !self.buffers.contains_key(path))
+            .collect::<Vec<_>>();
+        if added.is_empty() {
+            cx.notify();
+            return;
+        }
+        let open_buffers = self.project.update(cx, |project, cx| {
+            added
+                .iter()
+                .map(|path| project.open_buffer(path.clone(), cx))
+                .collect::<Vec<_>>()
+        });
+        self.update_task = cx.spawn(|this, mut cx| async move {
+            let buffers = join_all(open_buffers).await;
+            this.update(&mut cx, |this, cx| {
+                // The files may have been resolved while they were opened.
+                let paths = conflicted_paths(&this.project, cx);
+                for (path, buffer) in added.into_iter().zip(buffers) {
+                    let Some(buffer) = buffer.log_err() else {
+                        continue;
+                    };
+                    if !paths.contains(&path) || this.buffers.contains_key(&path) {
+                        continue;
+                    }
+                    this.push_buffer(path, buffer, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn push_buffer(
+        &mut self,
+        path: ProjectPath,
+        buffer: Model<Buffer>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut ranges = parse_conflicts(buffer.read(cx).as_rope())
+            .into_iter()
+            .map(|conflict| conflict.range)
+            .collect::<Vec<_>>();
+        // Conflicts between deletions and changes don't have markers.
+        if ranges.is_empty() {
+            ranges.push(0..buffer.read(cx).len());
+        }
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts_with_context_lines(
+                buffer.clone(),
+                ranges,
+                DEFAULT_MULTIBUFFER_CONTEXT,
+                cx,
+            );
+        });
+        self.buffers.insert(path, buffer);
+    }
+}
+
+/// Returns the paths of the files with conflicts in all of the project's
+/// repositories, sorted.
+fn conflicted_paths(project: &Model<Project>, cx: &AppContext) -> Vec<ProjectPath> {
+    let Some(git_state) = project.read(cx).git_state() else {
+        return Vec::new();
+    };
+    let mut paths = git_state
+        .read(cx)
+        .all_repositories()
+        .into_iter()
+        .flat_map(|repository| {
+            repository
+                .status()
+                .filter(|entry| entry.status.is_conflicted())
+                .filter_map(|entry| repository.unrelativize(&entry.repo_path))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    paths.sort_by(|a, b| (a.worktree_id, &a.path).cmp(&(b.worktree_id, &b.path)));
+    paths
+}
+
+impl Render for ConflictView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.buffers.is_empty() {
+            v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(Label::new("No merge conflicts").color(Color::Muted))
+                .into_any_element()
+        } else {
+            self.editor.clone().into_any_element()
+        };
+        div()
+            .size_full()
+            .key_context("ConflictView")
+            .bg(cx.theme().colors().editor_background)
+            .child(content)
+    }
+}
+
+impl FocusableView for ConflictView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for ConflictView {}
+
+impl Item for ConflictView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Warning))
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some("Conflicts".into())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Conflict View Opened")
+    }
+
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).is_dirty(cx)
+    }
+
+    fn has_deleted_file(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).has_deleted_file(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        self.editor.save(format, project, cx)
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: ProjectPath,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.editor.reload(project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, Item::deactivated);
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, cx))
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, cx)
+        });
+    }
+}
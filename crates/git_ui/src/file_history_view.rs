@@ -0,0 +1,411 @@
+use crate::{
+    commit_view::{build_change_set, build_revision_buffer},
+    log_view::format_commit_timestamp,
+};
+use anyhow::Context as _;
+use editor::{Editor, MultiBuffer};
+use git::history::LogEntry;
+use gpui::{
+    actions, uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, KeyContext, Model,
+    ScrollStrategy, Task, UniformListScrollHandle, View, WeakView,
+};
+use language::Buffer;
+use project::{Project, ProjectItem as _};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::NotifyTaskExt,
+    Workspace,
+};
+
+actions!(git, [FileHistory, OpenRevisionDiff]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &FileHistory, cx| {
+            FileHistoryView::deploy(workspace, cx);
+        });
+    })
+    .detach();
+}
+
+/// The commits that changed a file, following it across renames, each of
+/// which can be opened as the file's contents at that commit.
+pub struct FileHistoryView {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    entries: Vec<LogEntry>,
+    selected_entry: Option<usize>,
+    loading: bool,
+    error: Option<SharedString>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl FileHistoryView {
+    fn deploy(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some((buffer, _)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).text_anchor_for_position(head, cx)
+        }) else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let history = project.read(cx).file_history(&buffer, cx);
+        let workspace_handle = workspace.weak_handle();
+        let view = cx.new_view(|cx| Self::new(workspace_handle, project, buffer, history, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        history: Task<anyhow::Result<Vec<LogEntry>>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        cx.spawn(|this, mut cx| async move {
+            let entries = history.await;
+            this.update(&mut cx, |this, cx| {
+                this.loading = false;
+                match entries {
+                    Ok(entries) => {
+                        this.selected_entry = (!entries.is_empty()).then_some(0);
+                        this.entries = entries;
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .log_err();
+        })
+        .detach();
+
+        Self {
+            workspace,
+            project,
+            buffer,
+            entries: Vec::new(),
+            selected_entry: None,
+            loading: true,
+            error: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn file_name(&self, cx: &AppContext) -> String {
+        self.buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn select_entry(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(entry_ix);
+        self.scroll_handle
+            .scroll_to_item(entry_ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => (entry_ix + 1).min(self.entries.len() - 1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => entry_ix.saturating_sub(1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, cx: &mut ViewContext<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(self.entries.len() - 1, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(entry_ix) = self.selected_entry {
+            self.open_revision(entry_ix, false, cx);
+        }
+    }
+
+    fn open_revision_diff(&mut self, _: &OpenRevisionDiff, cx: &mut ViewContext<Self>) {
+        if let Some(entry_ix) = self.selected_entry {
+            self.open_revision(entry_ix, true, cx);
+        }
+    }
+
+    /// Opens the file as it was at the given entry's commit in a read-only
+    /// editor, or the working copy's changes since that commit if `diff` is
+    /// true.
+    fn open_revision(&mut self, entry_ix: usize, diff: bool, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(entry_ix) else {
+            return;
+        };
+        let Some(path) = entry.paths.first().cloned() else {
+            return;
+        };
+        let Some(project_path) = self.buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let sha = entry.sha;
+        let title = if diff {
+            format!(
+                "{} ({}..Working Copy)",
+                self.file_name(cx),
+                sha.display_short()
+            )
+        } else {
+            format!("{} @ {}", self.file_name(cx), sha.display_short())
+        };
+        let working_copy_text = self.buffer.read(cx).text();
+        let load = self
+            .project
+            .read(cx)
+            .load_text_at_revision(&self.buffer, sha, path, cx);
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+
+        cx.spawn(|_, mut cx| async move {
+            let revision_text = load.await?;
+            let (buffer, change_set) = if diff {
+                let buffer = build_revision_buffer(
+                    working_copy_text,
+                    project_path,
+                    false,
+                    &project,
+                    &mut cx,
+                )
+                .await?;
+                let change_set =
+                    build_change_set(&buffer, revision_text.unwrap_or_default(), &mut cx)?;
+                (buffer, Some(change_set))
+            } else {
+                let revision_text =
+                    revision_text.with_context(|| format!("file doesn't exist at {sha}"))?;
+                let buffer =
+                    build_revision_buffer(revision_text, project_path, false, &project, &mut cx)
+                        .await?;
+                (buffer, None)
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let multibuffer =
+                    cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, None, false, cx);
+                    editor.set_read_only(true);
+                    if let Some(change_set) = change_set {
+                        editor.set_expand_all_diff_hunks();
+                        editor.add_change_set(change_set, cx);
+                    }
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+            })
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("FileHistoryView");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(
+        &self,
+        entry_ix: usize,
+        now: OffsetDateTime,
+        local_offset: UtcOffset,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let entry = &self.entries[entry_ix];
+        // Show where the file was renamed, relative to its newest name.
+        let renamed_path = entry.paths.first().filter(|path| {
+            self.entries
+                .first()
+                .and_then(|newest| newest.paths.first())
+                .map_or(false, |newest_path| newest_path != *path)
+        });
+
+        ListItem::new(entry_ix)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_entry == Some(entry_ix))
+            .on_click(cx.listener(move |this, _, cx| {
+                this.select_entry(entry_ix, cx);
+                this.open_revision(entry_ix, false, cx);
+            }))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        Label::new(entry.sha.display_short())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(Label::new(entry.subject.clone()).single_line()),
+                    )
+                    .when_some(renamed_path, |this, path| {
+                        this.child(
+                            Label::new(path.to_string_lossy().to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .child(
+                        Label::new(entry.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(format_commit_timestamp(entry.timestamp, now, local_offset))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .end_slot(
+                IconButton::new(("diff-revision", entry_ix), IconName::Diff)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| {
+                        Tooltip::for_action("Diff Against Working Copy", &OpenRevisionDiff, cx)
+                    })
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.select_entry(entry_ix, cx);
+                        this.open_revision(entry_ix, true, cx);
+                    })),
+            )
+    }
+}
+
+impl Render for FileHistoryView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.entries.is_empty() {
+            let message: SharedString = if self.loading {
+                "Loading file history…".into()
+            } else if let Some(error) = &self.error {
+                error.clone()
+            } else {
+                "No commits changed this file".into()
+            };
+            div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            let now = OffsetDateTime::now_utc();
+            let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+            uniform_list(
+                cx.view().clone(),
+                "file-history-entries",
+                self.entries.len(),
+                move |this, range, cx| {
+                    range
+                        .map(|entry_ix| this.render_entry(entry_ix, now, local_offset, cx))
+                        .collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .id("file-history")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::open_revision_diff))
+            .child(content)
+    }
+}
+
+impl EventEmitter<ItemEvent> for FileHistoryView {}
+
+impl FocusableView for FileHistoryView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for FileHistoryView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, cx: &WindowContext) -> Option<SharedString> {
+        Some(format!("History: {}", self.file_name(cx)).into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("File History Opened")
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        let entries = self.entries.clone();
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                self.project.clone(),
+                self.buffer.clone(),
+                Task::ready(Ok(entries)),
+                cx,
+            )
+        }))
+    }
+}
@@ -1,3 +1,5 @@
+use crate::askpass_modal::AskPassModal;
+use crate::conflict_view::OpenConflicts;
 use crate::git_panel_settings::StatusStyle;
 use crate::{git_panel_settings::GitPanelSettings, git_status_icon};
 use anyhow::Result;
@@ -7,19 +9,23 @@ use editor::scroll::ScrollbarAutoHide;
 use editor::{Editor, EditorMode, EditorSettings, MultiBuffer, ShowScrollbar};
 use futures::channel::mpsc;
 use futures::StreamExt as _;
-use git::repository::RepoPath;
+use git::repository::{RepoPath, StashEntry};
 use git::status::FileStatus;
-use git::{CommitAllChanges, CommitChanges, RevertAll, StageAll, ToggleStaged, UnstageAll};
+use git::{
+    AmendCommit, CommitAllChanges, CommitChanges, Fetch, PopStash, Pull, PullRebase, Push,
+    RevertAll, SetUpstream, StageAll, StashChanges, ToggleStaged, UnstageAll,
+};
 use gpui::*;
 use menu::{SelectFirst, SelectLast, SelectNext, SelectPrev};
-use project::git::RepositoryHandle;
+use project::git::{RemoteOperation, RepositoryHandle};
 use project::{Fs, Project, ProjectPath};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
 use std::{collections::HashSet, ops::Range, path::PathBuf, sync::Arc, time::Duration, usize};
 use theme::ThemeSettings;
 use ui::{
-    prelude::*, Checkbox, Divider, DividerColor, ElevationIndex, Scrollbar, ScrollbarState, Tooltip,
+    prelude::*, Checkbox, CheckboxWithLabel, ContextMenu, Divider, DividerColor, ElevationIndex,
+    PopoverMenu, Scrollbar, ScrollbarState, Tooltip,
 };
 use util::{ResultExt, TryFutureExt};
 use workspace::notifications::{DetachAndPromptErr, NotificationId};
@@ -52,11 +58,62 @@ pub fn init(cx: &mut AppContext) {
             workspace.register_action(|workspace, _: &ToggleFocus, cx| {
                 workspace.toggle_panel_focus::<GitPanel>(cx);
             });
+            // Remote operations don't depend on the panel's focus, and show
+            // their progress in the activity indicator.
+            workspace.register_action(|workspace, _: &Fetch, cx| {
+                run_remote_operation(workspace, RemoteOperation::Fetch, cx);
+            });
+            workspace.register_action(|workspace, _: &Pull, cx| {
+                run_remote_operation(workspace, RemoteOperation::Pull { rebase: false }, cx);
+            });
+            workspace.register_action(|workspace, _: &PullRebase, cx| {
+                run_remote_operation(workspace, RemoteOperation::Pull { rebase: true }, cx);
+            });
+            workspace.register_action(|workspace, _: &Push, cx| {
+                run_remote_operation(
+                    workspace,
+                    RemoteOperation::Push {
+                        set_upstream: false,
+                    },
+                    cx,
+                );
+            });
+            workspace.register_action(|workspace, _: &SetUpstream, cx| {
+                run_remote_operation(workspace, RemoteOperation::Push { set_upstream: true }, cx);
+            });
         },
     )
     .detach();
 }
 
+/// Runs a remote operation on the active repository, asking for the
+/// credentials git prompts for in a modal.
+fn run_remote_operation(
+    workspace: &mut Workspace,
+    operation: RemoteOperation,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+        return;
+    };
+    let handle = cx.view().downgrade();
+    let window = cx.window_handle();
+    let task = repository.run_remote_operation(
+        operation,
+        move |prompt, cx| AskPassModal::prompt(handle.clone(), window, prompt, cx),
+        cx,
+    );
+    task.detach_and_prompt_err(
+        match operation {
+            RemoteOperation::Fetch => "Failed to fetch",
+            RemoteOperation::Pull { .. } => "Failed to pull",
+            RemoteOperation::Push { .. } => "Failed to push",
+        },
+        cx,
+        |e, _| Some(format!("{e:#}")),
+    );
+}
+
 #[derive(Debug, Clone)]
 pub enum Event {
     Focus,
@@ -96,6 +153,10 @@ pub struct GitPanel {
     all_staged: Option<bool>,
     width: Option<Pixels>,
     err_sender: mpsc::Sender<anyhow::Error>,
+    /// Whether committing amends HEAD instead of creating a new commit.
+    amend: bool,
+    stash_entries: Vec<StashEntry>,
+    load_stash_entries_task: Task<()>,
 }
 
 fn commit_message_editor(
@@ -195,6 +256,9 @@ impl GitPanel {
                 project,
                 err_sender,
                 workspace,
+                amend: false,
+                stash_entries: Vec::new(),
+                load_stash_entries_task: Task::ready(()),
             };
             git_panel.schedule_update(cx);
             git_panel.show_scrollbar = git_panel.should_show_scrollbar(cx);
@@ -544,8 +608,12 @@ impl GitPanel {
         println!("Discard all triggered");
     }
 
-    /// Commit all staged changes
+    /// Commit all staged changes, or amend the last commit with them
     fn commit_changes(&mut self, _: &git::CommitChanges, cx: &mut ViewContext<Self>) {
+        if self.amend {
+            self.amend_commit(&AmendCommit, cx);
+            return;
+        }
         let Some(active_repository) = self.active_repository.as_ref() else {
             return;
         };
@@ -555,6 +623,42 @@ impl GitPanel {
         active_repository.commit(self.err_sender.clone(), cx);
     }
 
+    /// Amend the last commit with the staged changes, replacing its message
+    /// unless the commit message is empty
+    fn amend_commit(&mut self, _: &git::AmendCommit, cx: &mut ViewContext<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        if !active_repository.can_amend(cx) {
+            return;
+        }
+        active_repository.amend(self.err_sender.clone(), cx);
+        self.amend = false;
+        cx.notify();
+    }
+
+    fn stash_changes(&mut self, _: &git::StashChanges, cx: &mut ViewContext<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        if let Err(e) = active_repository.stash_push(None, self.err_sender.clone()) {
+            self.show_err_toast("stash error", e, cx);
+        }
+    }
+
+    fn pop_stash(&mut self, _: &git::PopStash, cx: &mut ViewContext<Self>) {
+        self.pop_stash_entry(0, cx);
+    }
+
+    fn pop_stash_entry(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            return;
+        };
+        if let Err(e) = active_repository.stash_pop(index, self.err_sender.clone()) {
+            self.show_err_toast("stash pop error", e, cx);
+        }
+    }
+
     /// Commit all changes, regardless of whether they are staged or not
     fn commit_all_changes(&mut self, _: &git::CommitAllChanges, cx: &mut ViewContext<Self>) {
         let Some(active_repository) = self.active_repository.as_ref() else {
@@ -671,6 +775,7 @@ impl GitPanel {
             if let Some(this) = handle.upgrade() {
                 this.update(&mut cx, |this, cx| {
                     this.update_visible_entries(cx);
+                    this.load_stash_entries(cx);
                     let active_repository = this.active_repository.as_ref();
                     this.commit_editor =
                         cx.new_view(|cx| commit_message_editor(active_repository, cx));
@@ -680,6 +785,22 @@ impl GitPanel {
         });
     }
 
+    fn load_stash_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(active_repository) = self.active_repository.as_ref() else {
+            self.stash_entries.clear();
+            return;
+        };
+        let stash_list = active_repository.stash_list(cx);
+        self.load_stash_entries_task = cx.spawn(|this, mut cx| async move {
+            let stash_entries = stash_list.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.stash_entries = stash_entries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
     fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
         self.visible_entries.clear();
 
@@ -799,6 +920,12 @@ impl GitPanel {
             n => format!("{} changes", n),
         };
 
+        let has_conflicts = self.active_repository.as_ref().map_or(false, |repository| {
+            repository
+                .status()
+                .any(|entry| entry.status.is_conflicted())
+        });
+
         // for our use case treat None as false
         let all_staged = self.all_staged.unwrap_or(false);
 
@@ -840,6 +967,21 @@ impl GitPanel {
             .child(
                 h_flex()
                     .gap_2()
+                    .when(has_conflicts, |this| {
+                        this.child(
+                            self.panel_button("open-conflicts", "Conflicts")
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Resolve merge conflicts",
+                                        &OpenConflicts,
+                                        cx,
+                                    )
+                                })
+                                .on_click(|_, cx| cx.dispatch_action(OpenConflicts.boxed_clone())),
+                        )
+                    })
+                    .child(self.render_remote_menu(cx))
+                    .child(self.render_stash_menu(cx))
                     // TODO: Re-add once revert all is added
                     // .child(
                     //     IconButton::new("discard-changes", IconName::Undo)
@@ -901,6 +1043,91 @@ impl GitPanel {
             )
     }
 
+    fn render_remote_menu(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let upstream_tracking = self
+            .active_repository
+            .as_ref()
+            .and_then(RepositoryHandle::upstream_tracking);
+        let label = match upstream_tracking {
+            Some(tracking) if tracking.ahead > 0 || tracking.behind > 0 => {
+                format!("Sync ↓{} ↑{}", tracking.behind, tracking.ahead)
+            }
+            Some(_) => "Sync".to_string(),
+            None => "Publish".to_string(),
+        };
+        PopoverMenu::new("remote-menu")
+            .trigger(self.panel_button("remote", label))
+            .anchor(Corner::TopRight)
+            .menu(move |cx| {
+                let focus_handle = focus_handle.clone();
+                Some(ContextMenu::build(cx, move |menu, _| {
+                    let menu = menu
+                        .context(focus_handle)
+                        .action("Fetch", Fetch.boxed_clone());
+                    // Pulling and pushing need an upstream, which publishing sets.
+                    let menu = if upstream_tracking.is_some() {
+                        menu.action("Pull", Pull.boxed_clone())
+                            .action("Pull (Rebase)", PullRebase.boxed_clone())
+                            .action("Push", Push.boxed_clone())
+                    } else {
+                        menu.disabled_action("Pull", Pull.boxed_clone())
+                            .disabled_action("Pull (Rebase)", PullRebase.boxed_clone())
+                            .disabled_action("Push", Push.boxed_clone())
+                    };
+                    menu.separator()
+                        .action("Publish Branch", SetUpstream.boxed_clone())
+                }))
+            })
+    }
+
+    fn render_stash_menu(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let this = cx.view().downgrade();
+        let focus_handle = self.focus_handle(cx);
+        let has_changes = self
+            .active_repository
+            .as_ref()
+            .map_or(false, |active_repository| {
+                active_repository.entry_count() > 0
+            });
+        let stash_entries = self.stash_entries.clone();
+        PopoverMenu::new("stash-menu")
+            .trigger(self.panel_button("stash", "Stash"))
+            .anchor(Corner::TopRight)
+            .menu(move |cx| {
+                let this = this.clone();
+                let focus_handle = focus_handle.clone();
+                let stash_entries = stash_entries.clone();
+                Some(ContextMenu::build(cx, move |menu, _| {
+                    let menu = menu.context(focus_handle);
+                    let menu = if has_changes {
+                        menu.action("Stash Changes", StashChanges.boxed_clone())
+                    } else {
+                        menu.disabled_action("Stash Changes", StashChanges.boxed_clone())
+                    };
+                    if stash_entries.is_empty() {
+                        return menu;
+                    }
+                    stash_entries.into_iter().fold(
+                        menu.separator().header("Pop Stash"),
+                        |menu, entry| {
+                            let this = this.clone();
+                            menu.entry(
+                                format!("stash@{{{}}}: {}", entry.index, entry.message),
+                                None,
+                                move |cx| {
+                                    this.update(cx, |this, cx| {
+                                        this.pop_stash_entry(entry.index, cx)
+                                    })
+                                    .ok();
+                                },
+                            )
+                        },
+                    )
+                }))
+            })
+    }
+
     pub fn render_commit_editor(&self, cx: &ViewContext<Self>) -> impl IntoElement {
         let editor = self.commit_editor.clone();
         let editor_focus_handle = editor.read(cx).focus_handle(cx).clone();
@@ -917,18 +1144,36 @@ impl GitPanel {
         let focus_handle_1 = self.focus_handle(cx).clone();
         let focus_handle_2 = self.focus_handle(cx).clone();
 
+        let can_amend = self
+            .active_repository
+            .as_ref()
+            .map_or(false, |active_repository| active_repository.can_amend(cx));
+        let amend = self.amend;
+
         let commit_staged_button = self
-            .panel_button("commit-staged-changes", "Commit")
+            .panel_button(
+                "commit-staged-changes",
+                if amend { "Amend" } else { "Commit" },
+            )
             .tooltip(move |cx| {
                 let focus_handle = focus_handle_1.clone();
-                Tooltip::for_action_in(
-                    "Commit all staged changes",
-                    &CommitChanges,
-                    &focus_handle,
-                    cx,
-                )
+                if amend {
+                    Tooltip::for_action_in(
+                        "Amend the last commit with all staged changes",
+                        &AmendCommit,
+                        &focus_handle,
+                        cx,
+                    )
+                } else {
+                    Tooltip::for_action_in(
+                        "Commit all staged changes",
+                        &CommitChanges,
+                        &focus_handle,
+                        cx,
+                    )
+                }
             })
-            .disabled(!can_commit)
+            .disabled(if amend { !can_amend } else { !can_commit })
             .on_click(
                 cx.listener(|this, _: &ClickEvent, cx| this.commit_changes(&CommitChanges, cx)),
             );
@@ -963,9 +1208,19 @@ impl GitPanel {
                     h_flex()
                         .absolute()
                         .bottom_2p5()
+                        .left_3()
                         .right_3()
+                        .child(CheckboxWithLabel::new(
+                            "amend-commit",
+                            Label::new("Amend").size(LabelSize::Small),
+                            ToggleState::from(amend),
+                            cx.listener(|this, state: &ToggleState, cx| {
+                                this.amend = *state == ToggleState::Selected;
+                                cx.notify();
+                            }),
+                        ))
                         .child(div().gap_1().flex_grow())
-                        .child(if self.current_modifiers.alt {
+                        .child(if self.current_modifiers.alt && !amend {
                             commit_all_button
                         } else {
                             commit_staged_button
@@ -1265,6 +1520,9 @@ impl Render for GitPanel {
                 .on_action(cx.listener(|this, &CommitAllChanges, cx| {
                     this.commit_all_changes(&CommitAllChanges, cx)
                 }))
+                .on_action(cx.listener(Self::amend_commit))
+                .on_action(cx.listener(Self::stash_changes))
+                .on_action(cx.listener(Self::pop_stash))
             })
             .when(self.is_focused(cx), |this| {
                 this.on_action(cx.listener(Self::select_first))
@@ -4,12 +4,20 @@ use git_panel_settings::GitPanelSettings;
 use gpui::AppContext;
 use ui::{ActiveTheme, Color, Icon, IconName, IntoElement, WindowContext};
 
+mod askpass_modal;
+pub mod commit_view;
+pub mod conflict_view;
+pub mod file_history_view;
 pub mod git_panel;
 mod git_panel_settings;
+pub mod log_view;
 pub mod repository_selector;
 
 pub fn init(cx: &mut AppContext) {
     GitPanelSettings::register(cx);
+    log_view::init(cx);
+    conflict_view::init(cx);
+    file_history_view::init(cx);
 }
 
 // TODO: Add updated status colors to theme
@@ -0,0 +1,697 @@
+use crate::commit_view::CommitView;
+use editor::{Editor, EditorEvent};
+use git::{
+    history::{LogEntry, LogOptions},
+    repository::RepoPath,
+    Oid,
+};
+use gpui::{
+    actions, canvas, fill, point, px, size, uniform_list, AppContext, Bounds, EventEmitter,
+    FocusHandle, FocusableView, KeyContext, ScrollStrategy, Subscription, Task,
+    UniformListScrollHandle, View, WeakView,
+};
+use project::git::RepositoryHandle;
+use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    notifications::NotifyTaskExt,
+    Workspace,
+};
+
+actions!(git, [OpenLog]);
+
+/// The number of commits loaded at a time.
+const PAGE_SIZE: usize = 200;
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+const ROW_HEIGHT: Pixels = px(28.);
+const LANE_WIDTH: Pixels = px(14.);
+const LINE_WIDTH: Pixels = px(2.);
+const NODE_SIZE: Pixels = px(8.);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &OpenLog, cx| {
+            LogView::deploy(workspace, Vec::new(), cx);
+        });
+    })
+    .detach();
+}
+
+/// The commits of a repository, optionally limited to the ones changing some
+/// paths, searchable by message and author.
+pub struct LogView {
+    workspace: WeakView<Workspace>,
+    repository: RepositoryHandle,
+    paths: Vec<RepoPath>,
+    query_editor: View<Editor>,
+    entries: Vec<LogEntry>,
+    graph: CommitGraph,
+    selected_entry: Option<usize>,
+    has_more: bool,
+    error: Option<SharedString>,
+    load_task: Option<Task<()>>,
+    _search_task: Option<Task<()>>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    _query_subscription: Subscription,
+}
+
+impl LogView {
+    /// Opens the log of the active repository, limited to the commits that
+    /// changed the given paths if there are any.
+    pub fn deploy(
+        workspace: &mut Workspace,
+        paths: Vec<RepoPath>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        let workspace_handle = workspace.weak_handle();
+        let view = cx.new_view(|cx| Self::new(workspace_handle, repository, paths, cx));
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        repository: RepositoryHandle,
+        paths: Vec<RepoPath>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Search commit messages, or author:<name>", cx);
+            editor
+        });
+        let query_subscription = cx.subscribe(&query_editor, |this, _, event, cx| {
+            if let EditorEvent::BufferEdited = event {
+                this._search_task = Some(cx.spawn(|this, mut cx| async move {
+                    cx.background_executor().timer(SEARCH_DEBOUNCE).await;
+                    this.update(&mut cx, |this, cx| this.reload(cx)).log_err();
+                }));
+            }
+        });
+
+        let mut this = Self {
+            workspace,
+            repository,
+            paths,
+            query_editor,
+            entries: Vec::new(),
+            graph: CommitGraph::default(),
+            selected_entry: None,
+            has_more: false,
+            error: None,
+            load_task: None,
+            _search_task: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            _query_subscription: query_subscription,
+        };
+        this.load_more(cx);
+        this
+    }
+
+    /// The search in the query editor, as a message and an author filter.
+    fn filters(&self, cx: &AppContext) -> (Option<String>, Option<String>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let query = query.trim();
+        if query.is_empty() {
+            (None, None)
+        } else if let Some(author) = query.strip_prefix("author:") {
+            (None, Some(author.trim().to_string()))
+        } else {
+            (Some(query.to_string()), None)
+        }
+    }
+
+    /// Whether the commits are drawn as a graph, which is only meaningful when
+    /// every commit is listed.
+    fn show_graph(&self, cx: &AppContext) -> bool {
+        self.paths.is_empty() && self.filters(cx) == (None, None)
+    }
+
+    fn reload(&mut self, cx: &mut ViewContext<Self>) {
+        self.entries.clear();
+        self.graph = CommitGraph::default();
+        self.selected_entry = None;
+        self.has_more = false;
+        self.error = None;
+        self.load_task = None;
+        self.load_more(cx);
+    }
+
+    fn load_more(&mut self, cx: &mut ViewContext<Self>) {
+        if self.load_task.is_some() {
+            return;
+        }
+        let (message, author) = self.filters(cx);
+        let options = LogOptions {
+            skip: self.entries.len(),
+            limit: PAGE_SIZE,
+            paths: self.paths.clone(),
+            author,
+            message,
+            follow_renames: false,
+        };
+        let log = self.repository.log(options, cx);
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let entries = log.await;
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match entries {
+                    Ok(entries) => {
+                        this.has_more = entries.len() == PAGE_SIZE;
+                        for entry in &entries {
+                            this.graph.push(entry);
+                        }
+                        this.entries.extend(entries);
+                        if this.selected_entry.is_none() && !this.entries.is_empty() {
+                            this.selected_entry = Some(0);
+                        }
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    fn select_entry(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(entry_ix);
+        self.scroll_handle
+            .scroll_to_item(entry_ix, ScrollStrategy::Center);
+        if self.has_more && entry_ix + 1 == self.entries.len() {
+            self.load_more(cx);
+        }
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => (entry_ix + 1).min(self.entries.len() - 1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => entry_ix.saturating_sub(1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, cx: &mut ViewContext<Self>) {
+        if !self.entries.is_empty() {
+            self.select_entry(self.entries.len() - 1, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(entry_ix) = self.selected_entry {
+            self.open_commit(entry_ix, cx);
+        }
+    }
+
+    fn open_commit(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(entry_ix) else {
+            return;
+        };
+        CommitView::open(
+            self.repository.clone(),
+            entry.sha,
+            self.workspace.clone(),
+            cx,
+        )
+        .detach_and_notify_err(cx);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("GitLogView");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(
+        &self,
+        entry_ix: usize,
+        show_graph: bool,
+        now: OffsetDateTime,
+        local_offset: UtcOffset,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let entry = &self.entries[entry_ix];
+        let timestamp = format_commit_timestamp(entry.timestamp, now, local_offset);
+        let selected = self.selected_entry == Some(entry_ix);
+        let colors = cx.theme().colors();
+        let (selected_bg, hover_bg, border) = (
+            colors.ghost_element_selected,
+            colors.ghost_element_hover,
+            colors.border,
+        );
+
+        h_flex()
+            .id(entry_ix)
+            .w_full()
+            .h(ROW_HEIGHT)
+            .pr_2()
+            .gap_2()
+            .cursor_pointer()
+            .when(selected, |this| this.bg(selected_bg))
+            .hover(|style| style.bg(hover_bg))
+            .on_click(cx.listener(move |this, _, cx| {
+                this.select_entry(entry_ix, cx);
+                this.open_commit(entry_ix, cx);
+            }))
+            .when(show_graph, |this| {
+                this.children(self.graph.rows.get(entry_ix).map(render_graph_row))
+            })
+            .when(!show_graph, |this| this.pl_2())
+            .child(
+                Label::new(entry.sha.display_short())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .children(entry.refs.iter().map(|name| {
+                div()
+                    .px_1()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(border)
+                    .child(Label::new(name.clone()).size(LabelSize::XSmall))
+            }))
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(Label::new(entry.subject.clone()).single_line()),
+            )
+            .child(
+                Label::new(entry.author_name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(timestamp)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Icon::new(IconName::MagnifyingGlass).color(Color::Muted))
+            .child(div().flex_1().child(self.query_editor.clone()))
+            .child(
+                IconButton::new("reload-git-log", IconName::RotateCw)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Reload", cx))
+                    .on_click(cx.listener(|this, _, cx| this.reload(cx))),
+            )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        if !self.has_more || self.entries.is_empty() {
+            return None;
+        }
+        let loading = self.load_task.is_some();
+        Some(
+            h_flex().w_full().p_1().justify_center().child(
+                Button::new("load-more-commits", "Load More")
+                    .disabled(loading)
+                    .on_click(cx.listener(|this, _, cx| this.load_more(cx))),
+            ),
+        )
+    }
+}
+
+/// Formats the time of a commit relative to now.
+pub(crate) fn format_commit_timestamp(
+    timestamp: i64,
+    now: OffsetDateTime,
+    local_offset: UtcOffset,
+) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .map(|timestamp| {
+            time_format::format_localized_timestamp(
+                timestamp,
+                now,
+                local_offset,
+                time_format::TimestampFormat::Relative,
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn render_graph_row(row: &GraphRow) -> impl IntoElement {
+    let row = row.clone();
+    canvas(
+        |_, _| {},
+        move |bounds, _, cx| {
+            let lane_x = |lane: usize| bounds.origin.x + LANE_WIDTH * (lane as f32 + 0.5);
+            let middle = bounds.origin.y + bounds.size.height / 2.;
+            let top = bounds.origin.y;
+            let bottom = bounds.origin.y + bounds.size.height;
+            let players = cx.theme().players().clone();
+            let color = |lane: usize| players.color_for_participant(lane as u32).cursor;
+
+            let mut quads = Vec::new();
+            for &lane in &row.lanes_above {
+                let x = lane_x(lane) - LINE_WIDTH / 2.;
+                quads.push((
+                    Bounds::new(point(x, top), size(LINE_WIDTH, middle - top)),
+                    color(lane),
+                ));
+            }
+            for &lane in &row.lanes_below {
+                let x = lane_x(lane) - LINE_WIDTH / 2.;
+                quads.push((
+                    Bounds::new(point(x, middle), size(LINE_WIDTH, bottom - middle)),
+                    color(lane),
+                ));
+            }
+            for &lane in row.merged.iter().chain(&row.branched) {
+                let (start, end) = if lane < row.lane {
+                    (lane_x(lane), lane_x(row.lane))
+                } else {
+                    (lane_x(row.lane), lane_x(lane))
+                };
+                quads.push((
+                    Bounds::new(
+                        point(start - LINE_WIDTH / 2., middle - LINE_WIDTH / 2.),
+                        size(end - start + LINE_WIDTH, LINE_WIDTH),
+                    ),
+                    color(lane),
+                ));
+            }
+            for (bounds, color) in quads {
+                cx.paint_quad(fill(bounds, color));
+            }
+
+            let node = Bounds::new(
+                point(lane_x(row.lane) - NODE_SIZE / 2., middle - NODE_SIZE / 2.),
+                size(NODE_SIZE, NODE_SIZE),
+            );
+            cx.paint_quad(fill(node, color(row.lane)).corner_radii(NODE_SIZE / 2.));
+        },
+    )
+    .h_full()
+    .w(LANE_WIDTH * row.lane_count() as f32)
+    .flex_none()
+}
+
+/// Assigns the commits of a log, listed children before their parents, to
+/// the vertical lanes of a graph.
+#[derive(Default)]
+struct CommitGraph {
+    /// The commit expected next in each lane, if the lane is in use.
+    lanes: Vec<Option<Oid>>,
+    rows: Vec<GraphRow>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct GraphRow {
+    /// The lane of the commit.
+    lane: usize,
+    /// The lanes running into the row from the rows above.
+    lanes_above: Vec<usize>,
+    /// The lanes running out of the row to the rows below.
+    lanes_below: Vec<usize>,
+    /// The lanes of the commit's other children, ending at the commit.
+    merged: Vec<usize>,
+    /// The lanes of the commit's parents other than the first one.
+    branched: Vec<usize>,
+}
+
+impl GraphRow {
+    fn lane_count(&self) -> usize {
+        self.lanes_above
+            .iter()
+            .chain(&self.lanes_below)
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(self.lane)
+            + 1
+    }
+}
+
+impl CommitGraph {
+    fn push(&mut self, entry: &LogEntry) {
+        let lanes_above = self.used_lanes();
+        let mut children_lanes = self
+            .lanes
+            .iter()
+            .enumerate()
+            .filter(|(_, sha)| **sha == Some(entry.sha))
+            .map(|(lane, _)| lane);
+        let lane = match children_lanes.next() {
+            Some(lane) => lane,
+            None => self.allocate_lane(),
+        };
+        let merged = children_lanes.collect::<Vec<_>>();
+
+        let mut parents = entry.parents.iter();
+        self.lanes[lane] = parents.next().copied();
+        let mut branched = Vec::new();
+        for parent in parents {
+            let parent_lane = match self.lanes.iter().position(|sha| *sha == Some(*parent)) {
+                Some(parent_lane) => parent_lane,
+                None => {
+                    let parent_lane = self.allocate_lane();
+                    self.lanes[parent_lane] = Some(*parent);
+                    parent_lane
+                }
+            };
+            branched.push(parent_lane);
+        }
+
+        // The other children's lanes are only freed now, so that they aren't
+        // reused for the commit's own parents within the same row.
+        for &lane in &merged {
+            self.lanes[lane] = None;
+        }
+        while self.lanes.last() == Some(&None) {
+            self.lanes.pop();
+        }
+
+        self.rows.push(GraphRow {
+            lane,
+            lanes_above,
+            lanes_below: self.used_lanes(),
+            merged,
+            branched,
+        });
+    }
+
+    fn used_lanes(&self) -> Vec<usize> {
+        self.lanes
+            .iter()
+            .enumerate()
+            .filter(|(_, sha)| sha.is_some())
+            .map(|(lane, _)| lane)
+            .collect()
+    }
+
+    fn allocate_lane(&mut self) -> usize {
+        match self.lanes.iter().position(Option::is_none) {
+            Some(lane) => lane,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+}
+
+impl Render for LogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.entries.is_empty() {
+            let message: SharedString = if self.load_task.is_some() {
+                "Loading commits…".into()
+            } else if let Some(error) = &self.error {
+                error.clone()
+            } else {
+                "No commits found".into()
+            };
+            div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            let show_graph = self.show_graph(cx);
+            let now = OffsetDateTime::now_utc();
+            let local_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+            uniform_list(
+                cx.view().clone(),
+                "git-log-entries",
+                self.entries.len(),
+                move |this, range, cx| {
+                    range
+                        .map(|entry_ix| {
+                            this.render_entry(entry_ix, show_graph, now, local_offset, cx)
+                        })
+                        .collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .id("git-log")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .child(self.render_header(cx))
+            .child(content)
+            .children(self.render_footer(cx))
+    }
+}
+
+impl EventEmitter<ItemEvent> for LogView {}
+
+impl FocusableView for LogView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for LogView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        match self.paths.as_slice() {
+            [path] => Some(format!("History: {}", path.display()).into()),
+            _ => Some("Git Log".into()),
+        }
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git Log Opened")
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                self.repository.clone(),
+                self.paths.clone(),
+                cx,
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sha: &str, parents: &[&str]) -> LogEntry {
+        let oid = |sha: &str| sha.repeat(40).parse::<Oid>().unwrap();
+        LogEntry {
+            sha: oid(sha),
+            parents: parents.iter().map(|parent| oid(parent)).collect(),
+            author_name: "".into(),
+            author_email: "".into(),
+            timestamp: 0,
+            refs: Vec::new(),
+            subject: "".into(),
+            paths: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_commit_graph() {
+        // 5   merge of 4 into 3
+        // |\
+        // | 4
+        // 3 |
+        // |/
+        // 2
+        // 1
+        let mut graph = CommitGraph::default();
+        for entry in [
+            entry("5", &["3", "4"]),
+            entry("4", &["2"]),
+            entry("3", &["2"]),
+            entry("2", &["1"]),
+            entry("1", &[]),
+        ] {
+            graph.push(&entry);
+        }
+
+        let row = |lane,
+                   lanes_above: &[usize],
+                   lanes_below: &[usize],
+                   merged: &[usize],
+                   branched: &[usize]| GraphRow {
+            lane,
+            lanes_above: lanes_above.to_vec(),
+            lanes_below: lanes_below.to_vec(),
+            merged: merged.to_vec(),
+            branched: branched.to_vec(),
+        };
+        assert_eq!(
+            graph.rows,
+            [
+                row(0, &[], &[0, 1], &[], &[1]),
+                row(1, &[0, 1], &[0, 1], &[], &[]),
+                row(0, &[0, 1], &[0, 1], &[], &[]),
+                row(0, &[0, 1], &[0], &[1], &[]),
+                row(0, &[0], &[], &[], &[]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, KeyContext, Model,
+    ModelContext, ScrollStrategy, Task, UniformListScrollHandle, View, WeakView,
+};
+use language::{Anchor, Buffer, OffsetRangeExt};
+use project::{Location, Project};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent},
+    Pane, SplitDirection, Workspace,
+};
+
+actions!(
+    hierarchy_view,
+    [ToggleDirection, ExpandSelectedEntry, CollapseSelectedEntry]
+);
+
+/// A kind of hierarchy shown in a [`HierarchyView`], along with the direction
+/// in which it is browsed, such as the calls into a function.
+pub trait HierarchySource: Copy + 'static {
+    /// An item provided by the language server, whose children are resolved
+    /// when its node gets expanded.
+    type Item: Clone + 'static;
+
+    /// The key context identifying views of this hierarchy, in addition to
+    /// `HierarchyView`.
+    const KEY_CONTEXT: &'static str;
+    /// The lowercase name of the hierarchy, such as "call hierarchy".
+    const NAME: &'static str;
+    const TELEMETRY_EVENT_TEXT: &'static str;
+
+    /// The title of the hierarchy in this direction, such as "Incoming Calls".
+    fn label(&self) -> &'static str;
+
+    /// The opposite direction.
+    fn toggled(&self) -> Self;
+
+    /// Resolves the items at the given position, which become the roots of
+    /// the hierarchy.
+    fn prepare(
+        project: &mut Project,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<Self::Item>>>;
+
+    /// Resolves the children of the given item in this direction.
+    fn children(
+        &self,
+        project: &mut Project,
+        item: &Self::Item,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<Self::Item>>>>;
+
+    fn name(item: &Self::Item) -> &str;
+
+    fn detail(item: &Self::Item) -> Option<&str>;
+
+    /// The location that should be revealed when navigating to the item.
+    fn location(item: &Self::Item) -> &Location;
+}
+
+pub struct HierarchyChild<I> {
+    pub item: I,
+    /// The locations at which the child relates to its parent, such as call
+    /// sites. The first one is revealed instead of the item when present.
+    pub references: Vec<Location>,
+}
+
+/// A tree of the items related to the symbol under the cursor, whose nodes
+/// are lazily resolved by the language server as they get expanded.
+pub struct HierarchyView<S: HierarchySource> {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    origin_pane: WeakView<Pane>,
+    source: S,
+    roots: Vec<S::Item>,
+    nodes: Vec<HierarchyNode<S::Item>>,
+    visible_entries: Vec<usize>,
+    selected_entry: Option<usize>,
+    /// Incremented whenever the tree is rebuilt, so that responses for
+    /// discarded nodes are ignored.
+    generation: usize,
+    loading_roots: bool,
+    error: Option<SharedString>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+struct HierarchyNode<I> {
+    item: I,
+    references: Vec<Location>,
+    depth: usize,
+    expanded: bool,
+    children: NodeChildren,
+}
+
+enum NodeChildren {
+    Unresolved,
+    Resolving,
+    Resolved(Vec<usize>),
+}
+
+impl<S: HierarchySource> HierarchyView<S> {
+    /// Opens the hierarchy of the symbol under the cursor of the active editor
+    /// in a split.
+    pub fn deploy(workspace: &mut Workspace, source: S, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).text_anchor_for_position(head, cx)
+        }) else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let prepare = project.update(cx, |project, cx| S::prepare(project, &buffer, position, cx));
+        let workspace_handle = workspace.weak_handle();
+        let origin_pane = workspace.active_pane().downgrade();
+        let view = cx
+            .new_view(|cx| Self::new(workspace_handle, project, origin_pane, source, prepare, cx));
+        workspace.split_item(SplitDirection::Right, Box::new(view), cx);
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        origin_pane: WeakView<Pane>,
+        source: S,
+        prepare: Task<Result<Vec<S::Item>>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        cx.spawn(|this, mut cx| async move {
+            let roots = prepare.await;
+            this.update(&mut cx, |this, cx| {
+                this.loading_roots = false;
+                match roots {
+                    Ok(roots) => this.set_roots(roots, cx),
+                    Err(error) => {
+                        this.error = Some(format!("{error:#}").into());
+                        cx.notify();
+                    }
+                }
+            })
+            .log_err();
+        })
+        .detach();
+
+        Self {
+            workspace,
+            project,
+            origin_pane,
+            source,
+            roots: Vec::new(),
+            nodes: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_entry: None,
+            generation: 0,
+            loading_roots: true,
+            error: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn set_roots(&mut self, roots: Vec<S::Item>, cx: &mut ViewContext<Self>) {
+        self.roots = roots;
+        self.rebuild(cx);
+    }
+
+    fn rebuild(&mut self, cx: &mut ViewContext<Self>) {
+        self.generation += 1;
+        self.nodes = self
+            .roots
+            .iter()
+            .map(|item| HierarchyNode {
+                item: item.clone(),
+                references: Vec::new(),
+                depth: 0,
+                expanded: false,
+                children: NodeChildren::Unresolved,
+            })
+            .collect();
+        self.selected_entry = None;
+        self.update_visible_entries();
+        if !self.nodes.is_empty() {
+            self.selected_entry = Some(0);
+            self.expand(0, cx);
+        }
+        cx.notify();
+    }
+
+    fn toggle_direction(&mut self, _: &ToggleDirection, cx: &mut ViewContext<Self>) {
+        self.source = self.source.toggled();
+        self.rebuild(cx);
+        cx.emit(ItemEvent::UpdateTab);
+    }
+
+    fn expand(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(node) = self.nodes.get_mut(node_ix) else {
+            return;
+        };
+        node.expanded = true;
+        if !matches!(node.children, NodeChildren::Unresolved) {
+            self.update_visible_entries();
+            cx.notify();
+            return;
+        }
+
+        node.children = NodeChildren::Resolving;
+        let item = node.item.clone();
+        let source = self.source;
+        let children = self
+            .project
+            .update(cx, |project, cx| source.children(project, &item, cx));
+        let generation = self.generation;
+        cx.spawn(|this, mut cx| async move {
+            let children = children.await;
+            this.update(&mut cx, |this, cx| {
+                if this.generation == generation {
+                    this.insert_children(node_ix, children.log_err().unwrap_or_default(), cx);
+                }
+            })
+            .log_err();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn collapse(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        if let Some(node) = self.nodes.get_mut(node_ix) {
+            node.expanded = false;
+            self.update_visible_entries();
+            cx.notify();
+        }
+    }
+
+    fn insert_children(
+        &mut self,
+        parent_ix: usize,
+        children: Vec<HierarchyChild<S::Item>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let depth = self.nodes[parent_ix].depth + 1;
+        let mut child_ixs = Vec::with_capacity(children.len());
+        for child in children {
+            child_ixs.push(self.nodes.len());
+            self.nodes.push(HierarchyNode {
+                item: child.item,
+                references: child.references,
+                depth,
+                expanded: false,
+                children: NodeChildren::Unresolved,
+            });
+        }
+        self.nodes[parent_ix].children = NodeChildren::Resolved(child_ixs);
+        self.update_visible_entries();
+        cx.notify();
+    }
+
+    fn update_visible_entries(&mut self) {
+        let selected_node = self
+            .selected_entry
+            .and_then(|entry| self.visible_entries.get(entry).copied());
+
+        let mut visible_entries = Vec::new();
+        let mut stack = (0..self.roots.len().min(self.nodes.len()))
+            .rev()
+            .collect::<Vec<_>>();
+        while let Some(node_ix) = stack.pop() {
+            visible_entries.push(node_ix);
+            let node = &self.nodes[node_ix];
+            if node.expanded {
+                if let NodeChildren::Resolved(children) = &node.children {
+                    stack.extend(children.iter().rev().copied());
+                }
+            }
+        }
+        self.visible_entries = visible_entries;
+
+        if let Some(selected_node) = selected_node {
+            self.selected_entry = self
+                .visible_entries
+                .iter()
+                .position(|node_ix| *node_ix == selected_node);
+        }
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.visible_entries.get(self.selected_entry?).copied()
+    }
+
+    fn select_entry(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(entry_ix);
+        self.scroll_handle
+            .scroll_to_item(entry_ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.visible_entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => (entry_ix + 1).min(self.visible_entries.len() - 1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.visible_entries.is_empty() {
+            return;
+        }
+        let entry_ix = match self.selected_entry {
+            Some(entry_ix) => entry_ix.saturating_sub(1),
+            None => 0,
+        };
+        self.select_entry(entry_ix, cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
+        if !self.visible_entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, cx: &mut ViewContext<Self>) {
+        if !self.visible_entries.is_empty() {
+            self.select_entry(self.visible_entries.len() - 1, cx);
+        }
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            if self.nodes[node_ix].expanded {
+                self.select_next(&menu::SelectNext, cx);
+            } else {
+                self.expand(node_ix, cx);
+            }
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(node_ix) = self.selected_node() else {
+            return;
+        };
+        if self.nodes[node_ix].expanded {
+            self.collapse(node_ix, cx);
+            return;
+        }
+
+        // Move the selection to the parent entry.
+        let depth = self.nodes[node_ix].depth;
+        let Some(entry_ix) = self.selected_entry else {
+            return;
+        };
+        if let Some(parent_entry_ix) = self.visible_entries[..entry_ix]
+            .iter()
+            .rposition(|node_ix| self.nodes[*node_ix].depth < depth)
+        {
+            self.select_entry(parent_entry_ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.open_node(node_ix, true, cx);
+        }
+    }
+
+    /// Reveals the given node in an editor, selecting its first reference if
+    /// it's known, or the item itself otherwise.
+    fn open_node(&mut self, node_ix: usize, focus: bool, cx: &mut ViewContext<Self>) {
+        let node = &self.nodes[node_ix];
+        let location = node
+            .references
+            .first()
+            .cloned()
+            .unwrap_or_else(|| S::location(&node.item).clone());
+
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let origin_pane = self.origin_pane.upgrade();
+        workspace.update(cx, |workspace, cx| {
+            let pane = origin_pane.unwrap_or_else(|| workspace.adjacent_pane(cx));
+            let editor = workspace.open_project_item::<Editor>(
+                pane,
+                location.buffer.clone(),
+                focus,
+                focus,
+                cx,
+            );
+            editor.update(cx, |editor, cx| {
+                let range = location.range.to_offset(location.buffer.read(cx));
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([range.start..range.start]);
+                });
+            });
+        });
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyView");
+        dispatch_context.add(S::KEY_CONTEXT);
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    fn render_entry(
+        &self,
+        entry_ix: usize,
+        node_ix: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let node = &self.nodes[node_ix];
+        let has_children =
+            !matches!(&node.children, NodeChildren::Resolved(children) if children.is_empty());
+        let path = S::location(&node.item)
+            .buffer
+            .read(cx)
+            .file()
+            .map(|file| file.path().to_string_lossy().to_string());
+        let reference_count = node.references.len();
+
+        ListItem::new(entry_ix)
+            .spacing(ListItemSpacing::Sparse)
+            .indent_level(node.depth)
+            .indent_step_size(px(12.))
+            .toggle_state(self.selected_entry == Some(entry_ix))
+            .toggle(has_children.then_some(node.expanded))
+            .on_toggle(cx.listener(move |this, _, cx| {
+                if this.nodes[node_ix].expanded {
+                    this.collapse(node_ix, cx);
+                } else {
+                    this.expand(node_ix, cx);
+                }
+            }))
+            .on_click(cx.listener(move |this, _, cx| {
+                this.select_entry(entry_ix, cx);
+                this.open_node(node_ix, false, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(S::name(&node.item).to_string()))
+                    .when_some(S::detail(&node.item), |this, detail| {
+                        this.child(
+                            Label::new(detail.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        )
+                    })
+                    .when_some(path, |this, path| {
+                        this.child(Label::new(path).size(LabelSize::Small).color(Color::Muted))
+                    })
+                    .when(reference_count > 1, |this| {
+                        this.child(
+                            Label::new(format!("({reference_count})"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    }),
+            )
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let title = self
+            .roots
+            .first()
+            .map(|root| format!("{}: {}", self.source.label(), S::name(root)))
+            .unwrap_or_else(|| self.source.label().to_string());
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(title))
+            .child(
+                IconButton::new("toggle-direction", IconName::ChevronUpDown)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::for_action("Toggle Direction", &ToggleDirection, cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        this.toggle_direction(&ToggleDirection, cx);
+                    })),
+            )
+    }
+}
+
+impl<S: HierarchySource> Render for HierarchyView<S> {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.visible_entries.is_empty() {
+            let message: SharedString = if self.loading_roots {
+                format!("Loading {}…", S::NAME).into()
+            } else if let Some(error) = &self.error {
+                error.clone()
+            } else {
+                format!("No {} is available at the cursor", S::NAME).into()
+            };
+            div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message).color(Color::Muted))
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "hierarchy-entries",
+                self.visible_entries.len(),
+                |this, range, cx| {
+                    range
+                        .map(|entry_ix| {
+                            let node_ix = this.visible_entries[entry_ix];
+                            this.render_entry(entry_ix, node_ix, cx)
+                        })
+                        .collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .id("hierarchy")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .child(self.render_header(cx))
+            .child(content)
+    }
+}
+
+impl<S: HierarchySource> EventEmitter<ItemEvent> for HierarchyView<S> {}
+
+impl<S: HierarchySource> FocusableView for HierarchyView<S> {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<S: HierarchySource> Item for HierarchyView<S> {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(self.source.label().into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTree))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some(S::TELEMETRY_EVENT_TEXT)
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        let roots = self.roots.clone();
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                self.project.clone(),
+                self.origin_pane.clone(),
+                self.source,
+                Task::ready(Ok(roots)),
+                cx,
+            )
+        }))
+    }
+}
@@ -136,6 +136,15 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code with the semantic tokens reported by
+    /// language servers, on top of the tree-sitter highlights.
+    pub semantic_tokens: bool,
+    /// Whether to fold code using the folding ranges reported by language
+    /// servers, instead of the indentation-based ones.
+    pub lsp_folding_ranges: bool,
+    /// Whether to show the code lenses reported by language servers above
+    /// the items they refer to.
+    pub code_lens: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -380,6 +389,23 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code with the semantic tokens reported by language servers.
+    /// These are layered over the tree-sitter highlights, and let the language server
+    /// distinguish things like mutable variables or macros.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Whether to fold code using the folding ranges reported by language servers.
+    /// When disabled, or when no language server provides them, folds are
+    /// computed from the indentation of the buffer.
+    ///
+    /// Default: false
+    pub lsp_folding_ranges: Option<bool>,
+    /// Whether to show the code lenses reported by language servers, such as
+    /// "Run test" or "3 references", above the items they refer to.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -1166,6 +1192,9 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.lsp_folding_ranges, src.lsp_folding_ranges);
+    merge(&mut settings.code_lens, src.code_lens);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -10,7 +10,13 @@ use client::Client;
 use collections::{hash_map, HashMap, HashSet};
 use fs::Fs;
 use futures::{channel::oneshot, future::Shared, Future, FutureExt as _, StreamExt};
-use git::{blame::Blame, diff::BufferDiff, repository::RepoPath};
+use git::{
+    blame::Blame,
+    diff::BufferDiff,
+    history::{LogEntry, LogOptions},
+    repository::RepoPath,
+    Oid,
+};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Subscription,
     Task, WeakModel,
@@ -902,6 +908,8 @@ impl BufferStore {
         client.add_model_message_handler(Self::handle_update_buffer_file);
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_request_handler(Self::handle_blame_buffer);
+        client.add_model_request_handler(Self::handle_git_file_history);
+        client.add_model_request_handler(Self::handle_load_file_at_revision);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_get_permalink_to_line);
         client.add_model_request_handler(Self::handle_get_staged_text);
@@ -1209,6 +1217,108 @@ impl BufferStore {
         }
     }
 
+    /// Lists the commits that changed the buffer's file, following it across
+    /// renames.
+    pub fn file_history(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<LogEntry>>> {
+        let buffer = buffer.read(cx);
+        let Some(file) = File::from_dyn(buffer.file()) else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+
+        match file.worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let worktree = worktree.snapshot();
+                let params = maybe!({
+                    let Some(local_repo) = worktree.local_repo_for_path(&file.path) else {
+                        return Ok(None);
+                    };
+                    let relative_path = local_repo
+                        .relativize(&file.path)
+                        .context("failed to relativize buffer path")?;
+                    anyhow::Ok(Some((local_repo.repo().clone(), relative_path)))
+                });
+
+                cx.background_executor().spawn(async move {
+                    let Some((repo, relative_path)) = params? else {
+                        return Ok(Vec::new());
+                    };
+                    repo.log(&LogOptions {
+                        paths: vec![relative_path],
+                        follow_renames: true,
+                        ..Default::default()
+                    })
+                })
+            }
+            Worktree::Remote(worktree) => {
+                let buffer_id = buffer.remote_id();
+                let project_id = worktree.project_id();
+                let client = worktree.client();
+                cx.spawn(|_| async move {
+                    let response = client
+                        .request(proto::GitFileHistory {
+                            project_id,
+                            buffer_id: buffer_id.into(),
+                        })
+                        .await?;
+                    response
+                        .entries
+                        .into_iter()
+                        .map(deserialize_log_entry)
+                        .collect()
+                })
+            }
+        }
+    }
+
+    /// Loads the contents of a file of the buffer's repository at the given
+    /// commit, or `None` if it didn't exist at that commit.
+    pub fn load_text_at_revision(
+        &self,
+        buffer: &Model<Buffer>,
+        sha: Oid,
+        path: RepoPath,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        let buffer = buffer.read(cx);
+        let Some(file) = File::from_dyn(buffer.file()) else {
+            return Task::ready(Err(anyhow!("buffer has no file")));
+        };
+
+        match file.worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let Some(repo) = worktree
+                    .snapshot()
+                    .local_repo_for_path(&file.path)
+                    .map(|local_repo| local_repo.repo().clone())
+                else {
+                    return Task::ready(Err(anyhow!("buffer is not in a git repository")));
+                };
+                cx.background_executor()
+                    .spawn(async move { repo.load_text_at_revision(sha, &path) })
+            }
+            Worktree::Remote(worktree) => {
+                let buffer_id = buffer.remote_id();
+                let project_id = worktree.project_id();
+                let client = worktree.client();
+                cx.spawn(|_| async move {
+                    let response = client
+                        .request(proto::LoadFileAtRevision {
+                            project_id,
+                            buffer_id: buffer_id.into(),
+                            sha: sha.as_bytes().into(),
+                            path: path.to_string_lossy().into(),
+                        })
+                        .await?;
+                    Ok(response.text)
+                })
+            }
+        }
+    }
+
     pub fn get_permalink_to_line(
         &self,
         buffer: &Model<Buffer>,
@@ -1928,6 +2038,38 @@ impl BufferStore {
         Ok(serialize_blame_buffer_response(blame))
     }
 
+    pub async fn handle_git_file_history(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitFileHistory>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GitFileHistoryResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.read_with(&cx, |this, _| this.get_existing(buffer_id))??;
+        let entries = this
+            .update(&mut cx, |this, cx| this.file_history(&buffer, cx))?
+            .await?;
+        Ok(proto::GitFileHistoryResponse {
+            entries: entries.into_iter().map(serialize_log_entry).collect(),
+        })
+    }
+
+    pub async fn handle_load_file_at_revision(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::LoadFileAtRevision>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::LoadFileAtRevisionResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let sha = Oid::from_bytes(&envelope.payload.sha)?;
+        let path = RepoPath::from(envelope.payload.path.as_str());
+        let buffer = this.read_with(&cx, |this, _| this.get_existing(buffer_id))??;
+        let text = this
+            .update(&mut cx, |this, cx| {
+                this.load_text_at_revision(&buffer, sha, path, cx)
+            })?
+            .await?;
+        Ok(proto::LoadFileAtRevisionResponse { text })
+    }
+
     pub async fn handle_get_permalink_to_line(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::GetPermalinkToLine>,
@@ -2468,6 +2610,48 @@ fn deserialize_blame_buffer_response(
     })
 }
 
+fn serialize_log_entry(entry: LogEntry) -> proto::GitLogEntry {
+    proto::GitLogEntry {
+        sha: entry.sha.as_bytes().into(),
+        parents: entry
+            .parents
+            .iter()
+            .map(|parent| parent.as_bytes().into())
+            .collect(),
+        author_name: entry.author_name.to_string(),
+        author_email: entry.author_email.to_string(),
+        timestamp: entry.timestamp,
+        refs: entry.refs.iter().map(|name| name.to_string()).collect(),
+        subject: entry.subject.to_string(),
+        paths: entry
+            .paths
+            .iter()
+            .map(|path| path.to_string_lossy().into())
+            .collect(),
+    }
+}
+
+fn deserialize_log_entry(entry: proto::GitLogEntry) -> Result<LogEntry> {
+    Ok(LogEntry {
+        sha: Oid::from_bytes(&entry.sha)?,
+        parents: entry
+            .parents
+            .iter()
+            .map(|parent| Oid::from_bytes(parent))
+            .collect::<Result<_>>()?,
+        author_name: entry.author_name.into(),
+        author_email: entry.author_email.into(),
+        timestamp: entry.timestamp,
+        refs: entry.refs.into_iter().map(Into::into).collect(),
+        subject: entry.subject.into(),
+        paths: entry
+            .paths
+            .iter()
+            .map(|path| RepoPath::from(path.as_str()))
+            .collect(),
+    })
+}
+
 fn get_permalink_in_rust_registry_src(
     provider_registry: Arc<GitHostingProviderRegistry>,
     path: PathBuf,
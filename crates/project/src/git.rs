@@ -1,28 +1,33 @@
 use crate::worktree_store::{WorktreeStore, WorktreeStoreEvent};
 use crate::{Project, ProjectPath};
-use anyhow::anyhow;
-use futures::channel::mpsc;
+use anyhow::{anyhow, Context as _};
+use futures::channel::{mpsc, oneshot};
 use futures::{SinkExt as _, StreamExt as _};
 use git::{
-    repository::{GitRepository, RepoPath},
+    history::{CommitDetails, LogEntry, LogOptions},
+    remote_command::RemoteCommandDelegate,
+    repository::{GitRepository, RepoPath, StashEntry, UpstreamTracking},
     status::{GitSummary, TrackedSummary},
+    Oid,
 };
 use gpui::{
-    AppContext, Context as _, EventEmitter, Model, ModelContext, SharedString, Subscription,
-    WeakModel,
+    AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, SharedString,
+    Subscription, Task, WeakModel,
 };
 use language::{Buffer, LanguageRegistry};
+use rpc::{proto, AnyProtoClient};
 use settings::WorktreeId;
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 use text::Rope;
 use util::maybe;
-use worktree::{RepositoryEntry, StatusEntry};
+use worktree::{RepositoryEntry, StatusEntry, Worktree};
 
 pub struct GitState {
     repositories: Vec<RepositoryHandle>,
     active_index: Option<usize>,
     update_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
     languages: Arc<LanguageRegistry>,
+    remote_operation: Option<RemoteOperationStatus>,
     _subscription: Subscription,
 }
 
@@ -31,7 +36,7 @@ pub struct RepositoryHandle {
     git_state: WeakModel<GitState>,
     worktree_id: WorktreeId,
     repository_entry: RepositoryEntry,
-    git_repo: Arc<dyn GitRepository>,
+    git_repo: GitRepo,
     commit_message: Model<Buffer>,
     update_sender: mpsc::UnboundedSender<(Message, mpsc::Sender<anyhow::Error>)>,
 }
@@ -52,17 +57,93 @@ impl PartialEq<RepositoryEntry> for RepositoryHandle {
     }
 }
 
+/// The repository a handle operates on, which belongs to the host of the
+/// project when the worktree is remote.
+#[derive(Clone)]
+enum GitRepo {
+    Local(Arc<dyn GitRepository>),
+    Remote {
+        project_id: u64,
+        client: AnyProtoClient,
+        worktree_id: WorktreeId,
+        work_directory: Arc<Path>,
+    },
+}
+
 enum Message {
-    StageAndCommit(Arc<dyn GitRepository>, Rope, Vec<RepoPath>),
-    Commit(Arc<dyn GitRepository>, Rope),
-    Stage(Arc<dyn GitRepository>, Vec<RepoPath>),
-    Unstage(Arc<dyn GitRepository>, Vec<RepoPath>),
+    StageAndCommit(GitRepo, Rope, Vec<RepoPath>),
+    Commit(GitRepo, Rope),
+    Amend(GitRepo, Option<Rope>),
+    Stage(GitRepo, Vec<RepoPath>),
+    Unstage(GitRepo, Vec<RepoPath>),
+    SetIndexText(GitRepo, RepoPath, Option<String>),
+    StashPush(GitRepo, Option<String>),
+    StashPop(GitRepo, usize),
 }
 
 pub enum Event {
     RepositoriesUpdated,
 }
 
+/// A git command that exchanges commits with the remotes of a repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteOperation {
+    Fetch,
+    Pull {
+        rebase: bool,
+    },
+    /// Pushes the branch to its upstream, or to the default remote when
+    /// `set_upstream` is true, making it the upstream of the branch.
+    Push {
+        set_upstream: bool,
+    },
+}
+
+impl RemoteOperation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Fetch => "Fetching",
+            Self::Pull { .. } => "Pulling",
+            Self::Push { .. } => "Pushing",
+        }
+    }
+}
+
+/// The remote operation that's running, along with the last line of progress
+/// git printed for it.
+#[derive(Clone, Debug)]
+pub struct RemoteOperationStatus {
+    pub operation: RemoteOperation,
+    pub progress: Option<SharedString>,
+}
+
+/// Forwards the progress and credential prompts of a git command, which runs
+/// on a background thread, to the main thread.
+struct RemoteOperationDelegate {
+    events: mpsc::UnboundedSender<RemoteOperationEvent>,
+}
+
+enum RemoteOperationEvent {
+    Progress(String),
+    AskPassword(String, oneshot::Sender<anyhow::Result<String>>),
+}
+
+impl RemoteCommandDelegate for RemoteOperationDelegate {
+    fn progress(&self, message: &str) {
+        self.events
+            .unbounded_send(RemoteOperationEvent::Progress(message.to_string()))
+            .ok();
+    }
+
+    fn ask_password(&self, prompt: String) -> oneshot::Receiver<anyhow::Result<String>> {
+        let (tx, rx) = oneshot::channel();
+        self.events
+            .unbounded_send(RemoteOperationEvent::AskPassword(prompt, tx))
+            .ok();
+        rx
+    }
+}
+
 impl EventEmitter<Event> for GitState {}
 
 impl GitState {
@@ -75,21 +156,7 @@ impl GitState {
             mpsc::unbounded::<(Message, mpsc::Sender<anyhow::Error>)>();
         cx.spawn(|_, cx| async move {
             while let Some((msg, mut err_sender)) = update_receiver.next().await {
-                let result = cx
-                    .background_executor()
-                    .spawn(async move {
-                        match msg {
-                            Message::StageAndCommit(repo, message, paths) => {
-                                repo.stage_paths(&paths)?;
-                                repo.commit(&message.to_string())?;
-                                Ok(())
-                            }
-                            Message::Stage(repo, paths) => repo.stage_paths(&paths),
-                            Message::Unstage(repo, paths) => repo.unstage_paths(&paths),
-                            Message::Commit(repo, message) => repo.commit(&message.to_string()),
-                        }
-                    })
-                    .await;
+                let result = Self::process_message(msg, &cx).await;
                 if let Err(e) = result {
                     err_sender.send(e).await.ok();
                 }
@@ -104,10 +171,37 @@ impl GitState {
             repositories: vec![],
             active_index: None,
             update_sender,
+            remote_operation: None,
             _subscription,
         }
     }
 
+    /// Returns the remote operation that's running, if any.
+    pub fn remote_operation(&self) -> Option<&RemoteOperationStatus> {
+        self.remote_operation.as_ref()
+    }
+
+    async fn process_message(msg: Message, cx: &AsyncAppContext) -> anyhow::Result<()> {
+        match msg {
+            Message::StageAndCommit(repo, message, paths) => {
+                repo.stage(paths, cx).await?;
+                repo.commit(Some(message.to_string()), false, cx).await
+            }
+            Message::Commit(repo, message) => {
+                repo.commit(Some(message.to_string()), false, cx).await
+            }
+            Message::Amend(repo, message) => {
+                repo.commit(message.map(|message| message.to_string()), true, cx)
+                    .await
+            }
+            Message::Stage(repo, paths) => repo.stage(paths, cx).await,
+            Message::Unstage(repo, paths) => repo.unstage(paths, cx).await,
+            Message::SetIndexText(repo, path, text) => repo.set_index_text(path, text, cx).await,
+            Message::StashPush(repo, message) => repo.stash_push(message, cx).await,
+            Message::StashPop(repo, index) => repo.stash_pop(index, cx).await,
+        }
+    }
+
     pub fn active_repository(&self) -> Option<RepositoryHandle> {
         self.active_index
             .map(|index| self.repositories[index].clone())
@@ -129,12 +223,20 @@ impl GitState {
             for worktree in worktree_store.worktrees() {
                 worktree.update(cx, |worktree, cx| {
                     let snapshot = worktree.snapshot();
-                    let Some(local) = worktree.as_local() else {
-                        return;
-                    };
                     for repo in snapshot.repositories().iter() {
-                        let Some(local_repo) = local.get_local_repo(repo) else {
-                            continue;
+                        let git_repo = match worktree {
+                            Worktree::Local(local) => {
+                                let Some(local_repo) = local.get_local_repo(repo) else {
+                                    continue;
+                                };
+                                GitRepo::Local(local_repo.repo().clone())
+                            }
+                            Worktree::Remote(remote) => GitRepo::Remote {
+                                project_id: remote.project_id(),
+                                client: remote.client(),
+                                worktree_id: snapshot.id(),
+                                work_directory: Arc::from(&*repo.work_directory),
+                            },
                         };
                         let existing = self
                             .repositories
@@ -167,7 +269,7 @@ impl GitState {
                                 git_state: this.clone(),
                                 worktree_id: worktree.id(),
                                 repository_entry: repo.clone(),
-                                git_repo: local_repo.repo().clone(),
+                                git_repo,
                                 commit_message,
                                 update_sender: self.update_sender.clone(),
                             }
@@ -191,6 +293,278 @@ impl GitState {
     pub fn all_repositories(&self) -> Vec<RepositoryHandle> {
         self.repositories.clone()
     }
+
+    /// Returns the innermost repository containing the given path, along with
+    /// the path relative to the repository's work directory.
+    pub fn repository_and_path_for_project_path(
+        &self,
+        path: &ProjectPath,
+    ) -> Option<(RepositoryHandle, RepoPath)> {
+        self.repositories
+            .iter()
+            .filter(|handle| {
+                handle.worktree_id == path.worktree_id
+                    && handle.repository_entry.directory_contains(&path.path)
+            })
+            .max_by_key(|handle| handle.repository_entry.work_directory.components().count())
+            .and_then(|handle| {
+                let repo_path = handle.repository_entry.relativize(&path.path).ok()?;
+                Some((handle.clone(), repo_path))
+            })
+    }
+}
+
+impl GitRepo {
+    fn repository_proto(worktree_id: WorktreeId, work_directory: &Path) -> proto::ProjectPath {
+        proto::ProjectPath {
+            worktree_id: worktree_id.to_proto(),
+            path: work_directory.to_string_lossy().to_string(),
+        }
+    }
+
+    async fn stage(&self, paths: Vec<RepoPath>, cx: &AsyncAppContext) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move { repo.stage_paths(&paths) })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitStage {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        paths: paths.iter().map(RepoPath::to_proto).collect(),
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn unstage(&self, paths: Vec<RepoPath>, cx: &AsyncAppContext) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move { repo.unstage_paths(&paths) })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitUnstage {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        paths: paths.iter().map(RepoPath::to_proto).collect(),
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Commits the staged changes, or amends HEAD with them. Amending without
+    /// a message keeps the message of HEAD.
+    async fn commit(
+        &self,
+        message: Option<String>,
+        amend: bool,
+        cx: &AsyncAppContext,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move {
+                        if amend {
+                            repo.amend_commit(message.as_deref())
+                        } else {
+                            repo.commit(&message.context("missing commit message")?)
+                        }
+                    })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitCommit {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        message,
+                        amend,
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn set_index_text(
+        &self,
+        path: RepoPath,
+        text: Option<String>,
+        cx: &AsyncAppContext,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move { repo.set_index_text(&path, text) })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitSetIndexText {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        path: path.to_proto(),
+                        text,
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn stash_push(
+        &self,
+        message: Option<String>,
+        cx: &AsyncAppContext,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move { repo.stash_push(message.as_deref()) })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitStashPush {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        message,
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn stash_pop(&self, index: usize, cx: &AsyncAppContext) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move { repo.stash_pop(index) })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                client
+                    .request(proto::GitStashPop {
+                        project_id: *project_id,
+                        repository: Some(Self::repository_proto(*worktree_id, work_directory)),
+                        index: index as u64,
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl GitRepo {
+    async fn run_remote_operation(
+        &self,
+        operation: RemoteOperation,
+        delegate: RemoteOperationDelegate,
+        cx: &AsyncAppContext,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Local(repo) => {
+                let repo = repo.clone();
+                cx.background_executor()
+                    .spawn(async move {
+                        match operation {
+                            RemoteOperation::Fetch => repo.fetch(&delegate),
+                            RemoteOperation::Pull { rebase } => repo.pull(rebase, &delegate),
+                            RemoteOperation::Push { set_upstream } => {
+                                repo.push(set_upstream, &delegate)
+                            }
+                        }
+                    })
+                    .await
+            }
+            Self::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => {
+                let project_id = *project_id;
+                let repository = Some(Self::repository_proto(*worktree_id, work_directory));
+                match operation {
+                    RemoteOperation::Fetch => {
+                        client
+                            .request(proto::GitFetch {
+                                project_id,
+                                repository,
+                            })
+                            .await?;
+                    }
+                    RemoteOperation::Pull { rebase } => {
+                        client
+                            .request(proto::GitPull {
+                                project_id,
+                                repository,
+                                rebase,
+                            })
+                            .await?;
+                    }
+                    RemoteOperation::Push { set_upstream } => {
+                        client
+                            .request(proto::GitPush {
+                                project_id,
+                                repository,
+                                set_upstream,
+                            })
+                            .await?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 impl RepositoryHandle {
@@ -231,6 +605,93 @@ impl RepositoryHandle {
         self.repository_entry.status()
     }
 
+    pub fn status_for_path(&self, path: &RepoPath) -> Option<StatusEntry> {
+        self.repository_entry.status_for_path(path)
+    }
+
+    pub fn branch(&self) -> Option<Arc<str>> {
+        self.repository_entry.branch()
+    }
+
+    pub fn upstream_tracking(&self) -> Option<UpstreamTracking> {
+        self.repository_entry.upstream_tracking()
+    }
+
+    /// Fetches, pulls or pushes the repository, reporting its progress in the
+    /// state of the project's [`GitState`]. Credentials that git prompts for
+    /// are asked from the user with `ask_password`.
+    ///
+    /// Only one remote operation can run at a time.
+    pub fn run_remote_operation(
+        &self,
+        operation: RemoteOperation,
+        ask_password: impl Fn(String, &mut AppContext) -> oneshot::Receiver<anyhow::Result<String>>
+            + 'static,
+        cx: &mut AppContext,
+    ) -> Task<anyhow::Result<()>> {
+        let Some(git_state) = self.git_state.upgrade() else {
+            return Task::ready(Err(anyhow!("git state was dropped")));
+        };
+        let git_repo = self.git_repo.clone();
+        git_state.update(cx, |git_state, cx| {
+            if let Some(status) = &git_state.remote_operation {
+                return Task::ready(Err(anyhow!(
+                    "{} is already in progress",
+                    status.operation.label()
+                )));
+            }
+            git_state.remote_operation = Some(RemoteOperationStatus {
+                operation,
+                progress: None,
+            });
+            cx.notify();
+
+            cx.spawn(|this, mut cx| async move {
+                let (events_tx, mut events_rx) = mpsc::unbounded();
+                let delegate = RemoteOperationDelegate { events: events_tx };
+                let run = {
+                    let cx = cx.clone();
+                    async move {
+                        git_repo
+                            .run_remote_operation(operation, delegate, &cx)
+                            .await
+                    }
+                };
+                // The events stop once the operation finishes and drops the delegate.
+                let handle_events = async {
+                    while let Some(event) = events_rx.next().await {
+                        match event {
+                            RemoteOperationEvent::Progress(message) => {
+                                this.update(&mut cx, |git_state, cx| {
+                                    if let Some(status) = &mut git_state.remote_operation {
+                                        status.progress = Some(message.into());
+                                        cx.notify();
+                                    }
+                                })
+                                .ok();
+                            }
+                            RemoteOperationEvent::AskPassword(prompt, answer_tx) => {
+                                let Ok(answer) = cx.update(|cx| ask_password(prompt, cx)) else {
+                                    continue;
+                                };
+                                if let Ok(answer) = answer.await {
+                                    answer_tx.send(answer).ok();
+                                }
+                            }
+                        }
+                    }
+                };
+                let (result, ()) = futures::join!(run, handle_events);
+
+                this.update(&mut cx, |git_state, cx| {
+                    git_state.remote_operation = None;
+                    cx.notify();
+                })?;
+                result
+            })
+        })
+    }
+
     pub fn unrelativize(&self, path: &RepoPath) -> Option<ProjectPath> {
         let path = self.repository_entry.unrelativize(path)?;
         Some((self.worktree_id, path).into())
@@ -240,6 +701,102 @@ impl RepositoryHandle {
         self.commit_message.clone()
     }
 
+    /// Lists the commits of the repository matching the given options.
+    pub fn log(&self, options: LogOptions, cx: &AppContext) -> Task<anyhow::Result<Vec<LogEntry>>> {
+        let GitRepo::Local(git_repo) = self.git_repo.clone() else {
+            return Task::ready(Err(anyhow!(
+                "the log of remote repositories is not supported"
+            )));
+        };
+        cx.background_executor()
+            .spawn(async move { git_repo.log(&options) })
+    }
+
+    /// Loads the message of a commit and the files it changed.
+    pub fn commit_details(&self, sha: Oid, cx: &AppContext) -> Task<anyhow::Result<CommitDetails>> {
+        let GitRepo::Local(git_repo) = self.git_repo.clone() else {
+            return Task::ready(Err(anyhow!(
+                "the commits of remote repositories are not supported"
+            )));
+        };
+        cx.background_executor()
+            .spawn(async move { git_repo.commit_details(sha) })
+    }
+
+    /// Lists the stash entries of the repository, most recent first.
+    pub fn stash_list(&self, cx: &AppContext) -> Task<anyhow::Result<Vec<StashEntry>>> {
+        match self.git_repo.clone() {
+            GitRepo::Local(git_repo) => cx
+                .background_executor()
+                .spawn(async move { git_repo.stash_list() }),
+            GitRepo::Remote {
+                project_id,
+                client,
+                worktree_id,
+                work_directory,
+            } => cx.background_executor().spawn(async move {
+                let response = client
+                    .request(proto::GitStashList {
+                        project_id,
+                        repository: Some(GitRepo::repository_proto(worktree_id, &work_directory)),
+                    })
+                    .await?;
+                Ok(response
+                    .entries
+                    .into_iter()
+                    .map(|entry| StashEntry {
+                        index: entry.index as usize,
+                        message: entry.message.into(),
+                    })
+                    .collect())
+            }),
+        }
+    }
+
+    /// Saves the local changes of the repository to a new stash entry.
+    pub fn stash_push(
+        &self,
+        message: Option<String>,
+        err_sender: mpsc::Sender<anyhow::Error>,
+    ) -> anyhow::Result<()> {
+        self.update_sender
+            .unbounded_send((
+                Message::StashPush(self.git_repo.clone(), message),
+                err_sender,
+            ))
+            .map_err(|_| anyhow!("Failed to submit stash operation"))?;
+        Ok(())
+    }
+
+    /// Applies the stash entry at the given index and drops it from the stash.
+    pub fn stash_pop(
+        &self,
+        index: usize,
+        err_sender: mpsc::Sender<anyhow::Error>,
+    ) -> anyhow::Result<()> {
+        self.update_sender
+            .unbounded_send((Message::StashPop(self.git_repo.clone(), index), err_sender))
+            .map_err(|_| anyhow!("Failed to submit stash pop operation"))?;
+        Ok(())
+    }
+
+    /// Replaces the contents of a path in the index, which is how individual
+    /// hunks are staged.
+    pub fn set_index_text(
+        &self,
+        path: RepoPath,
+        text: Option<String>,
+        err_sender: mpsc::Sender<anyhow::Error>,
+    ) -> anyhow::Result<()> {
+        self.update_sender
+            .unbounded_send((
+                Message::SetIndexText(self.git_repo.clone(), path, text),
+                err_sender,
+            ))
+            .map_err(|_| anyhow!("Failed to submit index update"))?;
+        Ok(())
+    }
+
     pub fn stage_entries(
         &self,
         entries: Vec<RepoPath>,
@@ -314,6 +871,15 @@ impl RepositoryHandle {
             && (commit_all || self.have_staged_changes());
     }
 
+    pub fn can_amend(&self, cx: &AppContext) -> bool {
+        self.have_staged_changes()
+            || self
+                .commit_message
+                .read(cx)
+                .chars()
+                .any(|c| !c.is_ascii_whitespace())
+    }
+
     pub fn commit(&self, mut err_sender: mpsc::Sender<anyhow::Error>, cx: &mut AppContext) {
         let message = self.commit_message.read(cx).as_rope().clone();
         let result = self.update_sender.unbounded_send((
@@ -335,6 +901,33 @@ impl RepositoryHandle {
         });
     }
 
+    /// Amends HEAD with the staged changes, using the commit message if it
+    /// isn't blank and keeping the message of HEAD otherwise.
+    pub fn amend(&self, mut err_sender: mpsc::Sender<anyhow::Error>, cx: &mut AppContext) {
+        let message = self.commit_message.read(cx).as_rope().clone();
+        let message = message
+            .chars()
+            .any(|c| !c.is_ascii_whitespace())
+            .then_some(message);
+        let result = self.update_sender.unbounded_send((
+            Message::Amend(self.git_repo.clone(), message),
+            err_sender.clone(),
+        ));
+        if result.is_err() {
+            cx.spawn(|_| async move {
+                err_sender
+                    .send(anyhow!("Failed to submit amend operation"))
+                    .await
+                    .ok();
+            })
+            .detach();
+            return;
+        }
+        self.commit_message.update(cx, |commit_message, cx| {
+            commit_message.set_text("", cx);
+        });
+    }
+
     pub fn commit_all(&self, mut err_sender: mpsc::Sender<anyhow::Error>, cx: &mut AppContext) {
         let to_stage = self
             .repository_entry
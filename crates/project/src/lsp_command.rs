@@ -2,9 +2,10 @@ mod signature_help;
 
 use crate::{
     lsp_store::{LocalLspStore, LspStore},
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, PrepareRenameResponse, ProjectTransaction, ResolveState,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, CoreCompletion, DocumentHighlight,
+    DocumentLink, FoldingRange, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
+    InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink,
+    MarkupContent, PrepareRenameResponse, ProjectTransaction, ResolveState, TypeHierarchyItem,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -18,7 +19,7 @@ use language::{
     point_from_lsp, point_to_lsp,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     AdapterServerCapabilities, CodeActionKind, CodeActionOptions, CompletionContext,
@@ -240,6 +241,75 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+#[derive(Debug)]
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetIncomingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetOutgoingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSupertypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSubtypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSemanticTokensFull;
+
+#[derive(Debug)]
+pub(crate) struct GetSemanticTokensDelta {
+    pub previous_result_id: String,
+}
+
+/// Semantic tokens as reported by a language server, before they're decoded
+/// against the buffer they were requested for.
+#[derive(Debug, Default)]
+pub(crate) struct SemanticTokensResponse {
+    pub result_id: Option<String>,
+    pub legend: lsp::SemanticTokensLegend,
+    /// `None` if the server didn't report any tokens, in which case they
+    /// should be requested again in full.
+    pub data: Option<SemanticTokensData>,
+}
+
+#[derive(Debug)]
+pub(crate) enum SemanticTokensData {
+    Full(Vec<lsp::SemanticToken>),
+    Delta(Vec<lsp::SemanticTokensEdit>),
+}
+
+#[derive(Debug)]
+pub(crate) struct GetFoldingRanges;
+
+#[derive(Debug)]
+pub(crate) struct GetCodeLens;
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+
+#[derive(Debug)]
+pub(crate) struct GetSelectionRanges {
+    pub positions: Vec<Anchor>,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = PrepareRenameResponse;
@@ -3180,3 +3250,1951 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+async fn location_from_lsp(
+    uri: lsp::Url,
+    range: lsp::Range,
+    lsp_store: &Model<LspStore>,
+    lsp_adapter: &Arc<CachedLspAdapter>,
+    language_server: &Arc<LanguageServer>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let target_buffer_handle = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.open_local_buffer_via_lsp(
+                uri,
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+
+    target_buffer_handle
+        .clone()
+        .update(cx, |target_buffer, _| Location {
+            range: anchor_range_from_lsp(target_buffer, range),
+            buffer: target_buffer_handle,
+        })
+}
+
+fn anchor_range_from_lsp(buffer: &Buffer, range: lsp::Range) -> Range<Anchor> {
+    let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+    let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+    buffer.anchor_after(start)..buffer.anchor_before(end)
+}
+
+fn location_to_proto(
+    location: &Location,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::Location {
+    lsp_store
+        .buffer_store()
+        .update(cx, |buffer_store, cx| {
+            buffer_store.create_buffer_for_peer(&location.buffer, peer_id, cx)
+        })
+        .detach_and_log_err(cx);
+    proto::Location {
+        start: Some(serialize_anchor(&location.range.start)),
+        end: Some(serialize_anchor(&location.range.end)),
+        buffer_id: location.buffer.read(cx).remote_id().into(),
+    }
+}
+
+async fn location_from_proto(
+    location: proto::Location,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location end"))?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
+
+async fn call_hierarchy_item_from_lsp(
+    lsp_item: lsp::CallHierarchyItem,
+    lsp_store: &Model<LspStore>,
+    lsp_adapter: &Arc<CachedLspAdapter>,
+    language_server: &Arc<LanguageServer>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let location = location_from_lsp(
+        lsp_item.uri.clone(),
+        lsp_item.selection_range,
+        lsp_store,
+        lsp_adapter,
+        language_server,
+        cx,
+    )
+    .await?;
+    Ok(CallHierarchyItem {
+        server_id: language_server.server_id(),
+        location,
+        lsp_item,
+    })
+}
+
+fn call_hierarchy_item_to_proto(
+    item: &CallHierarchyItem,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::CallHierarchyItem {
+    proto::CallHierarchyItem {
+        server_id: item.server_id.0 as u64,
+        location: Some(location_to_proto(&item.location, lsp_store, peer_id, cx)),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+async fn call_hierarchy_item_from_proto(
+    item: proto::CallHierarchyItem,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let location = item
+        .location
+        .ok_or_else(|| anyhow!("missing call hierarchy item location"))?;
+    Ok(CallHierarchyItem {
+        server_id: LanguageServerId(item.server_id as usize),
+        location: location_from_proto(location, lsp_store, cx).await?,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
+
+fn call_hierarchy_calls_to_proto(
+    calls: Vec<CallHierarchyCall>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::CallHierarchyCall> {
+    calls
+        .into_iter()
+        .map(|call| proto::CallHierarchyCall {
+            item: Some(call_hierarchy_item_to_proto(
+                &call.item, lsp_store, peer_id, cx,
+            )),
+            call_sites: call
+                .call_sites
+                .iter()
+                .map(|location| location_to_proto(location, lsp_store, peer_id, cx))
+                .collect(),
+        })
+        .collect()
+}
+
+async fn call_hierarchy_calls_from_proto(
+    calls: Vec<proto::CallHierarchyCall>,
+    lsp_store: Model<LspStore>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyCall>> {
+    let mut result = Vec::with_capacity(calls.len());
+    for call in calls {
+        let item = call
+            .item
+            .ok_or_else(|| anyhow!("missing call hierarchy item"))?;
+        let item = call_hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?;
+        let mut call_sites = Vec::with_capacity(call.call_sites.len());
+        for call_site in call.call_sites {
+            call_sites.push(location_from_proto(call_site, &lsp_store, &mut cx).await?);
+        }
+        result.push(CallHierarchyCall { item, call_sites });
+    }
+    Ok(result)
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare call hierarchy"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            &capabilities.server_capabilities.call_hierarchy_provider,
+            None | Some(lsp::CallHierarchyServerCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::CallHierarchyPrepareParams> {
+        Ok(lsp::CallHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut items = Vec::new();
+        for lsp_item in message.unwrap_or_default() {
+            items.push(
+                call_hierarchy_item_from_lsp(
+                    lsp_item,
+                    &lsp_store,
+                    &lsp_adapter,
+                    &language_server,
+                    &mut cx,
+                )
+                .await?,
+            );
+        }
+        Ok(items)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: response
+                .iter()
+                .map(|item| call_hierarchy_item_to_proto(item, lsp_store, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let mut items = Vec::with_capacity(message.items.len());
+        for item in message.items {
+            items.push(call_hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn display_name(&self) -> &str {
+        "Get incoming calls"
+    }
+
+    fn status(&self) -> Option<String> {
+        Some("Finding incoming calls...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::CallHierarchyIncomingCallsParams> {
+        Ok(lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item = call_hierarchy_item_from_lsp(
+                call.from,
+                &lsp_store,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            // Incoming call sites are relative to the caller.
+            let caller_buffer = item.location.buffer.clone();
+            let call_sites: Vec<Location> = caller_buffer.update(&mut cx, |caller, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: item.location.buffer.clone(),
+                        range: anchor_range_from_lsp(caller, range),
+                    })
+                    .collect()
+            })?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetIncomingCallsResponse {
+        proto::GetIncomingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetIncomingCallsResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn display_name(&self) -> &str {
+        "Get outgoing calls"
+    }
+
+    fn status(&self) -> Option<String> {
+        Some("Finding outgoing calls...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::CallHierarchyOutgoingCallsParams> {
+        Ok(lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, language_server) =
+            language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item = call_hierarchy_item_from_lsp(
+                call.to,
+                &lsp_store,
+                &lsp_adapter,
+                &language_server,
+                &mut cx,
+            )
+            .await?;
+            // Outgoing call sites are relative to the item that was queried, which
+            // lives in the buffer the request was made for.
+            let call_sites: Vec<Location> = buffer.update(&mut cx, |caller, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: buffer.clone(),
+                        range: anchor_range_from_lsp(caller, range),
+                    })
+                    .collect()
+            })?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetOutgoingCallsResponse {
+        proto::GetOutgoingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetOutgoingCallsResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+async fn type_hierarchy_items_from_lsp(
+    lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+    lsp_store: Model<LspStore>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+    let mut items = Vec::new();
+    for lsp_item in lsp_items.unwrap_or_default() {
+        let location = location_from_lsp(
+            lsp_item.uri.clone(),
+            lsp_item.selection_range,
+            &lsp_store,
+            &lsp_adapter,
+            &language_server,
+            &mut cx,
+        )
+        .await?;
+        items.push(TypeHierarchyItem {
+            server_id: language_server.server_id(),
+            location,
+            lsp_item,
+        });
+    }
+    Ok(items)
+}
+
+fn type_hierarchy_items_to_proto(
+    items: Vec<TypeHierarchyItem>,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::TypeHierarchyItem> {
+    items
+        .iter()
+        .map(|item| proto::TypeHierarchyItem {
+            server_id: item.server_id.0 as u64,
+            location: Some(location_to_proto(&item.location, lsp_store, peer_id, cx)),
+            lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+        })
+        .collect()
+}
+
+async fn type_hierarchy_items_from_proto(
+    items: Vec<proto::TypeHierarchyItem>,
+    lsp_store: Model<LspStore>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let mut result = Vec::with_capacity(items.len());
+    for item in items {
+        let location = item
+            .location
+            .ok_or_else(|| anyhow!("missing type hierarchy item location"))?;
+        result.push(TypeHierarchyItem {
+            server_id: LanguageServerId(item.server_id as usize),
+            location: location_from_proto(location, &lsp_store, &mut cx).await?,
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        });
+    }
+    Ok(result)
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn display_name(&self) -> &str {
+        "Prepare type hierarchy"
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::TypeHierarchyPrepareParams> {
+        Ok(lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: make_lsp_text_document_position(path, self.position)?,
+            work_done_progress_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, lsp_store, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn display_name(&self) -> &str {
+        "Get supertypes"
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::TypeHierarchySupertypesParams> {
+        Ok(lsp::TypeHierarchySupertypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, lsp_store, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSupertypesResponse {
+        proto::GetSupertypesResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSupertypesResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn display_name(&self) -> &str {
+        "Get subtypes"
+    }
+
+    fn status(&self) -> Option<String> {
+        Some("Finding subtypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::TypeHierarchySubtypesParams> {
+        Ok(lsp::TypeHierarchySubtypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(message, lsp_store, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSubtypesResponse {
+        proto::GetSubtypesResponse {
+            items: type_hierarchy_items_to_proto(response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSubtypesResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn semantic_tokens_options(
+    capabilities: &ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+fn semantic_tokens_legend(
+    lsp_store: &Model<LspStore>,
+    server_id: LanguageServerId,
+    cx: &AsyncAppContext,
+) -> Result<lsp::SemanticTokensLegend> {
+    let language_server = lsp_store
+        .read_with(cx, |lsp_store, _| {
+            lsp_store.language_server_for_id(server_id)
+        })?
+        .ok_or_else(|| anyhow!("no language server with id {server_id}"))?;
+    Ok(semantic_tokens_options(&language_server.capabilities())
+        .map(|options| options.legend.clone())
+        .unwrap_or_default())
+}
+
+fn semantic_tokens_to_proto(tokens: &[lsp::SemanticToken]) -> Vec<u32> {
+    tokens
+        .iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+fn semantic_tokens_from_proto(data: &[u32]) -> Result<Vec<lsp::SemanticToken>> {
+    let chunks = data.chunks_exact(5);
+    if !chunks.remainder().is_empty() {
+        return Err(anyhow!("semantic token data length is not divisible by 5"));
+    }
+    Ok(chunks
+        .map(|chunk| lsp::SemanticToken {
+            delta_line: chunk[0],
+            delta_start: chunk[1],
+            length: chunk[2],
+            token_type: chunk[3],
+            token_modifiers_bitset: chunk[4],
+        })
+        .collect())
+}
+
+fn semantic_tokens_response_to_proto(
+    response: SemanticTokensResponse,
+) -> proto::SemanticTokensResponse {
+    proto::SemanticTokensResponse {
+        result_id: response.result_id,
+        token_types: response
+            .legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect(),
+        token_modifiers: response
+            .legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect(),
+        data: response.data.map(|data| match data {
+            SemanticTokensData::Full(tokens) => {
+                proto::semantic_tokens_response::Data::Tokens(proto::SemanticTokens {
+                    data: semantic_tokens_to_proto(&tokens),
+                })
+            }
+            SemanticTokensData::Delta(edits) => {
+                proto::semantic_tokens_response::Data::Edits(proto::SemanticTokensEdits {
+                    edits: edits
+                        .into_iter()
+                        .map(|edit| proto::SemanticTokensEdit {
+                            start: edit.start,
+                            delete_count: edit.delete_count,
+                            data: semantic_tokens_to_proto(&edit.data.unwrap_or_default()),
+                        })
+                        .collect(),
+                })
+            }
+        }),
+    }
+}
+
+fn semantic_tokens_response_from_proto(
+    message: proto::SemanticTokensResponse,
+) -> Result<SemanticTokensResponse> {
+    let data = match message.data {
+        Some(proto::semantic_tokens_response::Data::Tokens(tokens)) => Some(
+            SemanticTokensData::Full(semantic_tokens_from_proto(&tokens.data)?),
+        ),
+        Some(proto::semantic_tokens_response::Data::Edits(edits)) => {
+            Some(SemanticTokensData::Delta(
+                edits
+                    .edits
+                    .into_iter()
+                    .map(|edit| {
+                        Ok(lsp::SemanticTokensEdit {
+                            start: edit.start,
+                            delete_count: edit.delete_count,
+                            data: Some(semantic_tokens_from_proto(&edit.data)?),
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ))
+        }
+        None => None,
+    };
+    Ok(SemanticTokensResponse {
+        result_id: message.result_id,
+        legend: lsp::SemanticTokensLegend {
+            token_types: message
+                .token_types
+                .into_iter()
+                .map(lsp::SemanticTokenType::from)
+                .collect(),
+            token_modifiers: message
+                .token_modifiers
+                .into_iter()
+                .map(lsp::SemanticTokenModifier::from)
+                .collect(),
+        },
+        data,
+    })
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokensFull {
+    type Response = SemanticTokensResponse;
+    type LspRequest = lsp::request::SemanticTokensFullRequest;
+    type ProtoRequest = proto::GetSemanticTokensFull;
+
+    fn display_name(&self) -> &str {
+        "Get semantic tokens"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        semantic_tokens_options(&capabilities.server_capabilities).map_or(false, |options| {
+            !matches!(
+                options.full,
+                None | Some(lsp::SemanticTokensFullOptions::Bool(false))
+            )
+        })
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::SemanticTokensParams> {
+        Ok(lsp::SemanticTokensParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensResult>,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        let legend = semantic_tokens_legend(&lsp_store, server_id, &cx)?;
+        Ok(match message {
+            Some(lsp::SemanticTokensResult::Tokens(tokens)) => SemanticTokensResponse {
+                result_id: tokens.result_id,
+                legend,
+                data: Some(SemanticTokensData::Full(tokens.data)),
+            },
+            Some(lsp::SemanticTokensResult::Partial(tokens)) => SemanticTokensResponse {
+                result_id: None,
+                legend,
+                data: Some(SemanticTokensData::Full(tokens.data)),
+            },
+            None => SemanticTokensResponse {
+                legend,
+                ..Default::default()
+            },
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokensFull {
+        proto::GetSemanticTokensFull {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokensFull,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: SemanticTokensResponse,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::SemanticTokensResponse {
+        semantic_tokens_response_to_proto(response)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::SemanticTokensResponse,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        semantic_tokens_response_from_proto(message)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokensFull) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokensDelta {
+    type Response = SemanticTokensResponse;
+    type LspRequest = lsp::request::SemanticTokensFullDeltaRequest;
+    type ProtoRequest = proto::GetSemanticTokensDelta;
+
+    fn display_name(&self) -> &str {
+        "Get semantic token changes"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        semantic_tokens_options(&capabilities.server_capabilities).map_or(false, |options| {
+            matches!(
+                options.full,
+                Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+            )
+        })
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::SemanticTokensDeltaParams> {
+        Ok(lsp::SemanticTokensDeltaParams {
+            text_document: make_text_document_identifier(path)?,
+            previous_result_id: self.previous_result_id.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensFullDeltaResult>,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        let legend = semantic_tokens_legend(&lsp_store, server_id, &cx)?;
+        Ok(match message {
+            Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => SemanticTokensResponse {
+                result_id: tokens.result_id,
+                legend,
+                data: Some(SemanticTokensData::Full(tokens.data)),
+            },
+            Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                SemanticTokensResponse {
+                    result_id: delta.result_id,
+                    legend,
+                    data: Some(SemanticTokensData::Delta(delta.edits)),
+                }
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                SemanticTokensResponse {
+                    result_id: None,
+                    legend,
+                    data: Some(SemanticTokensData::Delta(edits)),
+                }
+            }
+            None => SemanticTokensResponse {
+                legend,
+                ..Default::default()
+            },
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokensDelta {
+        proto::GetSemanticTokensDelta {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            previous_result_id: self.previous_result_id.clone(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokensDelta,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            previous_result_id: message.previous_result_id,
+        })
+    }
+
+    fn response_to_proto(
+        response: SemanticTokensResponse,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::SemanticTokensResponse {
+        semantic_tokens_response_to_proto(response)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::SemanticTokensResponse,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<SemanticTokensResponse> {
+        semantic_tokens_response_from_proto(message)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokensDelta) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn display_name(&self) -> &str {
+        "Get folding ranges"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match capabilities.server_capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::FoldingRangeParams> {
+        Ok(lsp::FoldingRangeParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut lsp_ranges = lsp_ranges.unwrap_or_default();
+            // Only lines can be folded, so when several ranges start on the same
+            // line, keep the largest one.
+            lsp_ranges.sort_unstable_by_key(|range| (range.start_line, Reverse(range.end_line)));
+            lsp_ranges.dedup_by_key(|range| range.start_line);
+            lsp_ranges
+                .into_iter()
+                .filter_map(|lsp_range| {
+                    let start_row = lsp_range.start_line;
+                    let end_row = lsp_range.end_line.min(max_row);
+                    if start_row >= end_row {
+                        return None;
+                    }
+                    let start = Point::new(start_row, buffer.line_len(start_row));
+                    let end = Point::new(end_row, buffer.line_len(end_row));
+                    Some(FoldingRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: lsp_range.kind,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<FoldingRange>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        let ranges = response
+            .into_iter()
+            .map(|range| proto::FoldingRange {
+                start: Some(serialize_anchor(&range.range.start)),
+                end: Some(serialize_anchor(&range.range.end)),
+                kind: range.kind.map(|kind| {
+                    match kind {
+                        lsp::FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                        lsp::FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                        lsp::FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                    }
+                    .into()
+                }),
+            })
+            .collect();
+        proto::GetFoldingRangesResponse { ranges }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        let mut ranges = Vec::new();
+        for range in message.ranges {
+            let start = range
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range start"))?;
+            let end = range
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            let kind = range
+                .kind
+                .and_then(proto::folding_range::Kind::from_i32)
+                .map(|kind| match kind {
+                    proto::folding_range::Kind::Comment => lsp::FoldingRangeKind::Comment,
+                    proto::folding_range::Kind::Imports => lsp::FoldingRangeKind::Imports,
+                    proto::folding_range::Kind::Region => lsp::FoldingRangeKind::Region,
+                });
+            ranges.push(FoldingRange {
+                range: start..end,
+                kind,
+            });
+        }
+        Ok(ranges)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetCodeLens {
+    pub fn can_resolve_lenses(capabilities: &lsp::ServerCapabilities) -> bool {
+        capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    /// Returns whether the language server can execute the command of the lens,
+    /// as opposed to commands that it expects the client to implement.
+    pub fn is_executable(lsp_lens: &lsp::CodeLens, capabilities: &lsp::ServerCapabilities) -> bool {
+        let Some(command) = &lsp_lens.command else {
+            return false;
+        };
+        capabilities
+            .execute_command_provider
+            .as_ref()
+            .map_or(false, |options| options.commands.contains(&command.command))
+    }
+
+    pub fn project_to_proto_lens(lens: CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+            executable: lens.executable,
+            resolve_state: Some(serialize_resolve_state(lens.resolve_state)),
+        }
+    }
+
+    pub fn proto_to_project_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing code lens start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing code lens end"))?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+            executable: lens.executable,
+            resolve_state: deserialize_resolve_state(lens.resolve_state)
+                .context("invalid code lens resolve state")?,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn display_name(&self) -> &str {
+        "Get code lens"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::CodeLensParams> {
+        Ok(lsp::CodeLensParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_lenses: Option<Vec<lsp::CodeLens>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let language_server = lsp_store
+            .read_with(&cx, |lsp_store, _| {
+                lsp_store.language_server_for_id(server_id)
+            })?
+            .ok_or_else(|| anyhow!("no language server with id {server_id}"))?;
+        let capabilities = language_server.capabilities();
+        let can_resolve = GetCodeLens::can_resolve_lenses(&capabilities);
+
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_lenses
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|lsp_lens| {
+                    // Lenses are usually sent without their command, which is
+                    // expensive to compute, and resolved once they're shown.
+                    let resolve_state = if lsp_lens.command.is_some() {
+                        ResolveState::Resolved
+                    } else if can_resolve {
+                        ResolveState::CanResolve(server_id, None)
+                    } else {
+                        return None;
+                    };
+                    let executable = GetCodeLens::is_executable(&lsp_lens, &capabilities);
+                    let range = range_from_lsp(lsp_lens.range);
+                    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                    Some(CodeLens {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_lens,
+                        executable,
+                        resolve_state,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<CodeLens>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        let lenses = response
+            .into_iter()
+            .map(GetCodeLens::project_to_proto_lens)
+            .collect();
+        proto::GetCodeLensResponse { lenses }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let mut lenses = Vec::new();
+        for lens in message.lenses {
+            let lens = GetCodeLens::proto_to_project_lens(lens)?;
+            buffer
+                .update(&mut cx, |buffer, _| {
+                    buffer.wait_for_anchors([lens.range.start, lens.range.end])
+                })?
+                .await?;
+            lenses.push(lens);
+        }
+        Ok(lenses)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetDocumentLinks {
+    pub fn can_resolve_links(capabilities: &lsp::ServerCapabilities) -> bool {
+        capabilities
+            .document_link_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    pub fn project_to_lsp_link(link: DocumentLink, buffer: &Buffer) -> Result<lsp::DocumentLink> {
+        let data = match link.resolve_state {
+            ResolveState::CanResolve(_, data) => data,
+            ResolveState::Resolved | ResolveState::Resolving => None,
+        };
+        Ok(lsp::DocumentLink {
+            range: range_to_lsp(link.range.to_point_utf16(buffer))?,
+            target: link.target,
+            tooltip: link.tooltip,
+            data,
+        })
+    }
+
+    pub fn project_to_proto_link(link: DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            target: link.target.map(|target| target.to_string()),
+            tooltip: link.tooltip,
+            resolve_state: Some(serialize_resolve_state(link.resolve_state)),
+        }
+    }
+
+    pub fn proto_to_project_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing document link start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing document link end"))?;
+        Ok(DocumentLink {
+            range: start..end,
+            target: link.target.map(|target| target.parse()).transpose()?,
+            tooltip: link.tooltip,
+            resolve_state: deserialize_resolve_state(link.resolve_state)
+                .context("invalid document link resolve state")?,
+        })
+    }
+}
+
+fn serialize_resolve_state(resolve_state: ResolveState) -> proto::ResolveState {
+    let (state, lsp_resolve_state) = match resolve_state {
+        ResolveState::Resolved => (0, None),
+        ResolveState::CanResolve(server_id, data) => (
+            1,
+            Some(proto::resolve_state::LspResolveState {
+                server_id: server_id.0 as u64,
+                value: data.map(|data| {
+                    serde_json::to_string(&data).expect("failed to serialize resolve data")
+                }),
+            }),
+        ),
+        ResolveState::Resolving => (2, None),
+    };
+    proto::ResolveState {
+        state,
+        lsp_resolve_state,
+    }
+}
+
+fn deserialize_resolve_state(resolve_state: Option<proto::ResolveState>) -> Result<ResolveState> {
+    let Some(resolve_state) = resolve_state else {
+        return Ok(ResolveState::Resolved);
+    };
+    Ok(match resolve_state.state {
+        0 => ResolveState::Resolved,
+        1 => {
+            let lsp_resolve_state = resolve_state
+                .lsp_resolve_state
+                .ok_or_else(|| anyhow!("missing resolve data"))?;
+            let data = lsp_resolve_state
+                .value
+                .map(|value| serde_json::from_str::<lsp::LSPAny>(&value))
+                .transpose()
+                .context("invalid resolve data")?;
+            ResolveState::CanResolve(LanguageServerId(lsp_resolve_state.server_id as usize), data)
+        }
+        2 => ResolveState::Resolving,
+        invalid => return Err(anyhow!("invalid resolve state {invalid}")),
+    })
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn display_name(&self) -> &str {
+        "Get document links"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::DocumentLinkParams> {
+        Ok(lsp::DocumentLinkParams {
+            text_document: make_text_document_identifier(path)?,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_links: Option<Vec<lsp::DocumentLink>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        let language_server = lsp_store
+            .read_with(&cx, |lsp_store, _| {
+                lsp_store.language_server_for_id(server_id)
+            })?
+            .ok_or_else(|| anyhow!("no language server with id {server_id}"))?;
+        let can_resolve = GetDocumentLinks::can_resolve_links(&language_server.capabilities());
+
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_links
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|lsp_link| {
+                    // Servers may defer computing the targets of links, which are
+                    // then resolved when the user hovers them.
+                    let resolve_state = if lsp_link.target.is_some() {
+                        ResolveState::Resolved
+                    } else if can_resolve {
+                        ResolveState::CanResolve(server_id, lsp_link.data)
+                    } else {
+                        return None;
+                    };
+                    let range = range_from_lsp(lsp_link.range);
+                    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                    Some(DocumentLink {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        target: lsp_link.target,
+                        tooltip: lsp_link.tooltip,
+                        resolve_state,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        let links = response
+            .into_iter()
+            .map(GetDocumentLinks::project_to_proto_link)
+            .collect();
+        proto::GetDocumentLinksResponse { links }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        let mut links = Vec::new();
+        for link in message.links {
+            let link = GetDocumentLinks::proto_to_project_link(link)?;
+            buffer
+                .update(&mut cx, |buffer, _| {
+                    buffer.wait_for_anchors([link.range.start, link.range.end])
+                })?
+                .await?;
+            links.push(link);
+        }
+        Ok(links)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSelectionRanges {
+    type Response = Vec<Vec<Range<Anchor>>>;
+    type LspRequest = lsp::request::SelectionRangeRequest;
+    type ProtoRequest = proto::GetSelectionRanges;
+
+    fn display_name(&self) -> &str {
+        "Get selection ranges"
+    }
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match capabilities.server_capabilities.selection_range_provider {
+            Some(lsp::SelectionRangeProviderCapability::Simple(enabled)) => enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> Result<lsp::SelectionRangeParams> {
+        Ok(lsp::SelectionRangeParams {
+            text_document: make_text_document_identifier(path)?,
+            positions: self
+                .positions
+                .iter()
+                .map(|position| point_to_lsp(position.to_point_utf16(buffer)))
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_selection_ranges: Option<Vec<lsp::SelectionRange>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        let lsp_selection_ranges = lsp_selection_ranges.unwrap_or_default();
+        if lsp_selection_ranges.len() != self.positions.len() {
+            return Err(anyhow!(
+                "expected {} selection ranges, got {}",
+                self.positions.len(),
+                lsp_selection_ranges.len()
+            ));
+        }
+
+        buffer.read_with(&cx, |buffer, _| {
+            lsp_selection_ranges
+                .into_iter()
+                .map(|mut lsp_selection_range| {
+                    // Flatten the chain of parents into a list of ranges, from
+                    // the innermost to the outermost one.
+                    let mut ranges = Vec::new();
+                    loop {
+                        let range = range_from_lsp(lsp_selection_range.range);
+                        let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                        let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                        ranges.push(buffer.anchor_before(start)..buffer.anchor_after(end));
+                        match lsp_selection_range.parent {
+                            Some(parent) => lsp_selection_range = *parent,
+                            None => break,
+                        }
+                    }
+                    ranges
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSelectionRanges {
+        proto::GetSelectionRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            positions: self.positions.iter().map(serialize_anchor).collect(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSelectionRanges,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let positions = message
+            .positions
+            .into_iter()
+            .map(|position| deserialize_anchor(position).ok_or_else(|| anyhow!("invalid position")))
+            .collect::<Result<Vec<_>>>()?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_anchors(positions.iter().copied())
+            })?
+            .await?;
+        Ok(Self { positions })
+    }
+
+    fn response_to_proto(
+        response: Vec<Vec<Range<Anchor>>>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSelectionRangesResponse {
+        proto::GetSelectionRangesResponse {
+            selection_ranges: response
+                .into_iter()
+                .map(|ranges| proto::SelectionRanges {
+                    ranges: ranges
+                        .into_iter()
+                        .map(|range| proto::AnchorRange {
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSelectionRangesResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<Vec<Range<Anchor>>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let mut selection_ranges = Vec::new();
+        for ranges in message.selection_ranges {
+            let ranges = ranges
+                .ranges
+                .into_iter()
+                .map(|range| {
+                    let start = range.start.and_then(deserialize_anchor);
+                    let end = range.end.and_then(deserialize_anchor);
+                    start
+                        .zip(end)
+                        .map(|(start, end)| start..end)
+                        .ok_or_else(|| anyhow!("invalid selection range"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            buffer
+                .update(&mut cx, |buffer, _| {
+                    buffer
+                        .wait_for_anchors(ranges.iter().flat_map(|range| [range.start, range.end]))
+                })?
+                .await?;
+            selection_ranges.push(ranges);
+        }
+        Ok(selection_ranges)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSelectionRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
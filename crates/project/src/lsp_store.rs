@@ -11,8 +11,9 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    BufferSemanticTokens, CodeAction, CodeLens, Completion, CoreCompletion, DocumentLink,
+    FoldingRange, Hover, InlayHint, ProjectItem as _, ProjectPath, ProjectTransaction,
+    ResolveState, SemanticToken, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -78,6 +79,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use task::{Problem, ProblemSeverity};
 use text::{Anchor, BufferId, LineEnding, OffsetRangeExt};
 use url::Url;
 use util::{
@@ -95,6 +97,7 @@ pub use worktree::{
 };
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(125);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -164,10 +167,27 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    /// The ids of the last diagnostic reports pulled from each language server,
+    /// used to only receive the diagnostics of documents that changed since.
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    buffer_diagnostics_pulls: HashMap<BufferId, Task<()>>,
+    workspace_diagnostics_pulls: HashMap<LanguageServerId, Task<()>>,
+    /// The sources of the diagnostics reported by the problem matchers of
+    /// each task, by label.
+    task_diagnostics: HashMap<String, TaskDiagnosticSource>,
     _subscription: gpui::Subscription,
     lsp_tree: Model<LanguageServerTree>,
 }
 
+/// The diagnostics reported by the problem matchers of a task. They're stored
+/// under an id reserved among those of the language servers, so that they're
+/// tracked alongside theirs, but no language server is registered for it.
+struct TaskDiagnosticSource {
+    id: LanguageServerId,
+    /// The files the diagnostics were last reported in.
+    paths: HashSet<PathBuf>,
+}
+
 impl LocalLspStore {
     fn start_language_server(
         &mut self,
@@ -764,6 +784,64 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_semantic_tokens(cx);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.code_lens.clear();
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -2034,6 +2112,7 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.buffer_diagnostics_pulls.remove(&buffer.remote_id());
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server.unregister_buffer(buffer.remote_id());
             }
@@ -2891,6 +2970,21 @@ pub struct LspStore {
     _maintain_buffer_languages: Task<()>,
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
+    semantic_tokens: HashMap<BufferId, CachedSemanticTokens>,
+    folding_ranges: HashMap<BufferId, (clock::Global, Arc<[FoldingRange]>)>,
+    code_lens: HashMap<BufferId, (clock::Global, Arc<[CodeLens]>)>,
+    document_links: HashMap<BufferId, (clock::Global, Arc<[DocumentLink]>)>,
+}
+
+struct CachedSemanticTokens {
+    /// The buffer version the tokens were computed for, or `None` if the
+    /// language server asked for them to be recomputed.
+    version: Option<clock::Global>,
+    /// The id the server assigned to these tokens, used to only request the
+    /// changes made to them since.
+    result_id: Option<String>,
+    data: Vec<lsp::SemanticToken>,
+    tokens: Arc<BufferSemanticTokens>,
 }
 
 pub enum LspStoreEvent {
@@ -2908,6 +3002,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2962,6 +3058,8 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_on_type_formatting);
         client.add_model_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_model_request_handler(Self::handle_register_buffer_with_language_servers);
@@ -2978,6 +3076,20 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSemanticTokensFull>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSemanticTokensDelta>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -3064,6 +3176,10 @@ impl LspStore {
                 language_server_watcher_registrations: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                diagnostic_result_ids: Default::default(),
+                buffer_diagnostics_pulls: Default::default(),
+                workspace_diagnostics_pulls: Default::default(),
+                task_diagnostics: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -3085,6 +3201,10 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            semantic_tokens: Default::default(),
+            folding_ranges: Default::default(),
+            code_lens: Default::default(),
+            document_links: Default::default(),
             active_entry: None,
 
             _maintain_workspace_config,
@@ -3142,6 +3262,10 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            semantic_tokens: Default::default(),
+            folding_ranges: Default::default(),
+            code_lens: Default::default(),
+            document_links: Default::default(),
             active_entry: None,
             toolchain_store,
             _maintain_workspace_config,
@@ -3175,7 +3299,12 @@ impl LspStore {
                     local.register_buffer_with_language_servers(buffer, cx);
                 }
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.semantic_tokens.remove(buffer_id);
+                self.folding_ranges.remove(buffer_id);
+                self.code_lens.remove(buffer_id);
+                self.document_links.remove(buffer_id);
+            }
         }
     }
 
@@ -3265,11 +3394,22 @@ impl LspStore {
     ) {
         match event {
             language::BufferEvent::Edited { .. } => {
-                self.on_buffer_edited(buffer, cx);
+                self.on_buffer_edited(buffer.clone(), cx);
+                self.pull_buffer_diagnostics(&buffer, cx);
             }
 
             language::BufferEvent::Saved => {
-                self.on_buffer_saved(buffer, cx);
+                self.on_buffer_saved(buffer.clone(), cx);
+                // Saving may affect the diagnostics of other files, so pull
+                // them from the servers that report workspace diagnostics.
+                let server_ids = self.as_local().map_or(Vec::new(), |local| {
+                    buffer.update(cx, |buffer, cx| {
+                        local.language_server_ids_for_buffer(buffer, cx)
+                    })
+                });
+                for server_id in server_ids {
+                    self.pull_workspace_diagnostics(server_id, cx);
+                }
             }
 
             _ => {}
@@ -3320,6 +3460,7 @@ impl LspStore {
                 local.unregister_old_buffer_from_language_servers(&buffer, cx);
             })
             .detach();
+            self.pull_buffer_diagnostics(buffer, cx);
         } else if let Some((upstream_client, upstream_project_id)) = self.upstream_client() {
             let buffer_id = buffer.read(cx).remote_id().to_proto();
             cx.background_executor()
@@ -3809,6 +3950,120 @@ impl LspStore {
         }
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[CodeLens]>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        if let Some((cached_version, lenses)) = self.code_lens.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(lenses.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let lenses: Arc<[CodeLens]> = request.await?.into();
+            this.update(&mut cx, |this, _| {
+                let is_latest = this
+                    .code_lens
+                    .get(&buffer_id)
+                    .map_or(true, |(cached_version, _)| {
+                        version.observed_all(cached_version)
+                    });
+                if is_latest {
+                    this.code_lens.insert(buffer_id, (version, lenses.clone()));
+                }
+            })?;
+            Ok(lenses)
+        })
+    }
+
+    /// Asks the language server for the command of a lens it sent without one.
+    pub fn resolve_code_lens(
+        &self,
+        lens: CodeLens,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        let ResolveState::CanResolve(server_id, _) = lens.resolve_state else {
+            return Task::ready(Ok(lens));
+        };
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                language_server_id: server_id.0 as u64,
+                lens: Some(GetCodeLens::project_to_proto_lens(lens.clone())),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens {
+                    Some(resolved_lens) => GetCodeLens::proto_to_project_lens(resolved_lens),
+                    None => Ok(lens),
+                }
+            })
+        } else {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(lens));
+            };
+            let capabilities = lang_server.capabilities();
+            if !GetCodeLens::can_resolve_lenses(&capabilities) {
+                return Task::ready(Ok(lens));
+            }
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(CodeLens {
+                    executable: GetCodeLens::is_executable(&lsp_lens, &capabilities),
+                    lsp_lens,
+                    resolve_state: ResolveState::Resolved,
+                    ..lens
+                })
+            })
+        }
+    }
+
+    /// Executes a code lens' command through `workspace/executeCommand`, like
+    /// a code action without edits.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(command) = lens.lsp_lens.command.filter(|_| lens.executable) else {
+            return Task::ready(Err(anyhow!(
+                "code lens command can't be executed by the language server"
+            )));
+        };
+        let action = CodeAction {
+            server_id: lens.server_id,
+            range: lens.range,
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..Default::default()
+            },
+        };
+        self.apply_code_action(buffer_handle, action, true, cx)
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -3866,6 +4121,281 @@ impl LspStore {
         }
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<BufferSemanticTokens>>> {
+        let snapshot = buffer.read(cx).text_snapshot();
+        let buffer_id = snapshot.remote_id();
+        let version = snapshot.version().clone();
+        let cached = self.semantic_tokens.get(&buffer_id);
+        if let Some(cached) = cached {
+            if cached.version.as_ref() == Some(&version) {
+                return Task::ready(Ok(cached.tokens.clone()));
+            }
+        }
+
+        let previous =
+            cached.and_then(|cached| Some((cached.result_id.clone()?, cached.data.clone())));
+        let delta_request = previous.map(|(previous_result_id, previous_data)| {
+            let request = self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                GetSemanticTokensDelta { previous_result_id },
+                cx,
+            );
+            (request, previous_data)
+        });
+        let buffer = buffer.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let mut response = None;
+            if let Some((request, previous_data)) = delta_request {
+                if let Some(delta) = request.await.log_err() {
+                    let data = match delta.data {
+                        Some(SemanticTokensData::Full(data)) => Some(data),
+                        Some(SemanticTokensData::Delta(edits)) => {
+                            apply_semantic_token_edits(previous_data, edits).log_err()
+                        }
+                        None => None,
+                    };
+                    response = data.map(|data| (delta.result_id, delta.legend, data));
+                }
+            }
+
+            // Fall back to requesting all of the tokens when the server can't
+            // send us the changes to the ones we have.
+            let (result_id, legend, data) = match response {
+                Some(response) => response,
+                None => {
+                    let response = this
+                        .update(&mut cx, |this, cx| {
+                            this.request_lsp(
+                                buffer.clone(),
+                                LanguageServerToQuery::Primary,
+                                GetSemanticTokensFull,
+                                cx,
+                            )
+                        })?
+                        .await?;
+                    let data = match response.data {
+                        Some(SemanticTokensData::Full(data)) => data,
+                        Some(SemanticTokensData::Delta(_)) | None => Vec::new(),
+                    };
+                    (response.result_id, response.legend, data)
+                }
+            };
+
+            let (data, tokens) = cx
+                .background_executor()
+                .spawn(async move {
+                    let tokens = semantic_tokens_from_lsp(&data, &snapshot);
+                    (data, tokens)
+                })
+                .await;
+            let tokens = Arc::new(BufferSemanticTokens { legend, tokens });
+            this.update(&mut cx, |this, _| {
+                let is_latest = this
+                    .semantic_tokens
+                    .get(&buffer_id)
+                    .and_then(|cached| cached.version.as_ref())
+                    .map_or(true, |cached_version| version.observed_all(cached_version));
+                if is_latest {
+                    this.semantic_tokens.insert(
+                        buffer_id,
+                        CachedSemanticTokens {
+                            version: Some(version),
+                            result_id,
+                            data,
+                            tokens: tokens.clone(),
+                        },
+                    );
+                }
+            })?;
+            Ok(tokens)
+        })
+    }
+
+    /// Invalidates the semantic tokens of all buffers, while keeping the
+    /// previous results around to request only the changes to them.
+    fn refresh_semantic_tokens(&mut self, cx: &mut ModelContext<Self>) {
+        for cached in self.semantic_tokens.values_mut() {
+            cached.version = None;
+        }
+        cx.emit(LspStoreEvent::RefreshSemanticTokens);
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[FoldingRange]>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        if let Some((cached_version, ranges)) = self.folding_ranges.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(ranges.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let ranges: Arc<[FoldingRange]> = request.await?.into();
+            this.update(&mut cx, |this, _| {
+                let is_latest = this
+                    .folding_ranges
+                    .get(&buffer_id)
+                    .map_or(true, |(cached_version, _)| {
+                        version.observed_all(cached_version)
+                    });
+                if is_latest {
+                    this.folding_ranges
+                        .insert(buffer_id, (version, ranges.clone()));
+                }
+            })?;
+            Ok(ranges)
+        })
+    }
+
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[DocumentLink]>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        if let Some((cached_version, links)) = self.document_links.get(&buffer_id) {
+            if *cached_version == version {
+                return Task::ready(Ok(links.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let links: Arc<[DocumentLink]> = request.await?.into();
+            this.update(&mut cx, |this, _| {
+                let is_latest = this
+                    .document_links
+                    .get(&buffer_id)
+                    .map_or(true, |(cached_version, _)| {
+                        version.observed_all(cached_version)
+                    });
+                if is_latest {
+                    this.document_links
+                        .insert(buffer_id, (version, links.clone()));
+                }
+            })?;
+            Ok(links)
+        })
+    }
+
+    /// Asks the language server for the target of a link it deferred computing.
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        let ResolveState::CanResolve(server_id, _) = link.resolve_state else {
+            return Task::ready(Ok(link));
+        };
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                language_server_id: server_id.0 as u64,
+                link: Some(GetDocumentLinks::project_to_proto_link(link.clone())),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                match response.link {
+                    Some(resolved_link) => GetDocumentLinks::proto_to_project_link(resolved_link),
+                    None => Ok(link),
+                }
+            })
+        } else {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(link));
+            };
+            if !GetDocumentLinks::can_resolve_links(&lang_server.capabilities()) {
+                return Task::ready(Ok(link));
+            }
+            let lsp_link =
+                match GetDocumentLinks::project_to_lsp_link(link.clone(), buffer_handle.read(cx)) {
+                    Ok(lsp_link) => lsp_link,
+                    Err(error) => return Task::ready(Err(error)),
+                };
+            cx.spawn(move |_, _| async move {
+                let resolved_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(lsp_link)
+                    .await
+                    .context("document link resolve LSP request")?;
+                Ok(DocumentLink {
+                    target: resolved_link.target,
+                    tooltip: resolved_link.tooltip.or(link.tooltip),
+                    resolve_state: ResolveState::Resolved,
+                    ..link
+                })
+            })
+        }
+    }
+
+    pub fn selection_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
+    /// Whether the primary language server of the buffer can provide
+    /// selection ranges. Buffers of remote projects are assumed to support
+    /// them, as only the host knows about their language servers.
+    pub fn supports_selection_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        if self.upstream_client().is_some() {
+            return true;
+        }
+        let Some(local) = self.as_local() else {
+            return false;
+        };
+        buffer.update(cx, |buffer, cx| {
+            local
+                .primary_language_server_for_buffer(buffer, cx)
+                .map_or(false, |(_, server)| {
+                    GetSelectionRanges {
+                        positions: Vec::new(),
+                    }
+                    .check_capabilities(server.adapter_server_capabilities())
+                })
+        })
+    }
+
     pub(crate) fn linked_edit(
         &mut self,
         buffer: &Model<Buffer>,
@@ -6715,6 +7245,27 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| this.refresh_semantic_tokens(cx))?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            this.code_lens.clear();
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_inlay_hints(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::InlayHints>,
@@ -6791,6 +7342,52 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = envelope
+            .payload
+            .lens
+            .ok_or_else(|| anyhow!("missing code lens"))?;
+        let lens = GetCodeLens::proto_to_project_lens(lens)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let lens = this
+            .update(&mut cx, |this, cx| this.resolve_code_lens(lens, buffer, cx))?
+            .await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(GetCodeLens::project_to_proto_lens(lens)),
+        })
+    }
+
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = envelope
+            .payload
+            .link
+            .ok_or_else(|| anyhow!("missing document link"))?;
+        let link = GetDocumentLinks::proto_to_project_link(link)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let link = this
+            .update(&mut cx, |this, cx| {
+                this.resolve_document_link(link, buffer, cx)
+            })?
+            .await?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(GetDocumentLinks::project_to_proto_link(link)),
+        })
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -7190,6 +7787,8 @@ impl LspStore {
         // Did any of the worktrees reference this server ID at least once?
         let mut was_referenced = false;
         // Remove this server ID from all entries in the given worktree.
+        local.diagnostic_result_ids.remove(&server_id);
+        local.workspace_diagnostics_pulls.remove(&server_id);
         local.language_server_ids.retain(|(worktree, _), ids| {
             if !ids.remove(&server_id) {
                 return true;
@@ -7495,6 +8094,350 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces the diagnostics reported by the task with the given label
+    /// with the problems found in its output.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        problems: Vec<Problem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let Some(local) = self.as_local_mut() else {
+            anyhow::bail!("called update_task_diagnostics on remote");
+        };
+        let source = local
+            .task_diagnostics
+            .entry(task_label.to_string())
+            .or_insert_with(|| TaskDiagnosticSource {
+                id: local.languages.next_language_server_id(),
+                paths: HashSet::default(),
+            });
+        let server_id = source.id;
+        let old_paths = mem::take(&mut source.paths);
+
+        let mut diagnostics_by_path = HashMap::<PathBuf, Vec<_>>::default();
+        for problem in problems {
+            let group_id = post_inc(&mut self.as_local_mut().unwrap().next_diagnostic_group_id);
+            let start = PointUtf16::new(problem.start.0, problem.start.1);
+            let end = problem
+                .end
+                .map_or(start, |(row, column)| PointUtf16::new(row, column));
+            diagnostics_by_path
+                .entry(problem.path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end),
+                    diagnostic: Diagnostic {
+                        source: Some(problem.source),
+                        code: problem.code,
+                        severity: match problem.severity {
+                            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                        },
+                        message: problem.message,
+                        group_id,
+                        is_primary: true,
+                        // The problems are found in the files the task read from disk.
+                        is_disk_based: true,
+                        is_unnecessary: false,
+                        data: None,
+                    },
+                });
+        }
+
+        let paths = diagnostics_by_path.keys().cloned().collect::<HashSet<_>>();
+        for path in old_paths.difference(&paths) {
+            self.update_diagnostic_entries(server_id, path.clone(), None, Vec::new(), cx)?;
+        }
+        for (path, diagnostics) in diagnostics_by_path {
+            self.update_diagnostic_entries(server_id, path, None, diagnostics, cx)?;
+        }
+        if let Some(source) = self
+            .as_local_mut()
+            .unwrap()
+            .task_diagnostics
+            .get_mut(task_label)
+        {
+            source.paths = paths;
+        }
+        Ok(())
+    }
+
+    /// Returns the label of the task whose problem matchers report diagnostics
+    /// under the given id, if any.
+    pub fn task_diagnostic_source(&self, id: LanguageServerId) -> Option<&str> {
+        self.as_local()?
+            .task_diagnostics
+            .iter()
+            .find(|(_, source)| source.id == id)
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Requests the diagnostics of a buffer from its language servers that
+    /// support pulling them, once the buffer stops changing.
+    fn pull_buffer_diagnostics(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let servers = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .filter(|(_, server)| server.capabilities().diagnostic_provider.is_some())
+                .map(|(adapter, server)| (adapter.clone(), server.clone()))
+                .collect::<Vec<_>>()
+        });
+        if servers.is_empty() {
+            return;
+        }
+        let Some(file) = File::from_dyn(buffer.read(cx).file()).and_then(|file| file.as_local())
+        else {
+            return;
+        };
+        let Ok(uri) = lsp::Url::from_file_path(file.abs_path(cx)) else {
+            return;
+        };
+
+        let pull = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                .await;
+            for (adapter, server) in servers {
+                Self::pull_document_diagnostics(
+                    &this,
+                    buffer_id,
+                    uri.clone(),
+                    adapter,
+                    server,
+                    &mut cx,
+                )
+                .await
+                .log_err();
+            }
+        });
+        if let Some(local) = self.as_local_mut() {
+            local.buffer_diagnostics_pulls.insert(buffer_id, pull);
+        }
+    }
+
+    async fn pull_document_diagnostics(
+        this: &WeakModel<Self>,
+        buffer_id: BufferId,
+        uri: lsp::Url,
+        adapter: Arc<CachedLspAdapter>,
+        server: Arc<LanguageServer>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        let server_id = server.server_id();
+        let (version, previous_result_id) = this
+            .update(cx, |this, _| {
+                let local = this.as_local()?;
+                let snapshots = local.buffer_snapshots.get(&buffer_id)?.get(&server_id)?;
+                let previous_result_id = local
+                    .diagnostic_result_ids
+                    .get(&server_id)
+                    .and_then(|result_ids| result_ids.get(&uri))
+                    .cloned();
+                Some((snapshots.last()?.version, previous_result_id))
+            })?
+            .ok_or_else(|| anyhow!("buffer is not open in language server {server_id}"))?;
+
+        let identifier = diagnostic_options(&server.capabilities())
+            .and_then(|options| options.identifier.clone());
+        let report = server
+            .request::<lsp::request::DocumentDiagnosticRequest>(lsp::DocumentDiagnosticParams {
+                text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                identifier,
+                previous_result_id,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+
+        this.update(cx, |this, cx| {
+            let (report, related_documents) = match report {
+                lsp::DocumentDiagnosticReportResult::Report(
+                    lsp::DocumentDiagnosticReport::Full(report),
+                ) => (
+                    Some(lsp::DocumentDiagnosticReportKind::Full(
+                        report.full_document_diagnostic_report,
+                    )),
+                    report.related_documents,
+                ),
+                lsp::DocumentDiagnosticReportResult::Report(
+                    lsp::DocumentDiagnosticReport::Unchanged(report),
+                ) => (
+                    Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                        report.unchanged_document_diagnostic_report,
+                    )),
+                    report.related_documents,
+                ),
+                // No partial result token is sent, so a partial result only
+                // carries the reports of the related documents.
+                lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                    (None, report.related_documents)
+                }
+            };
+            if let Some(report) = report {
+                this.apply_diagnostic_report(server_id, &adapter, uri, Some(version), report, cx)?;
+            }
+            for (uri, report) in related_documents.into_iter().flatten() {
+                this.apply_diagnostic_report(server_id, &adapter, uri, None, report, cx)
+                    .log_err();
+            }
+            anyhow::Ok(())
+        })?
+    }
+
+    /// Requests the diagnostics of all the files in the workspace from a
+    /// language server that supports pulling them.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = local.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let capabilities = server.capabilities();
+        let Some(options) = diagnostic_options(&capabilities) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+
+        let params = lsp::WorkspaceDiagnosticParams {
+            identifier: options.identifier.clone(),
+            previous_result_ids: local
+                .diagnostic_result_ids
+                .get(&server_id)
+                .into_iter()
+                .flatten()
+                .map(|(uri, result_id)| lsp::PreviousResultId {
+                    uri: uri.clone(),
+                    value: result_id.clone(),
+                })
+                .collect(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let adapter = adapter.clone();
+        let server = server.clone();
+        let pull = cx.spawn(move |this, mut cx| async move {
+            let report = server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(params)
+                .await
+                .log_err();
+            let items = match report {
+                Some(lsp::WorkspaceDiagnosticReportResult::Report(report)) => report.items,
+                Some(lsp::WorkspaceDiagnosticReportResult::Partial(report)) => report.items,
+                None => return,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(item) => (
+                            item.uri,
+                            item.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                item.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(item) => (
+                            item.uri,
+                            item.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                item.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    let version = version.map(|version| version as i32);
+                    this.apply_diagnostic_report(server_id, &adapter, uri, version, report, cx)
+                        .log_err();
+                }
+            })
+            .ok();
+        });
+        local.workspace_diagnostics_pulls.insert(server_id, pull);
+    }
+
+    /// Pulls the diagnostics of the workspace and of every open buffer again,
+    /// after a language server started or asked for them to be refreshed.
+    fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                local
+                    .buffer_snapshots
+                    .get(&buffer.read(cx).remote_id())
+                    .map_or(false, |snapshots| snapshots.contains_key(&server_id))
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_buffer_diagnostics(&buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    /// Merges a pulled diagnostic report into the diagnostics of its document,
+    /// like a `textDocument/publishDiagnostics` notification.
+    fn apply_diagnostic_report(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .ok_or_else(|| anyhow!("called apply_diagnostic_report on remote"))?;
+        let result_ids = local.diagnostic_result_ids.entry(server_id).or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                if let Some(result_id) = report.result_id {
+                    result_ids.insert(uri.clone(), result_id);
+                } else {
+                    result_ids.remove(&uri);
+                }
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+                Ok(())
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn insert_newly_running_language_server(
         &mut self,
@@ -7564,6 +8507,10 @@ impl LspStore {
             Some(key.0),
         ));
         cx.emit(LspStoreEvent::RefreshInlayHints);
+        cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        self.folding_ranges.clear();
+        self.code_lens.clear();
+        self.document_links.clear();
 
         if let Some((downstream_client, project_id)) = self.downstream_client.as_ref() {
             downstream_client
@@ -7651,6 +8598,7 @@ impl LspStore {
                 });
             }
         });
+        self.refresh_pulled_diagnostics(server_id, cx);
 
         cx.notify();
     }
@@ -8941,3 +9889,68 @@ fn test_glob_literal_prefix() {
         );
     }
 }
+
+/// Resolves the relative positions of the semantic tokens against the buffer
+/// snapshot they were requested for.
+fn semantic_tokens_from_lsp(
+    data: &[lsp::SemanticToken],
+    snapshot: &TextBufferSnapshot,
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut row = 0;
+    let mut column = 0;
+    for token in data {
+        if token.delta_line == 0 {
+            column += token.delta_start;
+        } else {
+            row += token.delta_line;
+            column = token.delta_start;
+        }
+
+        let start = snapshot.clip_point_utf16(Unclipped(PointUtf16::new(row, column)), Bias::Left);
+        let end = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(row, column + token.length)),
+            Bias::Left,
+        );
+        if start < end {
+            tokens.push(SemanticToken {
+                range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers_bitset,
+            });
+        }
+    }
+    tokens
+}
+
+/// Applies edits from a `textDocument/semanticTokens/full/delta` response to
+/// the tokens they were computed against.
+fn apply_semantic_token_edits(
+    mut data: Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Result<Vec<lsp::SemanticToken>> {
+    // Edits index into the flattened token data, where each token takes up
+    // five integers, and all refer to the original data.
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+    for edit in edits {
+        if edit.start % 5 != 0 || edit.delete_count % 5 != 0 {
+            anyhow::bail!("semantic token edit doesn't align with token boundaries");
+        }
+        let start = edit.start as usize / 5;
+        let end = start + edit.delete_count as usize / 5;
+        if end > data.len() {
+            anyhow::bail!("semantic token edit is out of bounds");
+        }
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+    Ok(data)
+}
+
+fn diagnostic_options(capabilities: &lsp::ServerCapabilities) -> Option<&lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(&options.diagnostic_options)
+        }
+    }
+}
@@ -45,8 +45,10 @@ use image_store::{ImageItemEvent, ImageStoreEvent};
 
 use ::git::{
     blame::Blame,
-    repository::{Branch, GitRepository},
+    history::LogEntry,
+    repository::{Branch, GitRepository, RepoPath},
     status::FileStatus,
+    Oid,
 };
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BorrowAppContext, Context as _, EventEmitter, Hsla,
@@ -280,6 +282,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -412,6 +416,34 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A command that a language server shows above a range of a buffer, such as
+/// "Run test" or "3 references".
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server, which only has a
+    /// command once it's resolved.
+    pub lsp_lens: lsp::CodeLens,
+    /// Whether the language server can execute the code lens' command, as
+    /// opposed to commands that it expects the client to implement.
+    pub executable: bool,
+    /// Whether the code lens' command still has to be requested from the
+    /// language server.
+    pub resolve_state: ResolveState,
+}
+
+impl CodeLens {
+    pub fn title(&self) -> &str {
+        self.lsp_lens
+            .command
+            .as_ref()
+            .map_or("", |command| command.title.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -465,6 +497,95 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// An item in a call hierarchy, such as a function or a method.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyItem {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The location that should be revealed when navigating to this item, e.g. its name.
+    pub location: Location,
+    /// The raw item provided by the language server, used to query its calls.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+/// A call between two items in a call hierarchy.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The ranges at which the calls appear, within the calling item.
+    pub call_sites: Vec<Location>,
+}
+
+/// An item in a type hierarchy, such as a class or an interface.
+#[derive(Clone, Debug)]
+pub struct TypeHierarchyItem {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The location that should be revealed when navigating to this item, e.g. its name.
+    pub location: Location,
+    /// The raw item provided by the language server, used to query its super- and subtypes.
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+/// The semantic tokens a language server reported for a buffer.
+#[derive(Clone, Debug, Default)]
+pub struct BufferSemanticTokens {
+    /// The legend that the tokens' types and modifiers index into.
+    pub legend: lsp::SemanticTokensLegend,
+    /// The tokens, sorted by their position in the buffer.
+    pub tokens: Vec<SemanticToken>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl BufferSemanticTokens {
+    /// Returns the name of the token's type, such as `function` or `variable`.
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.legend
+            .token_types
+            .get(token.token_type as usize)
+            .map(|token_type| token_type.as_str())
+    }
+
+    /// Returns the names of the token's modifiers, such as `mutable` or `unsafe`.
+    pub fn token_modifiers<'a>(
+        &'a self,
+        token: &SemanticToken,
+    ) -> impl 'a + Iterator<Item = &'a str> {
+        let modifiers = token.token_modifiers;
+        self.legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(move |(ix, _)| *ix < 32 && modifiers & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+/// A region of a buffer that a language server reported as foldable.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// The folded text, from the end of the region's first line to the end of its last line.
+    pub range: Range<language::Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// A range of a buffer that a language server reported as a link to a file or URL.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    pub range: Range<language::Anchor>,
+    /// Servers may only compute the target once the link is resolved.
+    pub target: Option<lsp::Url>,
+    pub tooltip: Option<String>,
+    pub resolve_state: ResolveState,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -819,6 +940,9 @@ impl Project {
             cx.subscribe(&ssh, Self::on_ssh_event).detach();
             cx.observe(&ssh, |_, _, cx| cx.notify()).detach();
 
+            let git_state =
+                Some(cx.new_model(|cx| GitState::new(&worktree_store, languages.clone(), cx)));
+
             let this = Self {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
@@ -828,7 +952,7 @@ impl Project {
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
-                git_state: None,
+                git_state,
                 client_subscriptions: Vec::new(),
                 _subscriptions: vec![
                     cx.on_release(Self::release),
@@ -1036,6 +1160,9 @@ impl Project {
             cx.subscribe(&settings_observer, Self::on_settings_observer_event)
                 .detach();
 
+            let git_state =
+                Some(cx.new_model(|cx| GitState::new(&worktree_store, languages.clone(), cx)));
+
             let mut this = Self {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
@@ -1061,7 +1188,7 @@ impl Project {
                     remote_id,
                     replica_id,
                 },
-                git_state: None,
+                git_state,
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
@@ -2220,6 +2347,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2759,6 +2888,96 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes {
+                item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
     fn document_highlights_impl(
         &mut self,
         buffer: &Model<Buffer>,
@@ -2916,6 +3135,30 @@ impl Project {
         })
     }
 
+    /// Returns the code lenses of the buffer's primary language server, which
+    /// are fetched again only once the buffer changed. Their commands may have
+    /// to be resolved with [`Project::resolve_code_lens`] before they're shown.
+    pub fn code_lens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[CodeLens]>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.code_lens(buffer, cx))
+    }
+
+    /// Executes the command of a code lens on the language server that produced it.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer_handle, lens, cx)
+        })
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -2998,6 +3241,81 @@ impl Project {
         })
     }
 
+    /// Returns the semantic tokens of the buffer's primary language server,
+    /// which are cached until the buffer changes.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<BufferSemanticTokens>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(buffer, cx))
+    }
+
+    /// Returns the folding ranges of the buffer's primary language server,
+    /// which are cached until the buffer changes.
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[FoldingRange]>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.folding_ranges(buffer, cx))
+    }
+
+    /// Returns the links of the buffer's primary language server, which are
+    /// cached until the buffer changes.
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<[DocumentLink]>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.document_links(buffer, cx))
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        lens: CodeLens,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(lens, buffer_handle, cx)
+        })
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(link, buffer_handle, cx)
+        })
+    }
+
+    /// Returns, for each of the given positions, the ranges the primary
+    /// language server suggests to select around it, from the innermost
+    /// to the outermost one.
+    pub fn selection_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.selection_ranges(buffer, positions, cx)
+        })
+    }
+
+    pub fn supports_selection_ranges(&self, buffer: &Model<Buffer>, cx: &mut AppContext) -> bool {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.supports_selection_ranges(buffer, cx)
+        })
+    }
+
     pub fn search(
         &mut self,
         query: SearchQuery,
@@ -3566,6 +3884,26 @@ impl Project {
         self.buffer_store.read(cx).blame_buffer(buffer, version, cx)
     }
 
+    pub fn file_history(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<LogEntry>>> {
+        self.buffer_store.read(cx).file_history(buffer, cx)
+    }
+
+    pub fn load_text_at_revision(
+        &self,
+        buffer: &Model<Buffer>,
+        sha: Oid,
+        path: RepoPath,
+        cx: &AppContext,
+    ) -> Task<Result<Option<String>>> {
+        self.buffer_store
+            .read(cx)
+            .load_text_at_revision(buffer, sha, path, cx)
+    }
+
     pub fn get_permalink_to_line(
         &self,
         buffer: &Model<Buffer>,
@@ -1,5 +1,11 @@
-use crate::{Event, *};
-use ::git::diff::assert_hunks;
+use crate::{git::RemoteOperation, Event, *};
+use ::git::{
+    diff::assert_hunks,
+    history::{CommitDetails, CommitFile, LogEntry},
+    repository::{RepoPath, StashEntry, UpstreamTracking},
+    status::StatusCode,
+    Oid,
+};
 use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::{AppContext, SemanticVersion, UpdateGlobal};
@@ -19,10 +25,16 @@ use pretty_assertions::{assert_eq, assert_matches};
 use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
-use std::{str::FromStr, sync::OnceLock};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        OnceLock,
+    },
+};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{Problem, ProblemSeverity, ResolvedTask, TaskContext};
 use unindent::Unindent as _;
 use util::{
     assert_set_eq,
@@ -1236,6 +1248,117 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_task_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = 1;",
+            "b.rs": "let b = 2;"
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let problem = |path: &str, message: &str| Problem {
+        path: PathBuf::from(path),
+        start: (0, 4),
+        end: Some((0, 5)),
+        severity: ProblemSeverity::Error,
+        code: None,
+        message: message.to_string(),
+        source: "build".to_string(),
+    };
+
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics(
+                "build",
+                vec![
+                    problem("/dir/a.rs", "first error"),
+                    problem("/dir/b.rs", "second error"),
+                ],
+                cx,
+            )
+            .unwrap();
+    });
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("let ", None),
+                ("a", Some(DiagnosticSeverity::ERROR)),
+                (" = 1;", None),
+            ]
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summary(false, cx).error_count, 2);
+
+        // The diagnostics are reported under a source named after the task,
+        // which isn't a language server.
+        let (_, server_id, _) = project.diagnostic_summaries(false, cx).next().unwrap();
+        assert_eq!(
+            project
+                .lsp_store()
+                .read(cx)
+                .task_diagnostic_source(server_id),
+            Some("build")
+        );
+        assert!(project.language_server_statuses(cx).next().is_none());
+    });
+
+    // Rerunning the task replaces its diagnostics.
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics("build", vec![problem("/dir/b.rs", "second error")], cx)
+            .unwrap();
+    });
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[("let a = 1;", None)]
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project
+                .diagnostic_summaries(false, cx)
+                .collect::<Vec<_>>()
+                .len(),
+            1
+        );
+        assert_eq!(project.diagnostic_summary(false, cx).error_count, 1);
+    });
+
+    // A successful run clears them.
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_task_diagnostics("build", Vec::new(), cx)
+            .unwrap();
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(project.diagnostic_summaries(false, cx).next(), None);
+        assert_eq!(project.diagnostic_summary(false, cx).error_count, 0);
+    });
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2571,105 +2694,1054 @@ fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
     chunks
 }
 
-#[gpui::test(iterations = 10)]
-async fn test_definition(cx: &mut gpui::TestAppContext) {
+#[gpui::test(iterations = 10)]
+async fn test_definition(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "b.rs": "const y: i32 = crate::a()",
+        }),
+    )
+    .await;
+    fs.insert_tree(
+        "/another_dir",
+        json!({
+        "a.rs": "const fn a() { A }"}),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        let params = params.text_document_position_params;
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(params.position, lsp::Position::new(0, 22));
+
+        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+            lsp::Location::new(
+                lsp::Url::from_file_path("/another_dir/a.rs").unwrap(),
+                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            ),
+        )))
+    });
+    let mut definitions = project
+        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+        .await
+        .unwrap();
+
+    // Assert no new language server started
+    cx.executor().run_until_parked();
+    assert!(fake_servers.try_next().is_err());
+
+    assert_eq!(definitions.len(), 1);
+    let definition = definitions.pop().unwrap();
+    cx.update(|cx| {
+        let target_buffer = definition.target.buffer.read(cx);
+        assert_eq!(
+            target_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/another_dir/a.rs"),
+        );
+        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+        assert_eq!(
+            list_worktrees(&project, cx),
+            [
+                ("/another_dir/a.rs".as_ref(), false),
+                ("/dir".as_ref(), true)
+            ],
+        );
+
+        drop(definition);
+    });
+    cx.update(|cx| {
+        assert_eq!(list_worktrees(&project, cx), [("/dir".as_ref(), true)]);
+    });
+
+    fn list_worktrees<'a>(
+        project: &'a Model<Project>,
+        cx: &'a AppContext,
+    ) -> Vec<(&'a Path, bool)> {
+        project
+            .read(cx)
+            .worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (
+                    worktree.as_local().unwrap().abs_path().as_ref(),
+                    worktree.is_visible(),
+                )
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() { b() }",
+            "b.rs": "fn b() {}\nfn c() { b(); b() }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let lsp_item = |path: &str, name: &str, range: lsp::Range| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let b_item = lsp_item(
+        "/dir/b.rs",
+        "b",
+        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+    );
+
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let b_item = b_item.clone();
+        move |params, _| {
+            let b_item = b_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 3)
+                );
+                Ok(Some(vec![b_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![
+                lsp::CallHierarchyIncomingCall {
+                    from: lsp_item(
+                        "/dir/a.rs",
+                        "a",
+                        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                    ),
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(0, 9),
+                        lsp::Position::new(0, 10),
+                    )],
+                },
+                lsp::CallHierarchyIncomingCall {
+                    from: lsp_item(
+                        "/dir/b.rs",
+                        "c",
+                        lsp::Range::new(lsp::Position::new(1, 3), lsp::Position::new(1, 4)),
+                    ),
+                    from_ranges: vec![
+                        lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 10)),
+                        lsp::Range::new(lsp::Position::new(1, 14), lsp::Position::new(1, 15)),
+                    ],
+                },
+            ]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(0, 3), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "b");
+    assert_eq!(items[0].location.buffer, buffer);
+
+    let calls = project
+        .update(cx, |project, cx| project.incoming_calls(&items[0], cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let calls = calls
+            .iter()
+            .map(|call| {
+                let caller_buffer = call.item.location.buffer.read(cx);
+                (
+                    call.item.lsp_item.name.as_str(),
+                    caller_buffer
+                        .file()
+                        .unwrap()
+                        .as_local()
+                        .unwrap()
+                        .abs_path(cx),
+                    call.call_sites
+                        .iter()
+                        .map(|site| site.range.to_offset(site.buffer.read(cx)))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [
+                ("a", PathBuf::from("/dir/a.rs"), vec![9..10]),
+                ("c", PathBuf::from("/dir/b.rs"), vec![19..20, 24..25]),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait A {}",
+            "b.rs": "struct B;\nimpl A for B {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let lsp_item =
+        |path: &str, name: &str, kind: lsp::SymbolKind, range: lsp::Range| lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range: range,
+            data: None,
+        };
+    let a_item = lsp_item(
+        "/dir/a.rs",
+        "A",
+        lsp::SymbolKind::INTERFACE,
+        lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 7)),
+    );
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 6)
+                );
+                Ok(Some(vec![a_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "A");
+            Ok(Some(vec![lsp_item(
+                "/dir/b.rs",
+                "B",
+                lsp::SymbolKind::STRUCT,
+                lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 8)),
+            )]))
+        },
+    );
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(0, 6), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "A");
+    assert_eq!(items[0].location.buffer, buffer);
+
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&items[0], cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let subtypes = subtypes
+            .iter()
+            .map(|item| {
+                let buffer = item.location.buffer.read(cx);
+                (
+                    item.lsp_item.name.as_str(),
+                    buffer.file().unwrap().as_local().unwrap().abs_path(cx),
+                    item.location.range.to_offset(buffer),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(subtypes, [("B", PathBuf::from("/dir/b.rs"), 7..8)]);
+    });
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn main() { let mut x = 1; }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensOptions {
+                        legend: lsp::SemanticTokensLegend {
+                            token_types: vec![
+                                lsp::SemanticTokenType::FUNCTION,
+                                lsp::SemanticTokenType::VARIABLE,
+                            ],
+                            token_modifiers: vec![
+                                lsp::SemanticTokenModifier::DECLARATION,
+                                lsp::SemanticTokenModifier::new("mutable"),
+                            ],
+                        },
+                        full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let full_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>({
+        let full_requests = full_requests.clone();
+        move |_, _| {
+            full_requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(lsp::SemanticTokensResult::Tokens(
+                    lsp::SemanticTokens {
+                        result_id: Some("1".into()),
+                        data: vec![
+                            lsp::SemanticToken {
+                                delta_line: 0,
+                                delta_start: 3,
+                                length: 4,
+                                token_type: 0,
+                                token_modifiers_bitset: 0b01,
+                            },
+                            lsp::SemanticToken {
+                                delta_line: 0,
+                                delta_start: 17,
+                                length: 1,
+                                token_type: 1,
+                                token_modifiers_bitset: 0b11,
+                            },
+                        ],
+                    },
+                )))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            // Only the first token moves to the next line, as the second one
+            // is positioned relative to it.
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 0,
+                        delete_count: 5,
+                        data: Some(vec![lsp::SemanticToken {
+                            delta_line: 1,
+                            delta_start: 3,
+                            length: 4,
+                            token_type: 0,
+                            token_modifiers_bitset: 0b01,
+                        }]),
+                    }],
+                },
+            )))
+        },
+    );
+
+    let summarize = |tokens: &BufferSemanticTokens, cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            tokens
+                .tokens
+                .iter()
+                .map(|token| {
+                    (
+                        token.range.to_offset(buffer),
+                        tokens.token_type(token).unwrap().to_string(),
+                        tokens.token_modifiers(token).collect::<Vec<_>>().join(" "),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        summarize(&tokens, cx),
+        [
+            (3..7, "function".to_string(), "declaration".to_string()),
+            (
+                20..21,
+                "variable".to_string(),
+                "declaration mutable".to_string()
+            ),
+        ]
+    );
+
+    // Tokens are cached until the buffer changes.
+    project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(full_requests.load(SeqCst), 1);
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        summarize(&tokens, cx),
+        [
+            (4..8, "function".to_string(), "declaration".to_string()),
+            (
+                21..22,
+                "variable".to_string(),
+                "declaration mutable".to_string()
+            ),
+        ]
+    );
+    assert_eq!(full_requests.load(SeqCst), 1);
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn main() {\n    let x = 1;\n    // a\n    // b\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>({
+        let requests = requests.clone();
+        move |_, _| {
+            requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(vec![
+                    lsp::FoldingRange {
+                        start_line: 0,
+                        end_line: 1,
+                        ..Default::default()
+                    },
+                    lsp::FoldingRange {
+                        start_line: 0,
+                        end_line: 3,
+                        ..Default::default()
+                    },
+                    lsp::FoldingRange {
+                        start_line: 2,
+                        end_line: 3,
+                        kind: Some(lsp::FoldingRangeKind::Comment),
+                        ..Default::default()
+                    },
+                    // Ranges spanning a single line can't be folded.
+                    lsp::FoldingRange {
+                        start_line: 4,
+                        end_line: 4,
+                        ..Default::default()
+                    },
+                ]))
+            }
+        }
+    });
+
+    let ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| (range.range.to_offset(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (11..44, None),
+                (35..44, Some(lsp::FoldingRangeKind::Comment)),
+            ]
+        );
+    });
+
+    // Ranges are cached until the buffer changes.
+    project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(requests.load(SeqCst), 1);
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(requests.load(SeqCst), 2);
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "#[test]\nfn one() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["run".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let lens_range = lsp::Range::new(lsp::Position::new(1, 3), lsp::Position::new(1, 6));
+    let lens_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>({
+        let lens_requests = lens_requests.clone();
+        move |_, _| {
+            lens_requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(vec![
+                    lsp::CodeLens {
+                        range: lens_range,
+                        command: Some(lsp::Command {
+                            title: "Run test".into(),
+                            command: "run".into(),
+                            arguments: Some(vec![json!("one")]),
+                        }),
+                        data: None,
+                    },
+                    // The references are only counted when the lens is resolved.
+                    lsp::CodeLens {
+                        range: lens_range,
+                        command: None,
+                        data: Some(json!(1)),
+                    },
+                ]))
+            }
+        }
+    });
+    let resolve_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>({
+        let resolve_requests = resolve_requests.clone();
+        move |lens, _| {
+            resolve_requests.fetch_add(1, SeqCst);
+            async move {
+                assert_eq!(lens.data, Some(json!(1)));
+                Ok(lsp::CodeLens {
+                    command: Some(lsp::Command {
+                        title: "2 references".into(),
+                        command: "showReferences".into(),
+                        arguments: None,
+                    }),
+                    ..lens
+                })
+            }
+        }
+    });
+
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses
+                .iter()
+                .map(|lens| (
+                    lens.range.to_offset(buffer),
+                    lens.title().to_string(),
+                    lens.executable
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (11..14, "Run test".to_string(), true),
+                (11..14, String::new(), false),
+            ]
+        );
+    });
+    assert_eq!(lenses[0].resolve_state, ResolveState::Resolved);
+    assert_eq!(
+        lenses[1].resolve_state,
+        ResolveState::CanResolve(fake_server.server.server_id(), None)
+    );
+    assert_eq!(resolve_requests.load(SeqCst), 0);
+
+    // Lenses are only requested again once the buffer changes.
+    project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(lens_requests.load(SeqCst), 1);
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(lens_requests.load(SeqCst), 2);
+
+    let resolved_lens = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(lenses[1].clone(), buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(resolved_lens.title(), "2 references");
+    assert!(!resolved_lens.executable);
+    assert_eq!(resolved_lens.resolve_state, ResolveState::Resolved);
+    assert_eq!(resolve_requests.load(SeqCst), 1);
+
+    // Lens commands are run through `workspace/executeCommand`.
+    let mut execute_requests =
+        fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "run");
+            assert_eq!(params.arguments, [json!("one")]);
+            Ok(None)
+        });
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), lenses[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    execute_requests.next().await.unwrap();
+
+    let result = project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), resolved_lens, cx)
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "let c = 1;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let document_pulls = Arc::new(AtomicUsize::new(0));
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            initializer: Some(Box::new({
+                let document_pulls = document_pulls.clone();
+                move |fake_server| {
+                    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+                        |_, _| async move {
+                            Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                                lsp::WorkspaceDiagnosticReport {
+                                    items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                                        lsp::WorkspaceFullDocumentDiagnosticReport {
+                                            uri: lsp::Url::from_file_path("/dir/b.rs").unwrap(),
+                                            version: None,
+                                            full_document_diagnostic_report:
+                                                lsp::FullDocumentDiagnosticReport {
+                                                    result_id: Some("b1".into()),
+                                                    items: vec![lsp::Diagnostic {
+                                                        range: lsp::Range::new(
+                                                            lsp::Position::new(0, 4),
+                                                            lsp::Position::new(0, 5),
+                                                        ),
+                                                        severity: Some(DiagnosticSeverity::WARNING),
+                                                        message: "unused variable".into(),
+                                                        ..Default::default()
+                                                    }],
+                                                },
+                                        },
+                                    )],
+                                },
+                            ))
+                        },
+                    );
+
+                    let document_pulls = document_pulls.clone();
+                    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+                        move |params, _| {
+                            let pull = document_pulls.fetch_add(1, SeqCst);
+                            async move {
+                                assert_eq!(
+                                    params.text_document.uri,
+                                    lsp::Url::from_file_path("/dir/a.rs").unwrap()
+                                );
+                                if pull == 0 {
+                                    assert_eq!(params.previous_result_id, None);
+                                    Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                        lsp::DocumentDiagnosticReport::Full(
+                                            lsp::RelatedFullDocumentDiagnosticReport {
+                                                related_documents: None,
+                                                full_document_diagnostic_report:
+                                                    lsp::FullDocumentDiagnosticReport {
+                                                        result_id: Some("a1".into()),
+                                                        items: vec![lsp::Diagnostic {
+                                                            range: lsp::Range::new(
+                                                                lsp::Position::new(0, 8),
+                                                                lsp::Position::new(0, 9),
+                                                            ),
+                                                            severity: Some(
+                                                                DiagnosticSeverity::ERROR,
+                                                            ),
+                                                            message: "undefined variable".into(),
+                                                            ..Default::default()
+                                                        }],
+                                                    },
+                                            },
+                                        ),
+                                    ))
+                                } else {
+                                    // The diagnostics didn't change since the previous pull.
+                                    assert_eq!(params.previous_result_id.as_deref(), Some("a1"));
+                                    Ok(lsp::DocumentDiagnosticReportResult::Report(
+                                        lsp::DocumentDiagnosticReport::Unchanged(
+                                            lsp::RelatedUnchangedDocumentDiagnosticReport {
+                                                related_documents: None,
+                                                unchanged_document_diagnostic_report:
+                                                    lsp::UnchangedDocumentDiagnosticReport {
+                                                        result_id: "a1".into(),
+                                                    },
+                                            },
+                                        ),
+                                    ))
+                                }
+                            }
+                        },
+                    );
+                }
+            })),
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    fake_servers.next().await.unwrap();
+    cx.executor()
+        .advance_clock(std::time::Duration::from_secs(1));
+    cx.executor().run_until_parked();
+
+    assert_eq!(document_pulls.load(SeqCst), 1);
+    let buffer_diagnostics = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        buffer_diagnostics(cx),
+        [(8..9, "undefined variable".to_string())]
+    );
+    project.update(cx, |project, cx| {
+        let summary = project.diagnostic_summary(false, cx);
+        assert_eq!(summary.error_count, 1);
+        assert_eq!(summary.warning_count, 1);
+    });
+
+    // Diagnostics are pulled again after edits, keeping the previous ones
+    // when the server reports that they're unchanged.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, " ")], None, cx));
+    cx.executor()
+        .advance_clock(std::time::Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(document_pulls.load(SeqCst), 2);
+    assert_eq!(
+        buffer_diagnostics(cx),
+        [(9..10, "undefined variable".to_string())]
+    );
+}
+
+#[gpui::test]
+async fn test_document_links(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "b.rs": "const y: i32 = crate::a()",
+            "a.rs": "// see https://example.com\nmod b;\n",
+            "b.rs": "",
         }),
     )
     .await;
-    fs.insert_tree(
-        "/another_dir",
-        json!({
-        "a.rs": "const fn a() { A }"}),
-    )
-    .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
     language_registry.add(rust_lang());
-    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
     let (buffer, _handle) = project
         .update(cx, |project, cx| {
-            project.open_local_buffer_with_lsp("/dir/b.rs", cx)
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
         })
         .await
         .unwrap();
     let fake_server = fake_servers.next().await.unwrap();
-    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
-        let params = params.text_document_position_params;
-        assert_eq!(
-            params.text_document.uri.to_file_path().unwrap(),
-            Path::new("/dir/b.rs"),
-        );
-        assert_eq!(params.position, lsp::Position::new(0, 22));
 
-        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
-            lsp::Location::new(
-                lsp::Url::from_file_path("/another_dir/a.rs").unwrap(),
-                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-            ),
-        )))
+    let requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::DocumentLinkRequest, _, _>({
+        let requests = requests.clone();
+        move |_, _| {
+            requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(vec![
+                    lsp::DocumentLink {
+                        range: lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 26)),
+                        target: Some("https://example.com".parse().unwrap()),
+                        tooltip: None,
+                        data: None,
+                    },
+                    // This link's target is only computed once it's resolved.
+                    lsp::DocumentLink {
+                        range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 5)),
+                        target: None,
+                        tooltip: Some("Open b.rs".to_string()),
+                        data: Some(json!(1)),
+                    },
+                ]))
+            }
+        }
     });
-    let mut definitions = project
-        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+    let resolve_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::DocumentLinkResolve, _, _>({
+        let resolve_requests = resolve_requests.clone();
+        move |link, _| {
+            resolve_requests.fetch_add(1, SeqCst);
+            async move {
+                assert_eq!(link.data, Some(json!(1)));
+                Ok(lsp::DocumentLink {
+                    target: Some(lsp::Url::from_file_path("/dir/b.rs").unwrap()),
+                    ..link
+                })
+            }
+        }
+    });
+
+    let links = project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
         .await
         .unwrap();
-
-    // Assert no new language server started
-    cx.executor().run_until_parked();
-    assert!(fake_servers.try_next().is_err());
-
-    assert_eq!(definitions.len(), 1);
-    let definition = definitions.pop().unwrap();
-    cx.update(|cx| {
-        let target_buffer = definition.target.buffer.read(cx);
-        assert_eq!(
-            target_buffer
-                .file()
-                .unwrap()
-                .as_local()
-                .unwrap()
-                .abs_path(cx),
-            Path::new("/another_dir/a.rs"),
-        );
-        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+    buffer.read_with(cx, |buffer, _| {
         assert_eq!(
-            list_worktrees(&project, cx),
+            links
+                .iter()
+                .map(|link| (
+                    link.range.to_offset(buffer),
+                    link.target.as_ref().map(|target| target.to_string()),
+                    link.tooltip.clone()
+                ))
+                .collect::<Vec<_>>(),
             [
-                ("/another_dir/a.rs".as_ref(), false),
-                ("/dir".as_ref(), true)
-            ],
+                (7..26, Some("https://example.com/".to_string()), None),
+                (31..32, None, Some("Open b.rs".to_string())),
+            ]
         );
-
-        drop(definition);
-    });
-    cx.update(|cx| {
-        assert_eq!(list_worktrees(&project, cx), [("/dir".as_ref(), true)]);
     });
 
-    fn list_worktrees<'a>(
-        project: &'a Model<Project>,
-        cx: &'a AppContext,
-    ) -> Vec<(&'a Path, bool)> {
-        project
-            .read(cx)
-            .worktrees(cx)
-            .map(|worktree| {
-                let worktree = worktree.read(cx);
-                (
-                    worktree.as_local().unwrap().abs_path().as_ref(),
-                    worktree.is_visible(),
-                )
-            })
-            .collect::<Vec<_>>()
-    }
+    // Links are only resolved on demand.
+    assert_eq!(resolve_requests.load(SeqCst), 0);
+    let resolved_link = project
+        .update(cx, |project, cx| {
+            project.resolve_document_link(links[1].clone(), buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        resolved_link.target,
+        Some(lsp::Url::from_file_path("/dir/b.rs").unwrap())
+    );
+    assert_eq!(resolved_link.resolve_state, ResolveState::Resolved);
+    assert_eq!(resolve_requests.load(SeqCst), 1);
+
+    // Links are cached until the buffer changes.
+    project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(requests.load(SeqCst), 1);
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(requests.load(SeqCst), 2);
 }
 
 #[gpui::test]
@@ -5698,6 +6770,301 @@ async fn test_unstaged_changes_for_buffer(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_file_history(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "src": {
+                "lib.rs": "",
+                "main.rs": "fn main() {}\n",
+            }
+        }),
+    )
+    .await;
+
+    let first_sha = "1111111111111111111111111111111111111111"
+        .parse::<Oid>()
+        .unwrap();
+    let second_sha = "2222222222222222222222222222222222222222"
+        .parse::<Oid>()
+        .unwrap();
+    let entry = |sha: Oid, parents: Vec<Oid>, subject: &str| LogEntry {
+        sha,
+        parents,
+        author_name: "Jane Doe".into(),
+        author_email: "jane@example.com".into(),
+        timestamp: 1700000000,
+        refs: Vec::new(),
+        subject: subject.to_string().into(),
+        paths: Vec::new(),
+    };
+    let details = |sha: Oid, message: &str, path: &str, text: &str| CommitDetails {
+        sha,
+        message: message.to_string().into(),
+        files: vec![CommitFile {
+            path: path.into(),
+            old_text: None,
+            new_text: Some(text.to_string()),
+        }],
+    };
+    fs.with_git_state(Path::new("/dir/.git"), true, |state| {
+        state.log = vec![
+            entry(second_sha, vec![first_sha], "Add lib"),
+            entry(first_sha, Vec::new(), "Add main"),
+        ];
+        state.commit_details.insert(
+            first_sha,
+            details(first_sha, "Add main", "src/main.rs", "fn main() {}\n"),
+        );
+        state
+            .commit_details
+            .insert(second_sha, details(second_sha, "Add lib", "src/lib.rs", ""));
+        state
+            .revision_contents
+            .insert((first_sha, "src/main.rs".into()), "fn main() {}\n".into());
+    });
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/src/main.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let history = project
+        .update(cx, |project, cx| project.file_history(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|entry| (entry.sha, entry.paths.clone()))
+            .collect::<Vec<_>>(),
+        [(first_sha, vec![RepoPath::from("src/main.rs")])]
+    );
+
+    let text = project
+        .update(cx, |project, cx| {
+            project.load_text_at_revision(&buffer, first_sha, "src/main.rs".into(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(text.as_deref(), Some("fn main() {}\n"));
+    let text = project
+        .update(cx, |project, cx| {
+            project.load_text_at_revision(&buffer, second_sha, "src/main.rs".into(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(text, None);
+}
+
+#[gpui::test]
+async fn test_repository_stash_and_amend(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "src": {
+                "main.rs": "fn main() {}\n",
+            }
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.with_git_state(dot_git, true, |state| {
+        state.current_branch_name = Some("main".into());
+        state.log = vec![LogEntry {
+            sha: "1111111111111111111111111111111111111111".parse().unwrap(),
+            parents: Vec::new(),
+            author_name: "Jane Doe".into(),
+            author_email: "jane@example.com".into(),
+            timestamp: 1700000000,
+            refs: Vec::new(),
+            subject: "Add main".into(),
+            paths: Vec::new(),
+        }];
+    });
+    fs.set_status_for_repo_via_git_operation(
+        dot_git,
+        &[(Path::new("src/main.rs"), StatusCode::Modified.worktree())],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let (repository, repo_path) = project.update(cx, |project, cx| {
+        project
+            .git_state()
+            .unwrap()
+            .read(cx)
+            .repository_and_path_for_project_path(&(worktree_id, "src/main.rs").into())
+            .unwrap()
+    });
+    assert_eq!(repo_path, RepoPath::from("src/main.rs"));
+    let (err_sender, mut err_receiver) = futures::channel::mpsc::channel(1);
+
+    repository
+        .set_index_text(repo_path, Some("fn main() {}\n".into()), err_sender.clone())
+        .unwrap();
+    repository
+        .stash_push(Some("wip".into()), err_sender.clone())
+        .unwrap();
+    cx.run_until_parked();
+    fs.with_git_state(dot_git, false, |state| {
+        assert_eq!(
+            state.index_contents.get(Path::new("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        assert!(state.statuses.is_empty());
+    });
+    let stash_entries = project
+        .update(cx, |_, cx| repository.stash_list(cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        stash_entries,
+        [StashEntry {
+            index: 0,
+            message: "On main: wip".into(),
+        }]
+    );
+
+    repository.stash_pop(0, err_sender.clone()).unwrap();
+    cx.run_until_parked();
+    fs.with_git_state(dot_git, false, |state| {
+        assert!(state.stashes.is_empty());
+        assert_eq!(state.statuses.len(), 1);
+    });
+
+    // Popping a stash entry that doesn't exist reports an error.
+    repository.stash_pop(0, err_sender.clone()).unwrap();
+    cx.run_until_parked();
+    assert!(err_receiver.try_next().unwrap().is_some());
+
+    repository.commit_message().update(cx, |message, cx| {
+        message.set_text("Add the main function", cx);
+    });
+    cx.update(|cx| repository.amend(err_sender, cx));
+    cx.run_until_parked();
+    fs.with_git_state(dot_git, false, |state| {
+        assert_eq!(state.log[0].subject.as_ref(), "Add the main function");
+    });
+    assert_eq!(
+        repository
+            .commit_message()
+            .read_with(cx, |message, _| message.text()),
+        ""
+    );
+}
+
+#[gpui::test]
+async fn test_repository_remote_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "main.rs": "fn main() {}\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.with_git_state(dot_git, true, |state| {
+        state.current_branch_name = Some("main".into());
+    });
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.run_until_parked();
+    let active_repository = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| project.active_repository(cx).unwrap())
+    };
+    let run = |operation, cx: &mut gpui::TestAppContext| {
+        let repository = active_repository(cx);
+        cx.update(|cx| {
+            repository.run_remote_operation(
+                operation,
+                |_, _| futures::channel::oneshot::channel().1,
+                cx,
+            )
+        })
+    };
+    assert_eq!(active_repository(cx).upstream_tracking(), None);
+    let push = RemoteOperation::Push {
+        set_upstream: false,
+    };
+    let publish = RemoteOperation::Push { set_upstream: true };
+
+    // Pushing requires an upstream, unless the branch is published.
+    assert!(run(push, cx).await.is_err());
+    let publish_task = run(publish, cx);
+    project.read_with(cx, |project, cx| {
+        let git_state = project.git_state().unwrap().read(cx);
+        assert_eq!(git_state.remote_operation().unwrap().operation, publish);
+    });
+    // Only one remote operation runs at a time.
+    assert!(run(RemoteOperation::Fetch, cx).await.is_err());
+    publish_task.await.unwrap();
+    cx.run_until_parked();
+    project.read_with(cx, |project, cx| {
+        assert!(project
+            .git_state()
+            .unwrap()
+            .read(cx)
+            .remote_operation()
+            .is_none());
+    });
+    assert_eq!(
+        active_repository(cx).upstream_tracking(),
+        Some(UpstreamTracking {
+            ahead: 0,
+            behind: 0
+        })
+    );
+
+    fs.with_git_state(dot_git, true, |state| {
+        state.upstream_tracking.as_mut().unwrap().ahead = 1;
+        state.unfetched_commits = 2;
+    });
+    run(RemoteOperation::Fetch, cx).await.unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        active_repository(cx).upstream_tracking(),
+        Some(UpstreamTracking {
+            ahead: 1,
+            behind: 2
+        })
+    );
+
+    // The fetched commits need to be pulled before pushing.
+    assert!(run(push, cx).await.is_err());
+    run(RemoteOperation::Pull { rebase: true }, cx)
+        .await
+        .unwrap();
+    run(push, cx).await.unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        active_repository(cx).upstream_tracking(),
+        Some(UpstreamTracking {
+            ahead: 0,
+            behind: 0
+        })
+    );
+}
+
 async fn search(
     project: &Model<Project>,
     query: SearchQuery,
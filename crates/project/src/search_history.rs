@@ -42,6 +42,11 @@ impl SearchHistory {
         }
     }
 
+    /// The searches in the history, from the oldest to the most recent.
+    pub fn entries(&self) -> &[String] {
+        &self.history
+    }
+
     pub fn add(&mut self, cursor: &mut SearchHistoryCursor, search_string: String) {
         if let Some(selected_ix) = cursor.selection {
             if self.history.get(selected_ix) == Some(&search_string) {
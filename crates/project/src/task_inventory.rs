@@ -8,14 +8,15 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use collections::{HashMap, HashSet, VecDeque};
 use gpui::{AppContext, Context as _, Model, Task};
 use itertools::Itertools;
 use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables,
+    VariableName,
 };
 use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
@@ -181,12 +182,11 @@ impl Inventory {
 
         let new_resolved_tasks = worktree_tasks
             .filter_map(|(kind, task)| {
-                let id_base = kind.to_id_base();
-                Some((
-                    kind,
-                    task.resolve_task(&id_base, task_context)?,
-                    not_used_score,
-                ))
+                let resolved_task = self
+                    .resolve_task(&kind, &task, worktree, task_context)
+                    .log_err()
+                    .flatten()?;
+                Some((kind, resolved_task, not_used_score))
             })
             .filter(|(_, resolved_task, _)| {
                 match task_labels_to_ids.entry(resolved_task.resolved_label.clone()) {
@@ -207,6 +207,43 @@ impl Inventory {
         (previously_spawned_tasks, new_resolved_tasks)
     }
 
+    /// Resolves the template given with the [`TaskContext`], along with the tasks it depends on, recursively.
+    /// Dependencies are looked up by their labels among the tasks of the template's worktree (or the `worktree` given,
+    /// for the templates from other sources) first, and the global tasks after.
+    ///
+    /// Fails if any dependency is missing, cannot be resolved, or the dependencies form a cycle.
+    pub fn resolve_task(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        template: &TaskTemplate,
+        worktree: Option<WorktreeId>,
+        task_context: &TaskContext,
+    ) -> Result<Option<ResolvedTask>> {
+        let Some(mut resolved_task) =
+            template.resolve_task(&task_source_kind.to_id_base(), task_context)
+        else {
+            return Ok(None);
+        };
+        if template.depends_on.is_empty() {
+            return Ok(Some(resolved_task));
+        }
+
+        let worktree = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => worktree,
+        };
+        let templates = self
+            .worktree_templates_from_settings(worktree)
+            .chain(self.global_templates_from_settings())
+            .collect::<Vec<_>>();
+        let mut labels_stack = vec![template.label.clone()];
+        if let Some(spawn_in_terminal) = resolved_task.resolved.as_mut() {
+            spawn_in_terminal.dependencies =
+                resolve_dependencies(template, &templates, task_context, &mut labels_stack)?;
+        }
+        Ok(Some(resolved_task))
+    }
+
     /// Returns the last scheduled task by task_id if provided.
     /// Otherwise, returns the last scheduled task.
     pub fn last_scheduled_task(
@@ -328,6 +365,48 @@ impl Inventory {
     }
 }
 
+/// Resolves the tasks the template depends on, with their own dependencies.
+/// `labels_stack` holds the labels of the tasks being resolved, to detect the dependency cycles.
+fn resolve_dependencies(
+    template: &TaskTemplate,
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    task_context: &TaskContext,
+    labels_stack: &mut Vec<String>,
+) -> Result<Vec<SpawnInTerminal>> {
+    let mut dependencies = Vec::with_capacity(template.depends_on.len());
+    for label in &template.depends_on {
+        if let Some(cycle_start) = labels_stack.iter().position(|parent| parent == label) {
+            bail!(
+                "task dependency cycle: {}",
+                labels_stack[cycle_start..]
+                    .iter()
+                    .chain(Some(label))
+                    .map(|label| format!("`{label}`"))
+                    .join(" -> ")
+            );
+        }
+        let (kind, dependency) = templates
+            .iter()
+            .find(|(_, dependency)| &dependency.label == label)
+            .with_context(|| {
+                format!(
+                    "task `{}` depends on an unknown task `{label}`",
+                    template.label
+                )
+            })?;
+        let mut resolved = dependency
+            .resolve_task(&kind.to_id_base(), task_context)
+            .and_then(|resolved_task| resolved_task.resolved)
+            .with_context(|| format!("failed to resolve task `{label}`"))?;
+        labels_stack.push(label.clone());
+        resolved.dependencies =
+            resolve_dependencies(dependency, templates, task_context, labels_stack)?;
+        labels_stack.pop();
+        dependencies.push(resolved);
+    }
+    Ok(dependencies)
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -564,6 +643,7 @@ mod tests {
     use gpui::TestAppContext;
     use pretty_assertions::assert_eq;
     use serde_json::json;
+    use task::DependsOrder;
 
     use crate::task_store::TaskStore;
 
@@ -851,6 +931,100 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        let worktree = WorktreeId::from_usize(1);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            { "label": "lint", "command": "cargo clippy" },
+                            { "label": "ping", "command": "echo", "depends_on": ["pong"] },
+                            { "label": "pong", "command": "echo", "depends_on": ["ping"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+            inventory
+                .update_file_based_tasks(
+                    Some(SettingsLocation {
+                        worktree_id: worktree,
+                        path: Path::new(".zed"),
+                    }),
+                    Some(
+                        &json!([
+                            { "label": "build", "command": "cargo build" },
+                            { "label": "test", "command": "cargo test", "depends_on": ["build"] },
+                            {
+                                "label": "check",
+                                "depends_on": ["test", "lint"],
+                                "depends_order": "parallel",
+                            },
+                            { "label": "deploy", "command": "deploy", "depends_on": ["release"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let resolve = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (kind, template) = inventory
+                    .list_tasks(None, None, Some(worktree), cx)
+                    .into_iter()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                inventory
+                    .resolve_task(&kind, &template, Some(worktree), &TaskContext::default())
+                    .map(|resolved_task| resolved_task.unwrap().resolved.unwrap())
+            })
+        };
+        fn labels(task: &SpawnInTerminal) -> Vec<String> {
+            task.dependencies
+                .iter()
+                .map(|dependency| dependency.label.clone())
+                .collect()
+        }
+
+        let check = resolve("check", cx).unwrap();
+        assert_eq!(check.command, "");
+        assert_eq!(check.depends_order, DependsOrder::Parallel);
+        assert_eq!(labels(&check), vec!["test", "lint"]);
+        assert_eq!(labels(&check.dependencies[0]), vec!["build"]);
+        assert_eq!(
+            check.dependencies[0].depends_order,
+            DependsOrder::Sequence,
+            "should run dependencies one after another by default"
+        );
+        assert!(check.dependencies[1].dependencies.is_empty());
+
+        assert_eq!(
+            resolve("ping", cx).unwrap_err().to_string(),
+            "task dependency cycle: `ping` -> `pong` -> `ping`"
+        );
+        assert_eq!(
+            resolve("deploy", cx).unwrap_err().to_string(),
+            "task `deploy` depends on an unknown task `release`"
+        );
+
+        assert_eq!(
+            list_tasks_sorted_by_last_used(&inventory, Some(worktree), cx)
+                .await
+                .into_iter()
+                .map(|(_, label)| label)
+                .sorted()
+                .collect::<Vec<_>>(),
+            vec!["build", "check", "lint", "test"],
+            "should not list the tasks with broken dependencies"
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
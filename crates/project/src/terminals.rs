@@ -13,7 +13,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, SpawnInTerminal};
+use task::{ProblemMatcher, Shell, ShellBuilder, SpawnInTerminal};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -229,6 +229,7 @@ impl Project {
         };
 
         let mut python_venv_activate_command = None;
+        let mut problem_matchers = None;
 
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
@@ -264,6 +265,11 @@ impl Project {
                 }
             }
             TerminalKind::Task(spawn_task) => {
+                // Diagnostics are only reported for files of local projects.
+                if ssh_details.is_none() && !spawn_task.problem_matchers.is_empty() {
+                    problem_matchers =
+                        Some((spawn_task.label.clone(), spawn_task.problem_matchers));
+                }
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -362,10 +368,67 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+            if let Some((task_label, problem_matchers)) = problem_matchers {
+                this.report_task_problems(
+                    &terminal_handle,
+                    task_label,
+                    problem_matchers,
+                    path.map(|path| path.to_path_buf()),
+                    cx,
+                );
+            }
             terminal_handle
         })
     }
 
+    /// Clears the diagnostics of the task's previous runs, and reports the
+    /// problems found in its output once it finishes.
+    fn report_task_problems(
+        &mut self,
+        terminal: &Model<Terminal>,
+        task_label: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<PathBuf>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_task_diagnostics(&task_label, Vec::new(), cx)
+                .log_err();
+        });
+        cx.subscribe(terminal, move |project, terminal, event, cx| {
+            if !matches!(event, terminal::Event::TaskFinished) {
+                return;
+            }
+            let output_lines = terminal.read(cx).output_lines();
+            let problem_matchers = problem_matchers.clone();
+            let task_label = task_label.clone();
+            let cwd = cwd.clone();
+            let lsp_store = project.lsp_store.clone();
+            cx.spawn(|_, mut cx| async move {
+                let problems = cx
+                    .background_executor()
+                    .spawn({
+                        let task_label = task_label.clone();
+                        async move {
+                            task::match_problems(
+                                &problem_matchers,
+                                &output_lines,
+                                cwd.as_deref(),
+                                &task_label,
+                            )
+                        }
+                    })
+                    .await;
+                lsp_store.update(&mut cx, |lsp_store, cx| {
+                    lsp_store.update_task_diagnostics(&task_label, problems, cx)
+                })?
+            })
+            .detach_and_log_err(cx);
+        })
+        .detach();
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
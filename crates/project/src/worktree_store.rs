@@ -11,6 +11,10 @@ use futures::{
     future::{BoxFuture, Shared},
     FutureExt, SinkExt,
 };
+use git::{
+    remote_command::RemoteCommandDelegate,
+    repository::{GitRepository, RepoPath},
+};
 use gpui::{
     AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -78,6 +82,16 @@ impl WorktreeStore {
         client.add_model_request_handler(Self::handle_expand_project_entry);
         client.add_model_request_handler(Self::handle_git_branches);
         client.add_model_request_handler(Self::handle_update_branch);
+        client.add_model_request_handler(Self::handle_git_stage);
+        client.add_model_request_handler(Self::handle_git_unstage);
+        client.add_model_request_handler(Self::handle_git_commit);
+        client.add_model_request_handler(Self::handle_git_set_index_text);
+        client.add_model_request_handler(Self::handle_git_stash_push);
+        client.add_model_request_handler(Self::handle_git_stash_pop);
+        client.add_model_request_handler(Self::handle_git_stash_list);
+        client.add_model_request_handler(Self::handle_git_fetch);
+        client.add_model_request_handler(Self::handle_git_pull);
+        client.add_model_request_handler(Self::handle_git_push);
     }
 
     pub fn local(retain_worktrees: bool, fs: Arc<dyn Fs>) -> Self {
@@ -1133,6 +1147,201 @@ impl WorktreeStore {
 
         Ok(proto::Ack {})
     }
+
+    pub async fn handle_git_stage(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStage>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let paths = deserialize_repo_paths(&envelope.payload.paths);
+        cx.background_executor()
+            .spawn(async move { repo.stage_paths(&paths) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_unstage(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitUnstage>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let paths = deserialize_repo_paths(&envelope.payload.paths);
+        cx.background_executor()
+            .spawn(async move { repo.unstage_paths(&paths) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_commit(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitCommit>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let message = envelope.payload.message;
+        let amend = envelope.payload.amend;
+        cx.background_executor()
+            .spawn(async move {
+                if amend {
+                    repo.amend_commit(message.as_deref())
+                } else {
+                    repo.commit(&message.context("missing commit message")?)
+                }
+            })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_set_index_text(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitSetIndexText>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let path = RepoPath::from_str(&envelope.payload.path);
+        let text = envelope.payload.text;
+        cx.background_executor()
+            .spawn(async move { repo.set_index_text(&path, text) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_push(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let message = envelope.payload.message;
+        cx.background_executor()
+            .spawn(async move { repo.stash_push(message.as_deref()) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_pop(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPop>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let index = envelope.payload.index as usize;
+        cx.background_executor()
+            .spawn(async move { repo.stash_pop(index) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_list(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitStashListResponse> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let entries = cx
+            .background_executor()
+            .spawn(async move { repo.stash_list() })
+            .await?;
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::GitStashEntry {
+                    index: entry.index as u64,
+                    message: entry.message.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_fetch(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitFetch>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        cx.background_executor()
+            .spawn(async move { repo.fetch(&RemoteRequestDelegate) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_pull(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitPull>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let rebase = envelope.payload.rebase;
+        cx.background_executor()
+            .spawn(async move { repo.pull(rebase, &RemoteRequestDelegate) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_push(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitPush>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repo = Self::repository_for_request(&this, envelope.payload.repository, &cx)?;
+        let set_upstream = envelope.payload.set_upstream;
+        cx.background_executor()
+            .spawn(async move { repo.push(set_upstream, &RemoteRequestDelegate) })
+            .await?;
+        Ok(proto::Ack {})
+    }
+
+    /// Finds the local repository whose work directory a request from a
+    /// remote client refers to.
+    fn repository_for_request(
+        this: &Model<Self>,
+        repository: Option<proto::ProjectPath>,
+        cx: &AsyncAppContext,
+    ) -> Result<Arc<dyn GitRepository>> {
+        let repository = repository.context("missing repository")?;
+        this.read_with(cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(WorktreeId::from_proto(repository.worktree_id), cx)
+                .context("no worktree found for repository")?;
+            let worktree = worktree
+                .read(cx)
+                .as_local()
+                .context("repository's worktree is not local")?;
+            let entry = worktree
+                .git_entry(Path::new(&repository.path).into())
+                .with_context(|| format!("no git entry found for {:?}", repository.path))?;
+            anyhow::Ok(
+                worktree
+                    .get_local_repo(&entry)
+                    .context("no repository found")?
+                    .repo()
+                    .clone(),
+            )
+        })?
+    }
+}
+
+fn deserialize_repo_paths(paths: &[String]) -> Vec<RepoPath> {
+    paths.iter().map(|path| RepoPath::from_str(path)).collect()
+}
+
+/// Runs the remote commands requested by clients, whose users can't be
+/// prompted for credentials from the host.
+struct RemoteRequestDelegate;
+
+impl RemoteCommandDelegate for RemoteRequestDelegate {
+    fn progress(&self, _message: &str) {}
+
+    fn ask_password(&self, prompt: String) -> futures::channel::oneshot::Receiver<Result<String>> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        tx.send(Err(anyhow!(
+            "cannot prompt for credentials on the host of the project: {prompt}"
+        )))
+        .ok();
+        rx
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -214,6 +214,33 @@ messages!(
     (GetTypeDefinitionResponse, Background),
     (GetImplementation, Background),
     (GetImplementationResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokensFull, Background),
+    (GetSemanticTokensDelta, Background),
+    (SemanticTokensResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
     (GetLlmToken, Background),
     (GetLlmTokenResponse, Background),
     (GetStagedText, Foreground),
@@ -256,6 +283,8 @@ messages!(
     (CountLanguageModelTokensResponse, Background),
     (RefreshLlmToken, Background),
     (RefreshInlayHints, Foreground),
+    (RefreshSemanticTokens, Foreground),
+    (RefreshCodeLens, Foreground),
     (RejoinChannelBuffers, Foreground),
     (RejoinChannelBuffersResponse, Foreground),
     (RejoinRoom, Foreground),
@@ -358,8 +387,23 @@ messages!(
     (LanguageServerPromptRequest, Foreground),
     (LanguageServerPromptResponse, Foreground),
     (GitBranches, Background),
+    (GitFileHistory, Background),
+    (GitFileHistoryResponse, Background),
+    (LoadFileAtRevision, Background),
+    (LoadFileAtRevisionResponse, Background),
     (GitBranchesResponse, Background),
     (UpdateGitBranch, Background),
+    (GitStage, Background),
+    (GitUnstage, Background),
+    (GitCommit, Background),
+    (GitSetIndexText, Background),
+    (GitStashPush, Background),
+    (GitStashPop, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitFetch, Background),
+    (GitPull, Background),
+    (GitPush, Background),
     (ListToolchains, Foreground),
     (ListToolchainsResponse, Foreground),
     (ActivateToolchain, Foreground),
@@ -406,6 +450,20 @@ request_messages!(
     (GetDefinition, GetDefinitionResponse),
     (GetDeclaration, GetDeclarationResponse),
     (GetImplementation, GetImplementationResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokensFull, SemanticTokensResponse),
+    (GetSemanticTokensDelta, SemanticTokensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
     (GetHover, GetHoverResponse),
     (GetLlmToken, GetLlmTokenResponse),
@@ -442,6 +500,8 @@ request_messages!(
     (PrepareRename, PrepareRenameResponse),
     (CountLanguageModelTokens, CountLanguageModelTokensResponse),
     (RefreshInlayHints, Ack),
+    (RefreshSemanticTokens, Ack),
+    (RefreshCodeLens, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
     (ReloadBuffers, ReloadBuffersResponse),
@@ -491,7 +551,19 @@ request_messages!(
     (FlushBufferedMessages, Ack),
     (LanguageServerPromptRequest, LanguageServerPromptResponse),
     (GitBranches, GitBranchesResponse),
+    (GitFileHistory, GitFileHistoryResponse),
+    (LoadFileAtRevision, LoadFileAtRevisionResponse),
     (UpdateGitBranch, Ack),
+    (GitStage, Ack),
+    (GitUnstage, Ack),
+    (GitCommit, Ack),
+    (GitSetIndexText, Ack),
+    (GitStashPush, Ack),
+    (GitStashPop, Ack),
+    (GitStashList, GitStashListResponse),
+    (GitFetch, Ack),
+    (GitPull, Ack),
+    (GitPush, Ack),
     (ListToolchains, ListToolchainsResponse),
     (ActivateToolchain, Ack),
     (ActiveToolchain, ActiveToolchainResponse),
@@ -525,6 +597,20 @@ entity_messages!(
     GetDefinition,
     GetDeclaration,
     GetImplementation,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokensFull,
+    GetSemanticTokensDelta,
+    GetFoldingRanges,
+    GetCodeLens,
+    ResolveCodeLens,
+    GetDocumentLinks,
+    ResolveDocumentLink,
+    GetSelectionRanges,
     GetDocumentHighlights,
     GetHover,
     GetProjectSymbols,
@@ -546,6 +632,8 @@ entity_messages!(
     PerformRename,
     PrepareRename,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -580,7 +668,19 @@ entity_messages!(
     GetPermalinkToLine,
     LanguageServerPromptRequest,
     GitBranches,
+    GitFileHistory,
+    LoadFileAtRevision,
     UpdateGitBranch,
+    GitStage,
+    GitUnstage,
+    GitCommit,
+    GitSetIndexText,
+    GitStashPush,
+    GitStashPop,
+    GitStashList,
+    GitFetch,
+    GitPull,
+    GitPush,
     ListToolchains,
     ActivateToolchain,
     ActiveToolchain,
@@ -6,7 +6,7 @@ use futures::{
     future::Shared,
     stream,
 };
-use gpui::{AppContext, Model, Task, WindowContext};
+use gpui::{AppContext, Model, Task, ViewContext, WindowContext};
 use language::LanguageName;
 pub use native_kernel::*;
 
@@ -141,6 +141,12 @@ pub fn python_env_kernel_specifications(
     }
 }
 
+/// A view that a kernel reports its messages to, like a REPL session or a notebook.
+pub trait KernelSession: Sized {
+    fn route(&mut self, message: &JupyterMessage, cx: &mut ViewContext<Self>);
+    fn kernel_errored(&mut self, error_message: String, cx: &mut ViewContext<Self>);
+}
+
 pub trait RunningKernel: Send + Debug {
     fn request_tx(&self) -> mpsc::Sender<JupyterMessage>;
     fn working_directory(&self) -> &PathBuf;
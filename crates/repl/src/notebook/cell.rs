@@ -2,11 +2,15 @@
 use std::sync::Arc;
 
 use editor::{Editor, EditorMode, MultiBuffer};
-use futures::future::Shared;
-use gpui::{prelude::*, AppContext, Hsla, Task, TextStyleRefinement, View};
+use futures::{future::Shared, FutureExt as _};
+use gpui::{
+    prelude::*, AppContext, ClickEvent, EventEmitter, Hsla, Task, TextStyleRefinement, View,
+};
 use language::{Buffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
 use nbformat::v4::{CellId, CellMetadata, CellType};
+use runtimelib::{ExecutionState, JupyterMessageContent, UpdateDisplayData};
+use serde_json::json;
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{prelude::*, IconButtonShape};
@@ -80,28 +84,140 @@ pub enum Cell {
     Raw(View<RawCell>),
 }
 
-fn convert_outputs(outputs: &Vec<nbformat::v4::Output>, cx: &mut WindowContext) -> Vec<Output> {
+/// Emitted by cells asking for them to be run.
+pub enum CellEvent {
+    Run,
+}
+
+/// Returns a new, empty cell of the given type.
+pub fn new_cell(cell_type: CellType) -> nbformat::v4::Cell {
+    let id = uuid::Uuid::new_v4().to_string();
+    // Cells are built from their JSON so that they get nbformat's defaults.
+    let cell = match cell_type {
+        CellType::Code => json!({
+            "cell_type": "code",
+            "id": id,
+            "metadata": {},
+            "source": [],
+            "execution_count": null,
+            "outputs": [],
+        }),
+        CellType::Markdown => json!({
+            "cell_type": "markdown",
+            "id": id,
+            "metadata": {},
+            "source": [],
+        }),
+        CellType::Raw => json!({
+            "cell_type": "raw",
+            "id": id,
+            "metadata": {},
+            "source": [],
+        }),
+    };
+    serde_json::from_value(cell).expect("new cells are valid")
+}
+
+/// Splits the source of a cell into lines, the way nbformat stores it.
+fn source_lines(source: &str) -> Vec<String> {
+    source.split_inclusive('\n').map(str::to_string).collect()
+}
+
+/// Converts an output message of the kernel into the output stored in the notebook,
+/// like Jupyter does: the message's content, along with its type.
+fn output_from_message(content: &JupyterMessageContent) -> Option<nbformat::v4::Output> {
+    let (output_type, content) = match content {
+        JupyterMessageContent::StreamContent(stream) => ("stream", serde_json::to_value(stream)),
+        JupyterMessageContent::DisplayData(data) => ("display_data", serde_json::to_value(data)),
+        JupyterMessageContent::ExecuteResult(result) => {
+            ("execute_result", serde_json::to_value(result))
+        }
+        JupyterMessageContent::ErrorOutput(error) => ("error", serde_json::to_value(error)),
+        _ => return None,
+    };
+    let mut output = content.log_err()?;
+    let fields = output.as_object_mut()?;
+    // Transient data only lives as long as the kernel does.
+    fields.remove("transient");
+    fields.insert("output_type".to_string(), output_type.into());
+    serde_json::from_value(output).log_err()
+}
+
+fn cell_editor<V: 'static>(
+    text: String,
+    language: Shared<Task<Option<Arc<Language>>>>,
+    cx: &mut ViewContext<V>,
+) -> (View<Editor>, Task<()>) {
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+    let multi_buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+
+    let editor_view = cx.new_view(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight { max_lines: 1024 },
+            multi_buffer,
+            None,
+            false,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size.into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    });
+
+    let language_task = cx.spawn(|_, mut cx| async move {
+        let language = language.await;
+
+        buffer
+            .update(&mut cx, |buffer, cx| {
+                buffer.set_language(language, cx);
+            })
+            .ok();
+    });
+
+    (editor_view, language_task)
+}
+
+fn convert_outputs(outputs: &[nbformat::v4::Output], cx: &mut WindowContext) -> Vec<Output> {
     outputs
         .into_iter()
-        .map(|output| match output {
-            nbformat::v4::Output::Stream { text, .. } => Output::Stream {
-                content: cx.new_view(|cx| TerminalOutput::from(&text.0, cx)),
-            },
-            nbformat::v4::Output::DisplayData(display_data) => {
-                Output::new(&display_data.data, None, cx)
-            }
-            nbformat::v4::Output::ExecuteResult(execute_result) => {
-                Output::new(&execute_result.data, None, cx)
-            }
-            nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
-                ename: error.ename.clone(),
-                evalue: error.evalue.clone(),
-                traceback: cx.new_view(|cx| TerminalOutput::from(&error.traceback.join("\n"), cx)),
-            }),
-        })
+        .map(|output| convert_output(output, None, cx))
         .collect()
 }
 
+fn convert_output(
+    output: &nbformat::v4::Output,
+    display_id: Option<String>,
+    cx: &mut WindowContext,
+) -> Output {
+    match output {
+        nbformat::v4::Output::Stream { text, .. } => Output::Stream {
+            content: cx.new_view(|cx| TerminalOutput::from(&text.0, cx)),
+        },
+        nbformat::v4::Output::DisplayData(display_data) => {
+            Output::new(&display_data.data, display_id, cx)
+        }
+        nbformat::v4::Output::ExecuteResult(execute_result) => {
+            Output::new(&execute_result.data, display_id, cx)
+        }
+        nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
+            ename: error.ename.clone(),
+            evalue: error.evalue.clone(),
+            traceback: cx.new_view(|cx| TerminalOutput::from(&error.traceback.join("\n"), cx)),
+        }),
+    }
+}
+
 impl Cell {
     pub fn load(
         cell: &nbformat::v4::Cell,
@@ -117,37 +233,31 @@ impl Cell {
                 ..
             } => {
                 let source = source.join("");
+                let markdown_language = {
+                    let languages = languages.clone();
+                    cx.spawn(|_| async move { languages.language_for_name("Markdown").await.ok() })
+                        .shared()
+                };
 
                 let view = cx.new_view(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn(|this, mut cx| async move {
-                            let parsed_markdown = cx
-                                .background_executor()
-                                .spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(&mut cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
-
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let (editor, language_task) =
+                        cell_editor(source.clone(), markdown_language, cx);
+                    let mut cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
                         source: source.clone(),
+                        nbformat_cell: cell.clone(),
+                        editor,
+                        editing: false,
+                        language_task,
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                    };
+                    cell.parse_markdown(cx);
+                    cell
                 });
 
                 Cell::Markdown(view)
@@ -159,54 +269,19 @@ impl Cell {
                 source,
                 outputs,
             } => Cell::Code(cx.new_view(|cx| {
-                let text = source.join("");
-
-                let buffer = cx.new_model(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new_view(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight { max_lines: 1024 },
-                        multi_buffer,
-                        None,
-                        false,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size.into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
-
-                let buffer = buffer.clone();
-                let language_task = cx.spawn(|this, mut cx| async move {
-                    let language = notebook_language.await;
-
-                    buffer.update(&mut cx, |buffer, cx| {
-                        buffer.set_language(language.clone(), cx);
-                    });
-                });
+                let (editor, language_task) = cell_editor(source.join(""), notebook_language, cx);
 
                 CodeCell {
                     id: id.clone(),
                     metadata: metadata.clone(),
                     execution_count: *execution_count,
                     source: source.join(""),
-                    editor: editor_view,
+                    nbformat_cell: cell.clone(),
+                    editor,
                     outputs: convert_outputs(outputs, cx),
+                    output_data: outputs.clone(),
+                    clear_on_next_output: false,
+                    executing: false,
                     selected: false,
                     language_task,
                     cell_position: None,
@@ -220,11 +295,30 @@ impl Cell {
                 id: id.clone(),
                 metadata: metadata.clone(),
                 source: source.join(""),
+                nbformat_cell: cell.clone(),
                 selected: false,
                 cell_position: None,
             })),
         }
     }
+
+    /// The editor of the cell's source, for the cells that can be edited.
+    pub fn editor(&self, cx: &AppContext) -> Option<View<Editor>> {
+        match self {
+            Cell::Code(cell) => Some(cell.read(cx).editor.clone()),
+            Cell::Markdown(cell) => Some(cell.read(cx).editor.clone()),
+            Cell::Raw(_) => None,
+        }
+    }
+
+    /// Returns the cell as stored in the notebook, with its current source and outputs.
+    pub fn to_nbformat(&self, cx: &AppContext) -> nbformat::v4::Cell {
+        match self {
+            Cell::Code(cell) => cell.read(cx).to_nbformat(cx),
+            Cell::Markdown(cell) => cell.read(cx).to_nbformat(cx),
+            Cell::Raw(cell) => cell.read(cx).nbformat_cell.clone(),
+        }
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -321,6 +415,12 @@ pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    /// The cell as it was loaded, keeping the fields that Zed doesn't edit.
+    nbformat_cell: nbformat::v4::Cell,
+    editor: View<Editor>,
+    /// Whether the source is shown for editing, rather than rendered.
+    editing: bool,
+    language_task: Task<()>,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
@@ -328,6 +428,58 @@ pub struct MarkdownCell {
     languages: Arc<LanguageRegistry>,
 }
 
+impl MarkdownCell {
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Shows the cell's source for editing.
+    pub fn start_editing(&mut self, cx: &mut ViewContext<Self>) {
+        self.editing = true;
+        cx.focus_view(&self.editor);
+        cx.notify();
+    }
+
+    /// Renders the edited source of the cell.
+    pub fn finish_editing(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.editing {
+            return;
+        }
+        self.editing = false;
+        self.source = self.editor.read(cx).text(cx);
+        self.parse_markdown(cx);
+        cx.notify();
+    }
+
+    fn parse_markdown(&mut self, cx: &mut ViewContext<Self>) {
+        let languages = self.languages.clone();
+        let source = self.source.clone();
+
+        self.markdown_parsing_task = cx.spawn(|this, mut cx| async move {
+            let parsed_markdown = cx
+                .background_executor()
+                .spawn(async move { parse_markdown(&source, None, Some(languages)).await })
+                .await;
+
+            this.update(&mut cx, |cell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    fn to_nbformat(&self, cx: &AppContext) -> nbformat::v4::Cell {
+        let mut cell = self.nbformat_cell.clone();
+        if let nbformat::v4::Cell::Markdown { source, .. } = &mut cell {
+            *source = source_lines(&self.editor.read(cx).text(cx));
+        }
+        cell
+    }
+}
+
+impl EventEmitter<CellEvent> for MarkdownCell {}
+
 impl RenderableCell for MarkdownCell {
     const CELL_TYPE: CellType = CellType::Markdown;
 
@@ -356,8 +508,11 @@ impl RenderableCell for MarkdownCell {
         self
     }
 
-    fn control(&self, _: &ViewContext<Self>) -> Option<CellControl> {
-        None
+    fn control(&self, cx: &ViewContext<Self>) -> Option<CellControl> {
+        self.editing.then(|| {
+            CellControl::new("render-cell", CellControlType::RunCell)
+                .on_click(cx.listener(move |this, _, cx| this.finish_editing(cx)))
+        })
     }
 
     fn cell_position(&self) -> Option<&CellPosition> {
@@ -372,13 +527,54 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let Some(parsed) = self.parsed_markdown.as_ref() else {
-            return div();
+        let content = if self.editing {
+            div()
+                .py_1p5()
+                .w_full()
+                .child(
+                    div()
+                        .flex()
+                        .size_full()
+                        .flex_1()
+                        .py_3()
+                        .px_5()
+                        .rounded_lg()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .bg(cx.theme().colors().editor_background)
+                        .child(div().w_full().child(self.editor.clone())),
+                )
+                .into_any_element()
+        } else {
+            let Some(parsed) = self.parsed_markdown.as_ref() else {
+                return div();
+            };
+
+            let mut markdown_render_context =
+                markdown_preview::markdown_renderer::RenderContext::new(None, cx);
+
+            v_flex()
+                .id("markdown-content")
+                .size_full()
+                .flex_1()
+                .p_3()
+                .font_ui(cx)
+                .text_size(TextSize::Default.rems(cx))
+                .children(parsed.children.iter().map(|child| {
+                    div().relative().child(
+                        div()
+                            .relative()
+                            .child(render_markdown_block(child, &mut markdown_render_context)),
+                    )
+                }))
+                .on_click(cx.listener(|this, event: &ClickEvent, cx| {
+                    if event.up.click_count == 2 {
+                        this.start_editing(cx);
+                    }
+                }))
+                .into_any_element()
         };
 
-        let mut markdown_render_context =
-            markdown_preview::markdown_renderer::RenderContext::new(None, cx);
-
         v_flex()
             .size_full()
             // TODO: Move base cell render into trait impl so we don't have to repeat this
@@ -392,20 +588,7 @@ impl Render for MarkdownCell {
                     .gap(DynamicSpacing::Base08.rems(cx))
                     .bg(self.selected_bg_color(cx))
                     .child(self.gutter(cx))
-                    .child(
-                        v_flex()
-                            .size_full()
-                            .flex_1()
-                            .p_3()
-                            .font_ui(cx)
-                            .text_size(TextSize::Default.rems(cx))
-                            //
-                            .children(parsed.children.iter().map(|child| {
-                                div().relative().child(div().relative().child(
-                                    render_markdown_block(child, &mut markdown_render_context),
-                                ))
-                            })),
-                    ),
+                    .child(content),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, cx))
@@ -417,23 +600,173 @@ pub struct CodeCell {
     metadata: CellMetadata,
     execution_count: Option<i32>,
     source: String,
+    /// The cell as it was loaded, keeping the fields that Zed doesn't edit.
+    nbformat_cell: nbformat::v4::Cell,
     editor: View<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs as stored in the notebook, matching `outputs`.
+    output_data: Vec<nbformat::v4::Output>,
+    clear_on_next_output: bool,
+    executing: bool,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
 }
 
 impl CodeCell {
-    pub fn is_dirty(&self, cx: &AppContext) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
-    }
     pub fn has_outputs(&self) -> bool {
         !self.outputs.is_empty()
     }
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.output_data.clear();
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.executing
+    }
+
+    /// Returns the current source of the cell.
+    pub fn source_text(&self, cx: &AppContext) -> String {
+        self.editor.read(cx).text(cx)
+    }
+
+    /// Clears the outputs of the cell's previous execution.
+    pub fn start_execution(&mut self, cx: &mut ViewContext<Self>) {
+        self.clear_outputs();
+        self.clear_on_next_output = false;
+        self.executing = true;
+        cx.notify();
+    }
+
+    /// Shows an error that kept the cell from executing, without storing it in the notebook.
+    pub fn show_error(&mut self, message: String, cx: &mut ViewContext<Self>) {
+        self.clear_outputs();
+        self.executing = false;
+        self.outputs
+            .push(Output::Message(format!("Error: {message}")));
+        cx.notify();
+    }
+
+    /// Updates the cell with a message the kernel sent while executing it.
+    pub fn handle_message(&mut self, content: &JupyterMessageContent, cx: &mut ViewContext<Self>) {
+        match content {
+            JupyterMessageContent::Status(status) => {
+                self.executing = matches!(status.execution_state, ExecutionState::Busy);
+            }
+            JupyterMessageContent::ExecuteReply(reply) => {
+                self.execution_count = Some(reply.execution_count.0 as i32);
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_on_next_output = true;
+                } else {
+                    self.clear_outputs();
+                }
+            }
+            content => {
+                let Some(output) = output_from_message(content) else {
+                    return;
+                };
+                // The display id isn't stored in the notebook, but lets the
+                // kernel update the output in place while it runs.
+                let display_id = match content {
+                    JupyterMessageContent::DisplayData(data) => {
+                        data.transient.as_ref().and_then(|t| t.display_id.clone())
+                    }
+                    JupyterMessageContent::ExecuteResult(result) => {
+                        result.transient.as_ref().and_then(|t| t.display_id.clone())
+                    }
+                    _ => None,
+                };
+                if std::mem::take(&mut self.clear_on_next_output) {
+                    self.clear_outputs();
+                }
+                self.push_output(output, display_id, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Replaces the data of the outputs shown with the display id of the
+    /// update, returning whether there were any.
+    pub fn update_display_data(
+        &mut self,
+        update: &UpdateDisplayData,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let Some(display_id) = update.transient.display_id.as_ref() else {
+            return false;
+        };
+
+        let mut updated = false;
+        for (output, output_data) in self.outputs.iter_mut().zip(&mut self.output_data) {
+            if output.display_id().as_ref() != Some(display_id) {
+                continue;
+            }
+            match output_data {
+                nbformat::v4::Output::DisplayData(nbformat::v4::DisplayData { data, metadata })
+                | nbformat::v4::Output::ExecuteResult(nbformat::v4::ExecuteResult {
+                    data,
+                    metadata,
+                    ..
+                }) => {
+                    *data = update.data.clone();
+                    *metadata = update.metadata.clone();
+                }
+                _ => continue,
+            }
+            *output = Output::new(&update.data, Some(display_id.clone()), cx);
+            updated = true;
+        }
+        if updated {
+            cx.notify();
+        }
+        updated
+    }
+
+    fn push_output(
+        &mut self,
+        output: nbformat::v4::Output,
+        display_id: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        // Consecutive text of the same stream is shown as a single output.
+        if let (
+            nbformat::v4::Output::Stream { name, text },
+            Some(nbformat::v4::Output::Stream {
+                name: last_name,
+                text: last_text,
+            }),
+        ) = (&output, self.output_data.last_mut())
+        {
+            if name == last_name {
+                last_text.0.push_str(&text.0);
+                let merged = self.output_data[self.output_data.len() - 1..].to_vec();
+                self.outputs.pop();
+                self.outputs.extend(convert_outputs(&merged, cx));
+                return;
+            }
+        }
+        self.outputs.push(convert_output(&output, display_id, cx));
+        self.output_data.push(output);
+    }
+
+    fn to_nbformat(&self, cx: &AppContext) -> nbformat::v4::Cell {
+        let mut cell = self.nbformat_cell.clone();
+        if let nbformat::v4::Cell::Code {
+            source,
+            execution_count,
+            outputs,
+            ..
+        } = &mut cell
+        {
+            *source = source_lines(&self.source_text(cx));
+            *execution_count = self.execution_count;
+            *outputs = self.output_data.clone();
+        }
+        cell
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -510,10 +843,9 @@ impl RenderableCell for CodeCell {
             CellControl::new("rerun-cell", CellControlType::RerunCell)
         } else {
             CellControl::new("run-cell", CellControlType::RunCell)
-                .on_click(cx.listener(move |this, _, cx| this.run(cx)))
         };
 
-        Some(cell_control)
+        Some(cell_control.on_click(cx.listener(move |this, _, cx| this.run(cx))))
     }
 
     fn selected(&self) -> bool {
@@ -537,7 +869,7 @@ impl RenderableCell for CodeCell {
 
 impl RunnableCell for CodeCell {
     fn run(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Running code cell: {}", self.id);
+        cx.emit(CellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -551,6 +883,8 @@ impl RunnableCell for CodeCell {
     }
 }
 
+impl EventEmitter<CellEvent> for CodeCell {}
+
 impl Render for CodeCell {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         v_flex()
@@ -620,6 +954,12 @@ impl Render for CodeCell {
                                             Output::Image { content, .. } => {
                                                 Some(content.clone().into_any_element())
                                             }
+                                            Output::Svg { content, .. } => {
+                                                Some(content.clone().into_any_element())
+                                            }
+                                            Output::Json { content, .. } => {
+                                                Some(content.clone().into_any_element())
+                                            }
                                             Output::Message(message) => Some(
                                                 div().child(message.clone()).into_any_element(),
                                             ),
@@ -656,6 +996,7 @@ pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    nbformat_cell: nbformat::v4::Cell,
     selected: bool,
     cell_position: Option<CellPosition>,
 }
@@ -1,28 +1,40 @@
 #![allow(unused, dead_code)]
 use std::future::Future;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::{Context as _, Result};
+use anyhow::{anyhow, Context as _, Result};
 use client::proto::ViewId;
 use collections::HashMap;
+use editor::EditorEvent;
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::future::Shared;
 use futures::FutureExt;
 use gpui::{
     actions, list, prelude::*, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView,
-    ListScrollEvent, ListState, Model, Point, Task, View,
+    ListScrollEvent, ListState, Model, Point, Subscription, Task, View,
 };
 use language::{Language, LanguageRegistry};
-use project::{Project, ProjectEntryId, ProjectPath};
+use project::{Fs, Project, ProjectEntryId, ProjectPath};
+use runtimelib::{ExecuteRequest, JupyterMessage, JupyterMessageContent};
 use ui::{prelude::*, Tooltip};
+use util::ResultExt as _;
 use workspace::item::{ItemEvent, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
 use workspace::{Item, ItemHandle, ProjectItem, ToolbarItemLocation};
 use workspace::{ToolbarItemEvent, ToolbarItemView};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{new_cell, Cell, CellEvent, CellPosition, RenderableCell};
+use crate::kernels::{
+    Kernel, KernelSession, KernelSpecification, NativeRunningKernel, RemoteRunningKernel,
+};
+use crate::repl_store::ReplStore;
 
 use nbformat::v4::CellId;
+use nbformat::v4::CellType;
 use nbformat::v4::Metadata as NotebookMetadata;
 
 actions!(
@@ -35,6 +47,7 @@ actions!(
         MoveCellDown,
         AddMarkdownBlock,
         AddCodeBlock,
+        RunCell,
     ]
 );
 
@@ -77,6 +90,16 @@ pub struct NotebookEditor {
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
+    is_dirty: bool,
+
+    kernel: Kernel,
+    kernel_specification: Option<KernelSpecification>,
+    /// The cells being executed, by the id of their execution request.
+    executions: HashMap<String, CellId>,
+    /// The execution requests made while the kernel was starting.
+    pending_requests: Vec<JupyterMessage>,
+    cell_subscriptions: HashMap<CellId, Vec<Subscription>>,
 }
 
 impl NotebookEditor {
@@ -135,7 +158,7 @@ impl NotebookEditor {
             },
         );
 
-        Self {
+        let mut this = Self {
             project,
             languages: languages.clone(),
             focus_handle,
@@ -145,7 +168,62 @@ impl NotebookEditor {
             selected_cell_index: 0,
             cell_order: cell_order.clone(),
             cell_map: cell_map.clone(),
+            notebook_language,
+            is_dirty: false,
+            kernel: Kernel::Shutdown,
+            kernel_specification: None,
+            executions: HashMap::default(),
+            pending_requests: Vec::new(),
+            cell_subscriptions: HashMap::default(),
+        };
+        for (cell_id, cell) in cell_map {
+            this.subscribe_to_cell(cell_id, &cell, cx);
+        }
+        this
+    }
+
+    fn subscribe_to_cell(&mut self, cell_id: CellId, cell: &Cell, cx: &mut ViewContext<Self>) {
+        let mut subscriptions = Vec::new();
+        if let Some(editor) = cell.editor(cx) {
+            let id = cell_id.clone();
+            subscriptions.push(
+                cx.subscribe(
+                    &editor,
+                    move |this, _, event: &EditorEvent, cx| match event {
+                        EditorEvent::BufferEdited => this.mark_dirty(cx),
+                        EditorEvent::Focused => this.select_cell(&id, cx),
+                        _ => {}
+                    },
+                ),
+            );
+        }
+        if let Cell::Code(code_cell) = cell {
+            let id = cell_id.clone();
+            subscriptions.push(cx.subscribe(code_cell, move |this, _, CellEvent::Run, cx| {
+                this.run_cell(&id, cx)
+            }));
+        }
+        self.cell_subscriptions.insert(cell_id, subscriptions);
+    }
+
+    fn mark_dirty(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.is_dirty {
+            self.is_dirty = true;
+            cx.emit(ItemEvent::UpdateTab);
         }
+        cx.emit(ItemEvent::Edit);
+    }
+
+    /// Returns the notebook with the current cells, keeping the rest of the loaded one.
+    fn to_notebook(&self, cx: &AppContext) -> nbformat::v4::Notebook {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_nbformat(cx))
+            .collect();
+        notebook
     }
 
     fn has_outputs(&self, cx: &ViewContext<Self>) -> bool {
@@ -159,17 +237,175 @@ impl NotebookEditor {
     }
 
     fn clear_outputs(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_outputs(cx) {
+            return;
+        }
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
+                code_cell.update(cx, |cell, cx| {
                     cell.clear_outputs();
+                    cx.notify();
                 });
             }
         }
+        self.mark_dirty(cx);
     }
 
     fn run_cells(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Cells would all run here, if that was implemented!");
+        for cell_id in self.cell_order.clone() {
+            self.run_cell(&cell_id, cx);
+        }
+    }
+
+    /// Runs the selected cell and selects the next one.
+    fn run_selected_cell(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(cell_id) = self.cell_order.get(self.selected_cell_index).cloned() else {
+            return;
+        };
+        self.run_cell(&cell_id, cx);
+        if self.selected_cell_index + 1 < self.cell_count() {
+            self.set_selected_index(self.selected_cell_index + 1, true, cx);
+            self.focus_selected_cell(cx);
+        }
+        cx.notify();
+    }
+
+    /// Executes a code cell in the notebook's kernel, starting it if needed, or renders a markdown cell.
+    fn run_cell(&mut self, cell_id: &CellId, cx: &mut ViewContext<Self>) {
+        let Some(cell) = self.cell_map.get(cell_id).cloned() else {
+            return;
+        };
+        let code_cell = match cell {
+            Cell::Code(code_cell) => code_cell,
+            Cell::Markdown(markdown_cell) => {
+                markdown_cell.update(cx, |cell, cx| cell.finish_editing(cx));
+                return;
+            }
+            Cell::Raw(_) => return,
+        };
+
+        let code = code_cell.read(cx).source_text(cx);
+        if code.trim().is_empty() {
+            return;
+        }
+        if let Err(error) = self.ensure_kernel(cx) {
+            code_cell.update(cx, |cell, cx| cell.show_error(error.to_string(), cx));
+            return;
+        }
+
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+        self.executions
+            .insert(message.header.msg_id.clone(), cell_id.clone());
+        code_cell.update(cx, |cell, cx| cell.start_execution(cx));
+        self.send(message, cx);
+        self.mark_dirty(cx);
+    }
+
+    fn ensure_kernel(&mut self, cx: &mut ViewContext<Self>) -> Result<()> {
+        match &self.kernel {
+            Kernel::RunningKernel(_) | Kernel::StartingKernel(_) => return Ok(()),
+            Kernel::ShuttingDown | Kernel::Restarting => {
+                return Err(anyhow!("the kernel is shutting down"))
+            }
+            Kernel::ErroredLaunch(_) | Kernel::Shutdown => {}
+        }
+        let kernel_specification = self
+            .find_kernel_specification(cx)
+            .context("no kernel found for the notebook's language")?;
+        self.start_kernel(kernel_specification, cx);
+        Ok(())
+    }
+
+    /// Finds the kernel the notebook was written with, or else the one selected for its language.
+    fn find_kernel_specification(&self, cx: &AppContext) -> Option<KernelSpecification> {
+        let notebook_item = self.notebook_item.read(cx);
+        let worktree_id = notebook_item.project_path.worktree_id;
+        let kernel_name = notebook_item
+            .notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .map(|kernelspec| kernelspec.name.to_lowercase());
+        let store = ReplStore::global(cx);
+        let store = store.read(cx);
+
+        store
+            .kernel_specifications_for_worktree(worktree_id)
+            .find(|kernel_specification| {
+                Some(kernel_specification.name().to_lowercase()) == kernel_name
+            })
+            .cloned()
+            .or_else(|| {
+                let language = self.notebook_language.clone().now_or_never().flatten();
+                store.active_kernelspec(worktree_id, language, cx)
+            })
+    }
+
+    fn start_kernel(
+        &mut self,
+        kernel_specification: KernelSpecification,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let working_directory = self
+            .notebook_item
+            .read(cx)
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(std::env::temp_dir);
+        let fs = self.project.read(cx).fs().clone();
+        let entity_id = cx.entity_id();
+        let view = cx.view().downgrade();
+
+        let kernel = match kernel_specification.clone() {
+            KernelSpecification::Jupyter(kernel_specification)
+            | KernelSpecification::PythonEnv(kernel_specification) => NativeRunningKernel::new(
+                kernel_specification,
+                entity_id,
+                working_directory,
+                fs,
+                view,
+                cx,
+            ),
+            KernelSpecification::Remote(kernel_specification) => {
+                RemoteRunningKernel::new(kernel_specification, working_directory, view, cx)
+            }
+        };
+
+        let pending_kernel = cx
+            .spawn(|this, mut cx| async move {
+                let kernel = kernel.await;
+                this.update(&mut cx, |this, cx| match kernel {
+                    Ok(kernel) => {
+                        this.kernel = Kernel::RunningKernel(kernel);
+                        for message in mem::take(&mut this.pending_requests) {
+                            this.send(message, cx);
+                        }
+                        cx.notify();
+                    }
+                    Err(error) => this.kernel_errored(error.to_string(), cx),
+                })
+                .ok();
+            })
+            .shared();
+
+        self.kernel = Kernel::StartingKernel(pending_kernel);
+        self.kernel_specification = Some(kernel_specification);
+        cx.notify();
+    }
+
+    fn send(&mut self, message: JupyterMessage, _cx: &mut ViewContext<Self>) {
+        match &self.kernel {
+            Kernel::RunningKernel(kernel) => {
+                kernel.request_tx().try_send(message).log_err();
+            }
+            Kernel::StartingKernel(_) => self.pending_requests.push(message),
+            _ => {}
+        }
     }
 
     fn open_notebook(&mut self, _: &OpenNotebook, _cx: &mut ViewContext<Self>) {
@@ -177,19 +413,104 @@ impl NotebookEditor {
     }
 
     fn move_cell_up(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index == 0 || index >= self.cell_count() {
+            return;
+        }
+        self.cell_order.swap(index - 1, index);
+        self.cell_list.splice(index - 1..index + 1, 2);
+        self.set_selected_index(index - 1, true, cx);
+        self.mark_dirty(cx);
+        cx.notify();
     }
 
     fn move_cell_down(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_count() {
+            return;
+        }
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        self.set_selected_index(index + 1, true, cx);
+        self.mark_dirty(cx);
+        cx.notify();
     }
 
     fn add_markdown_block(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Add markdown block triggered");
+        self.add_cell(CellType::Markdown, cx);
     }
 
     fn add_code_block(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Add code block triggered");
+        self.add_cell(CellType::Code, cx);
+    }
+
+    /// Adds an empty cell below the selected one, and focuses it.
+    fn add_cell(&mut self, cell_type: CellType, cx: &mut ViewContext<Self>) {
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            (self.selected_cell_index + 1).min(self.cell_count())
+        };
+        let notebook_cell = new_cell(cell_type);
+        let cell_id = notebook_cell.id().clone();
+        let cell = Cell::load(
+            &notebook_cell,
+            &self.languages,
+            self.notebook_language.clone(),
+            cx,
+        );
+        if let Cell::Markdown(markdown_cell) = &cell {
+            markdown_cell.update(cx, |cell, cx| cell.start_editing(cx));
+        }
+
+        self.subscribe_to_cell(cell_id.clone(), &cell, cx);
+        self.cell_order.insert(index, cell_id.clone());
+        self.cell_map.insert(cell_id, cell);
+        self.cell_list.splice(index..index, 1);
+        self.set_selected_index(index, true, cx);
+        self.focus_selected_cell(cx);
+        self.mark_dirty(cx);
+        cx.notify();
+    }
+
+    fn select_cell(&mut self, cell_id: &CellId, cx: &mut ViewContext<Self>) {
+        if let Some(index) = self.cell_order.iter().position(|id| id == cell_id) {
+            if index != self.selected_cell_index {
+                self.set_selected_index(index, false, cx);
+                cx.notify();
+            }
+        }
+    }
+
+    fn focus_selected_cell(&mut self, cx: &mut ViewContext<Self>) {
+        let editor = self
+            .cell_order
+            .get(self.selected_cell_index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .and_then(|cell| cell.editor(cx));
+        if let Some(editor) = editor {
+            cx.focus_view(&editor);
+        }
+    }
+
+    /// Replaces the cells with the ones of the notebook, as it was loaded.
+    fn reload_cells(&mut self, cx: &mut ViewContext<Self>) {
+        let cells = self.notebook_item.read(cx).notebook.cells.clone();
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+        for cell in &cells {
+            let cell_id = cell.id().clone();
+            let cell = Cell::load(cell, &self.languages, self.notebook_language.clone(), cx);
+            self.subscribe_to_cell(cell_id.clone(), &cell, cx);
+            self.cell_order.push(cell_id.clone());
+            self.cell_map.insert(cell_id, cell);
+        }
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = 0;
+        self.is_dirty = false;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
     }
 
     fn cell_count(&self) -> usize {
@@ -285,6 +606,14 @@ impl NotebookEditor {
 
     fn render_notebook_controls(&self, cx: &ViewContext<Self>) -> impl IntoElement {
         let has_outputs = self.has_outputs(cx);
+        let kernel_status = match &self.kernel_specification {
+            Some(kernel_specification) => format!(
+                "{} ({})",
+                kernel_specification.name(),
+                self.kernel.status().to_string()
+            ),
+            None => "No kernel started".to_string(),
+        };
 
         v_flex()
             .max_w(px(CONTROL_SIZE + 4.0))
@@ -388,8 +717,10 @@ impl NotebookEditor {
                         cx,
                     ))
                     .child(
-                        Self::button_group(cx)
-                            .child(IconButton::new("repl", IconName::ReplNeutral)),
+                        Self::button_group(cx).child(
+                            IconButton::new("repl", IconName::ReplNeutral)
+                                .tooltip(move |cx| Tooltip::text(kernel_status.clone(), cx)),
+                        ),
                     ),
             )
     }
@@ -446,6 +777,7 @@ impl Render for NotebookEditor {
             .on_action(cx.listener(|this, &OpenNotebook, cx| this.open_notebook(&OpenNotebook, cx)))
             .on_action(cx.listener(|this, &ClearOutputs, cx| this.clear_outputs(cx)))
             .on_action(cx.listener(|this, &RunAll, cx| this.run_cells(cx)))
+            .on_action(cx.listener(|this, &RunCell, cx| this.run_selected_cell(cx)))
             .on_action(cx.listener(|this, &MoveCellUp, cx| this.move_cell_up(cx)))
             .on_action(cx.listener(|this, &MoveCellDown, cx| this.move_cell_down(cx)))
             .on_action(cx.listener(|this, &AddMarkdownBlock, cx| this.add_markdown_block(cx)))
@@ -508,23 +840,7 @@ impl project::ProjectItem for NotebookItem {
                     .ok_or_else(|| anyhow::anyhow!("Failed to find the absolute path"))?;
 
                 // todo: watch for changes to the file
-                let file_content = fs.load(&abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-                        let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
-                        notebook
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = NotebookItem::load(fs.as_ref(), &abs_path).await?;
 
                 let id = project
                     .update(&mut cx, |project, cx| project.entry_for_path(&path, cx))?
@@ -558,6 +874,26 @@ impl project::ProjectItem for NotebookItem {
 }
 
 impl NotebookItem {
+    async fn load(fs: &dyn Fs, abs_path: &Path) -> Result<nbformat::v4::Notebook> {
+        let file_content = fs.load(abs_path).await?;
+        let notebook = nbformat::parse_notebook(&file_content);
+
+        match notebook {
+            Ok(nbformat::Notebook::V4(notebook)) => Ok(notebook),
+            // 4.1 - 4.4 are converted to 4.5
+            Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+                // TODO: Decide if we want to mutate the notebook by including Cell IDs
+                // and any other conversions
+                let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
+                Ok(notebook)
+            }
+            // Bad notebooks and notebooks v4.0 and below are not supported
+            Err(e) => {
+                anyhow::bail!("Failed to parse notebook: {:?}", e);
+            }
+        }
+    }
+
     pub fn language_name(&self) -> Option<String> {
         self.notebook
             .metadata
@@ -586,7 +922,75 @@ impl NotebookItem {
     }
 }
 
-impl EventEmitter<()> for NotebookEditor {}
+impl EventEmitter<ItemEvent> for NotebookEditor {}
+
+impl KernelSession for NotebookEditor {
+    fn route(&mut self, message: &JupyterMessage, cx: &mut ViewContext<Self>) {
+        match &message.content {
+            JupyterMessageContent::Status(status) => {
+                self.kernel.set_execution_state(&status.execution_state);
+                cx.notify();
+            }
+            JupyterMessageContent::KernelInfoReply(reply) => {
+                self.kernel.set_kernel_info(reply);
+                cx.notify();
+            }
+            // Updates may target outputs of any cell, including ones that
+            // finished executing.
+            JupyterMessageContent::UpdateDisplayData(update) => {
+                let mut updated = false;
+                for cell in self.cell_map.values() {
+                    if let Cell::Code(code_cell) = cell {
+                        updated |=
+                            code_cell.update(cx, |cell, cx| cell.update_display_data(update, cx));
+                    }
+                }
+                if updated {
+                    self.mark_dirty(cx);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let Some(parent_message_id) = message
+            .parent_header
+            .as_ref()
+            .map(|header| header.msg_id.clone())
+        else {
+            return;
+        };
+        let Some(Cell::Code(code_cell)) = self
+            .executions
+            .get(&parent_message_id)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .cloned()
+        else {
+            return;
+        };
+        code_cell.update(cx, |cell, cx| cell.handle_message(&message.content, cx));
+        match &message.content {
+            JupyterMessageContent::Status(status)
+                if matches!(status.execution_state, runtimelib::ExecutionState::Idle) =>
+            {
+                self.executions.remove(&parent_message_id);
+            }
+            JupyterMessageContent::Status(_) => {}
+            _ => self.mark_dirty(cx),
+        }
+    }
+
+    fn kernel_errored(&mut self, error_message: String, cx: &mut ViewContext<Self>) {
+        self.kernel = Kernel::ErroredLaunch(error_message.clone());
+        self.pending_requests.clear();
+        for (_, cell_id) in self.executions.drain() {
+            if let Some(Cell::Code(code_cell)) = self.cell_map.get(&cell_id) {
+                code_cell.update(cx, |cell, cx| cell.show_error(error_message.clone(), cx));
+            }
+        }
+        cx.notify();
+    }
+}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -634,7 +1038,11 @@ impl EventEmitter<()> for NotebookEditor {}
 // }
 
 impl Item for NotebookEditor {
-    type Event = ();
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
 
     fn clone_on_split(
         &self,
@@ -695,45 +1103,93 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &AppContext) -> bool {
-        false
+        true
     }
-    // TODO
+
     fn save(
         &mut self,
         _format: bool,
-        _project: Model<Project>,
-        _cx: &mut ViewContext<Self>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let path = self.notebook_item.read(cx).path.clone();
+        self.save_to(path, project, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Model<Project>,
-        _path: ProjectPath,
-        _cx: &mut ViewContext<Self>,
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("failed to find the absolute path")));
+        };
+        let save = self.save_to(abs_path.clone(), project.clone(), cx);
+        let notebook_item = self.notebook_item.clone();
+        cx.spawn(|this, mut cx| async move {
+            save.await?;
+            let entry_id = project
+                .update(&mut cx, |project, cx| project.entry_for_path(&path, cx))?
+                .map(|entry| entry.id);
+            notebook_item.update(&mut cx, |notebook_item, _| {
+                notebook_item.path = abs_path;
+                notebook_item.project_path = path;
+                if let Some(entry_id) = entry_id {
+                    notebook_item.id = entry_id;
+                }
+            })?;
+            this.update(&mut cx, |_, cx| cx.emit(ItemEvent::UpdateTab))
+        })
     }
-    // TODO
-    fn reload(
-        &mut self,
-        _project: Model<Project>,
-        _cx: &mut ViewContext<Self>,
-    ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let fs = project.read(cx).fs().clone();
+        let path = self.notebook_item.read(cx).path.clone();
+        cx.spawn(|this, mut cx| async move {
+            let notebook = NotebookItem::load(fs.as_ref(), &path).await?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.notebook = notebook;
+                });
+                this.reload_cells(cx);
+            })
+        })
     }
 
-    fn is_dirty(&self, cx: &AppContext) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
+    fn is_dirty(&self, _: &AppContext) -> bool {
+        self.is_dirty
+    }
+}
+
+impl NotebookEditor {
+    /// Writes the notebook as nbformat v4 JSON, formatted like Jupyter does.
+    fn save_to(
+        &mut self,
+        abs_path: PathBuf,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = self.to_notebook(cx);
+        let fs = project.read(cx).fs().clone();
+        cx.spawn(|this, mut cx| async move {
+            let mut content = Vec::new();
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+            let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+            serde::Serialize::serialize(&notebook, &mut serializer)?;
+            content.push(b'\n');
+            fs.atomic_write(abs_path, String::from_utf8(content)?)
+                .await?;
+
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.notebook = notebook;
+                });
+                this.is_dirty = false;
+                cx.emit(ItemEvent::UpdateTab);
+                cx.notify();
+            })
         })
     }
 }
@@ -755,3 +1211,285 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use runtimelib::{DisplayData, StreamContent, UpdateDisplayData};
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_save_notebook(cx: &mut TestAppContext) {
+        let (fs, project, notebook, cx) = open_test_notebook(cx).await;
+        assert!(!notebook.read_with(cx, |notebook, cx| notebook.is_dirty(cx)));
+
+        notebook.update(cx, |notebook, cx| {
+            let editor = notebook.cell_map[&notebook.cell_order[1]]
+                .editor(cx)
+                .unwrap();
+            editor.update(cx, |editor, cx| editor.set_text("x = 2\nprint(x)", cx));
+        });
+        cx.run_until_parked();
+        assert!(notebook.read_with(cx, |notebook, cx| notebook.is_dirty(cx)));
+
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        assert!(!notebook.read_with(cx, |notebook, cx| notebook.is_dirty(cx)));
+
+        let content = fs.load(Path::new("/dir/notebook.ipynb")).await.unwrap();
+        assert!(matches!(
+            nbformat::parse_notebook(&content),
+            Ok(nbformat::Notebook::V4(_))
+        ));
+        let saved: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(saved["nbformat"], json!(4));
+        assert_eq!(saved["nbformat_minor"], json!(5));
+        assert_eq!(saved["metadata"]["kernelspec"]["name"], json!("python3"));
+        assert_eq!(saved["metadata"]["custom"], json!({ "kept": true }));
+        assert_eq!(saved["cells"][0]["metadata"]["tags"], json!(["intro"]));
+        assert_eq!(saved["cells"][1]["metadata"]["custom"], json!("kept"));
+        assert_eq!(saved["cells"][1]["source"], json!(["x = 2\n", "print(x)"]));
+        assert_eq!(saved["cells"][2]["source"], json!(["print(3)"]));
+    }
+
+    #[gpui::test]
+    async fn test_add_and_move_cells(cx: &mut TestAppContext) {
+        let (fs, project, notebook, cx) = open_test_notebook(cx).await;
+        let cell_ids = |notebook: &View<NotebookEditor>, cx: &mut VisualTestContext| {
+            notebook.read_with(cx, |notebook, _| {
+                serde_json::to_value(&notebook.cell_order).unwrap()
+            })
+        };
+
+        let new_cell_id = notebook.update(cx, |notebook, cx| {
+            notebook.add_cell(CellType::Code, cx);
+            assert_eq!(notebook.selected_index(), 1);
+            serde_json::to_value(&notebook.cell_order[1]).unwrap()
+        });
+        assert_eq!(
+            cell_ids(&notebook, cx),
+            json!(["intro", new_cell_id, "first", "second"])
+        );
+        assert!(notebook.read_with(cx, |notebook, cx| notebook.is_dirty(cx)));
+
+        notebook.update(cx, |notebook, cx| notebook.move_cell_down(cx));
+        assert_eq!(
+            cell_ids(&notebook, cx),
+            json!(["intro", "first", new_cell_id, "second"])
+        );
+        notebook.update(cx, |notebook, cx| {
+            notebook.move_cell_down(cx);
+            // The last cell can't move further down.
+            notebook.move_cell_down(cx);
+            assert_eq!(notebook.selected_index(), 3);
+            notebook.set_selected_index(0, false, cx);
+            notebook.move_cell_up(cx);
+            assert_eq!(notebook.selected_index(), 0);
+        });
+        assert_eq!(
+            cell_ids(&notebook, cx),
+            json!(["intro", "first", "second", new_cell_id])
+        );
+
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        assert!(!notebook.read_with(cx, |notebook, cx| notebook.is_dirty(cx)));
+        let content = fs.load(Path::new("/dir/notebook.ipynb")).await.unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            saved["cells"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|cell| json!([cell["id"], cell["cell_type"]]))
+                .collect::<Vec<_>>(),
+            [
+                json!(["intro", "markdown"]),
+                json!(["first", "code"]),
+                json!(["second", "code"]),
+                json!([new_cell_id, "code"]),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_execution_output_goes_to_its_cell(cx: &mut TestAppContext) {
+        let (fs, project, notebook, cx) = open_test_notebook(cx).await;
+
+        let request: JupyterMessage = ExecuteRequest {
+            code: "print(3)".to_string(),
+            ..ExecuteRequest::default()
+        }
+        .into();
+        notebook.update(cx, |notebook, _| {
+            let cell_id = notebook.cell_order[2].clone();
+            notebook
+                .executions
+                .insert(request.header.msg_id.clone(), cell_id);
+        });
+        let mut output: JupyterMessage = StreamContent::stdout("3\n").into();
+        output.parent_header = Some(request.header.clone());
+        notebook.update(cx, |notebook, cx| notebook.route(&output, cx));
+
+        notebook.read_with(cx, |notebook, cx| {
+            assert!(notebook.is_dirty(cx));
+            let has_outputs = |index: usize| match &notebook.cell_map[&notebook.cell_order[index]] {
+                Cell::Code(code_cell) => code_cell.read(cx).has_outputs(),
+                _ => false,
+            };
+            assert!(!has_outputs(1));
+            assert!(has_outputs(2));
+        });
+
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        let content = fs.load(Path::new("/dir/notebook.ipynb")).await.unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(saved["cells"][1]["outputs"], json!([]));
+        assert_eq!(
+            saved["cells"][2]["outputs"][0]["output_type"],
+            json!("stream")
+        );
+        assert_eq!(saved["cells"][2]["outputs"][0]["name"], json!("stdout"));
+    }
+
+    #[gpui::test]
+    async fn test_update_display_data(cx: &mut TestAppContext) {
+        let (fs, project, notebook, cx) = open_test_notebook(cx).await;
+
+        let request: JupyterMessage = ExecuteRequest {
+            code: "print(3)".to_string(),
+            ..ExecuteRequest::default()
+        }
+        .into();
+        notebook.update(cx, |notebook, _| {
+            let cell_id = notebook.cell_order[2].clone();
+            notebook
+                .executions
+                .insert(request.header.msg_id.clone(), cell_id);
+        });
+        let display_data: DisplayData = serde_json::from_value(json!({
+            "data": { "text/plain": "0%" },
+            "metadata": {},
+            "transient": { "display_id": "progress" }
+        }))
+        .unwrap();
+        let mut output: JupyterMessage = display_data.into();
+        output.parent_header = Some(request.header.clone());
+        notebook.update(cx, |notebook, cx| {
+            notebook.route(&output, cx);
+            // The execution finishes before the output gets updated.
+            notebook.executions.clear();
+        });
+
+        let update: UpdateDisplayData = serde_json::from_value(json!({
+            "data": { "text/plain": "100%" },
+            "metadata": {},
+            "transient": { "display_id": "progress" }
+        }))
+        .unwrap();
+        let update: JupyterMessage = update.into();
+        notebook.update(cx, |notebook, cx| notebook.route(&update, cx));
+
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        let content = fs.load(Path::new("/dir/notebook.ipynb")).await.unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let outputs = saved["cells"][2]["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0]["output_type"], json!("display_data"));
+        assert!(outputs[0]["data"]["text/plain"]
+            .to_string()
+            .contains("100%"));
+        assert_eq!(outputs[0].get("transient"), None);
+    }
+
+    async fn open_test_notebook(
+        cx: &mut TestAppContext,
+    ) -> (
+        Arc<FakeFs>,
+        Model<Project>,
+        View<NotebookEditor>,
+        &mut VisualTestContext,
+    ) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+
+        let notebook = json!({
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "id": "intro",
+                    "metadata": { "tags": ["intro"] },
+                    "source": ["# Notebook\n"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": 1,
+                    "id": "first",
+                    "metadata": { "custom": "kept" },
+                    "outputs": [],
+                    "source": ["print(1)"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": null,
+                    "id": "second",
+                    "metadata": {},
+                    "outputs": [],
+                    "source": ["print(3)"]
+                }
+            ],
+            "metadata": {
+                "kernelspec": {
+                    "display_name": "Python 3",
+                    "language": "python",
+                    "name": "python3"
+                },
+                "language_info": { "name": "python" },
+                "custom": { "kept": true }
+            },
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "notebook.ipynb": notebook.to_string() }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let project_path = ProjectPath {
+            worktree_id,
+            path: Path::new("notebook.ipynb").into(),
+        };
+        let notebook_item = cx
+            .update(|cx| {
+                <NotebookItem as project::ProjectItem>::try_open(&project, &project_path, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let (notebook, cx) =
+            cx.add_window_view(|cx| NotebookEditor::new(project.clone(), notebook_item, cx));
+        cx.run_until_parked();
+        (fs, project, notebook, cx)
+    }
+}
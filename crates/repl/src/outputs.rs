@@ -14,7 +14,10 @@
 //! The module supports several output types, including:
 //! - Plain text
 //! - Markdown
-//! - Images (PNG and JPEG)
+//! - HTML, converted to markdown
+//! - LaTeX, shown as source
+//! - Images (PNG, JPEG and SVG)
+//! - JSON, as a collapsible tree
 //! - Tables
 //! - Error messages
 //!
@@ -44,12 +47,21 @@ use language::Buffer;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
 use ui::{div, prelude::*, v_flex, IntoElement, Styled, Tooltip, ViewContext};
 
+mod html;
+use html::{html_to_markdown, latex_to_markdown};
+
 mod image;
 use image::ImageView;
 
+mod json;
+use json::JsonView;
+
 mod markdown;
 use markdown::MarkdownView;
 
+mod svg;
+use svg::SvgView;
+
 mod table;
 use table::TableView;
 
@@ -63,10 +75,14 @@ use workspace::Workspace;
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
-        MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
-        MimeType::Jpeg(_) => 3,
-        MimeType::Markdown(_) => 2,
+        MimeType::DataTable(_) => 9,
+        MimeType::Png(_) => 8,
+        MimeType::Jpeg(_) => 7,
+        MimeType::Svg(_) => 6,
+        MimeType::Markdown(_) => 5,
+        MimeType::Html(_) => 4,
+        MimeType::Latex(_) => 3,
+        MimeType::Json(_) => 2,
         MimeType::Plain(_) => 1,
         // All other media types are not supported in Zed at this time
         _ => 0,
@@ -116,6 +132,14 @@ pub enum Output {
         content: View<ImageView>,
         display_id: Option<String>,
     },
+    Svg {
+        content: View<SvgView>,
+        display_id: Option<String>,
+    },
+    Json {
+        content: View<JsonView>,
+        display_id: Option<String>,
+    },
     ErrorOutput(ErrorView),
     Message(String),
     Table {
@@ -212,6 +236,8 @@ impl Output {
             Self::Markdown { content, .. } => Some(content.clone().into_any_element()),
             Self::Stream { content, .. } => Some(content.clone().into_any_element()),
             Self::Image { content, .. } => Some(content.clone().into_any_element()),
+            Self::Svg { content, .. } => Some(content.clone().into_any_element()),
+            Self::Json { content, .. } => Some(content.clone().into_any_element()),
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
             Self::Table { content, .. } => Some(content.clone().into_any_element()),
             Self::ErrorOutput(error_view) => error_view.render(cx),
@@ -235,6 +261,12 @@ impl Output {
                 Self::Image { content, .. } => {
                     Self::render_output_controls(content.clone(), workspace.clone(), cx)
                 }
+                Self::Svg { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace.clone(), cx)
+                }
+                Self::Json { content, .. } => {
+                    Self::render_output_controls(content.clone(), workspace.clone(), cx)
+                }
                 Self::ErrorOutput(err) => {
                     Self::render_output_controls(err.traceback.clone(), workspace.clone(), cx)
                 }
@@ -251,6 +283,8 @@ impl Output {
             Output::Plain { display_id, .. } => display_id.clone(),
             Output::Stream { .. } => None,
             Output::Image { display_id, .. } => display_id.clone(),
+            Output::Svg { display_id, .. } => display_id.clone(),
+            Output::Json { display_id, .. } => display_id.clone(),
             Output::ErrorOutput(_) => None,
             Output::Message(_) => None,
             Output::Table { display_id, .. } => display_id.clone(),
@@ -272,6 +306,28 @@ impl Output {
                     display_id,
                 }
             }
+            Some(MimeType::Html(html)) => match html_to_markdown(html) {
+                Ok(markdown) => Output::Markdown {
+                    content: cx.new_view(|cx| MarkdownView::from(markdown, cx)),
+                    display_id,
+                },
+                Err(error) => Output::Message(format!("Failed to convert HTML: {}", error)),
+            },
+            Some(MimeType::Latex(latex)) => {
+                let markdown = latex_to_markdown(latex);
+                Output::Markdown {
+                    content: cx.new_view(|cx| MarkdownView::from(markdown, cx)),
+                    display_id,
+                }
+            }
+            Some(MimeType::Svg(svg)) => Output::Svg {
+                content: cx.new_view(|_| SvgView::from(svg)),
+                display_id,
+            },
+            Some(MimeType::Json(value)) => Output::Json {
+                content: cx.new_view(|_| JsonView::from(value)),
+                display_id,
+            },
             Some(MimeType::Png(data)) | Some(MimeType::Jpeg(data)) => match ImageView::from(data) {
                 Ok(view) => Output::Image {
                     content: cx.new_view(|_| view),
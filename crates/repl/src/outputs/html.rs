@@ -0,0 +1,113 @@
+//! Conversions of the `text/html` and `text/latex` outputs to markdown,
+//! so that they can be shown with the [`MarkdownView`](super::markdown::MarkdownView).
+
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::Result;
+use html_to_markdown::{convert_html_to_markdown, markdown, TagHandler};
+
+/// Converts simple HTML, like the tables of pandas' `_repr_html_`, to markdown.
+/// Stylesheets and scripts are dropped, as they would otherwise show up as text.
+pub fn html_to_markdown(html: &str) -> Result<String> {
+    let mut handlers: Vec<TagHandler> = vec![
+        Rc::new(RefCell::new(markdown::WebpageChromeRemover)),
+        Rc::new(RefCell::new(markdown::ParagraphHandler)),
+        Rc::new(RefCell::new(markdown::HeadingHandler)),
+        Rc::new(RefCell::new(markdown::ListHandler)),
+        Rc::new(RefCell::new(markdown::TableHandler::new())),
+        Rc::new(RefCell::new(markdown::StyledTextHandler)),
+        Rc::new(RefCell::new(markdown::CodeHandler)),
+    ];
+
+    convert_html_to_markdown(html.as_bytes(), &mut handlers)
+}
+
+/// Shows LaTeX as a code block, as math isn't rendered in markdown yet.
+pub fn latex_to_markdown(latex: &str) -> String {
+    let latex = latex.trim();
+    let latex = latex
+        .strip_prefix("$$")
+        .and_then(|latex| latex.strip_suffix("$$"))
+        .or_else(|| {
+            latex
+                .strip_prefix('$')
+                .and_then(|latex| latex.strip_suffix('$'))
+        })
+        .unwrap_or(latex)
+        .trim();
+    let latex = latex.strip_prefix("\\displaystyle").unwrap_or(latex).trim();
+
+    format!("```latex\n{latex}\n```")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = indoc! {r#"
+            <div>
+            <table border="1" class="dataframe">
+              <thead>
+                <tr><th>name</th><th>age</th></tr>
+              </thead>
+              <tbody>
+                <tr><td>Alice</td><td>30</td></tr>
+                <tr><td>Bob</td><td>28</td></tr>
+              </tbody>
+            </table>
+            </div>
+        "#};
+
+        let markdown = html_to_markdown(html).unwrap();
+        assert!(markdown.contains("| name | age |"), "{markdown}");
+        assert!(markdown.contains("| Alice | 30 |"), "{markdown}");
+        assert!(markdown.contains("| Bob | 28 |"), "{markdown}");
+    }
+
+    #[test]
+    fn test_html_to_markdown_skips_styles() {
+        let html = indoc! {r#"
+            <div>
+            <style scoped>
+                .dataframe tbody tr th:only-of-type {
+                    vertical-align: middle;
+                }
+            </style>
+            <table border="1" class="dataframe">
+              <thead>
+                <tr style="text-align: right;"><th>a</th><th>b</th></tr>
+              </thead>
+              <tbody>
+                <tr><td>1</td><td>2</td></tr>
+              </tbody>
+            </table>
+            <script>console.log("repr")</script>
+            </div>
+        "#};
+
+        let markdown = html_to_markdown(html).unwrap();
+        assert!(markdown.contains("| a | b |"), "{markdown}");
+        assert!(markdown.contains("| 1 | 2 |"), "{markdown}");
+        assert!(!markdown.contains("vertical-align"), "{markdown}");
+        assert!(!markdown.contains("console.log"), "{markdown}");
+    }
+
+    #[test]
+    fn test_latex_to_markdown() {
+        assert_eq!(
+            latex_to_markdown("$\\displaystyle x^{2} + 1$"),
+            "```latex\nx^{2} + 1\n```"
+        );
+        assert_eq!(
+            latex_to_markdown("$$\\frac{a}{b}$$\n"),
+            "```latex\n\\frac{a}{b}\n```"
+        );
+        assert_eq!(
+            latex_to_markdown("\\begin{equation}E = mc^2\\end{equation}"),
+            "```latex\n\\begin{equation}E = mc^2\\end{equation}\n```"
+        );
+    }
+}
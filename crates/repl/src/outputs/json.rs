@@ -0,0 +1,193 @@
+use collections::HashSet;
+use gpui::{AnyElement, ClipboardItem, Model, WindowContext};
+use language::Buffer;
+use serde_json::Value;
+use ui::{div, prelude::*, Disclosure, IntoElement, Styled};
+
+use crate::outputs::OutputContent;
+
+/// How deep the tree is expanded when the output is first shown.
+const INITIALLY_EXPANDED_DEPTH: usize = 1;
+
+/// JsonView renders an `application/json` output as a tree of collapsible objects and arrays.
+pub struct JsonView {
+    value: Value,
+    /// The paths of the objects and arrays whose expansion was toggled from the initial state.
+    toggled_paths: HashSet<String>,
+    pretty_json: String,
+}
+
+impl JsonView {
+    pub fn from(value: &Value) -> Self {
+        Self {
+            value: value.clone(),
+            toggled_paths: HashSet::default(),
+            pretty_json: serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()),
+        }
+    }
+
+    fn is_expanded(&self, path: &str, depth: usize) -> bool {
+        (depth < INITIALLY_EXPANDED_DEPTH) != self.toggled_paths.contains(path)
+    }
+
+    fn toggle(&mut self, path: &str, cx: &mut ViewContext<Self>) {
+        if !self.toggled_paths.remove(path) {
+            self.toggled_paths.insert(path.to_string());
+        }
+        cx.notify();
+    }
+
+    fn render_node(
+        &self,
+        key: Option<String>,
+        value: &Value,
+        path: String,
+        depth: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let key_label = key.map(|key| Label::new(format!("{key}:")).color(Color::Accent));
+
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, value)| (key.clone(), value))
+                .collect(),
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (index.to_string(), value))
+                .collect(),
+            _ => {
+                return h_flex()
+                    .gap_1()
+                    .pl_5()
+                    .children(key_label)
+                    .child(Self::render_leaf(value))
+                    .into_any_element();
+            }
+        };
+
+        let is_expanded = self.is_expanded(&path, depth);
+        let summary = match value {
+            Value::Object(_) if children.len() == 1 => "{…} 1 key".to_string(),
+            Value::Object(_) => format!("{{…}} {} keys", children.len()),
+            _ if children.len() == 1 => "[…] 1 item".to_string(),
+            _ => format!("[…] {} items", children.len()),
+        };
+
+        let toggle_path = path.clone();
+        let header = h_flex()
+            .gap_1()
+            .child(
+                Disclosure::new(ElementId::Name(path.clone().into()), is_expanded)
+                    .on_click(cx.listener(move |this, _, cx| this.toggle(&toggle_path, cx))),
+            )
+            .children(key_label)
+            .child(Label::new(summary).color(Color::Muted));
+
+        v_flex()
+            .child(header)
+            .when(is_expanded, |this| {
+                this.child(
+                    v_flex()
+                        .pl_4()
+                        .children(children.into_iter().map(|(key, value)| {
+                            let path = format!("{path}/{key}");
+                            self.render_node(Some(key), value, path, depth + 1, cx)
+                        })),
+                )
+            })
+            .into_any_element()
+    }
+
+    fn render_leaf(value: &Value) -> impl IntoElement {
+        match value {
+            Value::String(string) => Label::new(format!("{string:?}")).color(Color::Created),
+            Value::Number(number) => Label::new(number.to_string()).color(Color::Info),
+            Value::Bool(bool) => Label::new(bool.to_string()).color(Color::Warning),
+            _ => Label::new(value.to_string()).color(Color::Muted),
+        }
+    }
+}
+
+impl Render for JsonView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .py_1()
+            .child(self.render_node(None, &self.value, String::new(), 0, cx))
+    }
+}
+
+impl OutputContent for JsonView {
+    fn clipboard_content(&self, _cx: &WindowContext) -> Option<ClipboardItem> {
+        Some(ClipboardItem::new_string(self.pretty_json.clone()))
+    }
+
+    fn has_clipboard_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn has_buffer_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+
+    fn buffer_content(&mut self, cx: &mut WindowContext) -> Option<Model<Buffer>> {
+        let buffer = cx.new_model(|cx| {
+            let mut buffer = Buffer::local(self.pretty_json.clone(), cx)
+                .with_language(language::PLAIN_TEXT.clone(), cx);
+            buffer.set_capability(language::Capability::ReadOnly, cx);
+            buffer
+        });
+        Some(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    fn test_json_view(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+        });
+
+        let value = json!({
+            "name": "Alice",
+            "tags": ["a", "b"],
+            "address": { "city": "Paris" },
+        });
+        let (view, cx) = cx.add_window_view(|_| JsonView::from(&value));
+
+        view.update(cx, |view, cx| {
+            // Only the root is expanded at first.
+            assert!(view.is_expanded("", 0));
+            assert!(!view.is_expanded("/tags", 1));
+            assert!(!view.is_expanded("/address", 1));
+
+            view.toggle("/tags", cx);
+            assert!(view.is_expanded("/tags", 1));
+            assert!(!view.is_expanded("/address", 1));
+
+            view.toggle("", cx);
+            assert!(!view.is_expanded("", 0));
+            view.toggle("", cx);
+            assert!(view.is_expanded("", 0));
+        });
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert_eq!(
+                view.read(cx)
+                    .clipboard_content(cx)
+                    .and_then(|item| item.text()),
+                Some(serde_json::to_string_pretty(&value).unwrap())
+            );
+        });
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use gpui::{img, ClipboardItem, Image, ImageFormat, WindowContext};
+use ui::{div, prelude::*, IntoElement, Styled};
+
+use crate::outputs::OutputContent;
+
+/// SvgView renders an `image/svg+xml` output, rasterized by GPUI's SVG renderer.
+pub struct SvgView {
+    source: String,
+    image: Arc<Image>,
+}
+
+impl SvgView {
+    pub fn from(source: &str) -> Self {
+        let bytes = source.as_bytes().to_vec();
+        let image = Arc::new(Image {
+            id: gpui::hash(&bytes),
+            format: ImageFormat::Svg,
+            bytes,
+        });
+
+        Self {
+            source: source.to_string(),
+            image,
+        }
+    }
+}
+
+impl Render for SvgView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().max_w_full().child(img(self.image.clone()))
+    }
+}
+
+impl OutputContent for SvgView {
+    fn clipboard_content(&self, _cx: &WindowContext) -> Option<ClipboardItem> {
+        Some(ClipboardItem::new_string(self.source.clone()))
+    }
+
+    fn has_clipboard_content(&self, _cx: &WindowContext) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{size, DevicePixels, TestAppContext};
+
+    #[gpui::test]
+    fn test_svg_view(cx: &mut TestAppContext) {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20"><rect width="40" height="20" fill="red"/></svg>"#;
+        let (view, cx) = cx.add_window_view(|_| SvgView::from(svg));
+
+        cx.update(|cx| {
+            let view = view.read(cx);
+            let image = view.image.to_image_data(cx.svg_renderer()).unwrap();
+            assert_eq!(image.size(0), size(DevicePixels(40), DevicePixels(20)));
+            assert_eq!(
+                view.clipboard_content(cx).and_then(|item| item.text()),
+                Some(svg.to_string())
+            );
+        });
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     chunking::{self, Chunk},
     embedding::{Embedding, EmbeddingProvider, TextToEmbed},
     indexing::{IndexingEntryHandle, IndexingEntrySet},
+    keyword_index::{KeywordFile, KeywordIndex},
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::Bound;
@@ -12,7 +13,7 @@ use futures::{stream::StreamExt, FutureExt as _};
 use futures_batch::ChunksTimeoutStreamExt;
 use gpui::{AppContext, Model, Task};
 use heed::types::{SerdeBincode, Str};
-use language::LanguageRegistry;
+use language::{LanguageName, LanguageRegistry};
 use log;
 use project::{Entry, UpdatedEntriesSet, Worktree};
 use serde::{Deserialize, Serialize};
@@ -25,6 +26,7 @@ pub struct EmbeddingIndex {
     worktree: Model<Worktree>,
     db_connection: heed::Env,
     db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+    keyword_index: KeywordIndex,
     fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -32,11 +34,13 @@ pub struct EmbeddingIndex {
 }
 
 impl EmbeddingIndex {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         worktree: Model<Worktree>,
         fs: Arc<dyn Fs>,
         db_connection: heed::Env,
         embedding_db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        keyword_index: KeywordIndex,
         language_registry: Arc<LanguageRegistry>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
         entry_ids_being_indexed: Arc<IndexingEntrySet>,
@@ -46,6 +50,7 @@ impl EmbeddingIndex {
             fs,
             db_connection,
             db: embedding_db,
+            keyword_index,
             language_registry,
             embedding_provider,
             entry_ids_being_indexed,
@@ -56,6 +61,10 @@ impl EmbeddingIndex {
         &self.db
     }
 
+    pub fn keyword_index(&self) -> &KeywordIndex {
+        &self.keyword_index
+    }
+
     pub fn index_entries_changed_on_disk(
         &self,
         cx: &AppContext,
@@ -104,6 +113,7 @@ impl EmbeddingIndex {
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let keyword_index = self.keyword_index;
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_executor().spawn(async move {
             let txn = db_connection
@@ -144,7 +154,11 @@ impl EmbeddingIndex {
                                         ))
                                         .await?;
                                 }
-                                saved_mtime = db_embedded_file.mtime;
+                                // Files embedded before keywords were indexed need to be
+                                // indexed again.
+                                if keyword_index.contains(&txn, db_path)? {
+                                    saved_mtime = db_embedded_file.mtime;
+                                }
                                 db_entries.next();
                                 break;
                             }
@@ -250,6 +264,7 @@ impl EmbeddingIndex {
                                             language.as_ref(),
                                             &entry.path,
                                         ),
+                                        language: language.map(|language| language.name()),
                                         handle,
                                         path: entry.path,
                                         mtime: entry.mtime,
@@ -320,6 +335,12 @@ impl EmbeddingIndex {
 
                 let mut embeddings = embeddings.into_iter();
                 for chunked_file in chunked_files {
+                    let keyword_file = KeywordFile::new(
+                        chunked_file.path.clone(),
+                        chunked_file.language.map(|language| language.0.to_string()),
+                        &chunked_file.text,
+                        &chunked_file.chunks,
+                    );
                     let mut embedded_file = EmbeddedFile {
                         path: chunked_file.path,
                         mtime: chunked_file.mtime,
@@ -341,7 +362,7 @@ impl EmbeddingIndex {
 
                     if embedded_all_chunks {
                         embedded_files_tx
-                            .send((embedded_file, chunked_file.handle))
+                            .send((embedded_file, keyword_file, chunked_file.handle))
                             .await?;
                     }
                 }
@@ -358,11 +379,12 @@ impl EmbeddingIndex {
     fn persist_embeddings(
         &self,
         deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
-        embedded_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        embedded_files: channel::Receiver<(EmbeddedFile, KeywordFile, IndexingEntryHandle)>,
         cx: &AppContext,
     ) -> Task<Result<()>> {
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let keyword_index = self.keyword_index;
 
         cx.background_executor().spawn(async move {
             let mut deleted_entry_ranges = pin!(deleted_entry_ranges);
@@ -377,15 +399,17 @@ impl EmbeddingIndex {
                             let end = deletion_range.1.as_ref().map(|end| end.as_str());
                             log::debug!("deleting embeddings in range {:?}", &(start, end));
                             db.delete_range(&mut txn, &(start, end))?;
+                            keyword_index.delete_range(&mut txn, &(start, end))?;
                             txn.commit()?;
                         }
                     },
                     file = embedded_files.next() => {
-                        if let Some((file, _)) = file {
+                        if let Some((file, keyword_file, _)) = file {
                             let mut txn = db_connection.write_txn()?;
                             log::debug!("saving embedding for file {:?}", file.path);
                             let key = db_key_for_path(&file.path);
                             db.put(&mut txn, &key, &file)?;
+                            keyword_index.insert_file(&mut txn, &key, &keyword_file)?;
                             txn.commit()?;
                         }
                     },
@@ -447,13 +471,14 @@ struct ChunkFiles {
 pub struct ChunkedFile {
     pub path: Arc<Path>,
     pub mtime: Option<MTime>,
+    pub language: Option<LanguageName>,
     pub handle: IndexingEntryHandle,
     pub text: String,
     pub chunks: Vec<Chunk>,
 }
 
 pub struct EmbedFiles {
-    pub files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+    pub files: channel::Receiver<(EmbeddedFile, KeywordFile, IndexingEntryHandle)>,
     pub task: Task<Result<()>>,
 }
 
@@ -470,6 +495,6 @@ pub struct EmbeddedChunk {
     pub embedding: Embedding,
 }
 
-fn db_key_for_path(path: &Arc<Path>) -> String {
+pub(crate) fn db_key_for_path(path: &Arc<Path>) -> String {
     path.to_string_lossy().replace('/', "\0")
 }
@@ -0,0 +1,470 @@
+use crate::chunking::Chunk;
+use anyhow::Result;
+use collections::{Bound, HashMap};
+use heed::types::{SerdeBincode, Str};
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, path::Path, sync::Arc};
+
+/// Terms longer than this are not indexed. They are rarely searched for, and
+/// LMDB limits the size of keys.
+const MAX_TERM_LEN: usize = 64;
+
+/// BM25 term frequency saturation.
+const K1: f32 = 1.2;
+/// BM25 document length normalization.
+const B: f32 = 0.75;
+
+/// The key under which corpus statistics are stored in the postings database.
+/// Terms are never empty, so it can't collide with a posting key.
+const STATS_KEY: &str = "";
+
+/// An inverted index from terms to the chunks containing them, stored alongside
+/// the embeddings so chunks can be ranked with BM25.
+///
+/// The postings of a term are stored per file, under `{term}\0{file_key}`, so
+/// that indexing a file only touches its own postings.
+#[derive(Clone, Copy)]
+pub struct KeywordIndex {
+    files_db: heed::Database<Str, SerdeBincode<KeywordFile>>,
+    postings_db: heed::Database<Str, SerdeBincode<Vec<Posting>>>,
+    /// The language of each file, so that it can be filtered by without reading
+    /// all of its terms.
+    languages_db: heed::Database<Str, Str>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeywordFile {
+    pub path: Arc<Path>,
+    pub language: Option<String>,
+    pub chunks: Vec<KeywordChunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeywordChunk {
+    pub range: Range<usize>,
+    /// The number of terms in the chunk, used to normalize scores by length.
+    pub term_count: u32,
+    /// How often each distinct term occurs in the chunk.
+    pub term_frequencies: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Posting {
+    chunk_ix: u32,
+    term_frequency: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeywordStats {
+    chunk_count: u64,
+    term_count: u64,
+}
+
+#[derive(Debug)]
+pub struct KeywordMatch {
+    pub path: Arc<Path>,
+    pub range: Range<usize>,
+    pub score: f32,
+}
+
+impl KeywordFile {
+    pub fn new(path: Arc<Path>, language: Option<String>, text: &str, chunks: &[Chunk]) -> Self {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| {
+                let mut term_count = 0;
+                let mut term_frequencies = HashMap::<String, u32>::default();
+                for term in tokenize(&text[chunk.range.clone()]) {
+                    term_count += 1;
+                    *term_frequencies.entry(term).or_default() += 1;
+                }
+                let mut term_frequencies = term_frequencies.into_iter().collect::<Vec<_>>();
+                term_frequencies.sort_unstable();
+                KeywordChunk {
+                    range: chunk.range.clone(),
+                    term_count,
+                    term_frequencies,
+                }
+            })
+            .collect();
+        Self {
+            path,
+            language,
+            chunks,
+        }
+    }
+}
+
+impl KeywordIndex {
+    pub fn new(
+        files_db: heed::Database<Str, SerdeBincode<KeywordFile>>,
+        postings_db: heed::Database<Str, SerdeBincode<Vec<Posting>>>,
+        languages_db: heed::Database<Str, Str>,
+    ) -> Self {
+        Self {
+            files_db,
+            postings_db,
+            languages_db,
+        }
+    }
+
+    pub fn contains(&self, txn: &heed::RoTxn, file_key: &str) -> Result<bool> {
+        Ok(self.files_db.get(txn, file_key)?.is_some())
+    }
+
+    pub fn language(&self, txn: &heed::RoTxn, file_key: &str) -> Result<Option<String>> {
+        Ok(self
+            .languages_db
+            .get(txn, file_key)?
+            .map(|language| language.to_string()))
+    }
+
+    /// Replaces the terms indexed for the given file.
+    pub fn insert_file(
+        &self,
+        txn: &mut heed::RwTxn,
+        file_key: &str,
+        file: &KeywordFile,
+    ) -> Result<()> {
+        self.delete_file(txn, file_key)?;
+
+        let mut postings_by_term = HashMap::<&str, Vec<Posting>>::default();
+        let mut stats = self.stats(txn)?;
+        for (chunk_ix, chunk) in file.chunks.iter().enumerate() {
+            stats.chunk_count += 1;
+            stats.term_count += chunk.term_count as u64;
+            for (term, term_frequency) in &chunk.term_frequencies {
+                postings_by_term
+                    .entry(term.as_str())
+                    .or_default()
+                    .push(Posting {
+                        chunk_ix: chunk_ix as u32,
+                        term_frequency: *term_frequency,
+                    });
+            }
+        }
+
+        for (term, postings) in postings_by_term {
+            self.postings_db
+                .put(txn, &posting_key(term, file_key), &postings)?;
+        }
+        if let Some(language) = &file.language {
+            self.languages_db.put(txn, file_key, language)?;
+        }
+        self.put_stats(txn, &stats)?;
+        self.files_db.put(txn, file_key, file)?;
+        Ok(())
+    }
+
+    pub fn delete_range(
+        &self,
+        txn: &mut heed::RwTxn,
+        range: &(Bound<&str>, Bound<&str>),
+    ) -> Result<()> {
+        let file_keys = self
+            .files_db
+            .range(txn, range)?
+            .map(|entry| Ok(entry?.0.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        for file_key in file_keys {
+            self.delete_file(txn, &file_key)?;
+        }
+        Ok(())
+    }
+
+    fn delete_file(&self, txn: &mut heed::RwTxn, file_key: &str) -> Result<()> {
+        let Some(file) = self.files_db.get(txn, file_key)? else {
+            return Ok(());
+        };
+
+        let mut stats = self.stats(txn)?;
+        let mut terms = Vec::new();
+        for chunk in &file.chunks {
+            stats.chunk_count = stats.chunk_count.saturating_sub(1);
+            stats.term_count = stats.term_count.saturating_sub(chunk.term_count as u64);
+            terms.extend(chunk.term_frequencies.iter().map(|(term, _)| term.as_str()));
+        }
+        terms.sort_unstable();
+        terms.dedup();
+
+        for term in terms {
+            self.postings_db.delete(txn, &posting_key(term, file_key))?;
+        }
+        self.languages_db.delete(txn, file_key)?;
+        self.put_stats(txn, &stats)?;
+        self.files_db.delete(txn, file_key)?;
+        Ok(())
+    }
+
+    /// Returns the chunks containing terms of the query, ranked by BM25.
+    pub fn search(
+        &self,
+        txn: &heed::RoTxn,
+        query: &str,
+        limit: usize,
+        mut include_file: impl FnMut(&KeywordFile) -> bool,
+    ) -> Result<Vec<KeywordMatch>> {
+        let stats = self.stats(txn)?;
+        if stats.chunk_count == 0 {
+            return Ok(Vec::new());
+        }
+        let chunk_count = stats.chunk_count as f32;
+        let average_term_count = stats.term_count as f32 / chunk_count;
+
+        let mut query_terms = tokenize(query).collect::<Vec<_>>();
+        query_terms.sort_unstable();
+        query_terms.dedup();
+
+        let mut files = HashMap::<String, Option<KeywordFile>>::default();
+        let mut scores = HashMap::<(String, u32), f32>::default();
+        for term in query_terms {
+            let prefix = posting_key(&term, "");
+            let postings_by_file = self
+                .postings_db
+                .prefix_iter(txn, &prefix)?
+                .map(|entry| {
+                    let (key, postings) = entry?;
+                    Ok((key[prefix.len()..].to_string(), postings))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let document_frequency = postings_by_file
+                .iter()
+                .map(|(_, postings)| postings.len())
+                .sum::<usize>() as f32;
+            let idf =
+                (1.0 + (chunk_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln();
+
+            for (file_key, postings) in postings_by_file {
+                if !files.contains_key(&file_key) {
+                    let file = self
+                        .files_db
+                        .get(txn, &file_key)?
+                        .filter(|file| include_file(file));
+                    files.insert(file_key.clone(), file);
+                }
+                let Some(file) = files[&file_key].as_ref() else {
+                    continue;
+                };
+
+                for posting in postings {
+                    let Some(chunk) = file.chunks.get(posting.chunk_ix as usize) else {
+                        continue;
+                    };
+                    let term_frequency = posting.term_frequency as f32;
+                    let length_norm = 1.0 - B + B * chunk.term_count as f32 / average_term_count;
+                    *scores
+                        .entry((file_key.clone(), posting.chunk_ix))
+                        .or_default() +=
+                        idf * term_frequency * (K1 + 1.0) / (term_frequency + K1 * length_norm);
+                }
+            }
+        }
+
+        let mut matches = scores
+            .into_iter()
+            .filter_map(|((file_key, chunk_ix), score)| {
+                let file = files.get(&file_key)?.as_ref()?;
+                let chunk = file.chunks.get(chunk_ix as usize)?;
+                Some(KeywordMatch {
+                    path: file.path.clone(),
+                    range: chunk.range.clone(),
+                    score,
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_unstable_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.range.start.cmp(&b.range.start))
+        });
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    fn stats(&self, txn: &heed::RoTxn) -> Result<KeywordStats> {
+        Ok(self
+            .postings_db
+            .remap_data_type::<SerdeBincode<KeywordStats>>()
+            .get(txn, STATS_KEY)?
+            .unwrap_or_default())
+    }
+
+    fn put_stats(&self, txn: &mut heed::RwTxn, stats: &KeywordStats) -> Result<()> {
+        self.postings_db
+            .remap_data_type::<SerdeBincode<KeywordStats>>()
+            .put(txn, STATS_KEY, stats)?;
+        Ok(())
+    }
+}
+
+/// Terms never contain a NUL character, so the postings of a term are exactly
+/// the keys starting with `posting_key(term, "")`.
+fn posting_key(term: &str, file_key: &str) -> String {
+    format!("{term}\0{file_key}")
+}
+
+/// Splits text into lowercase terms. Identifiers are indexed as a whole and by
+/// their snake_case and camelCase parts, so `parseError` matches `parse error` too.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|word| word.trim_matches('_'))
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let parts = identifier_parts(word);
+            let whole = word.to_lowercase();
+            let parts = if parts.len() > 1 { parts } else { Vec::new() };
+            std::iter::once(whole).chain(parts.into_iter().map(|part| part.to_lowercase()))
+        })
+        .filter(|term| term.len() <= MAX_TERM_LEN)
+}
+
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for segment in word.split('_').filter(|segment| !segment.is_empty()) {
+        let mut start = 0;
+        let mut prev: Option<char> = None;
+        let mut chars = segment.char_indices().peekable();
+        while let Some((ix, c)) = chars.next() {
+            if let Some(prev) = prev {
+                let next_is_lower = chars.peek().map_or(false, |(_, next)| next.is_lowercase());
+                // Split `parseError` before `E`, and `HTTPServer` before `S`.
+                let is_boundary = (prev.is_lowercase() && c.is_uppercase())
+                    || (prev.is_uppercase() && c.is_uppercase() && next_is_lower);
+                if is_boundary {
+                    parts.push(&segment[start..ix]);
+                    start = ix;
+                }
+            }
+            prev = Some(c);
+        }
+        parts.push(&segment[start..]);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn parseError(e: HTTPServer) -> E0308 { MAX_TERM_LEN }").collect::<Vec<_>>(),
+            [
+                "fn",
+                "parseerror",
+                "parse",
+                "error",
+                "e",
+                "httpserver",
+                "http",
+                "server",
+                "e0308",
+                "max_term_len",
+                "max",
+                "term",
+                "len",
+            ]
+        );
+        assert_eq!(tokenize(&"a".repeat(MAX_TERM_LEN + 1)).count(), 0);
+    }
+
+    #[test]
+    fn test_keyword_search() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(1024 * 1024 * 1024)
+                .max_dbs(3)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = env.write_txn().unwrap();
+        let index = KeywordIndex::new(
+            env.create_database(&mut txn, Some("keywords")).unwrap(),
+            env.create_database(&mut txn, Some("postings")).unwrap(),
+            env.create_database(&mut txn, Some("languages")).unwrap(),
+        );
+
+        let chunks = |text: &str| {
+            text.match_indices('\n')
+                .scan(0, |start, (ix, _)| {
+                    let range = *start..ix + 1;
+                    *start = ix + 1;
+                    Some(Chunk {
+                        range,
+                        digest: Default::default(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        let a = "fn handle_error() {}\nfn render() {}\n";
+        let b = "// error E0308 mismatched types\nlet x = 1;\n";
+        index
+            .insert_file(
+                &mut txn,
+                "a.rs",
+                &KeywordFile::new(Path::new("a.rs").into(), Some("Rust".into()), a, &chunks(a)),
+            )
+            .unwrap();
+        index
+            .insert_file(
+                &mut txn,
+                "b.rs",
+                &KeywordFile::new(Path::new("b.rs").into(), None, b, &chunks(b)),
+            )
+            .unwrap();
+
+        let search = |txn: &heed::RoTxn, query: &str| {
+            index
+                .search(txn, query, 10, |_| true)
+                .unwrap()
+                .into_iter()
+                .map(|mat| (mat.path.to_string_lossy().to_string(), mat.range))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search(&txn, "E0308"), [("b.rs".to_string(), 0..32)]);
+        assert_eq!(
+            search(&txn, "error"),
+            [("a.rs".to_string(), 0..21), ("b.rs".to_string(), 0..32)]
+        );
+        assert_eq!(search(&txn, "handle_error"), [("a.rs".to_string(), 0..21)]);
+        assert_eq!(
+            index
+                .search(&txn, "error", 10, |file| file.language.is_some())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(index.contains(&txn, "a.rs").unwrap());
+        assert_eq!(
+            index.language(&txn, "a.rs").unwrap().as_deref(),
+            Some("Rust")
+        );
+        assert_eq!(index.language(&txn, "b.rs").unwrap(), None);
+
+        // Re-indexing a file replaces its terms.
+        let a = "fn render() {}\n";
+        index
+            .insert_file(
+                &mut txn,
+                "a.rs",
+                &KeywordFile::new(Path::new("a.rs").into(), Some("Rust".into()), a, &chunks(a)),
+            )
+            .unwrap();
+        assert_eq!(search(&txn, "handle_error"), []);
+        assert_eq!(search(&txn, "render"), [("a.rs".to_string(), 0..15)]);
+
+        index
+            .delete_range(
+                &mut txn,
+                &(Bound::Included("a.rs"), Bound::Included("a.rs")),
+            )
+            .unwrap();
+        assert_eq!(search(&txn, "render"), []);
+        assert!(!index.contains(&txn, "a.rs").unwrap());
+        assert_eq!(index.language(&txn, "a.rs").unwrap(), None);
+        assert_eq!(search(&txn, "E0308"), [("b.rs".to_string(), 0..32)]);
+    }
+}
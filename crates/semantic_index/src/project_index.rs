@@ -4,7 +4,7 @@ use crate::{
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
 use anyhow::{anyhow, Context, Result};
-use collections::HashMap;
+use collections::{hash_map::Entry, HashMap, HashSet};
 use fs::Fs;
 use futures::FutureExt;
 use gpui::{
@@ -23,7 +23,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use util::ResultExt;
+use util::{paths::PathMatcher, ResultExt};
 
 #[derive(Debug)]
 pub struct SearchResult {
@@ -51,6 +51,32 @@ pub struct WorktreeSearchResult {
     pub score: f32,
 }
 
+/// Restricts the files a search returns chunks from.
+#[derive(Clone, Debug, Default)]
+pub struct SearchFilter {
+    /// Globs matched against paths relative to their worktree. Every path is
+    /// included when empty.
+    pub files_to_include: PathMatcher,
+    /// The names of the languages to include, compared case-insensitively.
+    /// Every file is included when empty.
+    pub languages: Vec<String>,
+}
+
+impl SearchFilter {
+    fn includes_path(&self, path: &Path) -> bool {
+        self.files_to_include.sources().is_empty() || self.files_to_include.is_match(path)
+    }
+
+    fn includes_language(&self, language: Option<&str>) -> bool {
+        self.languages.is_empty()
+            || language.map_or(false, |language| {
+                self.languages
+                    .iter()
+                    .any(|included| included.eq_ignore_ascii_case(language))
+            })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Status {
     Idle,
@@ -228,10 +254,13 @@ impl ProjectIndex {
         }
     }
 
+    /// Searches the project for chunks relevant to the given queries, fusing a
+    /// ranking by embedding similarity with a ranking by keywords.
     pub fn search(
         &self,
         queries: Vec<String>,
         limit: usize,
+        filter: SearchFilter,
         cx: &AppContext,
     ) -> Task<Result<Vec<SearchResult>>> {
         let (chunks_tx, chunks_rx) = channel::bounded(1024);
@@ -239,6 +268,8 @@ impl ProjectIndex {
         for worktree_index in self.worktree_indices.values() {
             let worktree_index = worktree_index.clone();
             let chunks_tx = chunks_tx.clone();
+            let queries = queries.clone();
+            let filter = filter.clone();
             worktree_scan_tasks.push(cx.spawn(|cx| async move {
                 let index = match worktree_index {
                     WorktreeIndexHandle::Loading { index } => {
@@ -252,20 +283,48 @@ impl ProjectIndex {
                         let worktree_id = index.worktree().read(cx).id();
                         let db_connection = index.db_connection().clone();
                         let db = *index.embedding_index().db();
+                        let keyword_index = *index.embedding_index().keyword_index();
                         cx.background_executor().spawn(async move {
                             let txn = db_connection
                                 .read_txn()
                                 .context("failed to create read transaction")?;
+
+                            let mut keyword_results = Vec::new();
+                            for (query_index, query) in queries.iter().enumerate() {
+                                let matches = keyword_index.search(&txn, query, limit, |file| {
+                                    filter.includes_path(&file.path)
+                                        && filter.includes_language(file.language.as_deref())
+                                })?;
+                                keyword_results.extend(matches.into_iter().map(|mat| {
+                                    WorktreeSearchResult {
+                                        worktree_id,
+                                        path: mat.path,
+                                        range: mat.range,
+                                        query_index,
+                                        score: mat.score,
+                                    }
+                                }));
+                            }
+
                             let db_entries = db.iter(&txn).context("failed to iterate database")?;
                             for db_entry in db_entries {
-                                let (_key, db_embedded_file) = db_entry?;
+                                let (key, db_embedded_file) = db_entry?;
+                                if !filter.includes_path(&db_embedded_file.path) {
+                                    continue;
+                                }
+                                if !filter.languages.is_empty() {
+                                    let language = keyword_index.language(&txn, key)?;
+                                    if !filter.includes_language(language.as_deref()) {
+                                        continue;
+                                    }
+                                }
                                 for chunk in db_embedded_file.chunks {
                                     chunks_tx
                                         .send((worktree_id, db_embedded_file.path.clone(), chunk))
                                         .await?;
                                 }
                             }
-                            anyhow::Ok(())
+                            anyhow::Ok(keyword_results)
                         })
                     })?
                     .await
@@ -332,14 +391,31 @@ impl ProjectIndex {
                 })
                 .await;
 
+            let mut keyword_results = Vec::new();
             for scan_task in futures::future::join_all(worktree_scan_tasks).await {
-                scan_task.log_err();
+                if let Some(results) = scan_task.log_err() {
+                    keyword_results.extend(results);
+                }
             }
 
+            let mut vector_results = results_by_worker.into_iter().flatten().collect::<Vec<_>>();
+            vector_results
+                .sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            vector_results.truncate(limit);
+
+            // Several queries may match the same chunk, keep its best keyword score.
+            keyword_results
+                .sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            let mut seen_chunks = HashSet::default();
+            keyword_results.retain(|result| seen_chunks.insert(chunk_key(result)));
+            keyword_results.truncate(limit);
+
+            let fused_results = fuse_rankings([vector_results, keyword_results], limit);
+
             project.read_with(&cx, |project, cx| {
-                let mut search_results = Vec::with_capacity(results_by_worker.len() * limit);
-                for worker_results in results_by_worker {
-                    search_results.extend(worker_results.into_iter().filter_map(|result| {
+                let search_results = fused_results
+                    .into_iter()
+                    .filter_map(|result| {
                         Some(SearchResult {
                             worktree: project.worktree_for_id(result.worktree_id, cx)?,
                             path: result.path,
@@ -347,12 +423,8 @@ impl ProjectIndex {
                             score: result.score,
                             query_index: result.query_index,
                         })
-                    }));
-                }
-                search_results.sort_unstable_by(|a, b| {
-                    b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
-                });
-                search_results.truncate(limit);
+                    })
+                    .collect::<Vec<_>>();
 
                 #[cfg(debug_assertions)]
                 {
@@ -549,3 +621,84 @@ impl ProjectIndex {
 }
 
 impl EventEmitter<Status> for ProjectIndex {}
+
+/// Dampens the influence of the top ranks when fusing rankings, so a chunk
+/// ranked highly by several rankings beats one ranked first by a single one.
+const RRF_K: f32 = 60.0;
+
+fn chunk_key(result: &WorktreeSearchResult) -> (WorktreeId, Arc<Path>, usize, usize) {
+    (
+        result.worktree_id,
+        result.path.clone(),
+        result.range.start,
+        result.range.end,
+    )
+}
+
+/// Combines rankings of chunks with reciprocal-rank fusion. Each chunk is
+/// scored by the sum of `1 / (RRF_K + rank)` over the rankings containing it.
+fn fuse_rankings<const N: usize>(
+    rankings: [Vec<WorktreeSearchResult>; N],
+    limit: usize,
+) -> Vec<WorktreeSearchResult> {
+    let mut fused_results = Vec::<WorktreeSearchResult>::new();
+    let mut indices_by_chunk = HashMap::default();
+    for ranking in rankings {
+        for (rank, mut result) in ranking.into_iter().enumerate() {
+            let score = 1. / (RRF_K + rank as f32 + 1.);
+            match indices_by_chunk.entry(chunk_key(&result)) {
+                Entry::Occupied(entry) => fused_results[*entry.get()].score += score,
+                Entry::Vacant(entry) => {
+                    entry.insert(fused_results.len());
+                    result.score = score;
+                    fused_results.push(result);
+                }
+            }
+        }
+    }
+
+    fused_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    fused_results.truncate(limit);
+    fused_results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuse_rankings() {
+        let worktree_id = WorktreeId::from_usize(1);
+        let result = |path: &str, query_index: usize| WorktreeSearchResult {
+            worktree_id,
+            path: Path::new(path).into(),
+            range: 0..10,
+            query_index,
+            score: 0.,
+        };
+        let paths = |results: Vec<WorktreeSearchResult>| {
+            results
+                .into_iter()
+                .map(|result| {
+                    (
+                        result.path.to_string_lossy().to_string(),
+                        result.query_index,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let vector_results = vec![result("a", 0), result("b", 0), result("c", 1)];
+        let keyword_results = vec![result("c", 0), result("d", 0), result("b", 1)];
+        // Chunks found by both rankings come first, and keep the query index of
+        // the first ranking that found them.
+        assert_eq!(
+            paths(fuse_rankings([vector_results, keyword_results], 3)),
+            [
+                ("c".to_string(), 1),
+                ("b".to_string(), 0),
+                ("a".to_string(), 0),
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@ mod chunking;
 mod embedding;
 mod embedding_index;
 mod indexing;
+mod keyword_index;
 mod project_index;
 mod project_index_debug_view;
 mod summary_backlog;
@@ -24,7 +25,7 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
-pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
+pub use project_index::{LoadedSearchResult, ProjectIndex, SearchFilter, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
 pub use summary_index::FileSummary;
 
@@ -403,7 +404,7 @@ mod tests {
             .update(|cx| {
                 let project_index = project_index.read(cx);
                 let query = "garbage in, garbage out";
-                project_index.search(vec![query.into()], 4, cx)
+                project_index.search(vec![query.into()], 4, SearchFilter::default(), cx)
             })
             .await
             .unwrap();
@@ -419,8 +420,8 @@ mod tests {
             println!("score: {:?}", result.score);
         }
 
-        // Find result that is greater than 0.5
-        let search_result = results.iter().find(|result| result.score > 0.9).unwrap();
+        // The needle ranks first both by similarity and by keywords.
+        let search_result = &results[0];
 
         assert_eq!(search_result.path.to_string_lossy(), "fixture/needle.md");
 
@@ -464,6 +465,7 @@ mod tests {
             .send_blocking(ChunkedFile {
                 path: Path::new("test1.md").into(),
                 mtime: None,
+                language: None,
                 handle: indexing_entries.insert(ProjectEntryId::from_proto(0)),
                 text: "abcdefghijklmnop".to_string(),
                 chunks: [0..4, 4..8, 8..12, 12..16]
@@ -479,6 +481,7 @@ mod tests {
             .send_blocking(ChunkedFile {
                 path: Path::new("test2.md").into(),
                 mtime: None,
+                language: None,
                 handle: indexing_entries.insert(ProjectEntryId::from_proto(1)),
                 text: "qrstuvwxyz".to_string(),
                 chunks: [0..4, 4..8, 8..10]
@@ -498,7 +501,7 @@ mod tests {
 
         let embedded_files_rx = embed_files_task.files;
         let mut embedded_files = Vec::new();
-        while let Ok((embedded_file, _)) = embedded_files_rx.recv().await {
+        while let Ok((embedded_file, _, _)) = embedded_files_rx.recv().await {
             embedded_files.push(embedded_file);
         }
 
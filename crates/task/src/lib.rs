@@ -1,6 +1,7 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +14,11 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use problem_matcher::{
+    match_problems, BuiltInProblemMatcher, CustomProblemMatcher, FileLocation, FileLocationKind,
+    Problem, ProblemMatcher, ProblemPattern, ProblemPatterns, ProblemSeverity,
+};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +63,12 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Tasks to run before this one, resolved from the template's `depends_on` labels.
+    pub dependencies: Vec<SpawnInTerminal>,
+    /// Whether the dependencies run one after another or all at once.
+    pub depends_order: DependsOrder,
+    /// Matchers to find the problems in the task's output with.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particular [`TaskContext`] and now is ready to spawn the actual task.
@@ -0,0 +1,612 @@
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+/// A way to find the errors and warnings that the tools run by a task print:
+/// either one of the built-in matchers, or a custom one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the matchers for the common tools.
+    BuiltIn(BuiltInProblemMatcher),
+    /// A matcher defined with regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// Problem matchers for the common tools, also available under the names VS Code gives them (e.g. `$rustc`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltInProblemMatcher {
+    /// Errors and warnings of `rustc`, including the ones printed by `cargo`.
+    #[serde(alias = "$rustc", alias = "cargo")]
+    Rustc,
+    /// Errors of the TypeScript compiler.
+    #[serde(alias = "$tsc")]
+    Tsc,
+    /// Errors and warnings of `gcc` and `clang`.
+    #[serde(alias = "$gcc", alias = "clang")]
+    Gcc,
+    /// Errors and warnings of ESLint, in its default `stylish` format.
+    #[serde(alias = "$eslint-stylish")]
+    Eslint,
+}
+
+/// A problem matcher defined with regular expressions, in the same shape as VS Code's `problemMatcher`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// The source of the diagnostics, displayed along with their messages. Defaults to the task's label.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// The pattern matching a problem, or the patterns matching the consecutive lines of a problem.
+    pub pattern: ProblemPatterns,
+    /// How to find the files that the problems are reported in.
+    #[serde(default, alias = "fileLocation")]
+    pub file_location: FileLocation,
+    /// The severity of the problems whose pattern doesn't capture it.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+}
+
+/// One or several patterns of a problem matcher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    /// A pattern matching whole problems, each on a single line.
+    Single(ProblemPattern),
+    /// Patterns matching consecutive lines, each capturing parts of a problem.
+    Multiple(Vec<ProblemPattern>),
+}
+
+impl ProblemPatterns {
+    fn as_slice(&self) -> &[ProblemPattern] {
+        match self {
+            Self::Single(pattern) => std::slice::from_ref(pattern),
+            Self::Multiple(patterns) => patterns,
+        }
+    }
+}
+
+/// A regular expression matching a line of a problem, with the indices of the groups capturing its parts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// The group capturing the path of the file.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The group capturing the location of the problem, as `line`, `line,column` or `line,column,end_line,end_column`.
+    #[serde(default)]
+    pub location: Option<usize>,
+    /// The group capturing the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The group capturing the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The group capturing the line the problem ends at.
+    #[serde(default, alias = "endLine")]
+    pub end_line: Option<usize>,
+    /// The group capturing the column the problem ends at.
+    #[serde(default, alias = "endColumn")]
+    pub end_column: Option<usize>,
+    /// The group capturing the severity, like `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The group capturing the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The group capturing the message.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of several may match any number of consecutive lines, each being a separate problem.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// How to find the files that the problems are reported in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum FileLocation {
+    /// The paths are resolved against the task's working directory.
+    Kind(FileLocationKind),
+    /// The paths are resolved against the given directory, as in `["relative", "${ZED_WORKTREE_ROOT}/src"]`.
+    WithBase(FileLocationKind, String),
+}
+
+impl Default for FileLocation {
+    fn default() -> Self {
+        Self::Kind(FileLocationKind::default())
+    }
+}
+
+/// Whether the paths of the problems are absolute or relative.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileLocationKind {
+    /// The paths are absolute.
+    Absolute,
+    /// The paths are relative to the base directory.
+    Relative,
+    /// The paths are absolute when they look like it, relative otherwise.
+    #[default]
+    #[serde(alias = "autoDetect")]
+    AutoDetect,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+}
+
+impl ProblemSeverity {
+    fn parse(severity: &str) -> Option<Self> {
+        match severity.trim().to_lowercase().as_str() {
+            "error" | "fatal error" | "e" => Some(Self::Error),
+            "warning" | "warn" | "w" => Some(Self::Warning),
+            "info" | "information" | "note" | "hint" | "i" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in a task's output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The path of the file with the problem, absolute unless no base directory was known to resolve it.
+    pub path: PathBuf,
+    /// The 0-based line and column where the problem starts.
+    pub start: (u32, u32),
+    /// The 0-based line and column where the problem ends, if reported.
+    pub end: Option<(u32, u32)>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The code of the problem, like `E0308`.
+    pub code: Option<String>,
+    /// The message describing the problem.
+    pub message: String,
+    /// The tool or matcher that reported the problem.
+    pub source: String,
+}
+
+impl ProblemMatcher {
+    pub(crate) fn to_custom(&self) -> CustomProblemMatcher {
+        let (owner, patterns) = match self {
+            Self::Custom(matcher) => return matcher.clone(),
+            Self::BuiltIn(BuiltInProblemMatcher::Rustc) => (
+                "rustc",
+                vec![
+                    ProblemPattern {
+                        regexp: r"^(warning|error)(?:\[(\S+)\])?: (.*)$".to_string(),
+                        severity: Some(1),
+                        code: Some(2),
+                        message: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                    ProblemPattern {
+                        regexp: r"^\s*-->\s+(.+?):(\d+):(\d+)\s*$".to_string(),
+                        file: Some(1),
+                        line: Some(2),
+                        column: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                ],
+            ),
+            Self::BuiltIn(BuiltInProblemMatcher::Tsc) => (
+                "tsc",
+                vec![ProblemPattern {
+                    regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                        .to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    code: Some(5),
+                    message: Some(6),
+                    ..ProblemPattern::default()
+                }],
+            ),
+            Self::BuiltIn(BuiltInProblemMatcher::Gcc) => (
+                "gcc",
+                vec![ProblemPattern {
+                    regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error):\s+(.*)$"
+                        .to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    message: Some(5),
+                    ..ProblemPattern::default()
+                }],
+            ),
+            Self::BuiltIn(BuiltInProblemMatcher::Eslint) => (
+                "eslint",
+                vec![
+                    ProblemPattern {
+                        regexp: r"^([^\s].*)$".to_string(),
+                        file: Some(1),
+                        ..ProblemPattern::default()
+                    },
+                    ProblemPattern {
+                        regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S*))?$"
+                            .to_string(),
+                        line: Some(1),
+                        column: Some(2),
+                        severity: Some(3),
+                        message: Some(4),
+                        code: Some(5),
+                        repeat: true,
+                        ..ProblemPattern::default()
+                    },
+                ],
+            ),
+        };
+        CustomProblemMatcher {
+            owner: Some(owner.to_string()),
+            pattern: ProblemPatterns::Multiple(patterns),
+            file_location: FileLocation::default(),
+            severity: ProblemSeverity::default(),
+        }
+    }
+}
+
+/// Finds the problems in the output of a task, using all matchers given.
+/// Relative paths are resolved against the `cwd` given, unless the matcher specifies its own base directory.
+/// Problems without a file, a line or a message are skipped.
+pub fn match_problems(
+    matchers: &[ProblemMatcher],
+    output_lines: &[String],
+    cwd: Option<&Path>,
+    task_label: &str,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for matcher in matchers {
+        let matcher = matcher.to_custom();
+        let Some(patterns) = matcher
+            .pattern
+            .as_slice()
+            .iter()
+            .map(|pattern| Some((Regex::new(&pattern.regexp).log_err()?, pattern)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        if patterns.is_empty() {
+            continue;
+        }
+        let source = matcher.owner.as_deref().unwrap_or(task_label);
+
+        let mut next_pattern = 0;
+        let mut fields = ProblemFields::default();
+        for line in output_lines {
+            // A line that doesn't continue the current problem may start a new one.
+            if next_pattern > 0 {
+                let (regex, pattern) = &patterns[next_pattern];
+                if let Some(captures) = regex.captures(line) {
+                    if pattern.repeat && next_pattern == patterns.len() - 1 {
+                        let mut looped_fields = fields.clone();
+                        looped_fields.fill(pattern, &captures);
+                        problems.extend(looped_fields.to_problem(&matcher, source, cwd));
+                    } else {
+                        fields.fill(pattern, &captures);
+                        next_pattern += 1;
+                        if next_pattern == patterns.len() {
+                            problems.extend(fields.to_problem(&matcher, source, cwd));
+                            next_pattern = 0;
+                        }
+                    }
+                    continue;
+                }
+                next_pattern = 0;
+            }
+
+            let (regex, pattern) = &patterns[0];
+            if let Some(captures) = regex.captures(line) {
+                fields = ProblemFields::default();
+                fields.fill(pattern, &captures);
+                if patterns.len() == 1 {
+                    problems.extend(fields.to_problem(&matcher, source, cwd));
+                } else {
+                    next_pattern = 1;
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// The parts of a problem captured so far.
+#[derive(Clone, Debug, Default)]
+struct ProblemFields {
+    file: Option<String>,
+    location: Option<String>,
+    line: Option<String>,
+    column: Option<String>,
+    end_line: Option<String>,
+    end_column: Option<String>,
+    severity: Option<String>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemFields {
+    fn fill(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let capture = |group: Option<usize>| {
+            let text = captures.get(group?)?.as_str().trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+        for (field, group) in [
+            (&mut self.file, pattern.file),
+            (&mut self.location, pattern.location),
+            (&mut self.line, pattern.line),
+            (&mut self.column, pattern.column),
+            (&mut self.end_line, pattern.end_line),
+            (&mut self.end_column, pattern.end_column),
+            (&mut self.severity, pattern.severity),
+            (&mut self.code, pattern.code),
+            (&mut self.message, pattern.message),
+        ] {
+            if let Some(value) = capture(group) {
+                *field = Some(value);
+            }
+        }
+    }
+
+    fn to_problem(
+        &self,
+        matcher: &CustomProblemMatcher,
+        source: &str,
+        cwd: Option<&Path>,
+    ) -> Option<Problem> {
+        let number = |text: &Option<String>| text.as_deref()?.parse::<u32>().ok();
+        let (line, column, end_line, end_column) = match &self.location {
+            Some(location) => {
+                let mut numbers = location
+                    .split(',')
+                    .map(|number| number.trim().parse::<u32>().ok());
+                (
+                    numbers.next().flatten(),
+                    numbers.next().flatten(),
+                    numbers.next().flatten(),
+                    numbers.next().flatten(),
+                )
+            }
+            None => (
+                number(&self.line),
+                number(&self.column),
+                number(&self.end_line),
+                number(&self.end_column),
+            ),
+        };
+        // Tools report 1-based lines and columns.
+        let start = (
+            line?.saturating_sub(1),
+            column.unwrap_or(1).saturating_sub(1),
+        );
+        let end = end_line.map(|end_line| {
+            (
+                end_line.saturating_sub(1),
+                end_column.map_or(start.1, |column| column.saturating_sub(1)),
+            )
+        });
+        Some(Problem {
+            path: resolve_path(self.file.as_deref()?, &matcher.file_location, cwd),
+            start,
+            end,
+            severity: self
+                .severity
+                .as_deref()
+                .and_then(ProblemSeverity::parse)
+                .unwrap_or(matcher.severity),
+            code: self.code.clone(),
+            message: self.message.clone()?,
+            source: source.to_string(),
+        })
+    }
+}
+
+fn resolve_path(path: &str, file_location: &FileLocation, cwd: Option<&Path>) -> PathBuf {
+    let (kind, base) = match file_location {
+        FileLocation::Kind(kind) => (*kind, cwd),
+        FileLocation::WithBase(kind, base) => (*kind, Some(Path::new(base.as_str()))),
+    };
+    let path = Path::new(path);
+    match (kind, base) {
+        (FileLocationKind::Absolute, _) | (_, None) => path.to_path_buf(),
+        (FileLocationKind::AutoDetect, _) if path.is_absolute() => path.to_path_buf(),
+        (FileLocationKind::Relative | FileLocationKind::AutoDetect, Some(base)) => base.join(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(output: &str) -> Vec<String> {
+        output.lines().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_rustc_problems() {
+        let output = lines(
+            r#"   Compiling zed v0.1.0 (/project)
+error[E0308]: mismatched types
+ --> src/main.rs:2:18
+  |
+2 |     let x: u32 = "one";
+  |            ---   ^^^^^ expected `u32`, found `&str`
+
+warning: unused variable: `y`
+  --> /other/src/lib.rs:10:9
+error: could not compile `zed` (bin "zed") due to 1 previous error"#,
+        );
+        let problems = match_problems(
+            &[ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
+            &output,
+            Some(Path::new("/project")),
+            "cargo build",
+        );
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: PathBuf::from("/project/src/main.rs"),
+                    start: (1, 17),
+                    end: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0308".to_string()),
+                    message: "mismatched types".to_string(),
+                    source: "rustc".to_string(),
+                },
+                Problem {
+                    path: PathBuf::from("/other/src/lib.rs"),
+                    start: (9, 8),
+                    end: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                    source: "rustc".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_problems() {
+        let tsc = match_problems(
+            &[ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
+            &lines("src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'."),
+            Some(Path::new("/project")),
+            "tsc",
+        );
+        assert_eq!(tsc.len(), 1);
+        assert_eq!(tsc[0].path, PathBuf::from("/project/src/index.ts"));
+        assert_eq!(tsc[0].start, (2, 6));
+        assert_eq!(tsc[0].code.as_deref(), Some("TS2322"));
+
+        let gcc = match_problems(
+            &[ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc)],
+            &lines("main.c:4:5: warning: implicit declaration of function 'foo'\nmain.c: In function 'main':"),
+            None,
+            "make",
+        );
+        assert_eq!(gcc.len(), 1);
+        assert_eq!(gcc[0].path, PathBuf::from("main.c"));
+        assert_eq!(gcc[0].severity, ProblemSeverity::Warning);
+        assert_eq!(gcc[0].message, "implicit declaration of function 'foo'");
+    }
+
+    #[test]
+    fn test_looping_problems() {
+        let output = lines(
+            r#"/project/src/a.js
+  1:10  error    'x' is defined but never used  no-unused-vars
+  2:1   warning  Unexpected console statement   no-console
+
+/project/src/b.js
+  5:3  error  Missing semicolon  semi
+
+3 problems (2 errors, 1 warning)"#,
+        );
+        let problems = match_problems(
+            &[ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Eslint)],
+            &output,
+            None,
+            "eslint",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().into_owned(),
+                    problem.start,
+                    problem.severity,
+                    problem.message.as_str(),
+                    problem.code.as_deref(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/project/src/a.js".to_string(),
+                    (0, 9),
+                    ProblemSeverity::Error,
+                    "'x' is defined but never used",
+                    Some("no-unused-vars"),
+                ),
+                (
+                    "/project/src/a.js".to_string(),
+                    (1, 0),
+                    ProblemSeverity::Warning,
+                    "Unexpected console statement",
+                    Some("no-console"),
+                ),
+                (
+                    "/project/src/b.js".to_string(),
+                    (4, 2),
+                    ProblemSeverity::Error,
+                    "Missing semicolon",
+                    Some("semi"),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "owner": "lint",
+                "fileLocation": ["relative", "/project/src"],
+                "severity": "warning",
+                "pattern": {
+                    "regexp": "^(.*):(\\d+,\\d+,\\d+,\\d+): (.*)$",
+                    "file": 1,
+                    "location": 2,
+                    "message": 3
+                }
+            }"#,
+        )
+        .unwrap();
+        let problems = match_problems(
+            &[matcher],
+            &lines("lib.rs:3,1,3,5: bad name"),
+            Some(Path::new("/project")),
+            "lint",
+        );
+        assert_eq!(
+            problems,
+            vec![Problem {
+                path: PathBuf::from("/project/src/lib.rs"),
+                start: (2, 0),
+                end: Some((2, 4)),
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "bad name".to_string(),
+                source: "lint".to_string(),
+            }]
+        );
+
+        let built_in: Vec<ProblemMatcher> =
+            serde_json_lenient::from_str(r#"["$rustc", "tsc", "clang"]"#).unwrap();
+        assert_eq!(
+            built_in,
+            vec![
+                ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc),
+                ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc),
+                ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Gcc),
+            ]
+        );
+    }
+}
@@ -9,8 +9,9 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    problem_matcher::{CustomProblemMatcher, FileLocation},
+    ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId,
+    VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -24,7 +25,8 @@ use crate::{
 pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
-    /// Executable command to spawn.
+    /// Executable command to spawn. May be empty for tasks that only run their `depends_on` tasks.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -70,6 +72,30 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one. If any of them fails, this task is not run.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `sequence` — one after another, in the order they are listed (default)
+    /// * `parallel` — all at once
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Matchers to find the errors and warnings in the task's output, reported as project diagnostics when it finishes.
+    /// Either the name of a built-in matcher (`rustc`, `tsc`, `gcc` or `eslint`), or a custom matcher
+    /// with the same fields as VS Code's `problemMatcher`.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcher>,
+}
+
+/// How to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run the tasks one after another, in the order they are listed.
+    #[default]
+    Sequence,
+    /// Run all the tasks at once.
+    Parallel,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -121,8 +147,12 @@ impl TaskTemplate {
     ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
+    ///
+    /// The tasks from `depends_on` are not resolved, as they are looked up in the task sources.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -189,6 +219,31 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let problem_matchers = self
+            .problem_matchers
+            .iter()
+            .map(|matcher| match matcher {
+                ProblemMatcher::Custom(
+                    custom @ CustomProblemMatcher {
+                        file_location: FileLocation::WithBase(kind, base),
+                        ..
+                    },
+                ) => {
+                    let base = substitute_all_template_variables_in_str(
+                        base,
+                        &task_variables,
+                        &variable_names,
+                        &mut substituted_variables,
+                    )?;
+                    Some(ProblemMatcher::Custom(CustomProblemMatcher {
+                        file_location: FileLocation::WithBase(*kind, base),
+                        ..custom.clone()
+                    }))
+                }
+                matcher => Some(matcher.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -246,6 +301,9 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                dependencies: Vec::new(),
+                depends_order: self.depends_order,
+                problem_matchers,
             }),
         })
     }
@@ -394,6 +452,24 @@ mod tests {
                 "should not resolve task with blank label and/or command: {task_with_blank_property:?}"
             );
         }
+
+        let task_with_dependencies_only = TaskTemplate {
+            label: "test_label".to_string(),
+            depends_on: vec!["test_dependency".to_string()],
+            depends_order: DependsOrder::Parallel,
+            ..TaskTemplate::default()
+        };
+        let resolved = task_with_dependencies_only
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve task that only has dependencies")
+            .resolved
+            .unwrap();
+        assert_eq!(resolved.command, "");
+        assert_eq!(resolved.depends_order, DependsOrder::Parallel);
+        assert!(
+            resolved.dependencies.is_empty(),
+            "dependencies are resolved by the task sources"
+        );
     }
 
     #[test]
@@ -3,7 +3,10 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BuiltInProblemMatcher, CustomProblemMatcher, DependsOrder, FileLocation, ProblemMatcher,
+    TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +25,28 @@ struct VsCodeTaskDefinition {
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
+    depends_on: Option<DependsOn>,
+    depends_order: Option<VsCodeDependsOrder>,
+}
+
+/// Labels of the tasks to run first, either a single one or a list.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum DependsOn {
+    Single(String),
+    Multiple(Vec<String>),
+    /// Dependencies Zed can't run, such as the ones identifying a task by its
+    /// type instead of its label. Only the task depending on them is skipped.
+    Unsupported(serde_json_lenient::Value),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum VsCodeDependsOrder {
+    Sequence,
+    Parallel,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Clone, Deserialize, PartialEq, Debug)]
@@ -85,21 +110,41 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
+        let depends_on = match self.depends_on {
+            Some(DependsOn::Single(label)) => vec![label],
+            Some(DependsOn::Multiple(labels)) => labels,
+            Some(DependsOn::Unsupported(depends_on)) => {
+                bail!(
+                    "Unsupported `dependsOn` value in task {:?}: {depends_on}",
+                    self.label
+                )
+            }
+            None => Vec::new(),
+        };
+        // Unlike Zed, VSC runs the dependencies in parallel by default.
+        let depends_order = match self.depends_order {
+            Some(VsCodeDependsOrder::Sequence) => DependsOrder::Sequence,
+            _ if depends_on.is_empty() => DependsOrder::default(),
+            Some(VsCodeDependsOrder::Parallel | VsCodeDependsOrder::Unknown) | None => {
+                DependsOrder::Parallel
+            }
+        };
+        // `type` might not be set in tasks that only use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
         // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        let problem_matchers = match self.other_attributes.get("problemMatcher") {
+            Some(serde_json_lenient::Value::Array(matchers)) => matchers
+                .iter()
+                .filter_map(|matcher| problem_matcher(matcher, replacer))
+                .collect(),
+            Some(matcher) => problem_matcher(matcher, replacer).into_iter().collect(),
+            None => Vec::new(),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,6 +153,9 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matchers,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -118,6 +166,38 @@ impl VsCodeTaskDefinition {
     }
 }
 
+/// Converts a VSC problem matcher: the name of a built-in one, a custom one,
+/// or a built-in one looking for the files in another location.
+/// Matchers that Zed doesn't have, like the ones of background tasks, are skipped.
+fn problem_matcher(
+    value: &serde_json_lenient::Value,
+    replacer: &EnvVariableReplacer,
+) -> Option<ProblemMatcher> {
+    let mut matcher = match value.get("base") {
+        Some(base) => {
+            let base = ProblemMatcher::BuiltIn(
+                serde_json_lenient::from_value::<BuiltInProblemMatcher>(base.clone()).ok()?,
+            );
+            match value.get("fileLocation") {
+                Some(file_location) => ProblemMatcher::Custom(CustomProblemMatcher {
+                    file_location: serde_json_lenient::from_value(file_location.clone()).ok()?,
+                    ..base.to_custom()
+                }),
+                None => base,
+            }
+        }
+        None => serde_json_lenient::from_value(value.clone()).ok()?,
+    };
+    if let ProblemMatcher::Custom(CustomProblemMatcher {
+        file_location: FileLocation::WithBase(_, base),
+        ..
+    }) = &mut matcher
+    {
+        *base = replacer.replace(base);
+    }
+    Some(matcher)
+}
+
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
@@ -154,8 +234,9 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        vscode_format::{Command, DependsOn, VsCodeTaskDefinition},
+        BuiltInProblemMatcher, CustomProblemMatcher, DependsOrder, FileLocation, FileLocationKind,
+        ProblemMatcher, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -212,6 +293,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -226,6 +309,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -234,6 +319,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -242,6 +329,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
         ];
 
@@ -257,6 +346,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
@@ -274,12 +364,14 @@ mod tests {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc)],
                 ..Default::default()
             },
         ];
@@ -300,6 +392,8 @@ mod tests {
                     script: "watch".to_string(),
                 }),
                 options: None,
+                depends_on: None,
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -308,6 +402,8 @@ mod tests {
                     script: "build".to_string(),
                 }),
                 options: None,
+                depends_on: None,
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -317,6 +413,8 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                depends_on: None,
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -326,6 +424,8 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                depends_on: None,
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -334,18 +434,30 @@ mod tests {
                     script: "pretest".to_string(),
                 }),
                 options: None,
+                depends_on: None,
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
+                depends_on: Some(DependsOn::Multiple(vec![
+                    "Build Server".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
+                depends_on: Some(DependsOn::Multiple(vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: None,
                 other_attributes: Default::default(),
             },
         ];
@@ -366,26 +478,144 @@ mod tests {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    file_location: FileLocation::WithBase(
+                        FileLocationKind::Relative,
+                        "${ZED_WORKTREE_ROOT}/editors/code/".to_string(),
+                    ),
+                    ..ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc).to_custom()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    file_location: FileLocation::WithBase(
+                        FileLocationKind::Relative,
+                        "${ZED_WORKTREE_ROOT}/editors/code/".to_string(),
+                    ),
+                    ..ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Tsc).to_custom()
+                })],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                depends_order: DependsOrder::Parallel,
+                problem_matchers: vec![ProblemMatcher::BuiltIn(BuiltInProblemMatcher::Rustc)],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_depends_on() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "Test",
+                        "type": "shell",
+                        "command": "cargo test",
+                        "dependsOn": "Build",
+                        "dependsOrder": "sequence"
+                    },
+                    {
+                        "label": "Missing type"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![TaskTemplate {
+                label: "Test".to_string(),
+                command: "cargo test".to_string(),
+                depends_on: vec!["Build".to_string()],
+                depends_order: DependsOrder::Sequence,
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn skips_tasks_with_unsupported_depends_on() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "Build",
+                        "type": "shell",
+                        "command": "cargo build"
+                    },
+                    {
+                        "label": "Bundle",
+                        "type": "shell",
+                        "command": "npm run bundle",
+                        "dependsOn": { "type": "npm", "script": "build" }
+                    },
+                    {
+                        "label": "Check",
+                        "type": "shell",
+                        "command": "cargo check",
+                        "dependsOn": ["Build", { "type": "npm", "script": "lint" }]
+                    },
+                    {
+                        "label": "Test",
+                        "type": "shell",
+                        "command": "cargo test",
+                        "dependsOn": ["Build"],
+                        "dependsOrder": "someday"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "Build".to_string(),
+                    command: "cargo build".to_string(),
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "Test".to_string(),
+                    command: "cargo test".to_string(),
+                    depends_on: vec!["Build".to_string()],
+                    depends_order: DependsOrder::Parallel,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
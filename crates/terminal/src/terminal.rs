@@ -13,7 +13,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -102,6 +102,8 @@ pub enum Event {
     TitleChanged,
     BreadcrumbsChanged,
     CloseTerminal,
+    /// The terminal's task has finished and won't output anything else.
+    TaskFinished,
     Bell,
     Wakeup,
     BlinkChanged(bool),
@@ -1367,6 +1369,36 @@ impl Terminal {
         lines
     }
 
+    /// Returns all lines of the terminal, from the top of its scrollback,
+    /// with the lines that were wrapped to fit its width joined back.
+    pub fn output_lines(&self) -> Vec<String> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+
+        let mut lines = Vec::new();
+        let mut line_buffer = String::new();
+        let mut current_line = terminal.topmost_line();
+        while current_line <= terminal.bottommost_line() {
+            let row = &terminal.grid()[current_line];
+            let mut wrapped = false;
+            for cell in row {
+                if !cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+                    line_buffer.push(cell.c);
+                }
+                wrapped = cell.flags.contains(Flags::WRAPLINE);
+            }
+            if !wrapped {
+                lines.push(line_buffer.trim_end().to_string());
+                line_buffer.clear();
+            }
+            current_line = Line(current_line.0 + 1);
+        }
+        if !line_buffer.is_empty() {
+            lines.push(line_buffer.trim_end().to_string());
+        }
+        lines
+    }
+
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
@@ -1783,6 +1815,7 @@ impl Terminal {
             // After the task summary is output once, no more text is appended to the terminal.
             unsafe { append_text_to_term(&mut self.term.lock(), &lines_to_show) };
         }
+        cx.emit(Event::TaskFinished);
 
         match task.hide {
             HideStrategy::Never => {}
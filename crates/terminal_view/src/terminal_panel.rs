@@ -1,4 +1,6 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    cell::RefCell, cmp, mem, ops::ControlFlow, path::PathBuf, rc::Rc, sync::Arc, time::Duration,
+};
 
 use crate::{
     default_working_directory,
@@ -7,10 +9,14 @@ use crate::{
     },
     TerminalView,
 };
+use async_recursion::async_recursion;
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    future::{join_all, try_join_all, FutureExt as _, LocalBoxFuture, Shared},
+    TryFutureExt as _,
+};
 use gpui::{
     actions, Action, AnyView, AppContext, AsyncWindowContext, Corner, Entity, EventEmitter,
     ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
@@ -20,10 +26,10 @@ use itertools::Itertools;
 use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use task::{RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal, TaskId};
+use task::{DependsOrder, RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable,
@@ -33,7 +39,9 @@ use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent, PanelHandle},
     item::SerializableItem,
-    move_active_item, move_item, pane,
+    move_active_item, move_item,
+    notifications::NotifyResultExt,
+    pane,
     ui::IconName,
     ActivateNextPane, ActivatePane, ActivatePaneInDirection, ActivatePreviousPane, DraggedTab,
     ItemId, MoveItemToPane, MoveItemToPaneInDirection, NewTerminal, Pane, PaneGroup,
@@ -46,6 +54,11 @@ use zed_actions::assistant::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
 
+/// The runs of the dependencies of a task, by label, so that a task that several others
+/// depend on only runs once.
+type DependencyRuns =
+    Rc<RefCell<HashMap<String, Shared<LocalBoxFuture<'static, Result<(), Arc<anyhow::Error>>>>>>>;
+
 actions!(terminal_panel, [ToggleFocus]);
 
 pub fn init(cx: &mut AppContext) {
@@ -73,6 +86,7 @@ pub struct TerminalPanel {
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
     deferred_tasks: HashMap<TaskId, Task<()>>,
+    dependent_tasks: HashMap<TaskId, Task<()>>,
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
@@ -93,6 +107,7 @@ impl TerminalPanel {
             height: None,
             pending_terminals_to_add: 0,
             deferred_tasks: HashMap::default(),
+            dependent_tasks: HashMap::default(),
             assistant_enabled: false,
             assistant_tab_bar_button: None,
             active: false,
@@ -448,22 +463,12 @@ impl TerminalPanel {
     }
 
     fn spawn_task(&mut self, task: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
-        let Ok(is_local) = self
-            .workspace
-            .update(cx, |workspace, cx| workspace.project().read(cx).is_local())
-        else {
+        if !task.dependencies.is_empty() {
+            self.spawn_task_with_dependencies(task.clone(), cx);
+            return;
+        }
+        let Some(task) = self.prepare_task(task, cx) else {
             return;
-        };
-
-        let builder = ShellBuilder::new(is_local, &task.shell);
-        let command_label = builder.command_label(&task.command_label);
-        let (command, args) = builder.build(task.command.clone(), &task.args);
-
-        let task = SpawnInTerminal {
-            command_label,
-            command,
-            args,
-            ..task.clone()
         };
 
         if task.allow_concurrent_runs && task.use_new_terminal {
@@ -511,6 +516,165 @@ impl TerminalPanel {
         );
     }
 
+    /// Wraps the task's command into the shell it should be spawned with.
+    fn prepare_task(&self, task: &SpawnInTerminal, cx: &AppContext) -> Option<SpawnInTerminal> {
+        let is_local = self
+            .workspace
+            .upgrade()?
+            .read(cx)
+            .project()
+            .read(cx)
+            .is_local();
+        let builder = ShellBuilder::new(is_local, &task.shell);
+        let command_label = builder.command_label(&task.command_label);
+        let (command, args) = builder.build(task.command.clone(), &task.args);
+        Some(SpawnInTerminal {
+            command_label,
+            command,
+            args,
+            ..task.clone()
+        })
+    }
+
+    /// Runs the tasks the task depends on, and then the task itself, unless any of them fails.
+    /// Tasks without a command only run their dependencies.
+    fn spawn_task_with_dependencies(&mut self, task: SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        let id = task.id.clone();
+        let run_chain = cx.spawn(|terminal_panel, mut cx| async move {
+            let dependencies_result = Self::run_dependencies(
+                terminal_panel.clone(),
+                task.dependencies.clone(),
+                task.depends_order,
+                DependencyRuns::default(),
+                cx.clone(),
+            )
+            .await
+            .map_err(|error| anyhow!("{error:#}, so task `{}` was not run", task.full_label));
+            if dependencies_result.notify_async_err(&mut cx).is_none() || task.command.is_empty() {
+                return;
+            }
+            terminal_panel
+                .update(&mut cx, |terminal_panel, cx| {
+                    terminal_panel.spawn_task(
+                        &SpawnInTerminal {
+                            dependencies: Vec::new(),
+                            ..task
+                        },
+                        cx,
+                    )
+                })
+                .ok();
+        });
+        self.dependent_tasks.insert(id, run_chain);
+    }
+
+    /// Runs the dependencies, reusing the runs of the ones that other tasks in the chain
+    /// already depend on.
+    async fn run_dependencies(
+        terminal_panel: WeakView<Self>,
+        dependencies: Vec<SpawnInTerminal>,
+        depends_order: DependsOrder,
+        dependency_runs: DependencyRuns,
+        cx: AsyncWindowContext,
+    ) -> Result<(), Arc<anyhow::Error>> {
+        let runs = dependencies
+            .into_iter()
+            .map(|dependency| {
+                dependency_runs
+                    .borrow_mut()
+                    .entry(dependency.full_label.clone())
+                    .or_insert_with(|| {
+                        Self::run_dependency(
+                            terminal_panel.clone(),
+                            dependency,
+                            dependency_runs.clone(),
+                            cx.clone(),
+                        )
+                        .map_err(Arc::new)
+                        .boxed_local()
+                        .shared()
+                    })
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        match depends_order {
+            DependsOrder::Sequence => {
+                for run in runs {
+                    run.await?;
+                }
+            }
+            DependsOrder::Parallel => {
+                try_join_all(runs).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a task that others depend on, with its own dependencies first, and waits for it to finish.
+    /// Fails if the task did not finish successfully.
+    #[async_recursion(?Send)]
+    async fn run_dependency(
+        terminal_panel: WeakView<Self>,
+        mut dependency: SpawnInTerminal,
+        dependency_runs: DependencyRuns,
+        mut cx: AsyncWindowContext,
+    ) -> Result<()> {
+        let dependencies = mem::take(&mut dependency.dependencies);
+        Self::run_dependencies(
+            terminal_panel.clone(),
+            dependencies,
+            dependency.depends_order,
+            dependency_runs,
+            cx.clone(),
+        )
+        .await
+        .map_err(|error| anyhow!("{error:#}"))?;
+        if dependency.command.is_empty() {
+            return Ok(());
+        }
+
+        let label = dependency.full_label.clone();
+        let terminals_for_task = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+            terminal_panel.terminals_for_task(&label, cx)
+        })?;
+        if !dependency.allow_concurrent_runs {
+            wait_for_terminals_tasks(terminals_for_task.clone(), &mut cx).await;
+        }
+        let terminal = terminal_panel
+            .update(&mut cx, |terminal_panel, cx| {
+                let task = terminal_panel
+                    .prepare_task(&dependency, cx)
+                    .context("preparing task")?;
+                anyhow::Ok(match terminals_for_task.last().cloned() {
+                    Some((item_index, task_pane, terminal_view)) if !task.use_new_terminal => {
+                        let replace = terminal_panel.replace_terminal(
+                            task,
+                            task_pane,
+                            item_index,
+                            terminal_view,
+                            cx,
+                        );
+                        cx.spawn(
+                            |_, _| async move { replace.await.context("replacing task terminal") },
+                        )
+                    }
+                    _ => terminal_panel.spawn_in_new_terminal(task, cx),
+                })
+            })??
+            .await?;
+
+        terminal
+            .update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+            .await;
+        let status = terminal.read_with(&cx, |terminal, _| {
+            terminal.task().map(|task_state| task_state.status)
+        })?;
+        match status {
+            Some(TaskStatus::Completed { success: true }) => Ok(()),
+            _ => Err(anyhow!("task `{label}` failed")),
+        }
+    }
+
     pub fn spawn_in_new_terminal(
         &mut self,
         spawn_task: SpawnInTerminal,
@@ -746,7 +910,7 @@ impl TerminalPanel {
         terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<Self>,
-    ) -> Task<Option<()>> {
+    ) -> Task<Option<Model<Terminal>>> {
         let reveal = spawn_task.reveal;
         let reveal_target = spawn_task.reveal_target;
         let window = cx.window_handle();
@@ -769,7 +933,7 @@ impl TerminalPanel {
                 .log_err()?;
             terminal_to_replace
                 .update(&mut cx, |terminal_to_replace, cx| {
-                    terminal_to_replace.set_terminal(new_terminal, cx);
+                    terminal_to_replace.set_terminal(new_terminal.clone(), cx);
                 })
                 .ok()?;
 
@@ -839,7 +1003,7 @@ impl TerminalPanel {
                 RevealStrategy::Never => {}
             }
 
-            Some(())
+            Some(new_terminal)
         })
     }
 
@@ -1339,3 +1503,66 @@ impl Render for InlineAssistTabBarButton {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use task::{TaskContext, TaskTemplate};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_failed_dependency_stops_task(cx: &mut TestAppContext) {
+        // The tasks run in real terminals.
+        cx.executor().allow_parking();
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            Project::init_settings(cx);
+            language::init(cx);
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root_view(cx).unwrap();
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let terminal_panel = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| TerminalPanel::new(workspace, cx))
+        });
+
+        let resolve = |label: &str, command: &str| {
+            TaskTemplate {
+                label: label.to_string(),
+                command: command.to_string(),
+                reveal: RevealStrategy::Never,
+                ..TaskTemplate::default()
+            }
+            .resolve_task("test", &TaskContext::default())
+            .unwrap()
+            .resolved
+            .unwrap()
+        };
+        let task = SpawnInTerminal {
+            dependencies: vec![resolve("check", "false")],
+            ..resolve("build", "true")
+        };
+        terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_task(&task, cx)
+        });
+        let run_chain = terminal_panel
+            .update(cx, |terminal_panel, _| {
+                terminal_panel.dependent_tasks.remove(&task.id)
+            })
+            .unwrap();
+        run_chain.await;
+        cx.run_until_parked();
+
+        terminal_panel.update(cx, |terminal_panel, cx| {
+            assert_eq!(terminal_panel.terminals_for_task("check", cx).len(), 1);
+            assert!(terminal_panel.terminals_for_task("build", cx).is_empty());
+        });
+        workspace.update(cx, |workspace, _| {
+            assert_eq!(workspace.notification_ids().len(), 1);
+        });
+    }
+}
@@ -920,6 +920,7 @@ fn subscribe_for_terminal_events(
             },
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
+            Event::TaskFinished => {}
             Event::SelectionsChanged => {
                 cx.invalidate_character_coordinates();
                 cx.emit(SearchEvent::ActiveMatchChanged)
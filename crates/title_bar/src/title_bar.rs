@@ -539,10 +539,19 @@ impl TitleBar {
             names_and_branches.next().flatten()
         };
         let workspace = self.workspace.upgrade()?;
-        let branch_name = entry
+        let mut branch_name = entry
             .as_ref()
             .and_then(|entry| entry.branch())
             .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH))?;
+        // Show the commits to pull and push next to the branch.
+        if let Some(tracking) = entry.as_ref().and_then(|entry| entry.upstream_tracking()) {
+            if tracking.behind > 0 {
+                branch_name.push_str(&format!(" ↓{}", tracking.behind));
+            }
+            if tracking.ahead > 0 {
+                branch_name.push_str(&format!(" ↑{}", tracking.ahead));
+            }
+        }
         Some(
             Button::new("project_branch_trigger", branch_name)
                 .color(Color::Muted)
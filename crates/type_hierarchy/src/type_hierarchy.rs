@@ -0,0 +1,96 @@
+use anyhow::Result;
+use gpui::{actions, AppContext, Model, ModelContext, Task};
+use hierarchy_view::{HierarchyChild, HierarchySource, HierarchyView};
+use language::{Anchor, Buffer};
+use project::{Location, Project, TypeHierarchyItem};
+use workspace::Workspace;
+
+actions!(type_hierarchy, [ShowSupertypes, ShowSubtypes]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ShowSupertypes, cx| {
+            TypeHierarchyView::deploy(workspace, TypeDirection::Supertypes, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, cx| {
+            TypeHierarchyView::deploy(workspace, TypeDirection::Subtypes, cx);
+        });
+    })
+    .detach();
+}
+
+/// A tree of the supertypes or subtypes of the type under the cursor.
+pub type TypeHierarchyView = HierarchyView<TypeDirection>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeDirection {
+    /// Show the types each item derives from or implements.
+    Supertypes,
+    /// Show the types deriving from or implementing each item.
+    Subtypes,
+}
+
+impl HierarchySource for TypeDirection {
+    type Item = TypeHierarchyItem;
+
+    const KEY_CONTEXT: &'static str = "TypeHierarchyView";
+    const NAME: &'static str = "type hierarchy";
+    const TELEMETRY_EVENT_TEXT: &'static str = "Type Hierarchy Opened";
+
+    fn label(&self) -> &'static str {
+        match self {
+            TypeDirection::Supertypes => "Supertypes",
+            TypeDirection::Subtypes => "Subtypes",
+        }
+    }
+
+    fn toggled(&self) -> Self {
+        match self {
+            TypeDirection::Supertypes => TypeDirection::Subtypes,
+            TypeDirection::Subtypes => TypeDirection::Supertypes,
+        }
+    }
+
+    fn prepare(
+        project: &mut Project,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        project.prepare_type_hierarchy(buffer, position, cx)
+    }
+
+    fn children(
+        &self,
+        project: &mut Project,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<TypeHierarchyItem>>>> {
+        let types = match self {
+            TypeDirection::Supertypes => project.supertypes(item, cx),
+            TypeDirection::Subtypes => project.subtypes(item, cx),
+        };
+        cx.spawn(|_, _| async move {
+            Ok(types
+                .await?
+                .into_iter()
+                .map(|item| HierarchyChild {
+                    item,
+                    references: Vec::new(),
+                })
+                .collect())
+        })
+    }
+
+    fn name(item: &TypeHierarchyItem) -> &str {
+        &item.lsp_item.name
+    }
+
+    fn detail(item: &TypeHierarchyItem) -> Option<&str> {
+        item.lsp_item.detail.as_deref()
+    }
+
+    fn location(item: &TypeHierarchyItem) -> &Location {
+        &item.location
+    }
+}
@@ -25,7 +25,7 @@ use std::{
     sync::OnceLock,
     time::Instant,
 };
-use task::{HideStrategy, RevealStrategy, SpawnInTerminal, TaskId};
+use task::{DependsOrder, HideStrategy, RevealStrategy, SpawnInTerminal, TaskId};
 use ui::ActiveTheme;
 use util::ResultExt;
 use workspace::{notifications::NotifyResultExt, SaveIntent};
@@ -66,8 +66,15 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
+/// An ex command run from the command palette, which is kept in the command history.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct RunCommand {
+    pub(crate) command: String,
+    pub(crate) action: WrappedAction,
+}
+
 #[derive(Debug)]
-struct WrappedAction(Box<dyn Action>);
+pub(crate) struct WrappedAction(Box<dyn Action>);
 
 actions!(vim, [VisualCommand, CountCommand, ShellCommand]);
 impl_internal_actions!(
@@ -78,7 +85,9 @@ impl_internal_actions!(
         WithRange,
         WithCount,
         OnMatchingLines,
-        ShellExec
+        ShellExec,
+        VimNormal,
+        RunCommand
     ]
 );
 
@@ -238,6 +247,10 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
 
     Vim::action(editor, cx, |vim, action: &ShellExec, cx| {
         action.run(vim, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimNormal, cx| {
+        action.run(vim, cx)
     })
 }
 
@@ -846,6 +859,8 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
         } else {
             None
         }
+    } else if let Some(action) = VimNormal::parse(query, range.clone()) {
+        Some(action)
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -879,6 +894,17 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
     None
 }
 
+/// Intercepts the ex commands typed in the command palette, so they are added to the command history when run.
+pub fn command_palette_interceptor(input: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
+    let mut result = command_interceptor(input, cx)?;
+    result.action = RunCommand {
+        command: input.trim_start_matches(':').trim().to_string(),
+        action: WrappedAction(result.action),
+    }
+    .boxed_clone();
+    Some(result)
+}
+
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     let mut positions = Vec::new();
     let mut chars = query.chars();
@@ -1088,6 +1114,76 @@ impl OnMatchingLines {
     }
 }
 
+/// Replays keystrokes in normal mode, like `:[range]norm[al][!] {commands}`.
+/// With a range, they are replayed with a cursor at the start of each line.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct VimNormal {
+    range: Option<CommandRange>,
+    keystrokes: String,
+}
+
+impl VimNormal {
+    pub(crate) fn parse(query: &str, range: Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let command_end = query
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(query.len());
+        let command = &query[..command_end];
+        if command.len() < 4 || !"normal".starts_with(command) {
+            return None;
+        }
+        let commands = query[command_end..]
+            .strip_prefix('!')
+            .unwrap_or(&query[command_end..]);
+        let commands = commands.strip_prefix(' ')?;
+
+        // Like vim, end an insertion left incomplete, then keep only the first cursor.
+        let mut keystrokes = Vec::new();
+        for c in commands.chars() {
+            keystrokes.push(match c {
+                ' ' => "space".to_string(),
+                c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+                c => c.to_string(),
+            });
+        }
+        keystrokes.push("escape escape".to_string());
+
+        Some(
+            VimNormal {
+                range,
+                keystrokes: keystrokes.join(" "),
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, cx: &mut ViewContext<Vim>) {
+        vim.switch_mode(Mode::Normal, false, cx);
+        if let Some(range) = &self.range {
+            let result = vim.update_editor(cx, |vim, editor, cx| {
+                let range = range.buffer_range(vim, editor, cx)?;
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges(
+                        (range.start.0..=range.end.0)
+                            .map(|row| Point::new(row, 0)..Point::new(row, 0)),
+                    );
+                });
+                anyhow::Ok(())
+            });
+            if let Some(e @ Err(_)) = result {
+                let Some(workspace) = vim.workspace(cx) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+        }
+
+        cx.dispatch_action(workspace::SendKeystrokes(self.keystrokes.clone()).boxed_clone());
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ShellExec {
     command: String,
@@ -1275,6 +1371,9 @@ impl ShellExec {
                         shell,
                         show_summary: false,
                         show_command: false,
+                        dependencies: Vec::new(),
+                        depends_order: DependsOrder::default(),
+                        problem_matchers: Vec::new(),
                     }),
                 });
             });
@@ -1419,12 +1518,13 @@ impl ShellExec {
 mod test {
     use std::path::Path;
 
+    use super::VimNormal;
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
     use editor::Editor;
-    use gpui::TestAppContext;
+    use gpui::{Action as _, TestAppContext};
     use indoc::indoc;
     use ui::ViewContext;
     use workspace::Workspace;
@@ -1686,6 +1786,71 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_normal_command_parsing() {
+        let keystrokes = |query: &str| {
+            VimNormal::parse(query, None).map(|action| {
+                action
+                    .as_any()
+                    .downcast_ref::<VimNormal>()
+                    .unwrap()
+                    .keystrokes
+                    .clone()
+            })
+        };
+
+        assert_eq!(keystrokes("norm x").as_deref(), Some("x escape escape"));
+        assert_eq!(
+            keystrokes("normal A;").as_deref(),
+            Some("shift-a ; escape escape")
+        );
+        assert_eq!(
+            keystrokes("norm! I//").as_deref(),
+            Some("shift-i / / escape escape")
+        );
+        assert_eq!(
+            keystrokes("normal  dw").as_deref(),
+            Some("space d w escape escape")
+        );
+        assert_eq!(keystrokes("nor x"), None);
+        assert_eq!(keystrokes("normalx"), None);
+        assert_eq!(keystrokes("nohlsearch"), None);
+    }
+
+    // Relies on `workspace::SendKeystrokes`, see `test_remap`.
+    #[cfg(target_os = "macos")]
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.executor().allow_parking();
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % n o r m space shift-a ; enter");
+        cx.assert_state(
+            indoc! {"
+            aˇ;
+            b;
+            c;"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            a"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / a / n o r m space x enter");
+        cx.assert_state("ˇ\nb\n", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_command_matching_lines(cx: &mut TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -1,15 +1,135 @@
-use editor::{movement, scroll::Autoscroll, DisplayPoint, Editor};
-use gpui::{actions, Action};
-use language::{CharClassifier, CharKind};
+mod regex_prompt;
+
+use std::{ops::Range, sync::Arc};
+
+use editor::{
+    display_map::{DisplaySnapshot, ToDisplayPoint},
+    movement,
+    scroll::Autoscroll,
+    DisplayPoint, Editor, ToOffset,
+};
+use gpui::{actions, impl_actions, Action};
+use language::{CharClassifier, CharKind, Point, Selection, SelectionGoal};
+use multi_buffer::MultiBufferRow;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use ui::ViewContext;
 
-use crate::{motion::Motion, state::Mode, Vim};
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{Mode, Operator, Register},
+    surrounds::SurroundsType,
+    Vim,
+};
+use regex_prompt::RegexPrompt;
+
+actions!(
+    vim,
+    [
+        HelixNormalAfter,
+        HelixDelete,
+        HelixChange,
+        HelixYank,
+        HelixInsert,
+        HelixAppend,
+        HelixSelectLine,
+        HelixExtendToLineBounds,
+        HelixCollapseSelection,
+        HelixFlipSelections,
+        HelixKeepPrimarySelection,
+        HelixRemovePrimarySelection,
+        HelixSplitSelectionOnNewline,
+        HelixSelectRegex,
+        HelixSplitSelection,
+        HelixKeepSelections,
+        HelixRemoveSelections,
+    ]
+);
+
+#[derive(Clone, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HelixPaste {
+    #[serde(default)]
+    before: bool,
+}
+
+impl_actions!(vim, [HelixPaste]);
 
-actions!(vim, [HelixNormalAfter, HelixDelete]);
+/// What to do with the matches of the regex typed into the [`RegexPrompt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RegexSelection {
+    /// Select every match within the selections (`s`).
+    Select,
+    /// Split the selections on every match (`S`).
+    Split,
+    /// Keep only the selections that contain a match (`K`).
+    Keep,
+    /// Remove the selections that contain a match (`alt-K`).
+    Remove,
+}
 
 pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    Vim::action(editor, cx, Vim::helix_change);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, Vim::helix_paste);
+    Vim::action(editor, cx, Vim::helix_insert);
+    Vim::action(editor, cx, Vim::helix_append);
+    Vim::action(editor, cx, Vim::helix_select_line);
+    Vim::action(editor, cx, Vim::helix_extend_to_line_bounds);
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_remove_primary_selection);
+    Vim::action(editor, cx, Vim::helix_split_selection_on_newline);
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, cx| {
+        vim.helix_regex_prompt(RegexSelection::Select, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, cx| {
+        vim.helix_regex_prompt(RegexSelection::Split, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixKeepSelections, cx| {
+        vim.helix_regex_prompt(RegexSelection::Keep, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixRemoveSelections, cx| {
+        vim.helix_regex_prompt(RegexSelection::Remove, cx)
+    });
+}
+
+/// The character a Helix cursor sits on: the last character of a forward
+/// selection, or the head of a reversed or empty one.
+fn block_cursor(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> DisplayPoint {
+    if selection.is_empty() || selection.reversed {
+        selection.head()
+    } else {
+        movement::left(map, selection.head())
+    }
+}
+
+/// Turns every cursor into a selection of the character under it, so that
+/// edits act on it.
+fn expand_cursors(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            if selection.is_empty() && !selection.reversed {
+                selection.end = movement::right(map, selection.end);
+            }
+        });
+    });
+}
+
+/// The text a selection acts on. An empty selection is a cursor, which covers
+/// the character under it.
+fn selected_range(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> Range<Point> {
+    let end = if selection.is_empty() {
+        movement::right(map, selection.end)
+    } else {
+        selection.end
+    };
+    selection.start.to_point(map)..end.to_point(map)
 }
 
 impl Vim {
@@ -152,16 +272,17 @@ impl Vim {
             editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.move_with(|map, selection| {
                     let goal = selection.goal;
-                    let cursor = if selection.is_empty() || selection.reversed {
-                        selection.head()
-                    } else {
-                        movement::left(map, selection.head())
-                    };
+                    let cursor = block_cursor(map, selection);
 
-                    let (point, goal) = motion
+                    let (mut point, goal) = motion
                         .move_point(map, cursor, selection.goal, times, &text_layout_details)
                         .unwrap_or((cursor, goal));
 
+                    // Helix's line end is the last character, not the newline.
+                    if matches!(motion, Motion::EndOfLine { .. }) && point.column() > 0 {
+                        point = movement::left(map, point);
+                    }
+
                     selection.collapse_to(point, goal)
                 })
             });
@@ -224,26 +345,474 @@ impl Vim {
                     found
                 })
             }
+            Motion::FindForward { .. } | Motion::FindBackward { .. } => {
+                self.helix_select_to(motion, times, cx)
+            }
             _ => self.helix_move_and_collapse(motion, times, cx),
         }
     }
 
-    pub fn helix_delete(&mut self, _: &HelixDelete, cx: &mut ViewContext<Self>) {
-        self.store_visual_marks(cx);
-        self.update_editor(cx, |vim, editor, cx| {
-            // Fixup selections so they have helix's semantics.
-            // Specifically:
-            //  - Make sure that each cursor acts as a 1 character wide selection
-            editor.transact(cx, |editor, cx| {
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.move_with(|map, selection| {
-                        if selection.is_empty() && !selection.reversed {
-                            selection.end = movement::right(map, selection.end);
+    /// Selects from the cursor to where the motion lands, including both ends.
+    fn helix_select_to(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = block_cursor(map, selection);
+                    let Some((point, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    else {
+                        return;
+                    };
+
+                    if point >= cursor {
+                        selection.start = cursor;
+                        selection.end = movement::right(map, point);
+                        selection.reversed = false;
+                    } else {
+                        selection.start = point;
+                        selection.end = movement::right(map, cursor);
+                        selection.reversed = true;
+                    }
+                    selection.goal = goal;
+                })
+            });
+        });
+    }
+
+    pub fn helix_object(&mut self, object: Object, cx: &mut ViewContext<Self>) {
+        // Objects typed after `m r` pick the surrounds to change, as they do after `cs`.
+        let Some(Operator::Object { around }) = self.active_operator() else {
+            self.normal_object(object, cx);
+            return;
+        };
+        self.pop_operator(cx);
+
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut cursor = selection.clone();
+                    cursor.collapse_to(block_cursor(map, selection), selection.goal);
+                    if let Some(range) = object.range(map, cursor, around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                        }
+                    }
+                });
+            });
+        });
+    }
+
+    pub fn helix_select_line(&mut self, _: &HelixSelectLine, cx: &mut ViewContext<Self>) {
+        let count = Vim::take_count(cx).unwrap_or(1) as u32;
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let ends_at_line_start = end.column == 0 && end > start;
+
+                    // Pressing `x` on whole lines selects the next line as well.
+                    let is_line_selection = start.column == 0
+                        && (ends_at_line_start || (end == max_point && end > start));
+                    let last_row = if ends_at_line_start {
+                        end.row - 1
+                    } else {
+                        end.row
+                    };
+                    let extra_rows = if is_line_selection { count } else { count - 1 };
+                    let last_row = (last_row + extra_rows).min(max_point.row);
+
+                    let end = if last_row < max_point.row {
+                        Point::new(last_row + 1, 0)
+                    } else {
+                        max_point
+                    };
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                });
+            });
+        });
+    }
+
+    pub fn helix_extend_to_line_bounds(
+        &mut self,
+        _: &HelixExtendToLineBounds,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let mut end = selection.end.to_point(map);
+                    if end.column != 0 || end == start {
+                        end = if end.row < max_point.row {
+                            Point::new(end.row + 1, 0)
+                        } else {
+                            max_point
+                        };
+                    }
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.goal = SelectionGoal::None;
+                });
+            });
+        });
+    }
+
+    pub fn helix_collapse_selection(
+        &mut self,
+        _: &HelixCollapseSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let cursor = block_cursor(map, selection);
+                    selection.collapse_to(cursor, selection.goal);
+                });
+            });
+        });
+    }
+
+    pub fn helix_flip_selections(&mut self, _: &HelixFlipSelections, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| {
+                    if !selection.is_empty() {
+                        selection.swap_head_tail();
+                    }
+                });
+            });
+        });
+    }
+
+    pub fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    }
+
+    pub fn helix_remove_primary_selection(
+        &mut self,
+        _: &HelixRemovePrimarySelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let primary_id = s.newest_anchor().id;
+                let remaining = s
+                    .disjoint_anchors()
+                    .iter()
+                    .filter(|selection| selection.id != primary_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !remaining.is_empty() {
+                    s.select_anchors(remaining);
+                }
+            });
+        });
+    }
+
+    pub fn helix_split_selection_on_newline(
+        &mut self,
+        _: &HelixSplitSelectionOnNewline,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut ranges = Vec::new();
+            for selection in editor.selections.all::<Point>(cx) {
+                if selection.start.row == selection.end.row {
+                    ranges.push(selection.start..selection.end);
+                    continue;
+                }
+                for row in selection.start.row..=selection.end.row {
+                    let start = if row == selection.start.row {
+                        selection.start
+                    } else {
+                        Point::new(row, 0)
+                    };
+                    let end = if row == selection.end.row {
+                        selection.end
+                    } else {
+                        Point::new(row, snapshot.line_len(MultiBufferRow(row)))
+                    };
+                    if start < end {
+                        ranges.push(start..end);
+                    }
+                }
+            }
+            if ranges.is_empty() {
+                return;
+            }
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+        });
+    }
+
+    fn helix_regex_prompt(&mut self, kind: RegexSelection, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let vim = cx.view().downgrade();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| RegexPrompt::new(kind, vim, cx));
+        });
+    }
+
+    pub(crate) fn helix_select_regex(
+        &mut self,
+        kind: RegexSelection,
+        query: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Ok(regex) = Regex::new(query) else {
+            return;
+        };
+        self.update_editor(cx, |_, editor, cx| {
+            expand_cursors(editor, cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut ranges = Vec::new();
+            for selection in editor.selections.all::<usize>(cx) {
+                let text = snapshot
+                    .text_for_range(selection.range())
+                    .collect::<String>();
+                let mut matches = regex
+                    .find_iter(&text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| selection.start + found.start()..selection.start + found.end());
+                match kind {
+                    RegexSelection::Select => ranges.extend(matches),
+                    RegexSelection::Split => {
+                        let mut start = selection.start;
+                        for found in matches {
+                            if start < found.start {
+                                ranges.push(start..found.start);
+                            }
+                            start = found.end;
+                        }
+                        if start < selection.end {
+                            ranges.push(start..selection.end);
                         }
-                    });
+                    }
+                    RegexSelection::Keep => {
+                        if matches.next().is_some() {
+                            ranges.push(selection.range());
+                        }
+                    }
+                    RegexSelection::Remove => {
+                        if matches.next().is_none() {
+                            ranges.push(selection.range());
+                        }
+                    }
+                }
+            }
+            if ranges.is_empty() {
+                return;
+            }
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+        });
+    }
+
+    pub fn helix_insert(&mut self, _: &HelixInsert, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| selection.collapse_to(selection.start, selection.goal));
+            });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    pub fn helix_append(&mut self, _: &HelixAppend, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let end = if selection.is_empty() {
+                        movement::saturating_right(map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.collapse_to(end, selection.goal);
                 });
             });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    pub fn helix_yank(&mut self, _: &HelixYank, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |vim, editor, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            let ranges = selections
+                .iter()
+                .map(|selection| selected_range(&map, selection))
+                .collect();
+            vim.copy_ranges(editor, false, true, ranges, cx);
+        });
+    }
+
+    pub fn helix_change(&mut self, _: &HelixChange, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                expand_cursors(editor, cx);
+                vim.copy_selections_content(editor, false, cx);
+                editor.insert("", cx);
+            });
+        });
+        self.switch_mode(Mode::Insert, false, cx);
+    }
+
+    /// Pastes after (or before) each selection and selects what was pasted.
+    /// Text ending in a newline is pasted on its own line.
+    pub fn helix_paste(&mut self, action: &HelixPaste, cx: &mut ViewContext<Self>) {
+        self.record_current_action(cx);
+        let count = Vim::take_count(cx).unwrap_or(1);
+        self.update_editor(cx, |vim, editor, cx| {
+            let selected_register = vim.selected_register.take();
+            let Some(Register {
+                text,
+                clipboard_selections,
+            }) = Vim::update_globals(cx, |globals, cx| {
+                globals.read_register(selected_register, Some(editor), cx)
+            })
+            .filter(|register| !register.text.is_empty())
+            else {
+                return;
+            };
+
+            let (map, selections) = editor.selections.all_display(cx);
+            let snapshot = &map.buffer_snapshot;
+
+            // Each selection gets its own part of a register yanked from as many selections.
+            let texts = match clipboard_selections {
+                Some(clipboard_selections)
+                    if clipboard_selections.len() > 1
+                        && clipboard_selections.len() == selections.len() =>
+                {
+                    let mut start = 0;
+                    clipboard_selections
+                        .iter()
+                        .map(|clipboard_selection| {
+                            let end = (start + clipboard_selection.len).min(text.len());
+                            let part = text[start..end].to_string();
+                            start = end + 1;
+                            part
+                        })
+                        .collect::<Vec<_>>()
+                }
+                _ => vec![text.to_string(); selections.len()],
+            };
+
+            let mut edits = Vec::new();
+            let mut pasted = Vec::new();
+            let mut delta = 0;
+            for (selection, text) in selections.iter().zip(texts) {
+                let range = selected_range(&map, selection);
+                let mut text = text.repeat(count);
+                let mut leading_newline = false;
+                let position = if text.ends_with('\n') {
+                    if action.before {
+                        Point::new(range.start.row, 0)
+                    } else if range.end.column == 0 && range.end > range.start {
+                        range.end
+                    } else if range.end.row < snapshot.max_point().row {
+                        Point::new(range.end.row + 1, 0)
+                    } else {
+                        // The last line has no newline to paste after.
+                        text.pop();
+                        text.insert(0, '\n');
+                        leading_newline = true;
+                        snapshot.max_point()
+                    }
+                } else if action.before {
+                    range.start
+                } else {
+                    range.end
+                };
+
+                let offset = position.to_offset(snapshot);
+                let start = offset + delta + leading_newline as usize;
+                pasted.push(start..offset + delta + text.len());
+                delta += text.len();
+                edits.push((offset..offset, text));
+            }
+
+            editor.transact(cx, |editor, cx| {
+                editor.edit(edits, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(pasted));
+            });
+        });
+    }
+
+    /// Replaces every selected character, except newlines, with the typed one.
+    pub(crate) fn helix_replace(&mut self, text: &str, cx: &mut ViewContext<Self>) {
+        self.stop_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            let (map, selections) = editor.selections.all_display(cx);
+            let snapshot = &map.buffer_snapshot;
+            let mut edits = Vec::new();
+            let mut replaced = Vec::new();
+            let mut delta = 0isize;
+            for selection in selections.iter() {
+                let range = selected_range(&map, selection);
+                let range = range.start.to_offset(snapshot)..range.end.to_offset(snapshot);
+                let new_text = snapshot
+                    .text_for_range(range.clone())
+                    .flat_map(|chunk| chunk.chars())
+                    .map(|c| if c == '\n' { "\n" } else { text })
+                    .collect::<String>();
+
+                let start = (range.start as isize + delta) as usize;
+                if selection.is_empty() {
+                    replaced.push(start..start);
+                } else if selection.reversed {
+                    replaced.push(start + new_text.len()..start);
+                } else {
+                    replaced.push(start..start + new_text.len());
+                }
+                delta += new_text.len() as isize - range.len() as isize;
+                edits.push((range, new_text));
+            }
+
+            editor.transact(cx, |editor, cx| {
+                editor.edit(edits, cx);
+                editor.change_selections(None, cx, |s| s.select_ranges(replaced));
+            });
+        });
+        self.clear_operator(cx);
+    }
+
+    /// Surrounds each selection, or the character under each cursor, with the typed pair.
+    pub(crate) fn helix_surround(&mut self, text: Arc<str>, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| expand_cursors(editor, cx));
+        self.add_surrounds(text, SurroundsType::Selection, cx);
+        self.clear_operator(cx);
+    }
 
+    pub fn helix_delete(&mut self, _: &HelixDelete, cx: &mut ViewContext<Self>) {
+        self.store_visual_marks(cx);
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, expand_cursors);
             vim.copy_selections_content(editor, false, cx);
             editor.insert("", cx);
         });
@@ -370,4 +939,381 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    async fn helix_test_context(cx: &mut gpui::TestAppContext) -> VimTestContext {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.enable_helix();
+        cx
+    }
+
+    #[gpui::test]
+    async fn test_helix_mode_setting(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        cx.set_state("ˇhello", Mode::HelixNormal);
+        cx.simulate_keystrokes("i x");
+        cx.assert_state("xˇhello", Mode::Insert);
+        cx.simulate_keystrokes("escape");
+        cx.assert_state("xˇhello", Mode::HelixNormal);
+
+        // Select mode returns to helix's normal mode.
+        cx.simulate_keystrokes("v l");
+        assert_eq!(cx.mode(), Mode::Visual);
+        cx.simulate_keystrokes("escape");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            the lazy dog.ˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog."},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("2 x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            fox jumps over
+            ˇ»the lazy dog."},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_extend_to_line_bounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state(
+            indoc! {"
+            The qu«ick ˇ»brown
+            fox jumps over"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over"},
+            Mode::HelixNormal,
+        );
+
+        // Whole lines are already at their bounds.
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            «The quick brown
+            ˇ»fox jumps over"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_find_selects(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("ˇThe quick brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("f k");
+        cx.assert_state("«The quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("t w");
+        cx.assert_state("The quic«k broˇ»wn", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-f q");
+        cx.assert_state("The «ˇquick bro»wn", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_collapse_and_flip_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("The «ˇquick» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The ˇquick brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("The quicˇk brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("ˇThe quick ˇbrown ˇfox", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("ˇThe quick ˇbrown fox", Mode::HelixNormal);
+
+        cx.simulate_keystrokes(",");
+        cx.assert_state("The quick ˇbrown fox", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_split_selection_on_newline(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state(
+            indoc! {"
+            «The quick
+            brown fox
+            jumpsˇ» over"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("alt-s");
+        cx.assert_state(
+            indoc! {"
+            «The quickˇ»
+            «brown foxˇ»
+            «jumpsˇ» over"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_regex_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+
+        cx.set_state("«The quick brown foxˇ» jumps", Mode::HelixNormal);
+        cx.simulate_keystrokes("s");
+        cx.simulate_keystrokes("o");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("The quick br«oˇ»wn f«oˇ»x jumps", Mode::HelixNormal);
+
+        cx.set_state("«The quick brown foxˇ» jumps", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s");
+        cx.simulate_keystrokes("space");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("«Theˇ» «quickˇ» «brownˇ» «foxˇ» jumps", Mode::HelixNormal);
+
+        cx.set_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-k");
+        cx.simulate_keystrokes("u");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("«Theˇ» «quickˇ» «brownˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-shift-k");
+        cx.simulate_keystrokes("u");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("«Theˇ» quick «brownˇ»", Mode::HelixNormal);
+
+        // An empty or invalid regex leaves the selections alone.
+        cx.set_state("«The quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("s");
+        cx.simulate_keystrokes("(");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("«The quickˇ» brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_yank_and_paste(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("y");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("p");
+        cx.assert_state("The quick«quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("shift-p");
+        cx.assert_state("The quick«quickˇ»quick brown", Mode::HelixNormal);
+
+        // Lines are pasted below the selection.
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            "},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x y p");
+        cx.assert_state(
+            indoc! {"
+            one
+            «one
+            ˇ»two
+            "},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_change(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("c");
+        cx.assert_state("The ˇ brown", Mode::Insert);
+
+        cx.simulate_keystrokes("s l o w escape");
+        cx.assert_state("The slowˇ brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_insert_and_append(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("i");
+        cx.assert_state("The ˇquick brown", Mode::Insert);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("a");
+        cx.assert_state("The quickˇ brown", Mode::Insert);
+
+        cx.set_state("The quicˇk brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("a");
+        cx.assert_state("The quickˇ brown", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_replace_and_case(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("r x");
+        cx.assert_state("The «xxxxxˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("ˇThe quick brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("r a");
+        cx.assert_state("ˇahe quick brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("~");
+        cx.assert_state("The «QUICKˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("`");
+        cx.assert_state("The «quickˇ» brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state("The (quˇick) brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m i w");
+        cx.assert_state("The («quickˇ») brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("The «(quick)ˇ» brown", Mode::HelixNormal);
+
+        cx.set_state("The «quickˇ» brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s }");
+        cx.assert_state("The ˇ{quick} brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m r { [");
+        cx.assert_state("The ˇ[ quick ] brown", Mode::HelixNormal);
+
+        cx.simulate_keystrokes("m d [");
+        cx.assert_state("The ˇquick brown", Mode::HelixNormal);
+
+        cx.set_state("The (quick) brˇown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m m");
+        cx.assert_state("The (quick) brˇown", Mode::HelixNormal);
+        cx.set_state("The ˇ(quick) brown", Mode::HelixNormal);
+        cx.simulate_keystrokes("m m");
+        cx.assert_state("The (quickˇ) brown", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_goto_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_test_context(cx).await;
+        cx.set_state(
+            indoc! {"
+            The quick
+              fox ˇjumps
+            the dog"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("g l");
+        cx.assert_state(
+            indoc! {"
+            The quick
+              fox jumpˇs
+            the dog"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("g h");
+        cx.assert_state(
+            indoc! {"
+            The quick
+            ˇ  fox jumps
+            the dog"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("g s");
+        cx.assert_state(
+            indoc! {"
+            The quick
+              ˇfox jumps
+            the dog"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("g e");
+        cx.assert_state(
+            indoc! {"
+            The quick
+              fox jumps
+            ˇthe dog"},
+            Mode::HelixNormal,
+        );
+
+        cx.simulate_keystrokes("g g");
+        cx.assert_state(
+            indoc! {"
+            ˇThe quick
+              fox jumps
+            the dog"},
+            Mode::HelixNormal,
+        );
+    }
 }
@@ -0,0 +1,89 @@
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Subscription, View,
+    ViewContext, VisualContext, WeakView,
+};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::Vim;
+
+use super::RegexSelection;
+
+/// The prompt opened by `s`, `S`, `K` and `alt-K` to read the regex to select with.
+pub(crate) struct RegexPrompt {
+    kind: RegexSelection,
+    query_editor: View<Editor>,
+    vim: WeakView<Vim>,
+    _subscription: Subscription,
+}
+
+impl ModalView for RegexPrompt {}
+
+impl EventEmitter<DismissEvent> for RegexPrompt {}
+
+impl FocusableView for RegexPrompt {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl RegexPrompt {
+    pub fn new(kind: RegexSelection, vim: WeakView<Vim>, cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Regex", cx);
+            editor
+        });
+        let subscription = cx.subscribe(&query_editor, |_, _, event, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent)
+            }
+        });
+
+        Self {
+            kind,
+            query_editor,
+            vim,
+            _subscription: subscription,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self.kind {
+            RegexSelection::Select => "Select:",
+            RegexSelection::Split => "Split:",
+            RegexSelection::Keep => "Keep:",
+            RegexSelection::Remove => "Remove:",
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let kind = self.kind;
+        cx.emit(DismissEvent);
+        self.vim
+            .update(cx, |vim, cx| vim.helix_select_regex(kind, &query, cx))
+            .ok();
+    }
+}
+
+impl Render for RegexPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .w(rems(24.))
+            .px_2()
+            .py_1()
+            .gap_2()
+            .elevation_2(cx)
+            .key_context("HelixRegexPrompt")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(Label::new(self.label()).color(Color::Muted))
+            .child(self.query_editor.clone())
+    }
+}
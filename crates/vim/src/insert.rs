@@ -23,6 +23,10 @@ impl Vim {
             self.create_mark("^".into(), false, cx);
             self.update_editor(cx, |_, editor, cx| {
                 editor.dismiss_menus_and_popups(false, cx);
+                // Helix leaves the cursor on the character after the inserted text.
+                if Vim::normal_mode(cx) == Mode::HelixNormal {
+                    return;
+                }
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
                         *cursor.column_mut() = cursor.column().saturating_sub(1);
@@ -146,7 +146,19 @@ impl Vim {
                         }
                     }
 
-                    Mode::HelixNormal => {}
+                    Mode::HelixNormal => {
+                        let end = if selection.is_empty() {
+                            snapshot.clip_point(selection.start + Point::new(0, 1), Bias::Right)
+                        } else {
+                            selection.end
+                        };
+                        ranges.push(selection.start..end);
+                        if selection.reversed {
+                            cursor_positions.push(selection.end..selection.start);
+                        } else {
+                            cursor_positions.push(selection.start..selection.end);
+                        }
+                    }
                     Mode::Insert | Mode::Normal | Mode::Replace => {
                         let start = selection.start;
                         let mut end = start;
@@ -177,7 +189,9 @@ impl Vim {
                 })
             });
         });
-        self.switch_mode(Mode::Normal, true, cx)
+        if self.mode != Mode::HelixNormal {
+            self.switch_mode(Mode::Normal, true, cx)
+        }
     }
 }
 
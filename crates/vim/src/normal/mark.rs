@@ -4,14 +4,15 @@ use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor, ToPoint as _,
 };
 use gpui::ViewContext;
 use language::SelectionGoal;
+use multi_buffer::MultiBufferRow;
 
 use crate::{
     motion::{self, Motion},
-    state::Mode,
+    state::{GlobalMark, Mode},
     Vim,
 };
 
@@ -27,10 +28,67 @@ impl Vim {
         }) else {
             return;
         };
+        if is_global_mark(&text) {
+            self.create_global_mark(text.to_string(), anchors.first().copied(), cx);
+        }
         self.marks.insert(text.to_string(), anchors);
         self.clear_operator(cx);
     }
 
+    fn create_global_mark(
+        &mut self,
+        name: String,
+        anchor: Option<Anchor>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(anchor) = anchor else {
+            return;
+        };
+        let Some(mark) = self
+            .update_editor(cx, |_, editor, cx| {
+                let multi_buffer = editor.buffer().read(cx);
+                let point = anchor.to_point(&multi_buffer.snapshot(cx));
+                let (buffer, point, _) = multi_buffer.point_to_buffer_point(point, cx)?;
+                let abs_path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+                Some(GlobalMark { abs_path, point })
+            })
+            .flatten()
+        else {
+            return;
+        };
+        Vim::update_globals(cx, |globals, cx| globals.set_global_mark(name, mark, cx));
+    }
+
+    /// Opens the file of an uppercase mark, when it isn't set in this editor.
+    fn jump_to_global_mark(&mut self, name: &str, line: bool, cx: &mut ViewContext<Self>) {
+        let Some(mark) = Vim::globals(cx).global_marks.get(name).cloned() else {
+            return;
+        };
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let open_file = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(mark.abs_path.clone(), true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_file.await?;
+            let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))? else {
+                return Ok(());
+            };
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut point = snapshot.clip_point(mark.point, Bias::Left);
+                if line {
+                    point.column = snapshot.indent_size_for_line(MultiBufferRow(point.row)).len;
+                }
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     // When handling an action, you must create visual marks if you will switch to normal
     // mode without the default selection behavior.
     pub(crate) fn store_visual_marks(&mut self, cx: &mut ViewContext<Self>) {
@@ -88,7 +146,12 @@ impl Vim {
             _ => self.marks.get(&*text).cloned(),
         };
 
-        let Some(anchors) = anchors else { return };
+        let Some(anchors) = anchors else {
+            if is_global_mark(&text) && self.active_operator().is_none() {
+                self.jump_to_global_mark(&text, line, cx);
+            }
+            return;
+        };
 
         let is_active_operator = self.active_operator().is_some();
         if is_active_operator {
@@ -126,6 +189,12 @@ impl Vim {
     }
 }
 
+/// Uppercase marks are global: they can be jumped to from any file.
+fn is_global_mark(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.next().is_none()
+}
+
 pub fn jump_motion(
     map: &DisplaySnapshot,
     anchor: Anchor,
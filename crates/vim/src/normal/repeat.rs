@@ -1,6 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
+    command::command_interceptor,
     insert::NormalBefore,
     motion::Motion,
     normal::InsertBefore,
@@ -172,6 +173,19 @@ impl Vim {
             };
             register = last;
         }
+        if register == ':' {
+            globals.last_replayed_register = Some(register);
+            let Some(command) = globals.command_history.last().cloned() else {
+                return;
+            };
+            let Some(result) = command_interceptor(&command, cx) else {
+                return;
+            };
+            for _ in 0..count {
+                cx.dispatch_action(result.action.boxed_clone());
+            }
+            return;
+        }
         let Some(actions) = globals.recordings.get(&register) else {
             return;
         };
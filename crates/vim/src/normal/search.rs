@@ -11,7 +11,7 @@ use workspace::{notifications::NotifyResultExt, searchable::Direction};
 use crate::{
     command::CommandRange,
     motion::Motion,
-    state::{Mode, SearchState},
+    state::{Mode, SearchState, VimGlobals},
     Vim,
 };
 
@@ -190,6 +190,9 @@ impl Vim {
                 Some((prior_selections, prior_mode, prior_operator))
             })
         });
+        if let Some(workspace) = self.workspace(cx) {
+            VimGlobals::persist_search_history(workspace.read(cx), cx);
+        }
 
         let Some((mut prior_selections, prior_mode, prior_operator)) = result else {
             return;
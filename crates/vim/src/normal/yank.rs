@@ -193,7 +193,10 @@ impl Vim {
                 is_yank,
                 linewise,
                 cx,
-            )
+            );
+            if let Some(register) = selected_register {
+                globals.persist_register(register, cx);
+            }
         });
 
         let highlight_duration = VimSettings::get_global(cx).highlight_on_yank_duration;
@@ -0,0 +1,249 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Like vim's viminfo file, registers, uppercase marks and the command history
+    // are shared by all workspaces. The search history belongs to a workspace's
+    // project, so it's kept per workspace.
+    pub static ref DB: VimDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE vim_registers (
+                name TEXT PRIMARY KEY NOT NULL,
+                text TEXT NOT NULL,
+                clipboard_selections TEXT
+            ) STRICT;
+
+            CREATE TABLE vim_global_marks (
+                name TEXT PRIMARY KEY NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_command_history (
+                ix INTEGER PRIMARY KEY NOT NULL,
+                entry TEXT NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_search_history (
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                entry TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl VimDb {
+    query! {
+        pub fn get_registers() -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, text, clipboard_selections
+            FROM vim_registers
+        }
+    }
+
+    query! {
+        pub async fn save_register(
+            name: String,
+            text: String,
+            clipboard_selections: Option<String>
+        ) -> Result<()> {
+            INSERT INTO vim_registers
+                (name, text, clipboard_selections)
+            VALUES
+                (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                text = ?2,
+                clipboard_selections = ?3
+        }
+    }
+
+    query! {
+        pub fn get_global_marks() -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT name, path, row, column
+            FROM vim_global_marks
+        }
+    }
+
+    query! {
+        pub async fn save_global_mark(
+            name: String,
+            path: PathBuf,
+            row: u32,
+            column: u32
+        ) -> Result<()> {
+            INSERT INTO vim_global_marks
+                (name, path, row, column)
+            VALUES
+                (?1, ?2, ?3, ?4)
+            ON CONFLICT DO UPDATE SET
+                path = ?2,
+                row = ?3,
+                column = ?4
+        }
+    }
+
+    query! {
+        pub fn get_command_history() -> Result<Vec<String>> {
+            SELECT entry
+            FROM vim_command_history
+            ORDER BY ix
+        }
+    }
+
+    pub async fn save_command_history(&self, entries: Vec<String>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_command_history", || {
+                conn.exec(sql!(DELETE FROM vim_command_history))?()?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO vim_command_history (ix, entry) VALUES (?, ?)
+                ))?;
+                for (ix, entry) in entries.into_iter().enumerate() {
+                    insert((ix as u32, entry))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_search_history(workspace_id: WorkspaceId) -> Result<Vec<String>> {
+            SELECT entry
+            FROM vim_search_history
+            WHERE workspace_id = ?
+            ORDER BY ix
+        }
+    }
+
+    pub async fn save_search_history(
+        &self,
+        workspace_id: WorkspaceId,
+        entries: Vec<String>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_search_history", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_search_history WHERE workspace_id = ?
+                ))?(workspace_id)?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO vim_search_history (workspace_id, ix, entry) VALUES (?, ?, ?)
+                ))?;
+                for (ix, entry) in entries.into_iter().enumerate() {
+                    insert((workspace_id, ix as u32, entry))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::{Mode, VimGlobals},
+        test::VimTestContext,
+        Vim, VimPersistenceContent, VimSettings,
+    };
+    use gpui::UpdateGlobal as _;
+    use language::Point;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_vim_persistence() {
+        // The database is shared with the other tests, so this test uses
+        // register and mark names that they don't.
+        DB.save_register("x".into(), "one".into(), None)
+            .await
+            .unwrap();
+        DB.save_register("x".into(), "two".into(), Some("[]".into()))
+            .await
+            .unwrap();
+        assert!(DB.get_registers().unwrap().contains(&(
+            "x".to_string(),
+            "two".to_string(),
+            Some("[]".to_string())
+        )));
+
+        DB.save_global_mark("X".into(), PathBuf::from("/a.rs"), 3, 4)
+            .await
+            .unwrap();
+        assert!(DB.get_global_marks().unwrap().contains(&(
+            "X".to_string(),
+            PathBuf::from("/a.rs"),
+            3,
+            4
+        )));
+
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        DB.save_search_history(workspace_id, vec!["foo".into(), "bar".into()])
+            .await
+            .unwrap();
+        DB.save_search_history(workspace_id, vec!["baz".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            DB.get_search_history(workspace_id).unwrap(),
+            vec!["baz".to_string()]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_restore_persisted_state(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<VimSettings>(cx, |settings| {
+                    settings.persist = Some(VimPersistenceContent {
+                        registers: Some(true),
+                        marks: Some(true),
+                        search_history: Some(true),
+                        command_history: Some(true),
+                    });
+                });
+            });
+        });
+
+        cx.set_state("ˇone\ntwo\nthree\n", Mode::Normal);
+        cx.simulate_keystrokes("\" q y y j m shift-q : 3 enter");
+        cx.run_until_parked();
+        // Wait for the writes queued before this one.
+        DB.write(|_| ()).await;
+
+        // Forget the state, as if Zed was restarted.
+        cx.update(|cx| {
+            *Vim::globals(cx) = VimGlobals::default();
+            VimGlobals::load_persisted(cx);
+        });
+        cx.run_until_parked();
+        cx.update(|cx| {
+            let globals = Vim::globals(cx);
+            assert_eq!(
+                globals
+                    .registers
+                    .get(&'q')
+                    .map(|register| register.text.to_string()),
+                Some("one\n".to_string())
+            );
+            assert_eq!(
+                globals.global_marks.get("Q").map(|mark| mark.point),
+                Some(Point::new(1, 0))
+            );
+            assert_eq!(
+                globals.command_history.last().map(String::as_str),
+                Some("3")
+            );
+        });
+
+        cx.simulate_keystrokes("g g @ :");
+        cx.assert_state("one\ntwo\nˇthree\n", Mode::Normal);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::command::command_interceptor;
+use crate::command::command_palette_interceptor;
 use crate::normal::repeat::Replayer;
+use crate::persistence::DB;
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
@@ -10,13 +11,17 @@ use gpui::{
     Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakView,
 };
 use language::Point;
+use project::{search::SearchInputKind, search_history::SearchHistoryCursor};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use std::borrow::BorrowMut;
+use std::path::PathBuf;
 use std::{fmt::Display, ops::Range, sync::Arc};
 use ui::{SharedString, ViewContext};
+use util::ResultExt as _;
 use workspace::searchable::Direction;
+use workspace::Workspace;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
 pub enum Mode {
@@ -171,6 +176,16 @@ impl From<String> for Register {
     }
 }
 
+/// An uppercase mark, which points into a file rather than into a buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlobalMark {
+    pub abs_path: PathBuf,
+    pub point: Point,
+}
+
+/// How many commands the command history holds.
+const MAX_COMMAND_HISTORY_LEN: usize = 100;
+
 #[derive(Default, Clone)]
 pub struct VimGlobals {
     pub last_find: Option<Motion>,
@@ -198,6 +213,11 @@ pub struct VimGlobals {
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    pub global_marks: HashMap<String, GlobalMark>,
+    pub command_history: Vec<String>,
+    /// Whether the registers, marks and command history kept across restarts
+    /// were loaded, which happens once vim is enabled.
+    pub persisted_state_loaded: bool,
 
     pub focused_vim: Option<WeakView<Vim>>,
 }
@@ -217,11 +237,12 @@ impl VimGlobals {
 
         cx.observe_global::<SettingsStore>(move |cx| {
             if Vim::enabled(cx) {
+                VimGlobals::load_persisted(cx);
                 CommandPaletteFilter::update_global(cx, |filter, _| {
                     filter.show_namespace(Vim::NAMESPACE);
                 });
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
-                    interceptor.set(Box::new(command_interceptor));
+                    interceptor.set(Box::new(command_palette_interceptor));
                 });
             } else {
                 *Vim::globals(cx) = VimGlobals::default();
@@ -330,7 +351,8 @@ impl VimGlobals {
         };
         let lower = register.to_lowercase().next().unwrap_or(register);
         match lower {
-            '_' | ':' | '.' | '#' | '=' => None,
+            ':' => self.command_history.last().cloned().map(Register::from),
+            '_' | '.' | '#' | '=' => None,
             '+' => cx.read_from_clipboard().map(|item| item.into()),
             '*' => {
                 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -361,6 +383,178 @@ impl VimGlobals {
         }
     }
 
+    /// Loads the registers, marks and command history that were kept across
+    /// restarts. They're shared by all workspaces, like vim's viminfo file.
+    pub(crate) fn load_persisted(cx: &mut AppContext) {
+        if !Vim::enabled(cx) || Vim::globals(cx).persisted_state_loaded {
+            return;
+        }
+        Vim::globals(cx).persisted_state_loaded = true;
+
+        let persist = VimSettings::get_global(cx).persist;
+        let load = cx.background_executor().spawn(async move {
+            let registers = persist
+                .registers
+                .then(|| DB.get_registers().log_err())
+                .flatten()
+                .unwrap_or_default();
+            let marks = persist
+                .marks
+                .then(|| DB.get_global_marks().log_err())
+                .flatten()
+                .unwrap_or_default();
+            let command_history = persist
+                .command_history
+                .then(|| DB.get_command_history().log_err())
+                .flatten()
+                .unwrap_or_default();
+            (registers, marks, command_history)
+        });
+
+        cx.spawn(|mut cx| async move {
+            let (registers, marks, command_history) = load.await;
+            cx.update(|cx| {
+                Vim::update_globals(cx, |globals, _| {
+                    // Keep what was set while they were loading.
+                    for (name, text, clipboard_selections) in registers {
+                        let Some(name) = name.chars().next() else {
+                            continue;
+                        };
+                        let clipboard_selections = clipboard_selections
+                            .and_then(|selections| serde_json::from_str(&selections).log_err());
+                        globals.registers.entry(name).or_insert(Register {
+                            text: text.into(),
+                            clipboard_selections,
+                        });
+                    }
+                    for (name, abs_path, row, column) in marks {
+                        let point = Point::new(row, column);
+                        globals
+                            .global_marks
+                            .entry(name)
+                            .or_insert(GlobalMark { abs_path, point });
+                    }
+                    if globals.command_history.is_empty() {
+                        globals.command_history = command_history;
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Loads the search history that was kept for the workspace, unless it
+    /// already has one.
+    pub(crate) fn load_search_history(workspace: &Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        if !Vim::enabled(cx) || !VimSettings::get_global(cx).persist.search_history {
+            return;
+        }
+        let project = workspace.project().clone();
+        let load = cx
+            .background_executor()
+            .spawn(async move { DB.get_search_history(workspace_id) });
+        cx.spawn(|_, mut cx| async move {
+            let search_history = load.await?;
+            project.update(&mut cx, |project, _| {
+                let history = project.search_history_mut(SearchInputKind::Query);
+                if !history.entries().is_empty() {
+                    return;
+                }
+                let mut cursor = SearchHistoryCursor::default();
+                for search in search_history {
+                    history.add(&mut cursor, search);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Keeps a named register across restarts, if enabled.
+    pub(crate) fn persist_register(&self, register: char, cx: &AppContext) {
+        let name = register.to_ascii_lowercase();
+        if !name.is_ascii_lowercase() || !VimSettings::get_global(cx).persist.registers {
+            return;
+        }
+        let Some(register) = self.registers.get(&name) else {
+            return;
+        };
+        let text = register.text.to_string();
+        let clipboard_selections = register
+            .clipboard_selections
+            .as_ref()
+            .and_then(|selections| serde_json::to_string(selections).log_err());
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_register(name.to_string(), text, clipboard_selections)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
+    /// Sets an uppercase mark, and keeps it across restarts if enabled.
+    pub(crate) fn set_global_mark(&mut self, name: String, mark: GlobalMark, cx: &AppContext) {
+        self.global_marks.insert(name.clone(), mark.clone());
+        if !VimSettings::get_global(cx).persist.marks {
+            return;
+        }
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_global_mark(name, mark.abs_path, mark.point.row, mark.point.column)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
+    /// Adds a command to the command history, and keeps the history across restarts if enabled.
+    pub(crate) fn push_command_history(&mut self, command: String, cx: &AppContext) {
+        if command.is_empty() {
+            return;
+        }
+        self.command_history.retain(|previous| *previous != command);
+        self.command_history.push(command);
+        if self.command_history.len() > MAX_COMMAND_HISTORY_LEN {
+            self.command_history.remove(0);
+        }
+
+        if !VimSettings::get_global(cx).persist.command_history {
+            return;
+        }
+        let command_history = self.command_history.clone();
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_command_history(command_history).await.log_err();
+            })
+            .detach();
+    }
+
+    /// Keeps the search history of the workspace across restarts, if enabled.
+    pub(crate) fn persist_search_history(workspace: &Workspace, cx: &AppContext) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        if !VimSettings::get_global(cx).persist.search_history {
+            return;
+        }
+        let search_history = workspace
+            .project()
+            .read(cx)
+            .search_history(SearchInputKind::Query)
+            .entries()
+            .to_vec();
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_search_history(workspace_id, search_history)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+
     fn system_clipboard_is_newer(&self, cx: &ViewContext<Editor>) -> bool {
         cx.read_from_clipboard().is_some_and(|item| {
             if let Some(last_state) = &self.last_yank {
@@ -525,7 +719,9 @@ impl Operator {
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode == Mode::HelixNormal
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
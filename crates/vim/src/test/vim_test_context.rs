@@ -60,6 +60,15 @@ impl VimTestContext {
         cx.update(|cx| {
             SettingsStore::update_global(cx, |store, cx| {
                 store.update_user_settings::<VimModeSetting>(cx, |s| *s = Some(enabled));
+                // Tests share the database, so they only keep vim's state when they opt in.
+                store.update_user_settings::<VimSettings>(cx, |s| {
+                    s.persist = Some(VimPersistenceContent {
+                        registers: Some(false),
+                        marks: Some(false),
+                        search_history: Some(false),
+                        command_history: Some(false),
+                    })
+                });
             });
             let default_key_bindings = settings::KeymapFile::load_asset_allow_partial_failure(
                 "keymaps/default-macos.json",
@@ -119,6 +128,14 @@ impl VimTestContext {
         })
     }
 
+    pub fn enable_helix(&mut self) {
+        self.cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<HelixModeSetting>(cx, |s| *s = Some(true));
+            });
+        })
+    }
+
     pub fn disable_vim(&mut self) {
         self.cx.update(|cx| {
             SettingsStore::update_global(cx, |store, cx| {
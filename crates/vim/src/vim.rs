@@ -13,6 +13,7 @@ mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
@@ -21,6 +22,7 @@ mod visual;
 
 use anyhow::Result;
 use collections::HashMap;
+use command::RunCommand;
 use editor::{
     movement::{self, FindRange},
     Anchor, Bias, Editor, EditorEvent, EditorMode, ToPoint,
@@ -43,7 +45,7 @@ use std::{mem, ops::Range, sync::Arc};
 use surrounds::SurroundsType;
 use theme::ThemeSettings;
 use ui::{px, IntoElement, VisualContext};
-use vim_mode_setting::VimModeSetting;
+use vim_mode_setting::{HelixModeSetting, VimModeSetting};
 use workspace::{self, Pane, ResizeIntent, Workspace};
 
 use crate::state::ReplayableAction;
@@ -104,7 +106,26 @@ pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(|editor: &mut Editor, cx| Vim::register(editor, cx))
         .detach();
 
-    cx.observe_new_views(|workspace: &mut Workspace, _| {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        VimGlobals::load_persisted(cx);
+        VimGlobals::load_search_history(workspace, cx);
+        let mut was_enabled = Vim::enabled(cx);
+        cx.observe_global::<SettingsStore>(move |workspace, cx| {
+            let enabled = Vim::enabled(cx);
+            if enabled && !was_enabled {
+                VimGlobals::load_search_history(workspace, cx);
+            }
+            was_enabled = enabled;
+        })
+        .detach();
+
+        workspace.register_action(|_, action: &RunCommand, cx| {
+            Vim::update_globals(cx, |globals, cx| {
+                globals.push_command_history(action.command.clone(), cx)
+            });
+            cx.dispatch_action(action.action.boxed_clone());
+        });
+
         workspace.register_action(|workspace, _: &ToggleVimMode, cx| {
             let fs = workspace.app_state().fs.clone();
             let currently_enabled = Vim::enabled(cx);
@@ -245,10 +266,11 @@ impl Vim {
 
     pub fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
         let editor = cx.view().clone();
+        let mode = Vim::normal_mode(cx);
 
         cx.new_view(|cx| Vim {
-            mode: Mode::Normal,
-            last_mode: Mode::Normal,
+            mode,
+            last_mode: mode,
             temp_mode: false,
             exit_temporary_mode: false,
             operator_stack: Vec::new(),
@@ -285,9 +307,23 @@ impl Vim {
 
         let mut was_enabled = Vim::enabled(cx);
         let mut was_toggle = VimSettings::get_global(cx).toggle_relative_line_numbers;
+        let mut was_helix = HelixModeSetting::get_global(cx).0;
         cx.observe_global::<SettingsStore>(move |editor, cx| {
             let enabled = Vim::enabled(cx);
             let toggle = VimSettings::get_global(cx).toggle_relative_line_numbers;
+            let helix = HelixModeSetting::get_global(cx).0;
+            if enabled && was_enabled && helix != was_helix {
+                if let Some(vim) = editor.addon::<VimAddon>() {
+                    vim.view.update(cx, |_, cx| {
+                        cx.defer(|vim, cx| {
+                            if vim.mode == Mode::Normal || vim.mode == Mode::HelixNormal {
+                                vim.switch_mode(Mode::Normal, false, cx)
+                            }
+                        })
+                    });
+                }
+            }
+            was_helix = helix;
             if enabled && was_enabled && (toggle != was_toggle) {
                 if toggle {
                     let is_relative = editor
@@ -406,6 +442,15 @@ impl Vim {
         VimModeSetting::get_global(cx).0
     }
 
+    /// The mode that escape returns to: vim's normal mode, or Helix's when `helix_mode` is set.
+    pub fn normal_mode(cx: &AppContext) -> Mode {
+        if HelixModeSetting::get_global(cx).0 {
+            Mode::HelixNormal
+        } else {
+            Mode::Normal
+        }
+    }
+
     /// Called whenever an keystroke is typed so vim can observe all actions
     /// and keystrokes accordingly.
     fn observe_keystrokes(&mut self, keystroke_event: &KeystrokeEvent, cx: &mut ViewContext<Self>) {
@@ -516,6 +561,11 @@ impl Vim {
             self.temp_mode = false;
         }
 
+        let mode = if mode == Mode::Normal {
+            Vim::normal_mode(cx)
+        } else {
+            mode
+        };
         let last_mode = self.mode;
         let prior_mode = self.last_mode;
         let prior_tx = self.current_tx;
@@ -524,7 +574,7 @@ impl Vim {
         self.operator_stack.clear();
         self.selected_register.take();
         self.cancel_running_command(cx);
-        if mode == Mode::Normal || mode != last_mode {
+        if mode == Mode::Normal || mode == Mode::HelixNormal || mode != last_mode {
             self.current_tx.take();
             self.current_anchor.take();
         }
@@ -1139,6 +1189,7 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                     self.visual_replace(text, cx)
                 }
+                Mode::HelixNormal => self.helix_replace(&text, cx),
                 _ => self.clear_operator(cx),
             },
             Some(Operator::Digraph { first_char }) => {
@@ -1166,10 +1217,11 @@ impl Vim {
                     self.add_surrounds(text, SurroundsType::Selection, cx);
                     self.clear_operator(cx);
                 }
+                Mode::HelixNormal => self.helix_surround(text, cx),
                 _ => self.clear_operator(cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, cx);
                         self.clear_operator(cx);
@@ -1178,7 +1230,7 @@ impl Vim {
                 _ => self.clear_operator(cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal => {
                     self.delete_surrounds(text, cx);
                     self.clear_operator(cx);
                 }
@@ -1263,6 +1315,27 @@ pub enum UseSystemClipboard {
     OnYank,
 }
 
+/// Controls which parts of the vim state are kept across restarts.
+#[derive(Copy, Clone, Debug, Deserialize)]
+struct VimPersistence {
+    pub registers: bool,
+    pub marks: bool,
+    pub search_history: bool,
+    pub command_history: bool,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+struct VimPersistenceContent {
+    /// Whether to keep the named registers, from "a to "z.
+    pub registers: Option<bool>,
+    /// Whether to keep the uppercase marks, which can be jumped to from any file.
+    pub marks: Option<bool>,
+    /// Whether to keep the history of searches.
+    pub search_history: Option<bool>,
+    /// Whether to keep the history of commands, which `@:` repeats the last of.
+    pub command_history: Option<bool>,
+}
+
 #[derive(Deserialize)]
 struct VimSettings {
     pub toggle_relative_line_numbers: bool,
@@ -1271,6 +1344,7 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub persist: VimPersistence,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1281,6 +1355,7 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub persist: Option<VimPersistenceContent>,
 }
 
 impl Settings for VimSettings {
@@ -1,4 +1,5 @@
-//! Contains the [`VimModeSetting`] used to enable/disable Vim mode.
+//! Contains the [`VimModeSetting`] used to enable/disable Vim mode, and the
+//! [`HelixModeSetting`] used to switch it to Helix's selection-first keymap.
 //!
 //! This is in its own crate as we want other crates to be able to enable or
 //! disable Vim mode without having to depend on the `vim` crate in its
@@ -11,6 +12,7 @@ use settings::{Settings, SettingsSources};
 /// Initializes the `vim_mode_setting` crate.
 pub fn init(cx: &mut AppContext) {
     VimModeSetting::register(cx);
+    HelixModeSetting::register(cx);
 }
 
 /// Whether or not to enable Vim mode.
@@ -34,3 +36,26 @@ impl Settings for VimModeSetting {
         ))
     }
 }
+
+/// Whether or not vim mode uses Helix's keymap, where motions select text
+/// before it is acted upon. Only has an effect when Vim mode is enabled.
+///
+/// Default: false
+pub struct HelixModeSetting(pub bool);
+
+impl Settings for HelixModeSetting {
+    const KEY: Option<&'static str> = Some("helix_mode");
+
+    type FileContent = Option<bool>;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        Ok(Self(
+            sources
+                .user
+                .or(sources.server)
+                .copied()
+                .flatten()
+                .unwrap_or(sources.default.ok_or_else(Self::missing_default)?),
+        ))
+    }
+}
@@ -3,7 +3,7 @@ use remote::ConnectionState;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::ViewContext;
 
-use crate::Workspace;
+use crate::{notifications::NotifyResultExt, Workspace};
 
 pub fn schedule_task(
     workspace: &mut Workspace,
@@ -26,9 +26,21 @@ pub fn schedule_task(
         }
     }
 
-    if let Some(spawn_in_terminal) =
-        task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx)
+    let resolved_task = match workspace
+        .project
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
     {
+        Some(inventory) => {
+            inventory
+                .read(cx)
+                .resolve_task(&task_source_kind, task_to_resolve, None, task_cx)
+        }
+        None => Ok(task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx)),
+    };
+    if let Some(spawn_in_terminal) = resolved_task.notify_err(workspace, cx).flatten() {
         schedule_resolved_task(
             workspace,
             task_source_kind,
@@ -19,7 +19,7 @@ use futures::{
 };
 use fuzzy::CharBag;
 use git::{
-    repository::{GitRepository, RepoPath},
+    repository::{GitRepository, RepoPath, UpstreamTracking},
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
     },
@@ -198,6 +198,7 @@ pub struct RepositoryEntry {
     pub work_directory_id: ProjectEntryId,
     pub work_directory: WorkDirectory,
     pub(crate) branch: Option<Arc<str>>,
+    pub(crate) upstream_tracking: Option<UpstreamTracking>,
 }
 
 impl Deref for RepositoryEntry {
@@ -219,6 +220,11 @@ impl RepositoryEntry {
         self.branch.clone()
     }
 
+    /// Returns how the branch compares to its upstream, if it tracks one.
+    pub fn upstream_tracking(&self) -> Option<UpstreamTracking> {
+        self.upstream_tracking
+    }
+
     pub fn work_directory_id(&self) -> ProjectEntryId {
         self.work_directory_id
     }
@@ -245,6 +251,7 @@ impl RepositoryEntry {
         proto::RepositoryEntry {
             work_directory_id: self.work_directory_id.to_proto(),
             branch: self.branch.as_ref().map(|branch| branch.to_string()),
+            upstream_tracking: self.upstream_tracking.map(upstream_tracking_to_proto),
             updated_statuses: self
                 .statuses_by_path
                 .iter()
@@ -299,6 +306,7 @@ impl RepositoryEntry {
         proto::RepositoryEntry {
             work_directory_id: self.work_directory_id.to_proto(),
             branch: self.branch.as_ref().map(|branch| branch.to_string()),
+            upstream_tracking: self.upstream_tracking.map(upstream_tracking_to_proto),
             updated_statuses,
             removed_statuses,
         }
@@ -2462,6 +2470,9 @@ impl Snapshot {
                     self.repositories
                         .update(&PathKey(work_dir_entry.path.clone()), &(), |repo| {
                             repo.branch = repository.branch.map(Into::into);
+                            repo.upstream_tracking = repository
+                                .upstream_tracking
+                                .map(upstream_tracking_from_proto);
                             repo.statuses_by_path.edit(edits, &());
                         });
                 } else {
@@ -2484,6 +2495,9 @@ impl Snapshot {
                                 location_in_repo: None,
                             },
                             branch: repository.branch.map(Into::into),
+                            upstream_tracking: repository
+                                .upstream_tracking
+                                .map(upstream_tracking_from_proto),
                             statuses_by_path: statuses,
                         },
                         &(),
@@ -3282,6 +3296,7 @@ impl BackgroundScannerState {
                 work_directory_id: work_dir_id,
                 work_directory: work_directory.clone(),
                 branch: repository.branch_name().map(Into::into),
+                upstream_tracking: repository.upstream_tracking(),
                 statuses_by_path: Default::default(),
             },
             &(),
@@ -5094,6 +5109,7 @@ impl BackgroundScanner {
                         };
 
                         let branch = local_repository.repo_ptr.branch_name();
+                        let upstream_tracking = local_repository.repo_ptr.upstream_tracking();
                         local_repository.repo_ptr.reload_index();
 
                         state.snapshot.git_repositories.update(&entry_id, |entry| {
@@ -5103,7 +5119,10 @@ impl BackgroundScanner {
                         state.snapshot.snapshot.repositories.update(
                             &PathKey(work_dir.clone()),
                             &(),
-                            |entry| entry.branch = branch.map(Into::into),
+                            |entry| {
+                                entry.branch = branch.map(Into::into);
+                                entry.upstream_tracking = upstream_tracking;
+                            },
                         );
 
                         local_repository
@@ -6223,6 +6242,20 @@ fn tracked_status_to_proto(code: StatusCode) -> i32 {
     }
 }
 
+fn upstream_tracking_to_proto(tracking: UpstreamTracking) -> proto::GitUpstreamTracking {
+    proto::GitUpstreamTracking {
+        ahead: tracking.ahead,
+        behind: tracking.behind,
+    }
+}
+
+fn upstream_tracking_from_proto(tracking: proto::GitUpstreamTracking) -> UpstreamTracking {
+    UpstreamTracking {
+        ahead: tracking.ahead,
+        behind: tracking.behind,
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ProjectEntryId(usize);
 